@@ -0,0 +1,17 @@
+//checks a birth date against the age limits for a current year given on the command line,
+//e.g. `cargo run --example age -- 2006-06-15 2024`
+use halo2_circuits::age::AgeCircuit;
+use halo2_proofs::dev::MockProver;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let date = args.first().map_or("2006-06-15", String::as_str);
+    let current_year = args.get(1).map_or(2024, |year| year.parse().expect("current year should be a number"));
+
+    let circuit = AgeCircuit::from_birth_date(date).expect("birth date should be yyyy-mm-dd");
+    let prover = MockProver::run(9, &circuit, AgeCircuit::public_inputs(current_year)).unwrap();
+    match prover.verify() {
+        Ok(()) => println!("born {} is of age in {}", date, current_year),
+        Err(failures) => println!("born {} is not of age in {} ({} failed checks)", date, current_year, failures.len()),
+    }
+}
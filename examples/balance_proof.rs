@@ -0,0 +1,28 @@
+//proves a committed balance is at least a public threshold with the real prover, then
+//checks the verifier rejects the same proof against a higher threshold
+use halo2_circuits::{
+    balance_proof::{BalanceProofCircuit, BALANCE_PROOF_K},
+    params_cache::ParamsCache,
+    proving::{create_proof, keygen, verify_proof},
+};
+use halo2_proofs::{pasta::Fp, plonk::Circuit};
+
+fn main() {
+    let cache = ParamsCache::new();
+    let (params, pk) = keygen(&cache, BALANCE_PROOF_K, &BalanceProofCircuit::builder().build().without_witnesses())
+        .expect("keygen failed");
+
+    let builder = BalanceProofCircuit::builder().balance(1_500).blinding(Fp::from(0xdead_beef)).threshold(1_000);
+    let instances = builder.public_inputs().unwrap();
+    let proof = create_proof(&params, &pk, builder.build(), &instances).expect("proving failed");
+    println!("proof is {} bytes", proof.len());
+
+    verify_proof(&params, pk.get_vk(), &proof, &instances).expect("proof was rejected");
+    println!("balance >= {} verified", 1_000);
+
+    //the commitment stays the same, only the claimed threshold changes
+    let mut raised = instances.clone();
+    raised[0][1] = Fp::from(2_000);
+    assert!(verify_proof(&params, pk.get_vk(), &proof, &raised).is_err());
+    println!("the same proof is rejected for balance >= {}", 2_000);
+}
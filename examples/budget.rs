@@ -0,0 +1,18 @@
+//checks expenses given on the command line stay under a budget, the last argument,
+//e.g. `cargo run --example budget -- 40 99 60 200`
+use halo2_circuits::budget::{witness, BUDGET_K};
+use halo2_proofs::dev::MockProver;
+
+fn main() {
+    let mut args: Vec<u64> = std::env::args().skip(1).map(|arg| arg.parse().expect("arguments should be numbers")).collect();
+    let budget = args.pop().unwrap_or(200);
+    let expenses = if args.is_empty() { vec![40, 99, 60] } else { args };
+
+    let total: u64 = expenses.iter().sum();
+    let (circuit, instances) = witness(expenses, budget);
+    let prover = MockProver::run(BUDGET_K, &circuit, instances).unwrap();
+    match prover.verify() {
+        Ok(()) => println!("spending {} is within a budget of {}", total, budget),
+        Err(failures) => println!("spending {} is not within a budget of {} ({} failed checks)", total, budget, failures.len()),
+    }
+}
@@ -0,0 +1,18 @@
+//commits to a seed and proves the roll it gives, both from the command line, e.g.
+//`cargo run --example dice -- 11400714819323198485 4`
+use halo2_circuits::dice::{commit, roll, DiceCircuit, DICE_K};
+use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let seed = args.first().map_or(0x9e37_79b9_7f4a_7c15, |seed| seed.parse().expect("seed should be a u64"));
+    let claimed = args.get(1).map_or(roll(seed), |roll| roll.parse().expect("roll should be a number"));
+
+    let blinding = Fp::from(424242);
+    let instances = DiceCircuit::public_inputs(commit(seed, blinding), claimed);
+    let prover = MockProver::run(DICE_K, &DiceCircuit::new(seed, blinding), instances).unwrap();
+    match prover.verify() {
+        Ok(()) => println!("rolled {}", claimed),
+        Err(failures) => println!("{} isn't the committed roll ({} failed checks)", claimed, failures.len()),
+    }
+}
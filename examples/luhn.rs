@@ -0,0 +1,17 @@
+//checks a card number passes the luhn check and starts with a BIN, both given on the
+//command line, e.g. `cargo run --example luhn -- 4532015112830366 453201`
+use halo2_circuits::luhn::{LuhnCircuit, LUHN_K};
+use halo2_proofs::dev::MockProver;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let number = args.first().map_or("4532015112830366", String::as_str);
+    let bin = args.get(1).map_or(453201, |bin| bin.parse().expect("BIN should be a number"));
+
+    let circuit = LuhnCircuit::from_card_number(number).expect("card number should be 16 digits");
+    let prover = MockProver::run(LUHN_K, &circuit, LuhnCircuit::public_inputs(bin)).unwrap();
+    match prover.verify() {
+        Ok(()) => println!("{} is valid with BIN {}", number, bin),
+        Err(failures) => println!("{} is not valid with BIN {} ({} failed checks)", number, bin, failures.len()),
+    }
+}
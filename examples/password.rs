@@ -0,0 +1,17 @@
+//checks a password given on the command line against the policy and its own hash, e.g.
+//`cargo run --example password -- correctHorse9battery`
+use halo2_circuits::password::{hash, PasswordCircuit, PASSWORD_K};
+use halo2_proofs::dev::MockProver;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let password = args.first().map_or("correctHorse9battery", String::as_str);
+
+    let circuit = PasswordCircuit::from_password(password.as_bytes()).expect("password should be at most 64 bytes");
+    let hash = hash(password.as_bytes()).unwrap();
+    let prover = MockProver::run(PASSWORD_K, &circuit, PasswordCircuit::public_inputs(hash)).unwrap();
+    match prover.verify() {
+        Ok(()) => println!("{} meets the policy", password),
+        Err(failures) => println!("{} doesn't meet the policy ({} failed checks)", password, failures.len()),
+    }
+}
@@ -0,0 +1,20 @@
+//checks three accounts of a four leaf tree hold more than a liability given on the command
+//line, e.g. `cargo run --example reserves -- 4000`
+use halo2_circuits::reserves::{ReservesCircuit, RESERVES_K};
+use halo2_proofs::dev::MockProver;
+
+//(id, balance) for every leaf
+const ACCOUNTS: [(u64, u64); 4] = [(11, 500), (12, 1200), (13, 40), (14, 3000)];
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let liability = args.first().map_or(4000, |liability| liability.parse().expect("liability should be a number"));
+
+    let circuit = ReservesCircuit::<3, 2>::from_accounts(&ACCOUNTS, [0, 1, 3]).unwrap();
+    let instances = ReservesCircuit::<3, 2>::public_inputs(&ACCOUNTS, liability).unwrap();
+    let prover = MockProver::run(RESERVES_K, &circuit, instances).unwrap();
+    match prover.verify() {
+        Ok(()) => println!("reserves exceed {}", liability),
+        Err(failures) => println!("reserves don't exceed {} ({} failed checks)", liability, failures.len()),
+    }
+}
@@ -0,0 +1,20 @@
+//checks a solution against a puzzle, both as 81 digits in row order with '.' for a blank,
+//e.g. `cargo run --example sudoku -- 53..7....6..195... 534678912672195348...`
+use halo2_circuits::sudoku::{parse_grid, SudokuCircuit, SUDOKU_K};
+use halo2_proofs::dev::MockProver;
+
+const PUZZLE: &str = "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+const SOLUTION: &str = "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let puzzle = parse_grid(args.first().map_or(PUZZLE, String::as_str)).expect("puzzle should be 81 digits or '.'");
+    let solution = parse_grid(args.get(1).map_or(SOLUTION, String::as_str)).expect("solution should be 81 digits");
+
+    let circuit = SudokuCircuit::from_solution(&solution);
+    let prover = MockProver::run(SUDOKU_K, &circuit, SudokuCircuit::public_inputs(&puzzle)).unwrap();
+    match prover.verify() {
+        Ok(()) => println!("the solution solves the puzzle"),
+        Err(failures) => println!("the solution is wrong ({} failed checks)", failures.len()),
+    }
+}
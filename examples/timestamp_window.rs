@@ -0,0 +1,22 @@
+//checks a timestamp lies in a window given on the command line, all in unix seconds,
+//e.g. `cargo run --example timestamp_window -- 1700003600 1700000000 1700086400`
+use halo2_circuits::timestamp_window::{TimestampWindowCircuit, TIMESTAMP_WINDOW_K};
+use halo2_proofs::dev::MockProver;
+
+fn main() {
+    let args: Vec<u64> = std::env::args().skip(1).map(|arg| arg.parse().expect("arguments should be unix timestamps")).collect();
+    let (ts, start, end) = match args[..] {
+        [ts, start, end] => (ts, start, end),
+        _ => (1_700_003_600, 1_700_000_000, 1_700_086_400),
+    };
+
+    let instances = match TimestampWindowCircuit::public_inputs(start, end) {
+        Some(instances) => instances,
+        None => return println!("the window [{}, {}] is empty", start, end),
+    };
+    let prover = MockProver::run(TIMESTAMP_WINDOW_K, &TimestampWindowCircuit::new(ts), instances).unwrap();
+    match prover.verify() {
+        Ok(()) => println!("{} is in [{}, {}]", ts, start, end),
+        Err(failures) => println!("{} is not in [{}, {}] ({} failed checks)", ts, start, end, failures.len()),
+    }
+}
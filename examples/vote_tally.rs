@@ -0,0 +1,18 @@
+//tallies ballots given on the command line, each 0, 1 or 2, and checks the circuit agrees,
+//e.g. `cargo run --example vote_tally -- 0 2 1 2 2 0 1 2`
+use halo2_circuits::vote_tally::{tally, VoteTallyCircuit};
+use halo2_proofs::dev::MockProver;
+
+const N: usize = 8;
+
+fn main() {
+    let args: Vec<u64> = std::env::args().skip(1).map(|ballot| ballot.parse().expect("ballots should be numbers")).collect();
+    let ballots: [u64; N] = if args.is_empty() { [0, 2, 1, 2, 2, 0, 1, 2] } else { args.try_into().expect("expected 8 ballots") };
+    let tallies = tally(&ballots).expect("ballots should be 0, 1 or 2");
+
+    let prover = MockProver::run(5, &VoteTallyCircuit::from_ballots(ballots), VoteTallyCircuit::<N>::public_inputs(tallies)).unwrap();
+    match prover.verify() {
+        Ok(()) => println!("tallies {:?} verified", tallies),
+        Err(failures) => println!("tallies {:?} rejected ({} failed checks)", tallies, failures.len()),
+    }
+}
@@ -0,0 +1,226 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Fixed, Selector},
+    poly::Rotation,
+};
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, range_lookup::RangeTableConfig, report};
+
+//one step of a chain, applied to the running value
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChainOp {
+    Add(u64),
+    Mul(u64),
+}
+
+//proves y = f(x) for a chain f of adds and muls by constants fixed at configure time.
+//
+//the running value goes down one column, row i + 1 being op i applied to row i with the
+//op's constant in a fixed column next to it. x and every intermediate are looked up in
+//0..RANGE, which also keeps the steps from wrapping around the modulus. y sits on the row
+//after the last and is copied to it
+#[derive(Clone, Debug)]
+pub struct AdditionChainConfig<F: FieldExt, const RANGE: usize> {
+    pub acc: Column<Advice>,
+    pub constant: Column<Fixed>,
+    pub q_add: Selector,
+    pub q_mul: Selector,
+    pub q_range: Selector,
+    pub ops: Vec<ChainOp>,
+    pub table: RangeTableConfig<F, RANGE>,
+}
+
+impl<F: FieldExt, const RANGE: usize> AdditionChainConfig<F, RANGE> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, acc: Column<Advice>, ops: &[ChainOp]) -> Self {
+        assert!(!ops.is_empty());
+        let constant = cs.fixed_column();
+        let q_add = cs.selector();
+        let q_mul = cs.selector();
+        let q_range = cs.complex_selector();
+        let table = RangeTableConfig::configure(cs);
+
+        cs.enable_equality(acc);
+
+        cs.create_gate("chain add", |cs| {
+            let q = cs.query_selector(q_add);
+            let c = cs.query_fixed(constant, Rotation::cur());
+            let cur = cs.query_advice(acc, Rotation::cur());
+            let next = cs.query_advice(acc, Rotation::next());
+            Constraints::with_selector(q, [("next = cur + c", next - (cur + c))])
+        });
+        report::annotate_gate("addition_chain", "chain add");
+
+        cs.create_gate("chain mul", |cs| {
+            let q = cs.query_selector(q_mul);
+            let c = cs.query_fixed(constant, Rotation::cur());
+            let cur = cs.query_advice(acc, Rotation::cur());
+            let next = cs.query_advice(acc, Rotation::next());
+            Constraints::with_selector(q, [("next = cur * c", next - cur * c)])
+        });
+        report::annotate_gate("addition_chain", "chain mul");
+
+        let lookup_index = cs.lookup(|cs| {
+            let q = cs.query_selector(q_range);
+            let v = cs.query_advice(acc, Rotation::cur());
+            vec![(q * v, table.value)]
+        });
+        report::annotate_lookup("addition_chain", lookup_index);
+
+        Self {
+            acc,
+            constant,
+            q_add,
+            q_mul,
+            q_range,
+            ops: ops.to_vec(),
+            table,
+        }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>, ops: &[ChainOp]) -> Self {
+        let acc = pool.advice(cs);
+        Self::configure(cs, acc, ops)
+    }
+
+    //the chain evaluated natively, None once a value leaves 0..RANGE
+    pub fn eval(&self, x: u64) -> Option<u64> {
+        let x = (x < RANGE as u64).then_some(x)?;
+        self.ops.iter().try_fold(x, |acc, op| {
+            let next = match op {
+                ChainOp::Add(c) => acc.checked_add(*c)?,
+                ChainOp::Mul(c) => acc.checked_mul(*c)?,
+            };
+            (next < RANGE as u64).then_some(next)
+        })
+    }
+
+    //returns the output cell, constrained equal to y
+    pub fn assign(&self, layouter: impl Layouter<F>, x: Value<F>, y: Value<F>) -> Result<AssignedCell<F, F>, CircuitsError> {
+        let mut values = vec![x];
+        for op in &self.ops {
+            let prev = *values.last().unwrap();
+            values.push(match op {
+                ChainOp::Add(c) => prev + Value::known(F::from(*c)),
+                ChainOp::Mul(c) => prev * Value::known(F::from(*c)),
+            });
+        }
+        self.assign_with_values(layouter, values, y)
+    }
+
+    //values holds x and then every intermediate, the last being the output
+    fn assign_with_values(&self, mut layouter: impl Layouter<F>, values: Vec<Value<F>>, y: Value<F>) -> Result<AssignedCell<F, F>, CircuitsError> {
+        if values.len() != self.ops.len() + 1 {
+            return Err(CircuitsError::LengthMismatch { expected: self.ops.len() + 1, got: values.len() });
+        }
+        layouter.assign_region(
+            || "assign addition chain",
+            |mut region| {
+                let mut cells = vec![];
+                for (offset, value) in values.iter().enumerate() {
+                    self.q_range.enable(&mut region, offset)?;
+                    cells.push(region.assign_advice(|| format!("acc {}", offset), self.acc, offset, || *value)?);
+                }
+                for (offset, op) in self.ops.iter().enumerate() {
+                    let c = match op {
+                        ChainOp::Add(c) => {
+                            self.q_add.enable(&mut region, offset)?;
+                            c
+                        }
+                        ChainOp::Mul(c) => {
+                            self.q_mul.enable(&mut region, offset)?;
+                            c
+                        }
+                    };
+                    region.assign_fixed(|| "constant", self.constant, offset, || Value::known(F::from(*c)))?;
+                }
+                let output = cells.pop().unwrap();
+                let y = region.assign_advice(|| "y", self.acc, values.len(), || y)?;
+                region.constrain_equal(output.cell(), y.cell())?;
+                Ok(output)
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, known, ExpectedFailure, Forged};
+
+    //f(x) = 2x + 3
+    const CHAIN: [ChainOp; 2] = [ChainOp::Mul(2), ChainOp::Add(3)];
+
+    #[derive(Default)]
+    struct ChainCircuit {
+        x: Value<Fp>,
+        y: Value<Fp>,
+        //witnessed as x and the intermediates instead of the real ones when forged
+        values: Forged<Fp>,
+    }
+
+    impl Circuit<Fp> for ChainCircuit {
+        type Config = AdditionChainConfig<Fp, 16>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { values: self.values.without_witnesses(), ..Self::default() }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(1);
+            AdditionChainConfig::configure_with_pool(cs, &mut pool, &CHAIN)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            match self.values.values() {
+                Some(values) => config.assign_with_values(layouter.namespace(|| "chain"), values.to_vec(), self.y)?,
+                None => config.assign(layouter.namespace(|| "chain"), self.x, self.y)?,
+            };
+            Ok(())
+        }
+    }
+
+    fn prover(x: u64, y: u64, values: Option<Vec<u64>>) -> MockProver<Fp> {
+        let k = 5;
+        let values = values.map_or_else(Forged::honest, |values| Forged::from_u64s(&values));
+        MockProver::run(k, &ChainCircuit { x: known(x), y: known(y), values }, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_chain() {
+        prover(4, 11, None).assert_satisfied();
+        prover(0, 3, None).assert_satisfied();
+        prover(6, 15, None).assert_satisfied();
+    }
+
+    #[test]
+    fn test_eval() {
+        let mut cs = ConstraintSystem::<Fp>::default();
+        let mut pool = ColumnPool::new(1);
+        let config = AdditionChainConfig::<Fp, 16>::configure_with_pool(&mut cs, &mut pool, &CHAIN);
+        assert_eq!(config.eval(4), Some(11));
+        assert_eq!(config.eval(6), Some(15));
+        //2 * 7 + 3 = 17 leaves the range
+        assert_eq!(config.eval(7), None);
+    }
+
+    #[test]
+    fn test_wrong_output() {
+        let permutation = |row| ExpectedFailure { name: "permutation".to_string(), region: "assign addition chain".to_string(), row };
+        expect_failures(&prover(4, 12, None), &[permutation(2), permutation(3)]);
+    }
+
+    #[test]
+    fn test_chain_sound() {
+        //intermediates that end on the claimed 12 but skip a step of the chain
+        let failure = ExpectedFailure::constraint("chain add", "next = cur + c", "assign addition chain", 1);
+        expect_failures(&prover(4, 12, Some(vec![4, 8, 12])), &[failure]);
+        //2 * 7 = 14 is fine, 17 isn't
+        expect_failures(&prover(7, 17, None), &[ExpectedFailure::lookup(0, "assign addition chain", 2)]);
+    }
+}
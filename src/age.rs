@@ -0,0 +1,167 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    pasta::Fp,
+    plonk::{Advice, Assigned, Circuit, Column, ConstraintSystem, Constraints, Error, Fixed, Instance, Selector},
+    poly::Rotation,
+};
+
+use crate::{bounds::BoundsConfig, column_pool::ColumnPool, date::MAX_DAYS, proving::Instances, report};
+
+pub const MIN_AGE: u64 = 18;
+pub const MAX_AGE: u64 = 150;
+
+//gregorian leap years, every 4th year except centuries not divisible by 400
+fn is_leap_year(year: u64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+//the birth year of an iso "yyyy-mm-dd" date, None unless the month and day are valid in
+//that year. only the year goes into the circuit, ages are counted in calendar years
+pub fn birth_year(date: &str) -> Option<u64> {
+    let mut parts = date.splitn(3, '-').map(|part| part.parse::<u64>().ok());
+    let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+    let mut max_day = *MAX_DAYS.get(month.checked_sub(1)? as usize)?;
+    //MAX_DAYS allows february 29 whatever the year
+    if month == 2 && !is_leap_year(year) {
+        max_day = 28;
+    }
+    (1..=max_day).contains(&day).then_some(year)
+}
+
+#[derive(Clone, Debug)]
+pub struct AgeConfig {
+    pub current_year: Column<Advice>,
+    pub birth_year: Column<Advice>,
+    pub instance: Column<Instance>,
+    pub q_age: Selector,
+    //MIN_AGE <= age <= MAX_AGE on the bounds value column, with the bounds pinned to
+    //constants so the prover can't pick them
+    pub bounds: BoundsConfig<Fp, 256>,
+}
+
+//proves a private birth year gives an age of MIN_AGE to MAX_AGE in the current year, which
+//is the only public input so the verifier picks it
+#[derive(Clone, Debug, Default)]
+pub struct AgeCircuit {
+    pub birth_year: Value<Fp>,
+}
+
+impl AgeCircuit {
+    pub fn from_birth_date(date: &str) -> Option<Self> {
+        birth_year(date).map(|year| Self { birth_year: Value::known(Fp::from(year)) })
+    }
+
+    pub fn public_inputs(current_year: u64) -> Instances {
+        vec![vec![Fp::from(current_year)]]
+    }
+}
+
+impl Circuit<Fp> for AgeCircuit {
+    type Config = AgeConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let mut pool = ColumnPool::new(5);
+        let bounds = BoundsConfig::configure_with_pool(cs, &mut pool);
+        let [current_year, birth_year]: [Column<Advice>; 2] = pool.advice_many(cs, 2).try_into().unwrap();
+        let instance = cs.instance_column();
+        let constants = cs.fixed_column();
+        let q_age = cs.selector();
+
+        cs.enable_equality(instance);
+        cs.enable_equality(bounds.min);
+        cs.enable_equality(bounds.max);
+        cs.enable_equality(current_year);
+        cs.enable_constant(constants);
+
+        cs.create_gate("age", |cs| {
+            let q = cs.query_selector(q_age);
+            let current = cs.query_advice(current_year, Rotation::cur());
+            let birth = cs.query_advice(birth_year, Rotation::cur());
+            let age = cs.query_advice(bounds.value, Rotation::cur());
+            Constraints::with_selector(q, [("age = current - birth", age - (current - birth))])
+        });
+        report::annotate_gate("age", "age");
+
+        AgeConfig { current_year, birth_year, instance, q_age, bounds }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        config.bounds.table.load(&mut layouter)?;
+
+        //the age is only known once the current year is read from the instance, so it is
+        //assigned first and tied to the subtraction after
+        let age = layouter.assign_region(
+            || "assign age",
+            |mut region| {
+                config.q_age.enable(&mut region, 0)?;
+                let current = region.assign_advice_from_instance(|| "current year", config.instance, 0, config.current_year, 0)?;
+                region.assign_advice(|| "birth year", config.birth_year, 0, || self.birth_year)?;
+                let age = current.value().copied() - self.birth_year;
+                region.assign_advice(|| "age", config.bounds.value, 0, || age.map(Assigned::from))
+            },
+        )?;
+
+        let min = Value::known(Assigned::from(Fp::from(MIN_AGE)));
+        let max = Value::known(Assigned::from(Fp::from(MAX_AGE)));
+        let (checked, min, max) = config.bounds.assign_with_bounds(layouter.namespace(|| "age bounds"), age.value().copied(), min, max)?;
+        layouter.assign_region(
+            || "pin age bounds",
+            |mut region| {
+                let fixed_min = region.assign_advice_from_constant(|| "min", config.bounds.min, 0, Assigned::from(Fp::from(MIN_AGE)))?;
+                let fixed_max = region.assign_advice_from_constant(|| "max", config.bounds.max, 0, Assigned::from(Fp::from(MAX_AGE)))?;
+                region.constrain_equal(fixed_min.cell(), min.cell())?;
+                region.constrain_equal(fixed_max.cell(), max.cell())?;
+                region.constrain_equal(age.cell(), checked.cell())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::dev::MockProver;
+
+    use super::*;
+    use crate::test_utils::{expect_failures, ExpectedFailure};
+
+    const CURRENT_YEAR: u64 = 2024;
+
+    fn prover(birth: u64, current_year: u64) -> MockProver<Fp> {
+        let k = 9;
+        let circuit = AgeCircuit { birth_year: Value::known(Fp::from(birth)) };
+        MockProver::run(k, &circuit, AgeCircuit::public_inputs(current_year)).unwrap()
+    }
+
+    #[test]
+    fn test_boundary_ages() {
+        prover(CURRENT_YEAR - 18, CURRENT_YEAR).assert_satisfied();
+        prover(CURRENT_YEAR - 150, CURRENT_YEAR).assert_satisfied();
+        //17 is below the minimum, 151 above the maximum
+        expect_failures(&prover(CURRENT_YEAR - 17, CURRENT_YEAR), &[ExpectedFailure::lookup(0, "assign bounds", 0)]);
+        expect_failures(&prover(CURRENT_YEAR - 151, CURRENT_YEAR), &[ExpectedFailure::lookup(1, "assign bounds", 0)]);
+        //the same witness a year earlier, as the verifier sees it
+        expect_failures(&prover(CURRENT_YEAR - 18, CURRENT_YEAR - 1), &[ExpectedFailure::lookup(0, "assign bounds", 0)]);
+    }
+
+    #[test]
+    fn test_from_birth_date() {
+        assert_eq!(birth_year("2006-02-28"), Some(2006));
+        assert_eq!(birth_year("2006-02-29"), None);
+        assert_eq!(birth_year("2004-02-29"), Some(2004));
+        assert_eq!(birth_year("2000-02-29"), Some(2000));
+        assert_eq!(birth_year("1900-02-29"), None);
+        assert_eq!(birth_year("2006-04-31"), None);
+        assert_eq!(birth_year("2006-13-01"), None);
+        assert_eq!(birth_year("2006-00-01"), None);
+        assert_eq!(birth_year("2006-04"), None);
+
+        let circuit = AgeCircuit::from_birth_date("2006-06-15").unwrap();
+        MockProver::run(9, &circuit, AgeCircuit::public_inputs(CURRENT_YEAR)).unwrap().assert_satisfied();
+    }
+}
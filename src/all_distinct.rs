@@ -0,0 +1,144 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::{Field, FieldExt},
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, report};
+
+//checks a set of cells holds pairwise distinct values. every pair gets a row with both
+//values copied in and (a - b) * inv = 1, which has no solution for inv when a == b. that is
+//n * (n - 1) / 2 rows for n cells, fine for the handful of cells it is meant for
+#[derive(Clone, Debug)]
+pub struct AllDistinctConfig<F: FieldExt> {
+    pub a: Column<Advice>,
+    pub b: Column<Advice>,
+    pub inv: Column<Advice>,
+    pub q_distinct: Selector,
+    _marker: std::marker::PhantomData<F>,
+}
+
+//the row of the pair (i, j), i < j, in a region of n cells
+pub fn pair_offset(n: usize, i: usize, j: usize) -> usize {
+    assert!(i < j && j < n);
+    i * n - i * (i + 1) / 2 + (j - i - 1)
+}
+
+impl<F: FieldExt> AllDistinctConfig<F> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, a: Column<Advice>, b: Column<Advice>, inv: Column<Advice>) -> Self {
+        let q_distinct = cs.selector();
+
+        cs.enable_equality(a);
+        cs.enable_equality(b);
+
+        cs.create_gate("all distinct", |cs| {
+            let q = cs.query_selector(q_distinct);
+            let a = cs.query_advice(a, Rotation::cur());
+            let b = cs.query_advice(b, Rotation::cur());
+            let inv = cs.query_advice(inv, Rotation::cur());
+            Constraints::with_selector(q, [("(a - b) * inv = 1", (a - b) * inv - Expression::Constant(F::one()))])
+        });
+        report::annotate_gate("all_distinct", "all distinct");
+
+        Self { a, b, inv, q_distinct, _marker: std::marker::PhantomData }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 3);
+        Self::configure(cs, columns[0], columns[1], columns[2])
+    }
+
+    pub fn assign(&self, layouter: impl Layouter<F>, cells: &[AssignedCell<F, F>]) -> Result<(), CircuitsError> {
+        self.assign_annotated(layouter, "assign all distinct", cells)
+    }
+
+    //label names the region, so a circuit checking several sets can tell which one failed.
+    //the failing pair is at pair_offset within it
+    pub fn assign_annotated(&self, mut layouter: impl Layouter<F>, label: impl Into<String>, cells: &[AssignedCell<F, F>]) -> Result<(), CircuitsError> {
+        let label = label.into();
+        layouter.assign_region(
+            || label.as_str(),
+            |mut region| {
+                let mut offset = 0;
+                for (i, a) in cells.iter().enumerate() {
+                    for (j, b) in cells.iter().enumerate().skip(i + 1) {
+                        self.q_distinct.enable(&mut region, offset)?;
+                        a.copy_advice(|| format!("a {}", i), &mut region, self.a, offset)?;
+                        b.copy_advice(|| format!("b {}", j), &mut region, self.b, offset)?;
+                        //equal values have no inverse, the gate fails whatever goes here
+                        let inv = a.value().zip(b.value()).map(|(a, b)| (*a - *b).invert().unwrap_or(F::zero()));
+                        region.assign_advice(|| "inv", self.inv, offset, || inv)?;
+                        offset += 1;
+                    }
+                }
+                Ok(())
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, ExpectedFailure};
+
+    #[derive(Default)]
+    struct AllDistinctCircuit {
+        values: [Value<Fp>; 4],
+    }
+
+    impl Circuit<Fp> for AllDistinctCircuit {
+        type Config = (Column<Advice>, AllDistinctConfig<Fp>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(4);
+            let values = pool.advice(cs);
+            cs.enable_equality(values);
+            (values, AllDistinctConfig::configure_with_pool(cs, &mut pool))
+        }
+
+        fn synthesize(&self, (values, config): Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let cells = layouter.assign_region(
+                || "assign values",
+                |mut region| {
+                    self.values.iter().enumerate().map(|(offset, v)| region.assign_advice(|| "value", values, offset, || *v)).collect::<Result<Vec<_>, Error>>()
+                },
+            )?;
+            config.assign(layouter.namespace(|| "all distinct"), &cells)?;
+            Ok(())
+        }
+    }
+
+    fn prover(values: [u64; 4]) -> MockProver<Fp> {
+        let k = 5;
+        let circuit = AllDistinctCircuit { values: values.map(|v| Value::known(Fp::from(v))) };
+        MockProver::run(k, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_pair_offset() {
+        let pairs: Vec<_> = (0..4).flat_map(|i| (i + 1..4).map(move |j| (i, j))).collect();
+        for (offset, (i, j)) in pairs.into_iter().enumerate() {
+            assert_eq!(pair_offset(4, i, j), offset);
+        }
+    }
+
+    #[test]
+    fn test_all_distinct() {
+        prover([0, 1, 2, 3]).assert_satisfied();
+        prover([7, 0, 100, 3]).assert_satisfied();
+
+        let failure = |i, j| ExpectedFailure::constraint("all distinct", "(a - b) * inv = 1", "assign all distinct", pair_offset(4, i, j));
+        expect_failures(&prover([5, 1, 5, 3]), &[failure(0, 2)]);
+        expect_failures(&prover([2, 2, 2, 3]), &[failure(0, 1), failure(0, 2), failure(1, 2)]);
+    }
+}
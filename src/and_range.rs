@@ -0,0 +1,185 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Assigned, Column, ConstraintSystem, Error},
+};
+
+use std::marker::PhantomData;
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, RangeCheck, RangeConstrained};
+
+//runs two range checks on the same cell, the value is only accepted if both hold
+#[derive(Clone, Debug)]
+pub struct AndRangeConfig<F: FieldExt, A: RangeCheck<F>, B: RangeCheck<F>> {
+    pub value: Column<Advice>,
+    pub first: A,
+    pub second: B,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, A: RangeCheck<F>, B: RangeCheck<F>> AndRangeConfig<F, A, B> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, value: Column<Advice>, first: A, second: B) -> Self {
+        cs.enable_equality(value);
+        Self {
+            value,
+            first,
+            second,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>, first: A, second: B) -> Self {
+        let value = pool.advice(cs);
+        Self::configure(cs, value, first, second)
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), CircuitsError> {
+        self.first.load(layouter)?;
+        self.second.load(layouter)
+    }
+
+    pub fn assign(&self, mut layouter: impl Layouter<F>, value: Value<Assigned<F>>) -> Result<RangeConstrained<F>, CircuitsError> {
+        let cell = layouter.assign_region(
+            || "assign and range value",
+            |mut region| region.assign_advice(|| "value", self.value, 0, || value),
+        )?;
+        //both checks copy constrain against the same cell
+        self.first.check_cell(layouter.namespace(|| "first check"), &cell)?;
+        self.second.check_cell(layouter.namespace(|| "second check"), &cell)?;
+        Ok(RangeConstrained::Assigned(cell))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Selector, TableColumn},
+        poly::Rotation,
+    };
+
+    use super::*;
+    use crate::test_utils::value_u64;
+    use crate::range_lookup::RangeCheckLookupConfig;
+
+    //membership in the even numbers below 2 * HALF
+    #[derive(Clone, Debug)]
+    struct EvenCheckConfig<F: FieldExt, const HALF: usize> {
+        value: Column<Advice>,
+        q_enable: Selector,
+        table: TableColumn,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt, const HALF: usize> EvenCheckConfig<F, HALF> {
+        fn configure(cs: &mut ConstraintSystem<F>, value: Column<Advice>) -> Self {
+            let q_enable = cs.complex_selector();
+            let table = cs.lookup_table_column();
+            cs.enable_equality(value);
+            cs.lookup(|cs| {
+                let q = cs.query_selector(q_enable);
+                let v = cs.query_advice(value, Rotation::cur());
+                vec![(q * v, table)]
+            });
+            Self {
+                value,
+                q_enable,
+                table,
+                _marker: PhantomData,
+            }
+        }
+    }
+
+    impl<F: FieldExt, const HALF: usize> RangeCheck<F> for EvenCheckConfig<F, HALF> {
+        fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), CircuitsError> {
+            layouter.assign_table(
+                || "assign even table",
+                |mut table| {
+                    for i in 0..HALF {
+                        table.assign_cell(|| "even", self.table, i, || Value::known(F::from(2 * i as u64)))?;
+                    }
+                    Ok(())
+                },
+            ).map_err(CircuitsError::from)
+        }
+
+        fn check_value(&self, mut layouter: impl Layouter<F>, value: Value<Assigned<F>>) -> Result<RangeConstrained<F>, CircuitsError> {
+            layouter.assign_region(
+                || "assign even",
+                |mut region| {
+                    self.q_enable.enable(&mut region, 0)?;
+                    region.assign_advice(|| "even", self.value, 0, || value).map(RangeConstrained::Assigned)
+                },
+            ).map_err(CircuitsError::from)
+        }
+
+        fn check_cell(&self, mut layouter: impl Layouter<F>, cell: &AssignedCell<Assigned<F>, F>) -> Result<RangeConstrained<F>, CircuitsError> {
+            layouter.assign_region(
+                || "check even",
+                |mut region| {
+                    self.q_enable.enable(&mut region, 0)?;
+                    cell.copy_advice(|| "even", &mut region, self.value, 0).map(RangeConstrained::Assigned)
+                },
+            ).map_err(CircuitsError::from)
+        }
+    }
+
+    type Config<F> = AndRangeConfig<F, RangeCheckLookupConfig<F, 100>, EvenCheckConfig<F, 64>>;
+
+    #[derive(Default)]
+    struct AndRangeCircuit<F: FieldExt> {
+        value: Value<Assigned<F>>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for AndRangeCircuit<F> {
+        type Config = Config<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let value = cs.advice_column();
+            let below = cs.advice_column();
+            let even = cs.advice_column();
+            let below = RangeCheckLookupConfig::configure(cs, below);
+            let even = EvenCheckConfig::configure(cs, even);
+            AndRangeConfig::configure(cs, value, below, even)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            config.load(&mut layouter)?;
+            config.assign(layouter.namespace(|| "and range"), self.value)?;
+            Ok(())
+        }
+    }
+
+    fn verify(value: u64) -> bool {
+        let k = 7;
+        let circuit = AndRangeCircuit::<Fp> {
+            value: value_u64(value),
+        };
+        MockProver::run(k, &circuit, vec![]).unwrap().verify().is_ok()
+    }
+
+    #[test]
+    fn test_complete() {
+        for value in [0, 42, 98] {
+            assert!(verify(value));
+        }
+    }
+
+    #[test]
+    fn test_sound() {
+        //odd but below 100
+        assert!(!verify(43));
+        //even but not below 100
+        assert!(!verify(102));
+        //neither
+        assert!(!verify(101));
+    }
+}
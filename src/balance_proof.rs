@@ -0,0 +1,181 @@
+#![allow(warnings, unused)]
+use halo2_gadgets::poseidon::{
+    primitives::{self as poseidon, ConstantLength, P128Pow5T3},
+    Hash, Pow5Chip, Pow5Config,
+};
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    pasta::Fp,
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+};
+
+use crate::{column_pool::ColumnPool, monotonic::MonotonicConfig, proving::Instances};
+
+//balances and thresholds are u64s
+pub const BALANCE_BITS: usize = 64;
+//k the circuit fits in, the three 64 bit decompositions take most of the rows
+pub const BALANCE_PROOF_K: u32 = 9;
+
+//instance rows
+const COMMITMENT_ROW: usize = 0;
+const THRESHOLD_ROW: usize = 1;
+
+//the commitment the circuit opens, poseidon(balance, blinding)
+pub fn commit(balance: u64, blinding: Fp) -> Fp {
+    poseidon::Hash::<_, P128Pow5T3, ConstantLength<2>, 3, 2>::init().hash([Fp::from(balance), blinding])
+}
+
+#[derive(Clone, Debug)]
+pub struct BalanceProofConfig {
+    //balance, blinding and the threshold copied from the instance
+    pub private: Column<Advice>,
+    pub instance: Column<Instance>,
+    //threshold <= balance as a non-decreasing column of the two, so balance - threshold has
+    //to fit in BALANCE_BITS. its bit decomposition also range checks both values
+    pub ge: MonotonicConfig<Fp, BALANCE_BITS>,
+    pub poseidon: Pow5Config<Fp, 3, 2>,
+}
+
+//proves the balance opening a public commitment is at least a public threshold, without
+//revealing it. the instance column holds [commitment, threshold]
+#[derive(Clone, Debug, Default)]
+pub struct BalanceProofCircuit {
+    balance: Value<Fp>,
+    blinding: Value<Fp>,
+}
+
+impl BalanceProofCircuit {
+    pub fn builder() -> BalanceProofBuilder {
+        BalanceProofBuilder::default()
+    }
+}
+
+impl Circuit<Fp> for BalanceProofCircuit {
+    type Config = BalanceProofConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let mut pool = ColumnPool::new(5);
+        let private = pool.advice(cs);
+        let instance = cs.instance_column();
+        cs.enable_equality(private);
+        cs.enable_equality(instance);
+        let ge = MonotonicConfig::configure_with_pool(cs, &mut pool, false);
+
+        let state = [cs.advice_column(), cs.advice_column(), cs.advice_column()];
+        let partial_sbox = cs.advice_column();
+        let rc_a = [cs.fixed_column(), cs.fixed_column(), cs.fixed_column()];
+        let rc_b = [cs.fixed_column(), cs.fixed_column(), cs.fixed_column()];
+        cs.enable_constant(rc_b[0]);
+        let poseidon = Pow5Chip::configure::<P128Pow5T3>(cs, state, partial_sbox, rc_a, rc_b);
+
+        BalanceProofConfig { private, instance, ge, poseidon }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        let (balance, blinding, threshold) = layouter.assign_region(
+            || "load balance",
+            |mut region| {
+                let balance = region.assign_advice(|| "balance", config.private, 0, || self.balance)?;
+                let blinding = region.assign_advice(|| "blinding", config.private, 1, || self.blinding)?;
+                let threshold = region.assign_advice_from_instance(|| "threshold", config.instance, THRESHOLD_ROW, config.private, 2)?;
+                Ok((balance, blinding, threshold))
+            },
+        )?;
+
+        //the step from threshold to balance is decomposed, which bounds it. the values
+        //themselves are decomposed too, or a huge threshold could wrap around to a small
+        //step
+        config.ge.bits.decompose_cell(layouter.namespace(|| "balance bits"), &balance, BALANCE_BITS)?;
+        config.ge.bits.decompose_cell(layouter.namespace(|| "threshold bits"), &threshold, BALANCE_BITS)?;
+        config.ge.assign_cells(layouter.namespace(|| "balance >= threshold"), &[threshold, balance.clone()])?;
+
+        let hasher = Hash::<_, _, P128Pow5T3, ConstantLength<2>, 3, 2>::init(
+            Pow5Chip::construct(config.poseidon),
+            layouter.namespace(|| "init commitment"),
+        )?;
+        let commitment = hasher.hash(layouter.namespace(|| "commitment"), [balance, blinding])?;
+        layouter.constrain_instance(commitment.cell(), config.instance, COMMITMENT_ROW)
+    }
+}
+
+//collects the witness and public values. fields left unset stay unknown, so an empty
+//builder gives the circuit keygen needs
+#[derive(Clone, Debug, Default)]
+pub struct BalanceProofBuilder {
+    balance: Option<u64>,
+    blinding: Option<Fp>,
+    threshold: Option<u64>,
+}
+
+impl BalanceProofBuilder {
+    pub fn balance(mut self, balance: u64) -> Self {
+        self.balance = Some(balance);
+        self
+    }
+
+    pub fn blinding(mut self, blinding: Fp) -> Self {
+        self.blinding = Some(blinding);
+        self
+    }
+
+    pub fn threshold(mut self, threshold: u64) -> Self {
+        self.threshold = Some(threshold);
+        self
+    }
+
+    pub fn build(&self) -> BalanceProofCircuit {
+        BalanceProofCircuit {
+            balance: self.balance.map_or(Value::unknown(), |balance| Value::known(Fp::from(balance))),
+            blinding: self.blinding.map_or(Value::unknown(), Value::known),
+        }
+    }
+
+    //[commitment, threshold], None until balance, blinding and threshold are all set
+    pub fn public_inputs(&self) -> Option<Instances> {
+        let commitment = commit(self.balance?, self.blinding?);
+        Some(vec![vec![commitment, Fp::from(self.threshold?)]])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::dev::MockProver;
+
+    use super::*;
+
+    fn verify(balance: u64, threshold: u64) -> bool {
+        let builder = BalanceProofCircuit::builder().balance(balance).blinding(Fp::from(12345)).threshold(threshold);
+        let prover = MockProver::run(BALANCE_PROOF_K, &builder.build(), builder.public_inputs().unwrap()).unwrap();
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn test_balance_proof() {
+        assert!(verify(1000, 500));
+        assert!(verify(1000, 1000));
+        assert!(verify(u64::MAX, 0));
+        assert!(!verify(999, 1000));
+        assert!(!verify(0, u64::MAX));
+    }
+
+    #[test]
+    fn test_forged_commitment() {
+        let builder = BalanceProofCircuit::builder().balance(1000).blinding(Fp::from(12345)).threshold(500);
+        let mut instances = builder.public_inputs().unwrap();
+        //a commitment to a larger balance than the one witnessed
+        instances[0][COMMITMENT_ROW] = commit(2000, Fp::from(12345));
+        let prover = MockProver::run(BALANCE_PROOF_K, &builder.build(), instances).unwrap();
+        assert!(prover.verify().is_err());
+
+        //and the right balance with a different blinding
+        let mut instances = builder.public_inputs().unwrap();
+        instances[0][COMMITMENT_ROW] = commit(1000, Fp::from(1));
+        let prover = MockProver::run(BALANCE_PROOF_K, &builder.build(), instances).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
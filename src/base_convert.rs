@@ -0,0 +1,226 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use std::marker::PhantomData;
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, report, running_sum::RunningSumConfig, rows::{self, RowBudget, TablePadding}};
+
+//the rows (digit, bit0, bit1) with digit = bit0 + 2 * bit1. (0, 0, 0) is one of them, so
+//rows off the selector hit a real row
+#[derive(Clone, Debug)]
+pub struct Base4TableConfig<F: FieldExt> {
+    pub digit: TableColumn,
+    pub bit0: TableColumn,
+    pub bit1: TableColumn,
+    //what load_padded puts in the rows past the 4 digits
+    pub padding: TablePadding,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Base4TableConfig<F> {
+    pub fn configure(cs: &mut ConstraintSystem<F>) -> Self {
+        Self::configure_padded(cs, TablePadding::Unpadded)
+    }
+
+    pub fn configure_padded(cs: &mut ConstraintSystem<F>, padding: TablePadding) -> Self {
+        Self {
+            digit: cs.lookup_table_column(),
+            bit0: cs.lookup_table_column(),
+            bit1: cs.lookup_table_column(),
+            padding: padding.for_rows(4),
+            _marker: PhantomData,
+        }
+    }
+
+    fn load_rows(&self, layouter: &mut impl Layouter<F>, budget: Option<RowBudget>) -> Result<(), CircuitsError> {
+        let rows: Vec<_> = (0..4u64).map(|digit| vec![F::from(digit), F::from(digit & 1), F::from(digit >> 1)]).collect();
+        rows::load_table(layouter, "base 4 table", &[("digit", self.digit), ("bit0", self.bit0), ("bit1", self.bit1)], &rows, self.padding, budget)
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), CircuitsError> {
+        self.load_rows(layouter, None)
+    }
+
+    //load that first checks the table fits in the budget, then fills the rest of its usable
+    //rows as the padding says
+    pub fn load_padded(&self, layouter: &mut impl Layouter<F>, budget: RowBudget) -> Result<(), CircuitsError> {
+        self.load_rows(layouter, Some(budget))
+    }
+}
+
+//checks n base 4 digits and 2n bits are the same integer, both little endian.
+//
+//row i holds digit i with bits 2i and 2i + 1, the triple looked up in the table. the digits
+//are the terms of a base 4 running sum, most significant first like LeBytesConfig, whose
+//last row is the value. the table ties every digit to its two bits, so the bits recombine
+//to the same value without a sum of their own
+#[derive(Clone, Debug)]
+pub struct BaseConvertConfig<F: FieldExt> {
+    pub bit0: Column<Advice>,
+    pub bit1: Column<Advice>,
+    pub sum: RunningSumConfig<F>,
+    pub q_lookup: Selector,
+    pub table: Base4TableConfig<F>,
+}
+
+impl<F: FieldExt> BaseConvertConfig<F> {
+    pub fn configure(
+        cs: &mut ConstraintSystem<F>,
+        digit: Column<Advice>,
+        bit0: Column<Advice>,
+        bit1: Column<Advice>,
+        acc: Column<Advice>,
+        table: Base4TableConfig<F>,
+    ) -> Self {
+        let q_lookup = cs.complex_selector();
+        let sum = RunningSumConfig::configure(cs, digit, acc, 4);
+
+        for column in [bit0, bit1] {
+            cs.enable_equality(column);
+        }
+
+        let lookup_index = cs.lookup(|cs| {
+            let q = cs.query_selector(q_lookup);
+            let digit = cs.query_advice(digit, Rotation::cur());
+            let bit0 = cs.query_advice(bit0, Rotation::cur());
+            let bit1 = cs.query_advice(bit1, Rotation::cur());
+            vec![(q.clone() * digit, table.digit), (q.clone() * bit0, table.bit0), (q * bit1, table.bit1)]
+        });
+        report::annotate_lookup("base_convert", lookup_index);
+
+        Self { bit0, bit1, sum, q_lookup, table }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 4);
+        let table = Base4TableConfig::configure(cs);
+        Self::configure(cs, columns[0], columns[1], columns[2], columns[3], table)
+    }
+
+    //copies in digit and bit cells assigned elsewhere, little endian, and returns the cell of
+    //the integer both of them are
+    pub fn assign(&self, mut layouter: impl Layouter<F>, digits: &[AssignedCell<F, F>], bits: &[AssignedCell<F, F>]) -> Result<AssignedCell<F, F>, CircuitsError> {
+        if digits.is_empty() {
+            return Err(CircuitsError::LengthMismatch { expected: 1, got: 0 });
+        }
+        if bits.len() != 2 * digits.len() {
+            return Err(CircuitsError::LengthMismatch { expected: 2 * digits.len(), got: bits.len() });
+        }
+        let n = digits.len();
+        layouter.assign_region(
+            || "assign base convert",
+            |mut region| {
+                for offset in 0..n {
+                    //most significant digit first
+                    let i = n - 1 - offset;
+                    self.q_lookup.enable(&mut region, offset)?;
+                    bits[2 * i].copy_advice(|| "bit0", &mut region, self.bit0, offset)?;
+                    bits[2 * i + 1].copy_advice(|| "bit1", &mut region, self.bit1, offset)?;
+                }
+                let terms: Vec<_> = digits.iter().rev().map(|digit| digit.value().copied()).collect();
+                let rows = self.sum.assign_rows(&mut region, 0, &terms, None)?;
+                for ((copy, _), digit) in rows.iter().zip(digits.iter().rev()) {
+                    region.constrain_equal(digit.cell(), copy.cell())?;
+                }
+                Ok(rows[n - 1].1.clone())
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, ExpectedFailure};
+
+    #[derive(Default)]
+    struct ConvertCircuit {
+        //little endian
+        digits: Vec<u64>,
+        bits: Vec<u64>,
+        expected: Option<u64>,
+    }
+
+    impl Circuit<Fp> for ConvertCircuit {
+        type Config = BaseConvertConfig<Fp>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { digits: self.digits.clone(), bits: self.bits.clone(), expected: self.expected }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(5);
+            BaseConvertConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            let (digits, bits) = layouter.assign_region(
+                || "assign digits and bits",
+                |mut region| {
+                    let digits = self.digits.iter().enumerate().map(|(offset, digit)| {
+                        region.assign_advice(|| "digit", config.sum.term, offset, || Value::known(Fp::from(*digit)))
+                    }).collect::<Result<Vec<_>, Error>>()?;
+                    let bits = self.bits.iter().enumerate().map(|(offset, bit)| {
+                        region.assign_advice(|| "bit", config.bit0, offset, || Value::known(Fp::from(*bit)))
+                    }).collect::<Result<Vec<_>, Error>>()?;
+                    Ok((digits, bits))
+                },
+            )?;
+            let value = config.assign(layouter.namespace(|| "convert"), &digits, &bits)?;
+            if let Some(expected) = self.expected {
+                value.value().assert_if_known(|value| **value == Fp::from(expected));
+            }
+            Ok(())
+        }
+    }
+
+    fn circuit(value: u64) -> ConvertCircuit {
+        ConvertCircuit {
+            digits: (0..8).map(|i| (value >> (2 * i)) & 3).collect(),
+            bits: (0..16).map(|i| (value >> i) & 1).collect(),
+            expected: Some(value),
+        }
+    }
+
+    fn prover(circuit: ConvertCircuit) -> MockProver<Fp> {
+        let k = 6;
+        MockProver::run(k, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for value in (0..8).map(|_| rng.gen::<u16>() as u64).chain([0, 0xffff, 0x8001]) {
+            prover(circuit(value)).assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_mismatched_digit() {
+        //0xb4 is the digits 0, 1, 3, 2. with digit 1 changed to 2 the digits add up to 0xb8
+        //while the bits are still 0xb4, and (2, 1, 0) isn't a table row
+        let mut mismatched = circuit(0xb4);
+        mismatched.digits[1] = 2;
+        mismatched.expected = None;
+        assert!(prover(mismatched).verify().is_err());
+
+        //the digits 4, 0 add up to 4 like the bits 0, 0, 1, 0 do, so only the table catches
+        //them, on the rows of digit 0 and digit 1
+        let mut mismatched = circuit(4);
+        mismatched.digits[0] = 4;
+        mismatched.digits[1] = 0;
+        mismatched.expected = None;
+        let failures = [ExpectedFailure::lookup(0, "assign base convert", 6), ExpectedFailure::lookup(0, "assign base convert", 7)];
+        expect_failures(&prover(mismatched), &failures);
+    }
+}
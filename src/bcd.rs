@@ -0,0 +1,160 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Selector},
+    poly::Rotation,
+};
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, field::lower_128, range_lookup::RangeTableConfig, report, running_sum::RunningSumConfig};
+
+//checks value is NUM_DIGITS packed bcd digits, i.e. every nibble of it is a decimal digit.
+//
+//the same recomposition as LeBytesConfig with base 16, the nibbles are the terms of a
+//running sum most significant first and it ends at the value. the digits are looked
+//up in 0..10 rather than 0..16, which is what rules out the nibbles 0xA to 0xF
+#[derive(Clone, Debug)]
+pub struct BcdConfig<F: FieldExt, const NUM_DIGITS: usize> {
+    pub sum: RunningSumConfig<F>,
+    pub q_digit: Selector,
+    pub table: RangeTableConfig<F, 10>,
+}
+
+impl<F: FieldExt, const NUM_DIGITS: usize> BcdConfig<F, NUM_DIGITS> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, digit: Column<Advice>, acc: Column<Advice>) -> Self {
+        //the recomposition can't wrap around the modulus, and the digits are read off the
+        //lower 128 bits
+        assert!(NUM_DIGITS > 0 && 4 * NUM_DIGITS <= 128 && 4 * NUM_DIGITS < F::NUM_BITS as usize);
+        let q_digit = cs.complex_selector();
+        let sum = RunningSumConfig::configure(cs, digit, acc, 16);
+        let table = RangeTableConfig::configure(cs);
+
+        let lookup_index = cs.lookup(|cs| {
+            let q = cs.query_selector(q_digit);
+            let digit = cs.query_advice(digit, Rotation::cur());
+            vec![(q * digit, table.value)]
+        });
+        report::annotate_lookup("bcd", lookup_index);
+
+        Self { sum, q_digit, table }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 2);
+        Self::configure(cs, columns[0], columns[1])
+    }
+
+    //the digit cells of a value cell assigned elsewhere, least significant first
+    pub fn assign(&self, layouter: impl Layouter<F>, value: &AssignedCell<F, F>) -> Result<[AssignedCell<F, F>; NUM_DIGITS], CircuitsError> {
+        let digits = value.value().map(|v| {
+            let v = lower_128(v);
+            let mut digits = [F::zero(); NUM_DIGITS];
+            for (i, digit) in digits.iter_mut().enumerate() {
+                *digit = F::from(((v >> (4 * i)) & 0xf) as u64);
+            }
+            digits
+        });
+        self.assign_with_digits(layouter, value, digits)
+    }
+
+    //digits are least significant first
+    fn assign_with_digits(&self, mut layouter: impl Layouter<F>, value: &AssignedCell<F, F>, digits: Value<[F; NUM_DIGITS]>) -> Result<[AssignedCell<F, F>; NUM_DIGITS], CircuitsError> {
+        layouter.assign_region(
+            || "assign bcd",
+            |mut region| {
+                //most significant digit first
+                let terms: Vec<_> = (0..NUM_DIGITS).map(|offset| digits.map(|digits| digits[NUM_DIGITS - 1 - offset])).collect();
+                for offset in 0..NUM_DIGITS {
+                    self.q_digit.enable(&mut region, offset)?;
+                }
+                let rows = self.sum.assign_rows(&mut region, 0, &terms, None)?;
+                region.constrain_equal(rows[NUM_DIGITS - 1].1.cell(), value.cell())?;
+
+                Ok(rows.into_iter().rev().map(|(digit, _)| digit).collect::<Vec<_>>().try_into().unwrap())
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, ExpectedFailure};
+
+    #[derive(Default)]
+    struct BcdCircuit {
+        value: Value<Fp>,
+        //when set, witnesses these digits instead of the value's own nibbles
+        digits: Option<Value<[Fp; 2]>>,
+        //checked against the digit cells
+        expected: Option<[u64; 2]>,
+    }
+
+    impl Circuit<Fp> for BcdCircuit {
+        type Config = BcdConfig<Fp, 2>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(2);
+            BcdConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            let value = layouter.assign_region(|| "assign value", |mut region| region.assign_advice(|| "value", config.sum.acc, 0, || self.value))?;
+            let digits = match self.digits {
+                Some(digits) => config.assign_with_digits(layouter.namespace(|| "bcd"), &value, digits)?,
+                None => config.assign(layouter.namespace(|| "bcd"), &value)?,
+            };
+            if let Some(expected) = self.expected {
+                for (cell, expected) in digits.iter().zip(expected) {
+                    cell.value().assert_if_known(|v| **v == Fp::from(expected));
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn prover(circuit: BcdCircuit) -> MockProver<Fp> {
+        let k = 5;
+        MockProver::run(k, &circuit, vec![]).unwrap()
+    }
+
+    fn honest(value: u64, expected: Option<[u64; 2]>) -> BcdCircuit {
+        BcdCircuit { value: Value::known(Fp::from(value)), digits: None, expected }
+    }
+
+    #[test]
+    fn test_bcd() {
+        prover(honest(0x99, Some([9, 9]))).assert_satisfied();
+        prover(honest(0x19, Some([9, 1]))).assert_satisfied();
+        prover(honest(0x00, Some([0, 0]))).assert_satisfied();
+    }
+
+    #[test]
+    fn test_non_decimal_nibble() {
+        //0x1a is a fine byte, but its low nibble isn't a digit
+        expect_failures(&prover(honest(0x1a, None)), &[ExpectedFailure::lookup(0, "assign bcd", 1)]);
+        expect_failures(&prover(honest(0xa0, None)), &[ExpectedFailure::lookup(0, "assign bcd", 0)]);
+        //0x100 doesn't fit in two digits, its nibbles 0, 0 don't add back up to it
+        assert!(prover(honest(0x100, None)).verify().is_err());
+    }
+
+    #[test]
+    fn test_forged_digits() {
+        //10 * 16 + 0 adds up to 0xa0 but 10 isn't a digit
+        let digits = Value::known([Fp::from(0), Fp::from(10)]);
+        let circuit = BcdCircuit { value: Value::known(Fp::from(0xa0)), digits: Some(digits), expected: None };
+        expect_failures(&prover(circuit), &[ExpectedFailure::lookup(0, "assign bcd", 0)]);
+        //9 * 16 + 16 = 0xa0 with an overflowing low digit
+        let digits = Value::known([Fp::from(16), Fp::from(9)]);
+        let circuit = BcdCircuit { value: Value::known(Fp::from(0xa0)), digits: Some(digits), expected: None };
+        expect_failures(&prover(circuit), &[ExpectedFailure::lookup(0, "assign bcd", 1)]);
+    }
+}
@@ -0,0 +1,302 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use std::marker::PhantomData;
+
+use crate::{column_pool::ColumnPool, error::CircuitsError};
+
+//little endian bits of the canonical representation of v
+pub fn le_bits<F: FieldExt>(v: &F, num_bits: usize) -> Vec<bool> {
+    let repr = v.to_repr();
+    let bytes = repr.as_ref();
+    (0..num_bits).map(|i| (bytes[i / 8] >> (i % 8)) & 1 == 1).collect()
+}
+
+//decomposes a value into num_bits boolean cells.
+//
+//the bits are laid out most significant first so the running sum acc_i = 2 * acc_{i-1} + bit_i
+//needs no per-row coefficients, and the last acc is constrained equal to the value. the same
+//layout can also keep a running count of set bits in the weight column.
+#[derive(Clone, Debug)]
+pub struct BitDecomposeConfig<F: FieldExt> {
+    pub bit: Column<Advice>,
+    pub acc: Column<Advice>,
+    pub weight: Column<Advice>,
+    pub q_bool: Selector,
+    pub q_first: Selector,
+    pub q_step: Selector,
+    pub q_weight_first: Selector,
+    pub q_weight_step: Selector,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> BitDecomposeConfig<F> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, bit: Column<Advice>, acc: Column<Advice>, weight: Column<Advice>) -> Self {
+        let q_bool = cs.selector();
+        let q_first = cs.selector();
+        let q_step = cs.selector();
+        let q_weight_first = cs.selector();
+        let q_weight_step = cs.selector();
+
+        cs.enable_equality(bit);
+        cs.enable_equality(acc);
+        cs.enable_equality(weight);
+
+        cs.create_gate("bit boolean", |cs| {
+            let q = cs.query_selector(q_bool);
+            let b = cs.query_advice(bit, Rotation::cur());
+            Constraints::with_selector(q, [("boolean", b.clone() * (Expression::Constant(F::one()) - b))])
+        });
+
+        cs.create_gate("bit recompose first", |cs| {
+            let q = cs.query_selector(q_first);
+            let b = cs.query_advice(bit, Rotation::cur());
+            let acc = cs.query_advice(acc, Rotation::cur());
+            Constraints::with_selector(q, [("acc", acc - b)])
+        });
+
+        cs.create_gate("bit recompose step", |cs| {
+            let q = cs.query_selector(q_step);
+            let b = cs.query_advice(bit, Rotation::cur());
+            let acc_prev = cs.query_advice(acc, Rotation::prev());
+            let acc = cs.query_advice(acc, Rotation::cur());
+            Constraints::with_selector(q, [("acc", acc - (acc_prev * F::from(2) + b))])
+        });
+
+        cs.create_gate("popcount first", |cs| {
+            let q = cs.query_selector(q_weight_first);
+            let b = cs.query_advice(bit, Rotation::cur());
+            let w = cs.query_advice(weight, Rotation::cur());
+            Constraints::with_selector(q, [("weight", w - b)])
+        });
+
+        cs.create_gate("popcount step", |cs| {
+            let q = cs.query_selector(q_weight_step);
+            let b = cs.query_advice(bit, Rotation::cur());
+            let w_prev = cs.query_advice(weight, Rotation::prev());
+            let w = cs.query_advice(weight, Rotation::cur());
+            Constraints::with_selector(q, [("weight", w - (w_prev + b))])
+        });
+
+        Self {
+            bit,
+            acc,
+            weight,
+            q_bool,
+            q_first,
+            q_step,
+            q_weight_first,
+            q_weight_step,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 3);
+        Self::configure(cs, columns[0], columns[1], columns[2])
+    }
+
+    //a decomposition into num_bits bits is only unique while 2^num_bits <= p, i.e. up to
+    //F::CAPACITY bits. at F::NUM_BITS both x and x + p fit and "x < 2^n" proves nothing
+    fn check_num_bits(num_bits: usize) -> Result<(), CircuitsError> {
+        if num_bits == 0 || num_bits > F::CAPACITY as usize {
+            return Err(CircuitsError::RangeTooLarge { gadget: "bit_decompose", range: num_bits as u64, max: F::CAPACITY as u64 });
+        }
+        Ok(())
+    }
+
+    //lays out the bits of value from offset 0, returning the final acc, the bits little
+    //endian and the final weight when counting. full width is allowed here for HighZeroConfig,
+    //which constrains the high part to zero itself
+    pub(crate) fn assign_bits(
+        &self,
+        region: &mut Region<'_, F>,
+        value: Value<F>,
+        num_bits: usize,
+        count: bool,
+    ) -> Result<(AssignedCell<F, F>, Vec<AssignedCell<F, F>>, Option<AssignedCell<F, F>>), Error> {
+        assert!(num_bits > 0 && num_bits <= F::NUM_BITS as usize);
+        let bits = value.map(|v| le_bits(&v, num_bits));
+
+        let mut acc = Value::known(F::zero());
+        let mut weight = Value::known(F::zero());
+        let mut acc_cell = None;
+        let mut weight_cell = None;
+        let mut bit_cells = vec![];
+
+        for offset in 0..num_bits {
+            //most significant bit first
+            let i = num_bits - 1 - offset;
+            let bit = bits.as_ref().map(|bits| F::from(bits[i] as u64));
+
+            self.q_bool.enable(region, offset)?;
+            if offset == 0 {
+                self.q_first.enable(region, offset)?;
+            } else {
+                self.q_step.enable(region, offset)?;
+            }
+            bit_cells.push(region.assign_advice(|| "bit", self.bit, offset, || bit)?);
+
+            acc = acc.zip(bit).map(|(acc, bit)| acc * F::from(2) + bit);
+            acc_cell = Some(region.assign_advice(|| "acc", self.acc, offset, || acc)?);
+
+            if count {
+                if offset == 0 {
+                    self.q_weight_first.enable(region, offset)?;
+                } else {
+                    self.q_weight_step.enable(region, offset)?;
+                }
+                weight = weight + bit;
+                weight_cell = Some(region.assign_advice(|| "weight", self.weight, offset, || weight)?);
+            }
+        }
+
+        bit_cells.reverse();
+        Ok((acc_cell.unwrap(), bit_cells, weight_cell))
+    }
+
+    //returns the value cell and its bits, little endian. fails unless value < 2^num_bits
+    pub fn decompose(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+        num_bits: usize,
+    ) -> Result<(AssignedCell<F, F>, Vec<AssignedCell<F, F>>), CircuitsError> {
+        Self::check_num_bits(num_bits)?;
+        layouter.assign_region(
+            || "decompose value",
+            |mut region| {
+                let (acc, bits, _) = self.assign_bits(&mut region, value, num_bits, false)?;
+                let value = region.assign_advice(|| "value", self.acc, num_bits, || value)?;
+                region.constrain_equal(acc.cell(), value.cell())?;
+                Ok((value, bits))
+            },
+        ).map_err(CircuitsError::from)
+    }
+
+    //decomposes an existing cell, returning its bits little endian
+    pub fn decompose_cell(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        num_bits: usize,
+    ) -> Result<Vec<AssignedCell<F, F>>, CircuitsError> {
+        Self::check_num_bits(num_bits)?;
+        layouter.assign_region(
+            || "decompose cell",
+            |mut region| {
+                let (acc, bits, _) = self.assign_bits(&mut region, cell.value().copied(), num_bits, false)?;
+                region.constrain_equal(acc.cell(), cell.cell())?;
+                Ok(bits)
+            },
+        ).map_err(CircuitsError::from)
+    }
+
+    //number of set bits in value, which has to fit in num_bits
+    pub fn popcount(&self, mut layouter: impl Layouter<F>, value: Value<F>, num_bits: usize) -> Result<AssignedCell<F, F>, CircuitsError> {
+        Self::check_num_bits(num_bits)?;
+        layouter.assign_region(
+            || "popcount",
+            |mut region| {
+                let (acc, _, weight) = self.assign_bits(&mut region, value, num_bits, true)?;
+                let value = region.assign_advice(|| "value", self.acc, num_bits, || value)?;
+                region.constrain_equal(acc.cell(), value.cell())?;
+                Ok(weight.unwrap())
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    #[derive(Default)]
+    struct PopcountCircuit<F: FieldExt> {
+        value: Value<F>,
+        num_bits: usize,
+    }
+
+    impl<F: FieldExt> Circuit<F> for PopcountCircuit<F> {
+        type Config = (BitDecomposeConfig<F>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                value: Value::unknown(),
+                num_bits: self.num_bits,
+            }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let bit = cs.advice_column();
+            let acc = cs.advice_column();
+            let weight = cs.advice_column();
+            let instance = cs.instance_column();
+            cs.enable_equality(instance);
+            (BitDecomposeConfig::configure(cs, bit, acc, weight), instance)
+        }
+
+        fn synthesize(&self, (config, instance): Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let weight = config.popcount(layouter.namespace(|| "popcount"), self.value, self.num_bits)?;
+            //the claimed weight is public
+            layouter.constrain_instance(weight.cell(), instance, 0)
+        }
+    }
+
+    fn circuit(value: u64) -> PopcountCircuit<Fp> {
+        PopcountCircuit {
+            value: Value::known(Fp::from(value)),
+            num_bits: 4,
+        }
+    }
+
+    #[test]
+    fn test_popcount() {
+        let k = 4;
+        //13 = 0b1101
+        let prover = MockProver::run(k, &circuit(13), vec![vec![Fp::from(3)]]).unwrap();
+        prover.assert_satisfied();
+
+        let prover = MockProver::run(k, &circuit(0), vec![vec![Fp::from(0)]]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_popcount_sound() {
+        let k = 4;
+        //the weight can't be inflated
+        let prover = MockProver::run(k, &circuit(13), vec![vec![Fp::from(4)]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        //and the value has to fit in the bits
+        let prover = MockProver::run(k, &circuit(29), vec![vec![Fp::from(3)]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_num_bits_capacity() {
+        //Fp::NUM_BITS = 255 bits would let x + p through as well as x
+        let circuit = PopcountCircuit { value: Value::known(Fp::from(13)), num_bits: 255 };
+        assert!(matches!(MockProver::run(9, &circuit, vec![vec![Fp::from(3)]]), Err(Error::Synthesis)));
+        let circuit = PopcountCircuit { value: Value::known(Fp::from(13)), num_bits: 254 };
+        MockProver::run(9, &circuit, vec![vec![Fp::from(3)]]).unwrap().assert_satisfied();
+    }
+
+    #[test]
+    fn test_le_bits() {
+        assert_eq!(le_bits(&Fp::from(13), 5), vec![true, false, true, true, false]);
+    }
+}
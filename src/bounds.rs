@@ -0,0 +1,180 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Assigned, Column, ConstraintSystem, Error, Instance, Selector},
+    poly::Rotation,
+};
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, range_lookup::RangeTableConfig};
+
+//checks min <= value <= max by looking up value - min and max - value in 0..RANGE, so the
+//bounds can be at most RANGE - 1 apart
+#[derive(Clone, Debug)]
+pub struct BoundsConfig<F: FieldExt, const RANGE: usize> {
+    pub value: Column<Advice>,
+    pub min: Column<Advice>,
+    pub max: Column<Advice>,
+    pub q_enable: Selector,
+    pub table: RangeTableConfig<F, RANGE>,
+    //when set, min and max are bound to these instance rows
+    pub instance: Option<(Column<Instance>, usize, usize)>,
+}
+
+impl<F: FieldExt, const RANGE: usize> BoundsConfig<F, RANGE> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, value: Column<Advice>, min: Column<Advice>, max: Column<Advice>) -> Self {
+        let q_enable = cs.complex_selector();
+        let table = RangeTableConfig::configure(cs);
+
+        cs.enable_equality(value);
+
+        cs.lookup(|cs| {
+            let q = cs.query_selector(q_enable);
+            let v = cs.query_advice(value, Rotation::cur());
+            let min = cs.query_advice(min, Rotation::cur());
+            vec![(q * (v - min), table.value)]
+        });
+
+        cs.lookup(|cs| {
+            let q = cs.query_selector(q_enable);
+            let v = cs.query_advice(value, Rotation::cur());
+            let max = cs.query_advice(max, Rotation::cur());
+            vec![(q * (max - v), table.value)]
+        });
+
+        Self {
+            value,
+            min,
+            max,
+            q_enable,
+            table,
+            instance: None,
+        }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 3);
+        Self::configure(cs, columns[0], columns[1], columns[2])
+    }
+
+    //like configure but min and max are public, read from rows min_row and max_row of instance
+    pub fn configure_instance_bounds(
+        cs: &mut ConstraintSystem<F>,
+        value: Column<Advice>,
+        min: Column<Advice>,
+        max: Column<Advice>,
+        instance: Column<Instance>,
+        min_row: usize,
+        max_row: usize,
+    ) -> Self {
+        cs.enable_equality(min);
+        cs.enable_equality(max);
+        cs.enable_equality(instance);
+        Self {
+            instance: Some((instance, min_row, max_row)),
+            ..Self::configure(cs, value, min, max)
+        }
+    }
+
+    pub fn assign(
+        &self,
+        layouter: impl Layouter<F>,
+        value: Value<Assigned<F>>,
+        min: Value<Assigned<F>>,
+        max: Value<Assigned<F>>,
+    ) -> Result<AssignedCell<Assigned<F>, F>, CircuitsError> {
+        self.assign_with_bounds(layouter, value, min, max).map(|(value, _, _)| value)
+    }
+
+    //also returns the min and max cells, for callers that pin the bounds down themselves
+    pub fn assign_with_bounds(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<Assigned<F>>,
+        min: Value<Assigned<F>>,
+        max: Value<Assigned<F>>,
+    ) -> Result<(AssignedCell<Assigned<F>, F>, AssignedCell<Assigned<F>, F>, AssignedCell<Assigned<F>, F>), CircuitsError> {
+        let offset = 0;
+        let (value, min, max) = layouter.assign_region(
+            || "assign bounds",
+            |mut region| {
+                self.q_enable.enable(&mut region, offset)?;
+                let value = region.assign_advice(|| "value", self.value, offset, || value)?;
+                let min = region.assign_advice(|| "min", self.min, offset, || min)?;
+                let max = region.assign_advice(|| "max", self.max, offset, || max)?;
+                Ok((value, min, max))
+            },
+        )?;
+
+        if let Some((instance, min_row, max_row)) = self.instance {
+            layouter.constrain_instance(min.cell(), instance, min_row)?;
+            layouter.constrain_instance(max.cell(), instance, max_row)?;
+        }
+        Ok((value, min, max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::value_u64;
+
+    #[derive(Default)]
+    struct InstanceBoundsCircuit<F: FieldExt, const RANGE: usize> {
+        value: Value<Assigned<F>>,
+        min: Value<Assigned<F>>,
+        max: Value<Assigned<F>>,
+    }
+
+    impl<F: FieldExt, const RANGE: usize> Circuit<F> for InstanceBoundsCircuit<F, RANGE> {
+        type Config = BoundsConfig<F, RANGE>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let value = cs.advice_column();
+            let min = cs.advice_column();
+            let max = cs.advice_column();
+            let instance = cs.instance_column();
+            BoundsConfig::configure_instance_bounds(cs, value, min, max, instance, 0, 1)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            config.assign(layouter.namespace(|| "bounds"), self.value, self.min, self.max)?;
+            Ok(())
+        }
+    }
+
+    fn verify(value: u64, witnessed: (u64, u64), public: (u64, u64)) -> bool {
+        let k = 5;
+        let circuit = InstanceBoundsCircuit::<Fp, 16> {
+            value: value_u64(value),
+            min: value_u64(witnessed.0),
+            max: value_u64(witnessed.1),
+        };
+        let instance = vec![Fp::from(public.0), Fp::from(public.1)];
+        MockProver::run(k, &circuit, vec![instance]).unwrap().verify().is_ok()
+    }
+
+    #[test]
+    fn test_instance_bounds() {
+        for value in [10, 15, 20] {
+            assert!(verify(value, (10, 20), (10, 20)));
+        }
+    }
+
+    #[test]
+    fn test_instance_bounds_sound() {
+        //outside the public bounds
+        assert!(!verify(9, (10, 20), (10, 20)));
+        assert!(!verify(21, (10, 20), (10, 20)));
+        //in range of witnessed bounds that don't match the public ones
+        assert!(!verify(25, (15, 25), (10, 20)));
+    }
+}
@@ -0,0 +1,241 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{column_pool::ColumnPool, comparator::ComparatorConfig, div_mod::DivModByRangeConfig, error::CircuitsError, field::lower_128, report};
+
+//checks one step of a binary search for target over [lo, hi], all in 0..RANGE.
+//
+//mid = (lo + hi) / 2 is the quotient of lo + hi by 2, and lt = [mid < target] comes from the
+//comparator, which also looks mid and target up in 0..RANGE. the bounds move to
+//[mid + 1, hi] when lt is set and [lo, mid] when it isn't. lo and hi are taken as already in
+//range, e.g. the new bound cells out of the previous step
+#[derive(Clone, Debug)]
+pub struct BSearchStepConfig<F: FieldExt, const RANGE: usize> {
+    pub lo: Column<Advice>,
+    pub hi: Column<Advice>,
+    //lo + hi on the sum row, halved by div mod into mid on the update row
+    pub mid: Column<Advice>,
+    pub lt: Column<Advice>,
+    pub new_lo: Column<Advice>,
+    pub new_hi: Column<Advice>,
+    pub q_sum: Selector,
+    pub q_update: Selector,
+    pub div_mod: DivModByRangeConfig<F, 2>,
+    pub comparator: ComparatorConfig<F, RANGE>,
+}
+
+impl<F: FieldExt, const RANGE: usize> BSearchStepConfig<F, RANGE> {
+    pub fn configure(
+        cs: &mut ConstraintSystem<F>,
+        lo: Column<Advice>,
+        hi: Column<Advice>,
+        mid: Column<Advice>,
+        lt: Column<Advice>,
+        new_lo: Column<Advice>,
+        new_hi: Column<Advice>,
+        div_mod: DivModByRangeConfig<F, 2>,
+        comparator: ComparatorConfig<F, RANGE>,
+    ) -> Self {
+        assert!(RANGE > 1);
+        let q_sum = cs.selector();
+        let q_update = cs.selector();
+
+        for column in [lo, hi, mid, lt, new_lo, new_hi] {
+            cs.enable_equality(column);
+        }
+
+        cs.create_gate("bsearch sum", |cs| {
+            let q = cs.query_selector(q_sum);
+            let lo = cs.query_advice(lo, Rotation::cur());
+            let hi = cs.query_advice(hi, Rotation::cur());
+            let sum = cs.query_advice(mid, Rotation::cur());
+            Constraints::with_selector(q, [("sum = lo + hi", sum - (lo + hi))])
+        });
+        report::annotate_gate("bsearch_step", "bsearch sum");
+
+        cs.create_gate("bsearch update", |cs| {
+            let q = cs.query_selector(q_update);
+            let lo = cs.query_advice(lo, Rotation::cur());
+            let hi = cs.query_advice(hi, Rotation::cur());
+            let mid = cs.query_advice(mid, Rotation::cur());
+            let lt = cs.query_advice(lt, Rotation::cur());
+            let new_lo = cs.query_advice(new_lo, Rotation::cur());
+            let new_hi = cs.query_advice(new_hi, Rotation::cur());
+            let one = Expression::Constant(F::one());
+            //lt is copied from the comparator, which makes it boolean
+            Constraints::with_selector(
+                q,
+                [
+                    ("new lo", new_lo - (lo.clone() + lt.clone() * (mid.clone() + one.clone() - lo))),
+                    ("new hi", new_hi - (hi.clone() + (one - lt) * (mid - hi))),
+                ],
+            )
+        });
+        report::annotate_gate("bsearch_step", "bsearch update");
+
+        Self { lo, hi, mid, lt, new_lo, new_hi, q_sum, q_update, div_mod, comparator }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 6);
+        let div_mod = DivModByRangeConfig::configure_with_pool(cs, pool);
+        let comparator = ComparatorConfig::configure_with_pool(cs, pool);
+        Self::configure(cs, columns[0], columns[1], columns[2], columns[3], columns[4], columns[5], div_mod, comparator)
+    }
+
+    //mid < RANGE, so it fits in the bits of RANGE - 1
+    fn mid_bits() -> usize {
+        (usize::BITS - (RANGE - 1).leading_zeros()) as usize
+    }
+
+    //returns (mid, new_lo, new_hi) for cells lo, hi and target assigned elsewhere
+    pub fn assign(
+        &self,
+        layouter: impl Layouter<F>,
+        lo: &AssignedCell<F, F>,
+        hi: &AssignedCell<F, F>,
+        target: &AssignedCell<F, F>,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>, AssignedCell<F, F>), CircuitsError> {
+        let sum = lo.value().copied() + hi.value().copied();
+        let mid = sum.map(|sum| F::from_u128(lower_128(&sum) / 2));
+        let rem = sum.map(|sum| F::from_u128(lower_128(&sum) % 2));
+        self.assign_with_mid(layouter, lo, hi, target, mid, rem)
+    }
+
+    fn assign_with_mid(
+        &self,
+        mut layouter: impl Layouter<F>,
+        lo: &AssignedCell<F, F>,
+        hi: &AssignedCell<F, F>,
+        target: &AssignedCell<F, F>,
+        mid: Value<F>,
+        rem: Value<F>,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>, AssignedCell<F, F>), CircuitsError> {
+        let offset = 0;
+        let sum = layouter.assign_region(
+            || "assign bsearch sum",
+            |mut region| {
+                self.q_sum.enable(&mut region, offset)?;
+                let lo = lo.copy_advice(|| "lo", &mut region, self.lo, offset)?;
+                let hi = hi.copy_advice(|| "hi", &mut region, self.hi, offset)?;
+                region.assign_advice(|| "sum", self.mid, offset, || lo.value().copied() + hi.value().copied())
+            },
+        )?;
+        let (mid, _) = self.div_mod.assign_with_qr(layouter.namespace(|| "halve sum"), sum.value().copied(), Some(&sum), mid, rem, Self::mid_bits())?;
+
+        let lt = self.comparator.assign(layouter.namespace(|| "compare mid"), &mid, target)?;
+
+        layouter.assign_region(
+            || "assign bsearch update",
+            |mut region| {
+                self.q_update.enable(&mut region, offset)?;
+                lo.copy_advice(|| "lo", &mut region, self.lo, offset)?;
+                hi.copy_advice(|| "hi", &mut region, self.hi, offset)?;
+                let mid = mid.copy_advice(|| "mid", &mut region, self.mid, offset)?;
+                let lt = lt.copy_advice(|| "lt", &mut region, self.lt, offset)?;
+                let below = lt.value().map(|lt| lt.evaluate() == F::one());
+                let new_lo = below.zip(lo.value().copied()).zip(mid.value().copied()).map(|((below, lo), mid)| if below { mid + F::one() } else { lo });
+                let new_hi = below.zip(hi.value().copied()).zip(mid.value().copied()).map(|((below, hi), mid)| if below { hi } else { mid });
+                let new_lo = region.assign_advice(|| "new lo", self.new_lo, offset, || new_lo)?;
+                let new_hi = region.assign_advice(|| "new hi", self.new_hi, offset, || new_hi)?;
+                Ok((mid, new_lo, new_hi))
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, known, ExpectedFailure, Forged};
+
+    #[derive(Default)]
+    struct BSearchStepCircuit {
+        lo: Value<Fp>,
+        hi: Value<Fp>,
+        target: Value<Fp>,
+        //witnessed as (mid, remainder) instead of the real halving when forged
+        split: Forged<Fp>,
+        //checked against the (mid, new_lo, new_hi) cells
+        expected: Option<(u64, u64, u64)>,
+    }
+
+    impl Circuit<Fp> for BSearchStepCircuit {
+        type Config = BSearchStepConfig<Fp, 16>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { split: self.split.without_witnesses(), ..Self::default() }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(6);
+            BSearchStepConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.div_mod.table.load(&mut layouter)?;
+            config.comparator.table.load(&mut layouter)?;
+            let (lo, hi, target) = layouter.assign_region(
+                || "assign inputs",
+                |mut region| {
+                    let lo = region.assign_advice(|| "lo", config.lo, 0, || self.lo)?;
+                    let hi = region.assign_advice(|| "hi", config.hi, 0, || self.hi)?;
+                    //the comparator's columns come out of the same pool, so target goes in one
+                    //the inputs don't use
+                    let target = region.assign_advice(|| "target", config.mid, 0, || self.target)?;
+                    Ok((lo, hi, target))
+                },
+            )?;
+            let (mid, new_lo, new_hi) = match self.split.pair() {
+                Some((mid, rem)) => config.assign_with_mid(layouter.namespace(|| "step"), &lo, &hi, &target, mid, rem)?,
+                None => config.assign(layouter.namespace(|| "step"), &lo, &hi, &target)?,
+            };
+            if let Some(expected) = self.expected {
+                let cells = [(mid, expected.0), (new_lo, expected.1), (new_hi, expected.2)];
+                for (cell, expected) in cells {
+                    cell.value().assert_if_known(|v| **v == Fp::from(expected));
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn prover(lo: u64, hi: u64, target: u64, split: Option<(Fp, Fp)>, expected: Option<(u64, u64, u64)>) -> MockProver<Fp> {
+        let k = 5;
+        let circuit = BSearchStepCircuit {
+            lo: known(lo),
+            hi: known(hi),
+            target: known(target),
+            split: split.map_or_else(Forged::honest, |(mid, rem)| Forged::new(&[mid, rem])),
+            expected,
+        };
+        MockProver::run(k, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_bsearch_step() {
+        //target above mid moves lo up past it
+        prover(0, 15, 11, None, Some((7, 8, 15))).assert_satisfied();
+        //target at or below mid keeps mid as the new hi
+        prover(0, 15, 7, None, Some((7, 0, 7))).assert_satisfied();
+        prover(8, 11, 9, None, Some((9, 8, 9))).assert_satisfied();
+        //the last step of a search, lo = hi = target
+        prover(11, 11, 11, None, Some((11, 11, 11))).assert_satisfied();
+    }
+
+    #[test]
+    fn test_fabricated_mid() {
+        //0 + 15 = 15 = 2 * 6 + 3, the relation holds but the remainder isn't 0 or 1
+        expect_failures(&prover(0, 15, 11, Some((Fp::from(6), Fp::from(3))), None), &[ExpectedFailure::lookup(0, "assign div mod", 0)]);
+        //and 2 * 8 - 1 with the remainder wrapped around
+        expect_failures(&prover(0, 15, 11, Some((Fp::from(8), -Fp::one())), None), &[ExpectedFailure::lookup(0, "assign div mod", 0)]);
+    }
+}
@@ -0,0 +1,139 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    pasta::Fp,
+    plonk::{Advice, Assigned, Circuit, Column, ConstraintSystem, Constraints, Error, Instance, Selector},
+    poly::Rotation,
+};
+
+use std::iter;
+
+use crate::{column_pool::ColumnPool, comparator::ComparatorConfig, proving::Instances, range_lookup::RangeCheckLookupConfig, report, rows::RowBudget, running_sum::RunningSumConfig};
+
+//every expense is below ITEM_CAP
+pub const ITEM_CAP: usize = 100;
+//the total and the budget are compared in 0..BUDGET_RANGE, a total past it is rejected
+pub const BUDGET_RANGE: usize = 1024;
+//k the comparator table fits in, with room for the expenses next to it
+pub const BUDGET_K: u32 = 11;
+
+#[derive(Clone, Debug)]
+pub struct BudgetConfig {
+    //each expense in 0..ITEM_CAP, assigned as one batch
+    pub expenses: RangeCheckLookupConfig<Fp, ITEM_CAP>,
+    //the running total of a pinned zero and the expenses
+    pub total: RunningSumConfig<Fp>,
+    pub instance: Column<Instance>,
+    //total < budget, with lt pinned to 1
+    pub comparator: ComparatorConfig<Fp, BUDGET_RANGE>,
+}
+
+//proves a private list of expenses, each below ITEM_CAP, adds up to less than a public
+//budget, the only instance value
+#[derive(Clone, Debug, Default)]
+pub struct BudgetCircuit {
+    pub expenses: Vec<Value<Fp>>,
+}
+
+//the circuit for expenses and the instances for budget
+pub fn witness(expenses: Vec<u64>, budget: u64) -> (BudgetCircuit, Instances) {
+    let expenses = expenses.into_iter().map(|expense| Value::known(Fp::from(expense))).collect();
+    (BudgetCircuit { expenses }, vec![vec![Fp::from(budget)]])
+}
+
+impl Circuit<Fp> for BudgetCircuit {
+    type Config = BudgetConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self { expenses: vec![Value::unknown(); self.expenses.len()] }
+    }
+
+    fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let mut pool = ColumnPool::new(3);
+        let expenses = RangeCheckLookupConfig::configure_with_pool(cs, &mut pool);
+        let total = RunningSumConfig::configure_with_pool(cs, &mut pool, 1);
+        let comparator = ComparatorConfig::configure_with_pool(cs, &mut pool);
+        let instance = cs.instance_column();
+        let constants = cs.fixed_column();
+
+        cs.enable_equality(instance);
+        cs.enable_constant(constants);
+
+        BudgetConfig { expenses, total, instance, comparator }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        config.expenses.table.load(&mut layouter)?;
+        config.comparator.table.load(&mut layouter)?;
+
+        let expenses: Vec<Value<Assigned<Fp>>> = self.expenses.iter().map(|expense| expense.map(Assigned::from)).collect();
+        let checked = config.expenses.assign_lookup_batch(layouter.namespace(|| "expenses"), RowBudget::for_circuit::<Fp, Self>(BUDGET_K), &expenses)?;
+
+        //the total starts from a pinned zero on the first row, so an empty list adds up to 0
+        let total = layouter.assign_region(
+            || "assign total",
+            |mut region| {
+                let terms: Vec<_> = iter::once(Value::known(Fp::zero())).chain(checked.iter().map(|expense| expense.evaluate().value().copied())).collect();
+                let rows = config.total.assign_rows(&mut region, 0, &terms, None)?;
+                region.constrain_constant(rows[0].0.cell(), Fp::zero())?;
+                for (expense, (copy, _)) in checked.iter().zip(&rows[1..]) {
+                    region.constrain_equal(expense.evaluate().cell(), copy.cell())?;
+                }
+                Ok(rows.last().unwrap().1.clone())
+            },
+        )?;
+
+        //lt is pinned to 1 and copied into the comparison, so the budget holds exactly when the
+        //comparison does
+        let (budget, lt) = layouter.assign_region(
+            || "assign budget",
+            |mut region| {
+                let budget = region.assign_advice_from_instance(|| "budget", config.instance, 0, config.comparator.b, 0)?;
+                let lt = region.assign_advice_from_constant(|| "lt", config.comparator.lt, 0, Assigned::from(Fp::one()))?;
+                Ok((budget, lt))
+            },
+        )?;
+        config.comparator.assign_with_lt(layouter.namespace(|| "check budget"), &total, &budget, &lt)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::dev::MockProver;
+
+    use super::*;
+    use crate::test_utils::{expect_failures, ExpectedFailure};
+
+    fn prover(expenses: Vec<u64>, budget: u64) -> MockProver<Fp> {
+        let (circuit, instances) = witness(expenses, budget);
+        MockProver::run(BUDGET_K, &circuit, instances).unwrap()
+    }
+
+    //b - a - 1 goes negative once the total reaches the budget
+    fn over_budget() -> ExpectedFailure {
+        ExpectedFailure::lookup(3, "assign comparison", 0)
+    }
+
+    #[test]
+    fn test_budget_boundary() {
+        //40 + 99 + 60 = 199
+        prover(vec![40, 99, 60], 200).assert_satisfied();
+        expect_failures(&prover(vec![40, 99, 60], 199), &[over_budget()]);
+        expect_failures(&prover(vec![40, 99, 60], 150), &[over_budget()]);
+    }
+
+    #[test]
+    fn test_over_cap_item() {
+        //well within the budget, but the second item is over the cap
+        expect_failures(&prover(vec![40, 100, 60], 500), &[ExpectedFailure::lookup(0, "range_lookup<100> x3 assign batch", 1)]);
+    }
+
+    #[test]
+    fn test_no_expenses() {
+        prover(vec![], 1).assert_satisfied();
+        //nothing spent is still not less than a budget of 0
+        expect_failures(&prover(vec![], 0), &[over_budget()]);
+    }
+}
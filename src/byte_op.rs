@@ -0,0 +1,230 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use std::marker::PhantomData;
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, field::lower_128, report, rows::{self, RowBudget, TablePadding}};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteOp {
+    And,
+    Or,
+    Xor,
+}
+
+impl ByteOp {
+    pub fn apply(&self, a: u64, b: u64) -> u64 {
+        match self {
+            ByteOp::And => a & b,
+            ByteOp::Or => a | b,
+            ByteOp::Xor => a ^ b,
+        }
+    }
+}
+
+//the triples (a, b, a op b) for a, b in 0..2^BITS, 2^(2 * BITS) rows. 0 op 0 = 0 for every
+//op, so rows off the selector looking up (0, 0, 0) hit a real row
+#[derive(Clone, Debug)]
+pub struct ByteOpTableConfig<F: FieldExt, const BITS: usize> {
+    pub a: TableColumn,
+    pub b: TableColumn,
+    pub c: TableColumn,
+    pub op: ByteOp,
+    //what load_padded puts in the rows past the 2^(2 * BITS) triples
+    pub padding: TablePadding,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const BITS: usize> ByteOpTableConfig<F, BITS> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, op: ByteOp) -> Self {
+        Self::configure_padded(cs, op, TablePadding::Unpadded)
+    }
+
+    pub fn configure_padded(cs: &mut ConstraintSystem<F>, op: ByteOp, padding: TablePadding) -> Self {
+        //a byte is already a 2^16 row table
+        assert!(BITS > 0 && BITS <= 8);
+        Self {
+            a: cs.lookup_table_column(),
+            b: cs.lookup_table_column(),
+            c: cs.lookup_table_column(),
+            op,
+            padding: padding.for_rows(1 << (2 * BITS)),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), CircuitsError> {
+        self.load_rows(layouter, None)
+    }
+
+    //load that first checks the table fits in the budget, then fills the rest of its usable
+    //rows as the padding says
+    pub fn load_padded(&self, layouter: &mut impl Layouter<F>, budget: RowBudget) -> Result<(), CircuitsError> {
+        self.load_rows(layouter, Some(budget))
+    }
+
+    //row a * 2^BITS + b holds (a, b, a op b)
+    fn load_rows(&self, layouter: &mut impl Layouter<F>, budget: Option<RowBudget>) -> Result<(), CircuitsError> {
+        let size = 1u64 << BITS;
+        let rows: Vec<_> = (0..size)
+            .flat_map(|a| (0..size).map(move |b| (a, b)))
+            .map(|(a, b)| vec![F::from(a), F::from(b), F::from(self.op.apply(a, b))])
+            .collect();
+        let what = format!("{:?} table<{}>", self.op, BITS);
+        rows::load_table(layouter, &what, &[("a", self.a), ("b", self.b), ("c", self.c)], &rows, self.padding, budget)
+    }
+}
+
+//checks c = a op b for BITS wide operands with the whole triple in one lookup argument,
+//which also range checks a, b and c. BITS = 8 for bytes, narrower operands keep the table
+//small
+#[derive(Clone, Debug)]
+pub struct ByteOpConfig<F: FieldExt, const BITS: usize> {
+    pub a: Column<Advice>,
+    pub b: Column<Advice>,
+    pub c: Column<Advice>,
+    pub q_enable: Selector,
+    pub table: ByteOpTableConfig<F, BITS>,
+}
+
+impl<F: FieldExt, const BITS: usize> ByteOpConfig<F, BITS> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, a: Column<Advice>, b: Column<Advice>, c: Column<Advice>, table: ByteOpTableConfig<F, BITS>) -> Self {
+        let q_enable = cs.complex_selector();
+
+        cs.enable_equality(a);
+        cs.enable_equality(b);
+        cs.enable_equality(c);
+
+        let lookup_index = cs.lookup(|cs| {
+            let q = cs.query_selector(q_enable);
+            let a = cs.query_advice(a, Rotation::cur());
+            let b = cs.query_advice(b, Rotation::cur());
+            let c = cs.query_advice(c, Rotation::cur());
+            vec![(q.clone() * a, table.a), (q.clone() * b, table.b), (q * c, table.c)]
+        });
+        report::annotate_lookup("byte_op", lookup_index);
+
+        Self { a, b, c, q_enable, table }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>, op: ByteOp) -> Self {
+        let columns = pool.advice_many(cs, 3);
+        let table = ByteOpTableConfig::configure(cs, op);
+        Self::configure(cs, columns[0], columns[1], columns[2], table)
+    }
+
+    //returns the cell of c = a op b, for cells a and b assigned elsewhere
+    pub fn op3(&self, layouter: impl Layouter<F>, a: &AssignedCell<F, F>, b: &AssignedCell<F, F>) -> Result<AssignedCell<F, F>, CircuitsError> {
+        let op = self.table.op;
+        let c = a.value().zip(b.value()).map(|(a, b)| F::from(op.apply(lower_128(a) as u64, lower_128(b) as u64)));
+        self.assign_with_output(layouter, a, b, c)
+    }
+
+    fn assign_with_output(&self, mut layouter: impl Layouter<F>, a: &AssignedCell<F, F>, b: &AssignedCell<F, F>, c: Value<F>) -> Result<AssignedCell<F, F>, CircuitsError> {
+        let offset = 0;
+        layouter.assign_region(
+            || "assign byte op",
+            |mut region| {
+                self.q_enable.enable(&mut region, offset)?;
+                a.copy_advice(|| "a", &mut region, self.a, offset)?;
+                b.copy_advice(|| "b", &mut region, self.b, offset)?;
+                region.assign_advice(|| "c", self.c, offset, || c)
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, ExpectedFailure};
+
+    #[derive(Default)]
+    struct XorCircuit {
+        pairs: Vec<(u64, u64)>,
+        //when set, witnessed as the one pair's c instead of the real one
+        c: Option<u64>,
+    }
+
+    impl Circuit<Fp> for XorCircuit {
+        type Config = ByteOpConfig<Fp, 4>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { pairs: self.pairs.clone(), c: self.c }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(3);
+            ByteOpConfig::configure_with_pool(cs, &mut pool, ByteOp::Xor)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            for &(a, b) in &self.pairs {
+                let (a_cell, b_cell) = layouter.assign_region(
+                    || "assign operands",
+                    |mut region| {
+                        let a = region.assign_advice(|| "a", config.a, 0, || Value::known(Fp::from(a)))?;
+                        let b = region.assign_advice(|| "b", config.b, 0, || Value::known(Fp::from(b)))?;
+                        Ok((a, b))
+                    },
+                )?;
+                match self.c {
+                    Some(c) => {
+                        config.assign_with_output(layouter.namespace(|| "xor"), &a_cell, &b_cell, Value::known(Fp::from(c)))?;
+                    }
+                    None => {
+                        let c = config.op3(layouter.namespace(|| "xor"), &a_cell, &b_cell)?;
+                        c.value().assert_if_known(|c| **c == Fp::from(a ^ b));
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn prover(pairs: &[(u64, u64)], c: Option<u64>) -> MockProver<Fp> {
+        let k = 9;
+        MockProver::run(k, &XorCircuit { pairs: pairs.to_vec(), c }, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_one_lookup_argument() {
+        let mut cs = ConstraintSystem::<Fp>::default();
+        XorCircuit::configure(&mut cs);
+        //the triple is a single argument with three input expressions
+        let pinned = format!("{:?}", cs.pinned());
+        assert_eq!(pinned.matches("input_expressions").count(), 1);
+    }
+
+    #[test]
+    fn test_xor() {
+        prover(&[(0b1010, 0b0110), (0, 0), (15, 15), (15, 0), (3, 12)], None).assert_satisfied();
+    }
+
+    #[test]
+    fn test_wrong_output() {
+        let failure = ExpectedFailure::lookup(0, "assign byte op", 0);
+        //the and, the or, and a c outside the operand width
+        expect_failures(&prover(&[(0b1010, 0b0110)], Some(0b0010)), &[failure.clone()]);
+        expect_failures(&prover(&[(0b1010, 0b0110)], Some(0b1110)), &[failure.clone()]);
+        expect_failures(&prover(&[(0b1010, 0b0110)], Some(0b1100 + 16)), &[failure.clone()]);
+        //operands past the table
+        expect_failures(&prover(&[(16, 0)], None), &[failure]);
+    }
+
+    #[test]
+    fn test_ops() {
+        assert_eq!(ByteOp::And.apply(0b1100, 0b1010), 0b1000);
+        assert_eq!(ByteOp::Or.apply(0b1100, 0b1010), 0b1110);
+        assert_eq!(ByteOp::Xor.apply(0b1100, 0b1010), 0b0110);
+    }
+}
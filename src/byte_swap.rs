@@ -0,0 +1,161 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Selector},
+    poly::Rotation,
+};
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, le_bytes::LeBytesConfig, range_lookup::RangeTableConfig, report};
+
+//reverses the byte order of a NUM_BYTES wide value, e.g. to match big endian hash inputs.
+//
+//the region is a LeBytesConfig decomposition, so the bytes are looked up and packed into x
+//going down the rows. the swapped value packs the same byte cells the other way, with its
+//running sum going up the rows, swap_r = 256 * swap_{r+1} + byte_r, and ends at row 0
+#[derive(Clone, Debug)]
+pub struct ByteSwapConfig<F: FieldExt, const NUM_BYTES: usize> {
+    pub bytes: LeBytesConfig<F, NUM_BYTES>,
+    pub swap: Column<Advice>,
+    pub q_swap_last: Selector,
+    pub q_swap_step: Selector,
+}
+
+impl<F: FieldExt, const NUM_BYTES: usize> ByteSwapConfig<F, NUM_BYTES> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, bytes: LeBytesConfig<F, NUM_BYTES>, swap: Column<Advice>) -> Self {
+        let q_swap_last = cs.selector();
+        let q_swap_step = cs.selector();
+
+        cs.enable_equality(swap);
+
+        cs.create_gate("byte swap last", |cs| {
+            let q = cs.query_selector(q_swap_last);
+            let byte = cs.query_advice(bytes.sum.term, Rotation::cur());
+            let swap = cs.query_advice(swap, Rotation::cur());
+            Constraints::with_selector(q, [("swap", swap - byte)])
+        });
+        report::annotate_gate("byte_swap", "byte swap last");
+
+        cs.create_gate("byte swap step", |cs| {
+            let q = cs.query_selector(q_swap_step);
+            let byte = cs.query_advice(bytes.sum.term, Rotation::cur());
+            let swap_next = cs.query_advice(swap, Rotation::next());
+            let swap = cs.query_advice(swap, Rotation::cur());
+            Constraints::with_selector(q, [("swap", swap - (swap_next * F::from(256) + byte))])
+        });
+        report::annotate_gate("byte_swap", "byte swap step");
+
+        Self { bytes, swap, q_swap_last, q_swap_step }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let bytes = LeBytesConfig::configure_with_pool(cs, pool);
+        let swap = pool.advice(cs);
+        Self::configure(cs, bytes, swap)
+    }
+
+    pub fn table(&self) -> &RangeTableConfig<F, 256> {
+        &self.bytes.table
+    }
+
+    //returns the cell of x with its bytes reversed, for a cell x assigned elsewhere
+    pub fn assign(&self, mut layouter: impl Layouter<F>, x: &AssignedCell<F, F>) -> Result<AssignedCell<F, F>, CircuitsError> {
+        //little endian, from the canonical representation like LeBytesConfig::to_le_bytes
+        let bytes = x.value().map(|x| {
+            let repr = x.to_repr();
+            repr.as_ref()[..NUM_BYTES].iter().map(|b| F::from(*b as u64)).collect::<Vec<_>>()
+        });
+        layouter.assign_region(
+            || "assign byte swap",
+            |mut region| {
+                //most significant byte of x first
+                let byte_values: Vec<_> = (0..NUM_BYTES).map(|i| bytes.as_ref().map(|bytes| bytes[NUM_BYTES - 1 - i])).collect();
+                for offset in 0..NUM_BYTES {
+                    self.bytes.q_byte.enable(&mut region, offset)?;
+                    if offset == NUM_BYTES - 1 {
+                        self.q_swap_last.enable(&mut region, offset)?;
+                    } else {
+                        self.q_swap_step.enable(&mut region, offset)?;
+                    }
+                }
+                let rows = self.bytes.sum.assign_rows(&mut region, 0, &byte_values, None)?;
+                region.constrain_equal(rows[NUM_BYTES - 1].1.cell(), x.cell())?;
+
+                //the swapped sums are filled in from the last row up
+                let mut swap = Value::known(F::zero());
+                let mut swap_cell = None;
+                for offset in (0..NUM_BYTES).rev() {
+                    swap = swap.zip(byte_values[offset]).map(|(swap, byte)| swap * F::from(256) + byte);
+                    swap_cell = Some(region.assign_advice(|| "swap", self.swap, offset, || swap)?);
+                }
+                Ok(swap_cell.unwrap())
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct ByteSwapCircuit<const NUM_BYTES: usize> {
+        x: u128,
+        expected: Option<u64>,
+    }
+
+    impl<const NUM_BYTES: usize> Circuit<Fp> for ByteSwapCircuit<NUM_BYTES> {
+        type Config = ByteSwapConfig<Fp, NUM_BYTES>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { x: self.x, expected: self.expected }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(3);
+            ByteSwapConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table().load(&mut layouter)?;
+            let x = layouter.assign_region(|| "assign x", |mut region| region.assign_advice(|| "x", config.swap, 0, || Value::known(Fp::from_u128(self.x))))?;
+            let swapped = config.assign(layouter.namespace(|| "swap"), &x)?;
+            if let Some(expected) = self.expected {
+                swapped.value().assert_if_known(|swapped| **swapped == Fp::from(expected));
+            }
+            Ok(())
+        }
+    }
+
+    fn prover<const NUM_BYTES: usize>(x: u128, expected: Option<u64>) -> MockProver<Fp> {
+        //the byte table takes 256 rows
+        let k = 9;
+        MockProver::run(k, &ByteSwapCircuit::<NUM_BYTES> { x, expected }, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_swap_u32() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for x in [0, 1, 0x1234_5678, u32::MAX].into_iter().chain((0..4).map(|_| rng.gen::<u32>())) {
+            prover::<4>(x as u128, Some(x.swap_bytes() as u64)).assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_swap_u64() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for x in [0, 1, 0x0102_0304_0506_0708, u64::MAX].into_iter().chain((0..4).map(|_| rng.gen::<u64>())) {
+            prover::<8>(x as u128, Some(x.swap_bytes())).assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_x_too_wide() {
+        //the four bytes of 2^32 are 0 and don't pack back up to it
+        assert!(prover::<4>(1 << 32, None).verify().is_err());
+    }
+}
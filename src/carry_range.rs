@@ -0,0 +1,267 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use std::marker::PhantomData;
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, field::lower_128, report, rows::{self, RowBudget, TablePadding}};
+
+//adds two limbs and the carry out of the previous limb and splits the sum as
+//a + b + carry_in = low + carry * 2^WINDOW, with low looked up in 0..2^WINDOW and carry
+//boolean. that split is unique, so the carry cell is chained into the next limb's addition,
+//and the first limb takes a carry_in pinned to 0. the table is the gadget's own since 2^WINDOW can't be a RANGE
+//parameter of RangeTableConfig
+#[derive(Clone, Debug)]
+pub struct CarryRangeConfig<F: FieldExt, const WINDOW: usize> {
+    pub a: Column<Advice>,
+    pub b: Column<Advice>,
+    pub carry_in: Column<Advice>,
+    pub low: Column<Advice>,
+    pub carry: Column<Advice>,
+    pub q_enable: Selector,
+    pub table: TableColumn,
+    //what load_padded puts in the rows past 0..2^WINDOW
+    pub padding: TablePadding,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const WINDOW: usize> CarryRangeConfig<F, WINDOW> {
+    pub fn configure(
+        cs: &mut ConstraintSystem<F>,
+        a: Column<Advice>,
+        b: Column<Advice>,
+        carry_in: Column<Advice>,
+        low: Column<Advice>,
+        carry: Column<Advice>,
+    ) -> Self {
+        Self::configure_padded(cs, a, b, carry_in, low, carry, TablePadding::Unpadded)
+    }
+
+    pub fn configure_padded(
+        cs: &mut ConstraintSystem<F>,
+        a: Column<Advice>,
+        b: Column<Advice>,
+        carry_in: Column<Advice>,
+        low: Column<Advice>,
+        carry: Column<Advice>,
+        padding: TablePadding,
+    ) -> Self {
+        //the table has 2^WINDOW rows
+        assert!(WINDOW > 0 && WINDOW < 32);
+        let q_enable = cs.complex_selector();
+        let table = cs.lookup_table_column();
+        let constants = cs.fixed_column();
+
+        for column in [a, b, carry_in, low, carry] {
+            cs.enable_equality(column);
+        }
+        cs.enable_constant(constants);
+
+        cs.create_gate("carry range", |cs| {
+            let q = cs.query_selector(q_enable);
+            let a = cs.query_advice(a, Rotation::cur());
+            let b = cs.query_advice(b, Rotation::cur());
+            let carry_in = cs.query_advice(carry_in, Rotation::cur());
+            let low = cs.query_advice(low, Rotation::cur());
+            let carry = cs.query_advice(carry, Rotation::cur());
+            let one = Expression::Constant(F::one());
+            //carry_in is the previous limb's carry, which is already boolean
+            Constraints::with_selector(
+                q,
+                [
+                    ("a + b + carry_in = low + carry * 2^WINDOW", a + b + carry_in - (low + carry.clone() * F::from(1 << WINDOW))),
+                    ("carry boolean", carry.clone() * (one - carry)),
+                ],
+            )
+        });
+        report::annotate_gate("carry_range", "carry range");
+
+        let lookup_index = cs.lookup(|cs| {
+            let q = cs.query_selector(q_enable);
+            let low = cs.query_advice(low, Rotation::cur());
+            vec![(q * low, table)]
+        });
+        report::annotate_lookup("carry_range", lookup_index);
+
+        Self {
+            a,
+            b,
+            carry_in,
+            low,
+            carry,
+            q_enable,
+            table,
+            padding: padding.for_rows(1 << WINDOW),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 5);
+        Self::configure(cs, columns[0], columns[1], columns[2], columns[3], columns[4])
+    }
+
+    fn rows() -> Vec<Vec<F>> {
+        (0..1u64 << WINDOW).map(|v| vec![F::from(v)]).collect()
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), CircuitsError> {
+        rows::load_table(layouter, &format!("carry_range<{}> table", WINDOW), &[("low", self.table)], &Self::rows(), self.padding, None)
+    }
+
+    //load that first checks the table fits in the budget, then fills the rest of its usable
+    //rows as the padding says
+    pub fn load_padded(&self, layouter: &mut impl Layouter<F>, budget: RowBudget) -> Result<(), CircuitsError> {
+        rows::load_table(layouter, &format!("carry_range<{}> table", WINDOW), &[("low", self.table)], &Self::rows(), self.padding, Some(budget))
+    }
+
+    //returns the (low, carry) cells for limb cells a and b assigned elsewhere and the carry cell
+    //out of the previous limb, None for the first limb
+    pub fn assign_with_carry(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        carry_in: Option<&AssignedCell<F, F>>,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), CircuitsError> {
+        let carry_value = carry_in.map_or(Value::known(F::zero()), |carry_in| carry_in.value().copied());
+        let sum = (a.value().copied() + b.value().copied() + carry_value).map(|sum| lower_128(&sum));
+        let low = sum.map(|sum| F::from_u128(sum & ((1 << WINDOW) - 1)));
+        let carry = sum.map(|sum| F::from_u128(sum >> WINDOW));
+        self.assign_with_split(layouter, a, b, carry_in, low, carry)
+    }
+
+    fn assign_with_split(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        carry_in: Option<&AssignedCell<F, F>>,
+        low: Value<F>,
+        carry: Value<F>,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), CircuitsError> {
+        let offset = 0;
+        layouter.assign_region(
+            || "assign carry range",
+            |mut region| {
+                self.q_enable.enable(&mut region, offset)?;
+                a.copy_advice(|| "a", &mut region, self.a, offset)?;
+                b.copy_advice(|| "b", &mut region, self.b, offset)?;
+                match carry_in {
+                    Some(carry_in) => carry_in.copy_advice(|| "carry in", &mut region, self.carry_in, offset)?,
+                    None => region.assign_advice_from_constant(|| "carry in", self.carry_in, offset, F::zero())?,
+                };
+                let low = region.assign_advice(|| "low", self.low, offset, || low)?;
+                let carry = region.assign_advice(|| "carry", self.carry, offset, || carry)?;
+                Ok((low, carry))
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, known, ExpectedFailure, Forged};
+
+    #[derive(Default)]
+    struct CarryCircuit {
+        a: Value<Fp>,
+        b: Value<Fp>,
+        //the carry out of a previous limb, copied in from its own cell
+        carry_in: Option<Value<Fp>>,
+        //witnessed as (low, carry) instead of the real split when forged
+        split: Forged<Fp>,
+        expected: Option<(u64, u64)>,
+    }
+
+    impl Circuit<Fp> for CarryCircuit {
+        type Config = CarryRangeConfig<Fp, 4>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { carry_in: self.carry_in.map(|_| Value::unknown()), split: self.split.without_witnesses(), ..Self::default() }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(5);
+            CarryRangeConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.load(&mut layouter)?;
+            let (a, b, carry_in) = layouter.assign_region(
+                || "assign limbs",
+                |mut region| {
+                    let a = region.assign_advice(|| "a", config.a, 0, || self.a)?;
+                    let b = region.assign_advice(|| "b", config.b, 0, || self.b)?;
+                    let carry_in = self.carry_in.map(|carry_in| region.assign_advice(|| "carry in", config.carry_in, 0, || carry_in)).transpose()?;
+                    Ok((a, b, carry_in))
+                },
+            )?;
+            let (low, carry) = match self.split.pair() {
+                Some((low, carry)) => config.assign_with_split(layouter.namespace(|| "add"), &a, &b, carry_in.as_ref(), low, carry)?,
+                None => config.assign_with_carry(layouter.namespace(|| "add"), &a, &b, carry_in.as_ref())?,
+            };
+            if let Some((expected_low, expected_carry)) = self.expected {
+                low.value().assert_if_known(|low| **low == Fp::from(expected_low));
+                carry.value().assert_if_known(|carry| **carry == Fp::from(expected_carry));
+            }
+            Ok(())
+        }
+    }
+
+    fn prover(a: u64, b: u64, split: Option<(Fp, Fp)>, expected: Option<(u64, u64)>) -> MockProver<Fp> {
+        prover_with_carry(a, b, None, split, expected)
+    }
+
+    fn prover_with_carry(a: u64, b: u64, carry_in: Option<u64>, split: Option<(Fp, Fp)>, expected: Option<(u64, u64)>) -> MockProver<Fp> {
+        let k = 5;
+        let circuit = CarryCircuit {
+            a: known(a),
+            b: known(b),
+            carry_in: carry_in.map(known),
+            split: split.map_or_else(Forged::honest, |(low, carry)| Forged::new(&[low, carry])),
+            expected,
+        };
+        MockProver::run(k, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_carry() {
+        prover(12, 7, None, Some((3, 1))).assert_satisfied();
+        prover(8, 7, None, Some((15, 0))).assert_satisfied();
+        prover(15, 15, None, Some((14, 1))).assert_satisfied();
+        prover(0, 0, None, Some((0, 0))).assert_satisfied();
+    }
+
+    #[test]
+    fn test_carry_in() {
+        prover_with_carry(12, 7, Some(1), None, Some((4, 1))).assert_satisfied();
+        //the carry in is what pushes the sum over 15
+        prover_with_carry(8, 7, Some(1), None, Some((0, 1))).assert_satisfied();
+        prover_with_carry(8, 7, Some(0), None, Some((15, 0))).assert_satisfied();
+        //ignoring the carry in
+        let sum = ExpectedFailure::constraint("carry range", "a + b + carry_in = low + carry * 2^WINDOW", "assign carry range", 0);
+        expect_failures(&prover_with_carry(8, 7, Some(1), Some((Fp::from(15), Fp::zero())), None), &[sum]);
+    }
+
+    #[test]
+    fn test_carry_sound() {
+        let sum = ExpectedFailure::constraint("carry range", "a + b + carry_in = low + carry * 2^WINDOW", "assign carry range", 0);
+        let boolean = ExpectedFailure::constraint("carry range", "carry boolean", "assign carry range", 0);
+        let lookup = ExpectedFailure::lookup(0, "assign carry range", 0);
+        //dropping the carry
+        expect_failures(&prover(12, 7, Some((Fp::from(3), Fp::zero())), None), &[sum]);
+        //keeping the whole sum low, which adds up but isn't below 16
+        expect_failures(&prover(12, 7, Some((Fp::from(19), Fp::zero())), None), &[lookup.clone()]);
+        //a carry of 2 with the low part gone negative
+        expect_failures(&prover(12, 7, Some((-Fp::from(13), Fp::from(2))), None), &[boolean, lookup]);
+    }
+}
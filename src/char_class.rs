@@ -0,0 +1,287 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use std::marker::PhantomData;
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, field::lower_128, report, rows::{self, RowBudget, TablePadding}};
+
+//class bits. every byte has at most one of them, anything outside printable ascii has none
+pub const DIGIT: u8 = 1;
+pub const UPPER: u8 = 2;
+pub const LOWER: u8 = 4;
+pub const PUNCT: u8 = 8;
+pub const WHITESPACE: u8 = 16;
+pub const ALPHANUMERIC: u8 = DIGIT | UPPER | LOWER;
+
+pub fn char_class(byte: u8) -> u8 {
+    match byte {
+        b'0'..=b'9' => DIGIT,
+        b'A'..=b'Z' => UPPER,
+        b'a'..=b'z' => LOWER,
+        _ if byte.is_ascii_punctuation() => PUNCT,
+        _ if byte.is_ascii_whitespace() => WHITESPACE,
+        _ => 0,
+    }
+}
+
+//maps every byte to its class mask. byte 0 has mask 0, so rows off the selector looking up
+//(0, 0) hit a real row
+#[derive(Clone, Debug)]
+pub struct CharClassTableConfig<F: FieldExt> {
+    pub byte: TableColumn,
+    pub mask: TableColumn,
+    //what load_padded puts in the rows past the 256 pairs
+    pub padding: TablePadding,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> CharClassTableConfig<F> {
+    pub fn configure(cs: &mut ConstraintSystem<F>) -> Self {
+        Self::configure_padded(cs, TablePadding::Unpadded)
+    }
+
+    pub fn configure_padded(cs: &mut ConstraintSystem<F>, padding: TablePadding) -> Self {
+        Self {
+            byte: cs.lookup_table_column(),
+            mask: cs.lookup_table_column(),
+            padding: padding.for_rows(256),
+            _marker: PhantomData,
+        }
+    }
+
+    fn rows() -> Vec<Vec<F>> {
+        (0..=255u8).map(|byte| vec![F::from(byte as u64), F::from(char_class(byte) as u64)]).collect()
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), CircuitsError> {
+        rows::load_table(layouter, "char class table", &[("byte", self.byte), ("mask", self.mask)], &Self::rows(), self.padding, None)
+    }
+
+    //load that first checks the table fits in the budget, then fills the rest of its usable
+    //rows as the padding says
+    pub fn load_padded(&self, layouter: &mut impl Layouter<F>, budget: RowBudget) -> Result<(), CircuitsError> {
+        rows::load_table(layouter, "char class table", &[("byte", self.byte), ("mask", self.mask)], &Self::rows(), self.padding, Some(budget))
+    }
+}
+
+//the name of the gate checking a mask, one per mask so a failure says which set it was. gate
+//names have to be static, and there are only the 31 non-empty sets of the five class bits
+const ALLOWED_GATES: [&str; 31] = [
+    "char class allowed 00001",
+    "char class allowed 00010",
+    "char class allowed 00011",
+    "char class allowed 00100",
+    "char class allowed 00101",
+    "char class allowed 00110",
+    "char class allowed 00111",
+    "char class allowed 01000",
+    "char class allowed 01001",
+    "char class allowed 01010",
+    "char class allowed 01011",
+    "char class allowed 01100",
+    "char class allowed 01101",
+    "char class allowed 01110",
+    "char class allowed 01111",
+    "char class allowed 10000",
+    "char class allowed 10001",
+    "char class allowed 10010",
+    "char class allowed 10011",
+    "char class allowed 10100",
+    "char class allowed 10101",
+    "char class allowed 10110",
+    "char class allowed 10111",
+    "char class allowed 11000",
+    "char class allowed 11001",
+    "char class allowed 11010",
+    "char class allowed 11011",
+    "char class allowed 11100",
+    "char class allowed 11101",
+    "char class allowed 11110",
+    "char class allowed 11111",
+];
+
+pub fn allowed_gate_name(bits: u8) -> &'static str {
+    ALLOWED_GATES[bits as usize - 1]
+}
+
+//looks up the class mask of byte cells, one row per byte. each mask in allowed gets a gate
+//checking a row's mask has one of its bits, which since the masks have at most one bit set
+//is prod (mask - bit) = 0 over the bits
+#[derive(Clone, Debug)]
+pub struct CharClassConfig<F: FieldExt> {
+    pub byte: Column<Advice>,
+    pub mask: Column<Advice>,
+    pub q_lookup: Selector,
+    //the gate selector for each allowed mask
+    pub allowed: Vec<(u8, Selector)>,
+    pub table: CharClassTableConfig<F>,
+}
+
+impl<F: FieldExt> CharClassConfig<F> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, byte: Column<Advice>, mask: Column<Advice>, table: CharClassTableConfig<F>, allowed: &[u8]) -> Self {
+        let q_lookup = cs.complex_selector();
+
+        cs.enable_equality(byte);
+        cs.enable_equality(mask);
+
+        let lookup_index = cs.lookup(|cs| {
+            let q = cs.query_selector(q_lookup);
+            let byte = cs.query_advice(byte, Rotation::cur());
+            let mask = cs.query_advice(mask, Rotation::cur());
+            vec![(q.clone() * byte, table.byte), (q * mask, table.mask)]
+        });
+        report::annotate_lookup("char_class", lookup_index);
+
+        let allowed = allowed.iter().enumerate().map(|(i, &bits)| {
+            assert!(bits != 0 && bits < 32, "allowed masks are non-empty sets of the five class bits");
+            assert!(!allowed[..i].contains(&bits), "mask {:#07b} is allowed twice", bits);
+            let q_allowed = cs.selector();
+            let name = allowed_gate_name(bits);
+            cs.create_gate(name, |cs| {
+                let q = cs.query_selector(q_allowed);
+                let mask = cs.query_advice(mask, Rotation::cur());
+                let product = (0..5).filter(|i| bits & (1 << i) != 0).fold(Expression::Constant(F::one()), |product, i| {
+                    product * (mask.clone() - Expression::Constant(F::from(1 << i)))
+                });
+                Constraints::with_selector(q, [("mask in allowed", product)])
+            });
+            report::annotate_gate("char_class", name);
+            (bits, q_allowed)
+        }).collect();
+
+        Self { byte, mask, q_lookup, allowed, table }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>, allowed: &[u8]) -> Self {
+        let columns = pool.advice_many(cs, 2);
+        let table = CharClassTableConfig::configure(cs);
+        Self::configure(cs, columns[0], columns[1], table, allowed)
+    }
+
+    //the mask cells, in the order of bytes
+    pub fn assign(&self, layouter: impl Layouter<F>, bytes: &[AssignedCell<F, F>]) -> Result<Vec<AssignedCell<F, F>>, CircuitsError> {
+        self.assign_rows(layouter, bytes, None)
+    }
+
+    //the same, also checking every byte has a class in allowed, which has to be one of the
+    //masks given to configure
+    pub fn assign_allowed(&self, layouter: impl Layouter<F>, bytes: &[AssignedCell<F, F>], allowed: u8) -> Result<Vec<AssignedCell<F, F>>, CircuitsError> {
+        let q_allowed = self.allowed.iter().find(|(bits, _)| *bits == allowed).map(|(_, q)| *q);
+        assert!(q_allowed.is_some(), "mask {:#07b} wasn't configured", allowed);
+        self.assign_rows(layouter, bytes, q_allowed)
+    }
+
+    fn assign_rows(&self, mut layouter: impl Layouter<F>, bytes: &[AssignedCell<F, F>], q_allowed: Option<Selector>) -> Result<Vec<AssignedCell<F, F>>, CircuitsError> {
+        layouter.assign_region(
+            || "assign char classes",
+            |mut region| {
+                bytes.iter().enumerate().map(|(offset, byte)| {
+                    self.q_lookup.enable(&mut region, offset)?;
+                    if let Some(q_allowed) = q_allowed {
+                        q_allowed.enable(&mut region, offset)?;
+                    }
+                    let byte = byte.copy_advice(|| "byte", &mut region, self.byte, offset)?;
+                    let mask = byte.value().map(|byte| F::from(char_class(lower_128(byte) as u8) as u64));
+                    region.assign_advice(|| "mask", self.mask, offset, || mask)
+                }).collect::<Result<Vec<_>, Error>>()
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, ExpectedFailure};
+
+    #[derive(Default)]
+    struct CharClassCircuit {
+        bytes: Vec<u8>,
+        //when set, every byte has to have one of these classes
+        allowed: Option<u8>,
+    }
+
+    impl Circuit<Fp> for CharClassCircuit {
+        type Config = CharClassConfig<Fp>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { bytes: self.bytes.clone(), allowed: self.allowed }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(2);
+            CharClassConfig::configure_with_pool(cs, &mut pool, &[ALPHANUMERIC, WHITESPACE | PUNCT])
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            let bytes = layouter.assign_region(
+                || "assign bytes",
+                |mut region| {
+                    self.bytes.iter().enumerate().map(|(offset, byte)| {
+                        region.assign_advice(|| "byte", config.byte, offset, || Value::known(Fp::from(*byte as u64)))
+                    }).collect::<Result<Vec<_>, Error>>()
+                },
+            )?;
+            let masks = match self.allowed {
+                Some(allowed) => config.assign_allowed(layouter.namespace(|| "classes"), &bytes, allowed)?,
+                None => config.assign(layouter.namespace(|| "classes"), &bytes)?,
+            };
+            for (mask, byte) in masks.iter().zip(&self.bytes) {
+                mask.value().assert_if_known(|mask| **mask == Fp::from(char_class(*byte) as u64));
+            }
+            Ok(())
+        }
+    }
+
+    fn prover(bytes: &[u8], allowed: Option<u8>) -> MockProver<Fp> {
+        let k = 9;
+        MockProver::run(k, &CharClassCircuit { bytes: bytes.to_vec(), allowed }, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_char_class() {
+        assert_eq!(char_class(b'7'), DIGIT);
+        assert_eq!(char_class(b'Q'), UPPER);
+        assert_eq!(char_class(b'q'), LOWER);
+        assert_eq!(char_class(b'~'), PUNCT);
+        assert_eq!(char_class(b'\t'), WHITESPACE);
+        assert_eq!(char_class(0), 0);
+        assert_eq!(char_class(0xc3), 0);
+        //every byte at most one class
+        assert!((0..=255u8).all(|byte| char_class(byte).count_ones() <= 1));
+        //the masks looked up in circuit, checked against char_class
+        prover(b"aZ9 !\t\x7f\xff", None).assert_satisfied();
+    }
+
+    #[test]
+    fn test_alphanumeric() {
+        prover(b"Hello123", Some(ALPHANUMERIC)).assert_satisfied();
+        prover(b" ,\n.", Some(WHITESPACE | PUNCT)).assert_satisfied();
+    }
+
+    #[test]
+    fn test_disallowed_class() {
+        let failure = |allowed, row| ExpectedFailure::constraint(allowed_gate_name(allowed), "mask in allowed", "assign char classes", row);
+        expect_failures(&prover(b"Hello 123", Some(ALPHANUMERIC)), &[failure(ALPHANUMERIC, 5)]);
+        //bytes with no class fail every allowed mask
+        expect_failures(&prover(b"caf\xc3\xa9", Some(ALPHANUMERIC)), &[failure(ALPHANUMERIC, 3), failure(ALPHANUMERIC, 4)]);
+        expect_failures(&prover(b"a, b", Some(WHITESPACE | PUNCT)), &[failure(WHITESPACE | PUNCT, 0), failure(WHITESPACE | PUNCT, 3)]);
+    }
+
+    #[test]
+    fn test_allowed_gate_names() {
+        assert_eq!(allowed_gate_name(ALPHANUMERIC), "char class allowed 00111");
+        assert_eq!(allowed_gate_name(WHITESPACE | PUNCT), "char class allowed 11000");
+        let names: std::collections::HashSet<_> = (1..32).map(allowed_gate_name).collect();
+        assert_eq!(names.len(), 31);
+    }
+}
@@ -0,0 +1,169 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Assigned, Column, ConstraintSystem, Constraints, Error, Selector},
+    poly::Rotation,
+};
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, range_lookup::RangeTableConfig, report};
+
+//diff = a - b with diff in 0..RANGE, so a >= b. an underflow wraps around to p - (b - a),
+//which isn't in the table
+#[derive(Clone, Debug)]
+pub struct CheckedSubConfig<F: FieldExt, const RANGE: usize> {
+    pub a: Column<Advice>,
+    pub b: Column<Advice>,
+    pub diff: Column<Advice>,
+    pub q_enable: Selector,
+    pub table: RangeTableConfig<F, RANGE>,
+}
+
+impl<F: FieldExt, const RANGE: usize> CheckedSubConfig<F, RANGE> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, a: Column<Advice>, b: Column<Advice>, diff: Column<Advice>) -> Self {
+        let q_enable = cs.complex_selector();
+        let table = RangeTableConfig::configure(cs);
+
+        cs.enable_equality(a);
+        cs.enable_equality(b);
+        cs.enable_equality(diff);
+
+        cs.create_gate("checked sub", |cs| {
+            let q = cs.query_selector(q_enable);
+            let a = cs.query_advice(a, Rotation::cur());
+            let b = cs.query_advice(b, Rotation::cur());
+            let diff = cs.query_advice(diff, Rotation::cur());
+            Constraints::with_selector(q, [("difference", diff - (a - b))])
+        });
+        report::annotate_gate("checked_sub", "checked sub");
+
+        let lookup_index = cs.lookup(|cs| {
+            let q = cs.query_selector(q_enable);
+            let diff = cs.query_advice(diff, Rotation::cur());
+            vec![(q * diff, table.value)]
+        });
+        report::annotate_lookup("checked_sub", lookup_index);
+
+        Self {
+            a,
+            b,
+            diff,
+            q_enable,
+            table,
+        }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 3);
+        Self::configure(cs, columns[0], columns[1], columns[2])
+    }
+
+    //returns the difference cell
+    pub fn assign(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Value<Assigned<F>>,
+        b: Value<Assigned<F>>,
+    ) -> Result<AssignedCell<Assigned<F>, F>, CircuitsError> {
+        self.assign_with_diff(layouter, a, b, a - b)
+    }
+
+    fn assign_with_diff(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Value<Assigned<F>>,
+        b: Value<Assigned<F>>,
+        diff: Value<Assigned<F>>,
+    ) -> Result<AssignedCell<Assigned<F>, F>, CircuitsError> {
+        let offset = 0;
+        layouter
+            .assign_region(
+                || "assign checked sub",
+                |mut region| {
+                    self.q_enable.enable(&mut region, offset)?;
+                    region.assign_advice(|| "a", self.a, offset, || a)?;
+                    region.assign_advice(|| "b", self.b, offset, || b)?;
+                    region.assign_advice(|| "diff", self.diff, offset, || diff)
+                },
+            )
+            .map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, value_i64, value_u64, ExpectedFailure};
+
+    #[derive(Default)]
+    struct CheckedSubCircuit<F: FieldExt, const RANGE: usize> {
+        a: Value<Assigned<F>>,
+        b: Value<Assigned<F>>,
+        //when set, witnesses this difference instead of a - b
+        diff: Option<Value<Assigned<F>>>,
+    }
+
+    impl<F: FieldExt, const RANGE: usize> Circuit<F> for CheckedSubCircuit<F, RANGE> {
+        type Config = CheckedSubConfig<F, RANGE>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = cs.advice_column();
+            let b = cs.advice_column();
+            let diff = cs.advice_column();
+            CheckedSubConfig::configure(cs, a, b, diff)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            match self.diff {
+                Some(diff) => config.assign_with_diff(layouter.namespace(|| "sub"), self.a, self.b, diff)?,
+                None => config.assign(layouter.namespace(|| "sub"), self.a, self.b)?,
+            };
+            Ok(())
+        }
+    }
+
+    fn prover(a: u64, b: u64, diff: Option<Value<Assigned<Fp>>>) -> MockProver<Fp> {
+        let circuit = CheckedSubCircuit::<Fp, 16> {
+            a: value_u64(a),
+            b: value_u64(b),
+            diff,
+        };
+        MockProver::run(5, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_checked_sub() {
+        prover(7, 5, None).assert_satisfied();
+        prover(5, 5, None).assert_satisfied();
+    }
+
+    #[test]
+    fn test_underflow() {
+        //5 - 7 wraps to p - 2
+        expect_failures(&prover(5, 7, None), &[ExpectedFailure::lookup(0, "assign checked sub", 0)]);
+    }
+
+    #[test]
+    fn test_checked_sub_sound() {
+        //an in range difference that isn't a - b
+        expect_failures(
+            &prover(7, 4, Some(value_u64(2))),
+            &[ExpectedFailure::constraint("checked sub", "difference", "assign checked sub", 0)],
+        );
+        //hiding an underflow behind a small difference
+        expect_failures(
+            &prover(5, 7, Some(value_u64(2))),
+            &[ExpectedFailure::constraint("checked sub", "difference", "assign checked sub", 0)],
+        );
+        //the honest wrapped difference still fails the lookup
+        expect_failures(&prover(5, 7, Some(value_i64(-2))), &[ExpectedFailure::lookup(0, "assign checked sub", 0)]);
+    }
+}
@@ -0,0 +1,142 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Selector},
+    poly::Rotation,
+};
+
+use crate::{column_pool::ColumnPool, div_mod::DivModByRangeConfig, error::CircuitsError, range_lookup::RangeTableConfig, report, running_sum::RunningSumConfig};
+
+//the checksum (sum of N bytes) mod 256. the byte cells are copied in, looked up in 0..256 and
+//summed on a running column, and the sum goes through the div mod gadget whose remainder is
+//the checksum. the byte lookups share the div mod table
+#[derive(Clone, Debug)]
+pub struct ChecksumConfig<F: FieldExt, const N: usize> {
+    pub sum: RunningSumConfig<F>,
+    pub q_byte: Selector,
+    pub div_mod: DivModByRangeConfig<F, 256>,
+}
+
+impl<F: FieldExt, const N: usize> ChecksumConfig<F, N> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, byte: Column<Advice>, sum: Column<Advice>, div_mod: DivModByRangeConfig<F, 256>) -> Self {
+        assert!(N > 0);
+        let q_byte = cs.complex_selector();
+        let sum = RunningSumConfig::configure(cs, byte, sum, 1);
+
+        let table = div_mod.table.value;
+        let lookup_index = cs.lookup(|cs| {
+            let q = cs.query_selector(q_byte);
+            let byte = cs.query_advice(byte, Rotation::cur());
+            vec![(q * byte, table)]
+        });
+        report::annotate_lookup("checksum", lookup_index);
+
+        Self { sum, q_byte, div_mod }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 2);
+        let div_mod = DivModByRangeConfig::configure_with_pool(cs, pool);
+        Self::configure(cs, columns[0], columns[1], div_mod)
+    }
+
+    pub fn table(&self) -> &RangeTableConfig<F, 256> {
+        &self.div_mod.table
+    }
+
+    //returns the checksum cell of byte cells assigned elsewhere
+    pub fn assign(&self, mut layouter: impl Layouter<F>, bytes: &[AssignedCell<F, F>; N]) -> Result<AssignedCell<F, F>, CircuitsError> {
+        let sum = layouter.assign_region(
+            || "assign checksum",
+            |mut region| {
+                for offset in 0..N {
+                    self.q_byte.enable(&mut region, offset)?;
+                }
+                let values: Vec<_> = bytes.iter().map(|byte| byte.value().copied()).collect();
+                let rows = self.sum.assign_rows(&mut region, 0, &values, None)?;
+                for ((copy, _), byte) in rows.iter().zip(bytes) {
+                    region.constrain_equal(byte.cell(), copy.cell())?;
+                }
+                Ok(rows[N - 1].1.clone())
+            },
+        )?;
+
+        //the sum is at most 255 * N, so q < N
+        let q_bits = (usize::BITS - N.leading_zeros()) as usize;
+        let (_, r) = self.div_mod.assign_cell(layouter.namespace(|| "reduce checksum"), &sum, q_bits)?;
+        Ok(r)
+    }
+
+    //constrains the checksum of the bytes to a claimed cell assigned elsewhere
+    pub fn assign_claimed(&self, mut layouter: impl Layouter<F>, bytes: &[AssignedCell<F, F>; N], claimed: &AssignedCell<F, F>) -> Result<(), CircuitsError> {
+        let r = self.assign(layouter.namespace(|| "checksum"), bytes)?;
+        layouter.assign_region(|| "check checksum", |mut region| region.constrain_equal(claimed.cell(), r.cell())).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, ExpectedFailure};
+
+    #[derive(Default)]
+    struct ChecksumCircuit {
+        bytes: [Value<Fp>; 4],
+        claimed: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for ChecksumCircuit {
+        type Config = ChecksumConfig<Fp, 4>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(5);
+            ChecksumConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table().load(&mut layouter)?;
+            let (bytes, claimed) = layouter.assign_region(
+                || "assign inputs",
+                |mut region| {
+                    let bytes = self.bytes.iter().enumerate().map(|(offset, byte)| region.assign_advice(|| "byte", config.sum.term, offset, || *byte)).collect::<Result<Vec<_>, _>>()?;
+                    let bytes: [AssignedCell<Fp, Fp>; 4] = bytes.try_into().unwrap();
+                    let claimed = region.assign_advice(|| "claimed", config.sum.acc, 0, || self.claimed)?;
+                    Ok((bytes, claimed))
+                },
+            )?;
+            config.assign_claimed(layouter.namespace(|| "checksum"), &bytes, &claimed)?;
+            Ok(())
+        }
+    }
+
+    fn prover(bytes: [u64; 4], claimed: u64) -> MockProver<Fp> {
+        let k = 9;
+        let circuit = ChecksumCircuit { bytes: bytes.map(|b| Value::known(Fp::from(b))), claimed: Value::known(Fp::from(claimed)) };
+        MockProver::run(k, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_checksum() {
+        //200 + 100 + 255 + 3 = 558 = 2 * 256 + 46
+        prover([200, 100, 255, 3], 46).assert_satisfied();
+        prover([255; 4], 252).assert_satisfied();
+        prover([0; 4], 0).assert_satisfied();
+    }
+
+    #[test]
+    fn test_checksum_sound() {
+        let permutation = |region: &str| ExpectedFailure { name: "permutation".to_string(), region: region.to_string(), row: 0 };
+        //off by one, and the unreduced sum
+        for claimed in [47, 558] {
+            expect_failures(&prover([200, 100, 255, 3], claimed), &[permutation("assign div mod"), permutation("assign inputs")]);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Advice, Column, ConstraintSystem},
+};
+
+use std::marker::PhantomData;
+
+//hands out advice columns to gadget configs so a circuit composing several of them stays
+//at size columns. the first size requests allocate, after that the columns are reused
+//round robin. gadgets sharing a column is fine since each one only constrains rows its own
+//selectors are enabled on, and regions from different gadgets never overlap on a column.
+#[derive(Clone, Debug)]
+pub struct ColumnPool<F: FieldExt> {
+    size: usize,
+    columns: Vec<Column<Advice>>,
+    next: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> ColumnPool<F> {
+    pub fn new(size: usize) -> Self {
+        assert!(size > 0);
+        Self {
+            size,
+            columns: vec![],
+            next: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn advice(&mut self, cs: &mut ConstraintSystem<F>) -> Column<Advice> {
+        if self.columns.len() < self.size {
+            self.columns.push(cs.advice_column());
+        }
+        let column = self.columns[self.next];
+        self.next = (self.next + 1) % self.size;
+        column
+    }
+
+    //n distinct columns, for gadgets that query several columns on the same row
+    pub fn advice_many(&mut self, cs: &mut ConstraintSystem<F>, n: usize) -> Vec<Column<Advice>> {
+        assert!(n <= self.size, "a gadget needs {} distinct columns but the pool only has {}", n, self.size);
+        (0..n).map(|_| self.advice(cs)).collect()
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    //the columns allocated so far
+    pub fn columns(&self) -> &[Column<Advice>] {
+        &self.columns
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Assigned, Circuit, Error},
+    };
+
+    use super::*;
+    use crate::{prime::PrimeCheckConfig, range_check::RangeCheckCircuitConfig, range_lookup::RangeCheckLookupConfig, test_utils::value_u64};
+
+    #[derive(Default)]
+    struct PooledCircuit {
+        values: [Value<Assigned<Fp>>; 3],
+    }
+
+    impl Circuit<Fp> for PooledCircuit {
+        type Config = (RangeCheckCircuitConfig<Fp, 8>, RangeCheckLookupConfig<Fp, 16>, PrimeCheckConfig<Fp, 20>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(2);
+            (
+                RangeCheckCircuitConfig::configure_with_pool(cs, &mut pool),
+                RangeCheckLookupConfig::configure_with_pool(cs, &mut pool),
+                PrimeCheckConfig::configure_with_pool(cs, &mut pool),
+            )
+        }
+
+        fn synthesize(&self, (gate, lookup, prime): Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            lookup.table.load(&mut layouter)?;
+            prime.table.load(&mut layouter)?;
+            gate.assign(layouter.namespace(|| "gate"), self.values[0])?;
+            lookup.assign_lookup(layouter.namespace(|| "lookup"), self.values[1])?;
+            prime.assign(layouter.namespace(|| "prime"), self.values[2])?;
+            Ok(())
+        }
+    }
+
+    fn verify(values: [u64; 3]) -> bool {
+        let circuit = PooledCircuit {
+            values: values.map(value_u64),
+        };
+        MockProver::run(5, &circuit, vec![]).unwrap().verify().is_ok()
+    }
+
+    #[test]
+    fn test_pool_composition() {
+        assert!(verify([7, 15, 13]));
+        //each gadget still rejects on its own while sharing columns
+        assert!(!verify([8, 15, 13]));
+        assert!(!verify([7, 16, 13]));
+        assert!(!verify([7, 15, 12]));
+    }
+
+    #[test]
+    fn test_pool_column_count() {
+        let mut cs = ConstraintSystem::<Fp>::default();
+        PooledCircuit::configure(&mut cs);
+        //the fields aren't public, the pinned debug output has the count
+        assert!(format!("{:?}", cs.pinned()).contains("num_advice_columns: 2,"));
+
+        let mut cs = ConstraintSystem::<Fp>::default();
+        let mut pool = ColumnPool::new(2);
+        let columns = [pool.advice(&mut cs), pool.advice(&mut cs), pool.advice(&mut cs)];
+        assert_eq!(columns[0], columns[2]);
+        assert_ne!(columns[0], columns[1]);
+        assert_eq!(pool.columns().len(), pool.size());
+    }
+}
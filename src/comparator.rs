@@ -0,0 +1,200 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Region, Value},
+    plonk::{Advice, Assigned, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, field::lower_128, range_lookup::RangeTableConfig, report};
+
+//witnesses lt = [a < b] for a, b in 0..RANGE.
+//
+//a and b are looked up in 0..RANGE, and so is b - a - 1 when lt is set or a - b when it
+//isn't. whichever is wrong for the actual order goes negative and misses the table
+#[derive(Clone, Debug)]
+pub struct ComparatorConfig<F: FieldExt, const RANGE: usize> {
+    pub a: Column<Advice>,
+    pub b: Column<Advice>,
+    pub lt: Column<Advice>,
+    pub q_enable: Selector,
+    pub table: RangeTableConfig<F, RANGE>,
+}
+
+impl<F: FieldExt, const RANGE: usize> ComparatorConfig<F, RANGE> {
+    //table can be shared with other gadgets, clones of it are loaded together
+    pub fn configure(
+        cs: &mut ConstraintSystem<F>,
+        a: Column<Advice>,
+        b: Column<Advice>,
+        lt: Column<Advice>,
+        table: RangeTableConfig<F, RANGE>,
+    ) -> Self {
+        let q_enable = cs.complex_selector();
+
+        cs.enable_equality(a);
+        cs.enable_equality(b);
+        cs.enable_equality(lt);
+
+        cs.create_gate("comparator", |cs| {
+            let q = cs.query_selector(q_enable);
+            let lt = cs.query_advice(lt, Rotation::cur());
+            Constraints::with_selector(q, [("lt boolean", lt.clone() * (Expression::Constant(F::one()) - lt))])
+        });
+        report::annotate_gate("comparator", "comparator");
+
+        for column in [a, b] {
+            let lookup_index = cs.lookup(|cs| {
+                let q = cs.query_selector(q_enable);
+                let v = cs.query_advice(column, Rotation::cur());
+                vec![(q * v, table.value)]
+            });
+            report::annotate_lookup("comparator", lookup_index);
+        }
+
+        let lookup_index = cs.lookup(|cs| {
+            let q = cs.query_selector(q_enable);
+            let a = cs.query_advice(a, Rotation::cur());
+            let b = cs.query_advice(b, Rotation::cur());
+            let lt = cs.query_advice(lt, Rotation::cur());
+            let one = Expression::Constant(F::one());
+            let diff = lt.clone() * (b.clone() - a.clone() - one.clone()) + (one - lt) * (a - b);
+            vec![(q * diff, table.value)]
+        });
+        report::annotate_lookup("comparator", lookup_index);
+
+        Self { a, b, lt, q_enable, table }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 3);
+        let table = RangeTableConfig::configure(cs);
+        Self::configure(cs, columns[0], columns[1], columns[2], table)
+    }
+
+    pub fn lt_value(a: Value<Assigned<F>>, b: Value<Assigned<F>>) -> Value<Assigned<F>> {
+        a.zip(b).map(|(a, b)| {
+            let lt = lower_128(&a.evaluate()) < lower_128(&b.evaluate());
+            Assigned::from(F::from(lt as u64))
+        })
+    }
+
+    //returns the lt cell for cells a and b assigned elsewhere
+    pub fn assign<V>(&self, layouter: impl Layouter<F>, a: &AssignedCell<V, F>, b: &AssignedCell<V, F>) -> Result<AssignedCell<Assigned<F>, F>, CircuitsError>
+    where
+        V: Clone,
+        for<'v> Assigned<F>: From<&'v V>,
+    {
+        let lt = Self::lt_value(a.value().map(|a| Assigned::from(a)), b.value().map(|b| Assigned::from(b)));
+        //lt is Assigned<F> whatever V is, named since the From<&V> bound would otherwise pick V
+        self.assign_rows(layouter, a, b, |region, column, offset| region.assign_advice::<_, Assigned<F>, _, _>(|| "lt", column, offset, || lt))
+    }
+
+    //compares a and b with lt copied from an earlier result, so the two are bound to agree
+    pub fn assign_with_lt<V>(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<V, F>,
+        b: &AssignedCell<V, F>,
+        lt: &AssignedCell<Assigned<F>, F>,
+    ) -> Result<AssignedCell<Assigned<F>, F>, CircuitsError>
+    where
+        V: Clone,
+        for<'v> Assigned<F>: From<&'v V>,
+    {
+        self.assign_rows(layouter, a, b, |region, column, offset| lt.copy_advice(|| "lt", region, column, offset))
+    }
+
+    fn assign_rows<V>(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<V, F>,
+        b: &AssignedCell<V, F>,
+        assign_lt: impl Fn(&mut Region<'_, F>, Column<Advice>, usize) -> Result<AssignedCell<Assigned<F>, F>, Error>,
+    ) -> Result<AssignedCell<Assigned<F>, F>, CircuitsError>
+    where
+        V: Clone,
+        for<'v> Assigned<F>: From<&'v V>,
+    {
+        let offset = 0;
+        layouter.assign_region(
+            || "assign comparison",
+            |mut region| {
+                self.q_enable.enable(&mut region, offset)?;
+                a.copy_advice(|| "a", &mut region, self.a, offset)?;
+                b.copy_advice(|| "b", &mut region, self.b, offset)?;
+                assign_lt(&mut region, self.lt, offset)
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, value_u64, ExpectedFailure};
+
+    #[derive(Default)]
+    struct ComparatorCircuit {
+        a: Value<Assigned<Fp>>,
+        b: Value<Assigned<Fp>>,
+        //when set, witnesses this result instead of the real one
+        lt: Option<Value<Assigned<Fp>>>,
+    }
+
+    impl Circuit<Fp> for ComparatorCircuit {
+        type Config = ComparatorConfig<Fp, 16>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(3);
+            ComparatorConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            let (a, b) = layouter.assign_region(
+                || "assign inputs",
+                |mut region| Ok((region.assign_advice(|| "a", config.a, 0, || self.a)?, region.assign_advice(|| "b", config.b, 0, || self.b)?)),
+            )?;
+            match self.lt {
+                Some(lt) => config.assign_rows(layouter.namespace(|| "compare"), &a, &b, |region, column, offset| {
+                    region.assign_advice(|| "lt", column, offset, || lt)
+                })?,
+                None => config.assign(layouter.namespace(|| "compare"), &a, &b)?,
+            };
+            Ok(())
+        }
+    }
+
+    fn prover(a: u64, b: u64, lt: Option<u64>) -> MockProver<Fp> {
+        let k = 5;
+        let circuit = ComparatorCircuit { a: value_u64(a), b: value_u64(b), lt: lt.map(value_u64) };
+        MockProver::run(k, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_comparator() {
+        for (a, b) in [(0, 15), (3, 4), (4, 3), (7, 7), (15, 0)] {
+            prover(a, b, None).assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_comparator_sound() {
+        let diff = [ExpectedFailure::lookup(2, "assign comparison", 0)];
+        //claiming 5 < 5 and 6 >= 9
+        expect_failures(&prover(5, 5, Some(1)), &diff);
+        expect_failures(&prover(6, 9, Some(0)), &diff);
+        expect_failures(
+            &prover(6, 9, Some(2)),
+            &[ExpectedFailure::constraint("comparator", "lt boolean", "assign comparison", 0)],
+        );
+    }
+}
@@ -0,0 +1,198 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Assigned, Column, ConstraintSystem, Error, Expression, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use std::marker::PhantomData;
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, field::lower_128, rows::{self, RowBudget, TablePadding}};
+
+//longest day of each month. february gets 29 since there's no year to tell leap years apart
+pub const MAX_DAYS: [u64; 12] = [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
+//checks 1 <= day <= max_day(month) for month in 1..=12.
+//
+//(month, max_day) is looked up in a table of the twelve months, which also pins the month
+//down. the two day bounds are looked up in 0..=31.
+#[derive(Clone, Debug)]
+pub struct DayOfMonthConfig<F: FieldExt> {
+    pub month: Column<Advice>,
+    pub day: Column<Advice>,
+    pub max_day: Column<Advice>,
+    pub q_enable: Selector,
+    pub month_table: TableColumn,
+    pub max_day_table: TableColumn,
+    //holds 0..=31
+    pub day_table: TableColumn,
+    //what load_padded puts in the rows past either table, a filler row has to be in both
+    pub padding: TablePadding,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> DayOfMonthConfig<F> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, month: Column<Advice>, day: Column<Advice>, max_day: Column<Advice>) -> Self {
+        Self::configure_padded(cs, month, day, max_day, TablePadding::Unpadded)
+    }
+
+    pub fn configure_padded(cs: &mut ConstraintSystem<F>, month: Column<Advice>, day: Column<Advice>, max_day: Column<Advice>, padding: TablePadding) -> Self {
+        let q_enable = cs.complex_selector();
+        let month_table = cs.lookup_table_column();
+        let max_day_table = cs.lookup_table_column();
+        let day_table = cs.lookup_table_column();
+
+        cs.enable_equality(month);
+        cs.enable_equality(day);
+
+        cs.lookup(|cs| {
+            let q = cs.query_selector(q_enable);
+            let month = cs.query_advice(month, Rotation::cur());
+            let max_day = cs.query_advice(max_day, Rotation::cur());
+            vec![(q.clone() * month, month_table), (q * max_day, max_day_table)]
+        });
+
+        //day <= max_day
+        cs.lookup(|cs| {
+            let q = cs.query_selector(q_enable);
+            let day = cs.query_advice(day, Rotation::cur());
+            let max_day = cs.query_advice(max_day, Rotation::cur());
+            vec![(q * (max_day - day), day_table)]
+        });
+
+        //day >= 1
+        cs.lookup(|cs| {
+            let q = cs.query_selector(q_enable);
+            let day = cs.query_advice(day, Rotation::cur());
+            vec![(q * (day - Expression::Constant(F::one())), day_table)]
+        });
+
+        Self {
+            month,
+            day,
+            max_day,
+            q_enable,
+            month_table,
+            max_day_table,
+            day_table,
+            padding: padding.for_rows(Self::month_rows().len()),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 3);
+        Self::configure(cs, columns[0], columns[1], columns[2])
+    }
+
+    fn month_rows() -> Vec<Vec<F>> {
+        //disabled rows look up (0, 0)
+        std::iter::once((0, 0)).chain((1..).zip(MAX_DAYS)).map(|(month, max_day)| vec![F::from(month), F::from(max_day)]).collect()
+    }
+
+    fn day_rows() -> Vec<Vec<F>> {
+        (0..=31).map(|day| vec![F::from(day)]).collect()
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), CircuitsError> {
+        self.load_tables(layouter, None)
+    }
+
+    //load that first checks the table fits in the budget, then fills the rest of its usable
+    //rows as the padding says
+    pub fn load_padded(&self, layouter: &mut impl Layouter<F>, budget: RowBudget) -> Result<(), CircuitsError> {
+        self.load_tables(layouter, Some(budget))
+    }
+
+    fn load_tables(&self, layouter: &mut impl Layouter<F>, budget: Option<RowBudget>) -> Result<(), CircuitsError> {
+        let month_columns = [("month", self.month_table), ("max day", self.max_day_table)];
+        rows::load_table(layouter, "month table", &month_columns, &Self::month_rows(), self.padding, budget)?;
+        rows::load_table(layouter, "day table", &[("day", self.day_table)], &Self::day_rows(), self.padding, budget)
+    }
+
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        month: Value<Assigned<F>>,
+        day: Value<Assigned<F>>,
+    ) -> Result<AssignedCell<Assigned<F>, F>, CircuitsError> {
+        let offset = 0;
+        //an invalid month has no max day, any witness fails the month lookup
+        let max_day = month.map(|m| {
+            let m = lower_128(&m.evaluate()) as usize;
+            let max_day = if (1..=12).contains(&m) { MAX_DAYS[m - 1] } else { 0 };
+            Assigned::from(F::from(max_day))
+        });
+        layouter.assign_region(
+            || "assign day of month",
+            |mut region| {
+                self.q_enable.enable(&mut region, offset)?;
+                region.assign_advice(|| "month", self.month, offset, || month)?;
+                region.assign_advice(|| "max day", self.max_day, offset, || max_day)?;
+                region.assign_advice(|| "day", self.day, offset, || day)
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::value_u64;
+
+    #[derive(Default)]
+    struct DateCircuit<F: FieldExt> {
+        month: Value<Assigned<F>>,
+        day: Value<Assigned<F>>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for DateCircuit<F> {
+        type Config = DayOfMonthConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let month = cs.advice_column();
+            let day = cs.advice_column();
+            let max_day = cs.advice_column();
+            DayOfMonthConfig::configure(cs, month, day, max_day)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            config.load(&mut layouter)?;
+            config.assign(layouter.namespace(|| "date"), self.month, self.day)?;
+            Ok(())
+        }
+    }
+
+    fn verify(month: u64, day: u64) -> bool {
+        let k = 6;
+        let circuit = DateCircuit::<Fp> {
+            month: value_u64(month),
+            day: value_u64(day),
+        };
+        MockProver::run(k, &circuit, vec![]).unwrap().verify().is_ok()
+    }
+
+    #[test]
+    fn test_valid_dates() {
+        assert!(verify(2, 28));
+        assert!(verify(1, 31));
+        assert!(verify(12, 1));
+    }
+
+    #[test]
+    fn test_invalid_dates() {
+        assert!(!verify(2, 30));
+        assert!(!verify(4, 31));
+        assert!(!verify(1, 0));
+        assert!(!verify(13, 1));
+        assert!(!verify(0, 0));
+    }
+}
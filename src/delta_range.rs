@@ -0,0 +1,110 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Assigned, Column, ConstraintSystem, Error, Selector},
+    poly::Rotation,
+};
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, range_lookup::RangeTableConfig, report};
+
+//checks value[i + 1] - value[i] is in 0..MAX_DELTA on every enabled row. a decreasing step
+//wraps around to a huge field element, so it misses the table like a big jump does
+#[derive(Clone, Debug)]
+pub struct DeltaRangeCheckConfig<F: FieldExt, const MAX_DELTA: usize> {
+    pub value: Column<Advice>,
+    pub q_delta: Selector,
+    pub table: RangeTableConfig<F, MAX_DELTA>,
+}
+
+impl<F: FieldExt, const MAX_DELTA: usize> DeltaRangeCheckConfig<F, MAX_DELTA> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, value: Column<Advice>) -> Self {
+        let q_delta = cs.complex_selector();
+        let table = RangeTableConfig::configure(cs);
+
+        cs.enable_equality(value);
+
+        let lookup_index = cs.lookup(|cs| {
+            let q = cs.query_selector(q_delta);
+            let cur = cs.query_advice(value, Rotation::cur());
+            let next = cs.query_advice(value, Rotation::next());
+            vec![(q * (next - cur), table.value)]
+        });
+        report::annotate_lookup("delta_range", lookup_index);
+
+        Self { value, q_delta, table }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let value = pool.advice(cs);
+        Self::configure(cs, value)
+    }
+
+    //lays the values out on consecutive rows, the selector is on every row but the last
+    pub fn assign_column(&self, mut layouter: impl Layouter<F>, values: &[Value<Assigned<F>>]) -> Result<Vec<AssignedCell<Assigned<F>, F>>, CircuitsError> {
+        layouter.assign_region(
+            || "assign delta column",
+            |mut region| {
+                values.iter().enumerate().map(|(offset, value)| {
+                    if offset + 1 < values.len() {
+                        self.q_delta.enable(&mut region, offset)?;
+                    }
+                    region.assign_advice(|| format!("value {}", offset), self.value, offset, || *value)
+                }).collect()
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, values_u64, ExpectedFailure};
+
+    #[derive(Default)]
+    struct DeltaCircuit {
+        values: Vec<Value<Assigned<Fp>>>,
+    }
+
+    impl Circuit<Fp> for DeltaCircuit {
+        type Config = DeltaRangeCheckConfig<Fp, 4>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { values: vec![Value::unknown(); self.values.len()] }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(1);
+            DeltaRangeCheckConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            config.assign_column(layouter.namespace(|| "delta"), &self.values)?;
+            Ok(())
+        }
+    }
+
+    fn prover(values: &[u64]) -> MockProver<Fp> {
+        let k = 4;
+        MockProver::run(k, &DeltaCircuit { values: values_u64(values) }, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_small_steps() {
+        //steps of 0 to MAX_DELTA - 1, starting anywhere
+        prover(&[100, 100, 101, 104, 107]).assert_satisfied();
+        prover(&[5]).assert_satisfied();
+    }
+
+    #[test]
+    fn test_delta_sound() {
+        //4 -> 8 is a step of MAX_DELTA, checked on the row of the 4
+        expect_failures(&prover(&[1, 4, 8, 9]), &[ExpectedFailure::lookup(0, "assign delta column", 1)]);
+        //9 -> 8 wraps to p - 1
+        expect_failures(&prover(&[1, 4, 7, 9, 8]), &[ExpectedFailure::lookup(0, "assign delta column", 3)]);
+    }
+}
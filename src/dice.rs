@@ -0,0 +1,222 @@
+#![allow(warnings, unused)]
+use halo2_gadgets::poseidon::{
+    primitives::{self as poseidon, ConstantLength, P128Pow5T3},
+    Hash, Pow5Chip, Pow5Config,
+};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    pasta::Fp,
+    plonk::{Advice, Assigned, Circuit, Column, ConstraintSystem, Constraints, Error, Expression, Instance, Selector},
+    poly::Rotation,
+};
+
+use crate::{bounds::BoundsConfig, column_pool::ColumnPool, div_mod::DivModByRangeConfig, proving::Instances, report};
+
+pub const SIDES: u64 = 6;
+//seeds are u64s, so seed / 6 fits in 62 bits
+const SEED_Q_BITS: usize = 62;
+//k the circuit fits in
+pub const DICE_K: u32 = 7;
+
+//instance rows
+const COMMITMENT_ROW: usize = 0;
+const ROLL_ROW: usize = 1;
+
+//the commitment published before the roll, poseidon(seed, blinding)
+pub fn commit(seed: u64, blinding: Fp) -> Fp {
+    poseidon::Hash::<_, P128Pow5T3, ConstantLength<2>, 3, 2>::init().hash([Fp::from(seed), blinding])
+}
+
+pub fn roll(seed: u64) -> u64 {
+    seed % SIDES + 1
+}
+
+#[derive(Clone, Debug)]
+pub struct DiceConfig {
+    //seed and blinding
+    pub private: Column<Advice>,
+    pub r: Column<Advice>,
+    pub roll: Column<Advice>,
+    pub q_roll: Selector,
+    pub instance: Column<Instance>,
+    //seed = q * 6 + r
+    pub div_mod: DivModByRangeConfig<Fp, 6>,
+    //1 <= roll <= 6, against the pinned bounds
+    pub bounds: BoundsConfig<Fp, 8>,
+    pub poseidon: Pow5Config<Fp, 3, 2>,
+}
+
+//proves a public roll is (seed mod 6) + 1 for the seed opening a public commitment. the
+//instance column holds [commitment, roll]
+#[derive(Clone, Debug, Default)]
+pub struct DiceCircuit {
+    seed: Value<Fp>,
+    blinding: Value<Fp>,
+    //witnessed as seed's (q, r) instead of the real division, for the soundness tests
+    qr: Option<(Value<Fp>, Value<Fp>)>,
+}
+
+impl DiceCircuit {
+    pub fn new(seed: u64, blinding: Fp) -> Self {
+        Self { seed: Value::known(Fp::from(seed)), blinding: Value::known(blinding), qr: None }
+    }
+
+    pub fn public_inputs(commitment: Fp, roll: u64) -> Instances {
+        vec![vec![commitment, Fp::from(roll)]]
+    }
+}
+
+impl Circuit<Fp> for DiceCircuit {
+    type Config = DiceConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self { qr: self.qr.map(|_| (Value::unknown(), Value::unknown())), ..Self::default() }
+    }
+
+    fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let mut pool = ColumnPool::new(6);
+        let [private, r, roll]: [Column<Advice>; 3] = pool.advice_many(cs, 3).try_into().unwrap();
+        let instance = cs.instance_column();
+        let constants = cs.fixed_column();
+        let q_roll = cs.selector();
+
+        for column in [private, r, roll] {
+            cs.enable_equality(column);
+        }
+        cs.enable_equality(instance);
+        cs.enable_constant(constants);
+
+        cs.create_gate("dice roll", |cs| {
+            let q = cs.query_selector(q_roll);
+            let r = cs.query_advice(r, Rotation::cur());
+            let roll = cs.query_advice(roll, Rotation::cur());
+            Constraints::with_selector(q, [("roll = r + 1", roll - (r + Expression::Constant(Fp::one())))])
+        });
+        report::annotate_gate("dice", "dice roll");
+
+        let div_mod = DivModByRangeConfig::configure_with_pool(cs, &mut pool);
+        let bounds = BoundsConfig::configure_with_pool(cs, &mut pool);
+        cs.enable_equality(bounds.min);
+        cs.enable_equality(bounds.max);
+
+        let state = [cs.advice_column(), cs.advice_column(), cs.advice_column()];
+        let partial_sbox = cs.advice_column();
+        let rc_a = [cs.fixed_column(), cs.fixed_column(), cs.fixed_column()];
+        let rc_b = [cs.fixed_column(), cs.fixed_column(), cs.fixed_column()];
+        cs.enable_constant(rc_b[0]);
+        let poseidon = Pow5Chip::configure::<P128Pow5T3>(cs, state, partial_sbox, rc_a, rc_b);
+
+        DiceConfig { private, r, roll, q_roll, instance, div_mod, bounds, poseidon }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        config.div_mod.table.load(&mut layouter)?;
+        config.bounds.table.load(&mut layouter)?;
+
+        let (seed, blinding) = layouter.assign_region(
+            || "load seed",
+            |mut region| {
+                let seed = region.assign_advice(|| "seed", config.private, 0, || self.seed)?;
+                let blinding = region.assign_advice(|| "blinding", config.private, 1, || self.blinding)?;
+                Ok((seed, blinding))
+            },
+        )?;
+
+        let hasher = Hash::<_, _, P128Pow5T3, ConstantLength<2>, 3, 2>::init(
+            Pow5Chip::construct(config.poseidon.clone()),
+            layouter.namespace(|| "init commitment"),
+        )?;
+        let commitment = hasher.hash(layouter.namespace(|| "commitment"), [seed.clone(), blinding])?;
+        layouter.constrain_instance(commitment.cell(), config.instance, COMMITMENT_ROW)?;
+
+        let (_, r) = match self.qr {
+            Some((q, r)) => config.div_mod.assign_with_qr(layouter.namespace(|| "seed mod 6"), self.seed, Some(&seed), q, r, SEED_Q_BITS)?,
+            None => config.div_mod.assign_cell(layouter.namespace(|| "seed mod 6"), &seed, SEED_Q_BITS)?,
+        };
+
+        let roll = layouter.assign_region(
+            || "assign roll",
+            |mut region| {
+                config.q_roll.enable(&mut region, 0)?;
+                let r = r.copy_advice(|| "r", &mut region, config.r, 0)?;
+                region.assign_advice(|| "roll", config.roll, 0, || r.value().map(|r| *r + Fp::one()))
+            },
+        )?;
+        layouter.constrain_instance(roll.cell(), config.instance, ROLL_ROW)?;
+
+        let known = |v: u64| Value::known(Assigned::from(Fp::from(v)));
+        let (value, min, max) = config.bounds.assign_with_bounds(
+            layouter.namespace(|| "roll in 1..=6"),
+            roll.value().map(|roll| Assigned::from(*roll)),
+            known(1),
+            known(SIDES),
+        )?;
+        layouter.assign_region(
+            || "pin roll bounds",
+            |mut region| {
+                let one = region.assign_advice_from_constant(|| "one", config.private, 0, Fp::one())?;
+                let sides = region.assign_advice_from_constant(|| "sides", config.private, 1, Fp::from(SIDES))?;
+                region.constrain_equal(value.cell(), roll.cell())?;
+                region.constrain_equal(min.cell(), one.cell())?;
+                region.constrain_equal(max.cell(), sides.cell())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::dev::MockProver;
+
+    use super::*;
+    use crate::test_utils::Forged;
+
+    const SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
+    fn blinding() -> Fp {
+        Fp::from(424242)
+    }
+
+    fn verify(circuit: &DiceCircuit, roll: u64) -> bool {
+        let instances = DiceCircuit::public_inputs(commit(SEED, blinding()), roll);
+        let prover = MockProver::run(DICE_K, circuit, instances).unwrap();
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn test_honest_roll() {
+        assert_eq!(roll(SEED), SEED % 6 + 1);
+        assert!(verify(&DiceCircuit::new(SEED, blinding()), roll(SEED)));
+        for seed in [0, 5, 6, u64::MAX] {
+            let instances = DiceCircuit::public_inputs(commit(seed, blinding()), roll(seed));
+            let prover = MockProver::run(DICE_K, &DiceCircuit::new(seed, blinding()), instances).unwrap();
+            assert!(prover.verify().is_ok());
+        }
+    }
+
+    #[test]
+    fn test_inconsistent_roll() {
+        //every other face claimed against the committed seed
+        for face in (1..=SIDES).filter(|&face| face != roll(SEED)) {
+            assert!(!verify(&DiceCircuit::new(SEED, blinding()), face));
+        }
+        //the claimed roll with a seed other than the committed one
+        let other = SEED + 1;
+        assert!(!verify(&DiceCircuit::new(other, blinding()), roll(other)));
+    }
+
+    #[test]
+    fn test_out_of_range_rolls() {
+        //seed = 6 * q + r holds with an integer q for r = roll - 1, only the range checks rule
+        //0 and 7 out
+        for (seed, q, face) in [(5999, 1000, 0), (6000, 999, 7)] {
+            let r = Fp::from(face) - Fp::one();
+            assert_eq!(Fp::from(seed), Fp::from(q) * Fp::from(SIDES) + r);
+            let circuit = DiceCircuit { qr: Forged::new(&[Fp::from(q), r]).pair(), ..DiceCircuit::new(seed, blinding()) };
+            let instances = DiceCircuit::public_inputs(commit(seed, blinding()), face);
+            let prover = MockProver::run(DICE_K, &circuit, instances).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+}
@@ -0,0 +1,136 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Assigned, Column, ConstraintSystem, Constraints, Error, Selector},
+    poly::Rotation,
+};
+
+use crate::{column_pool::ColumnPool, comparator::ComparatorConfig, error::CircuitsError, range_lookup::RangeTableConfig, report};
+
+//checks [a1, a2) and [b1, b2) don't overlap, i.e. a2 <= b1 or b2 <= a1, with all four ends
+//in 0..RANGE. the comparator gives the negations b1 < a2 and a1 < b2, and the or holds when
+//they aren't both set. well-formedness of the intervals is up to the caller
+#[derive(Clone, Debug)]
+pub struct DisjointConfig<F: FieldExt, const RANGE: usize> {
+    pub comparator: ComparatorConfig<F, RANGE>,
+    pub q_disjoint: Selector,
+}
+
+impl<F: FieldExt, const RANGE: usize> DisjointConfig<F, RANGE> {
+    //the two flags are copied side by side into the comparator's a and b columns
+    pub fn configure(cs: &mut ConstraintSystem<F>, comparator: ComparatorConfig<F, RANGE>) -> Self {
+        let q_disjoint = cs.selector();
+
+        cs.create_gate("disjoint", |cs| {
+            let q = cs.query_selector(q_disjoint);
+            let b1_lt_a2 = cs.query_advice(comparator.a, Rotation::cur());
+            let a1_lt_b2 = cs.query_advice(comparator.b, Rotation::cur());
+            Constraints::with_selector(q, [("a2 <= b1 or b2 <= a1", b1_lt_a2 * a1_lt_b2)])
+        });
+        report::annotate_gate("disjoint", "disjoint");
+
+        Self { comparator, q_disjoint }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let comparator = ComparatorConfig::configure_with_pool(cs, pool);
+        Self::configure(cs, comparator)
+    }
+
+    pub fn table(&self) -> &RangeTableConfig<F, RANGE> {
+        &self.comparator.table
+    }
+
+    //for the cells of the four ends, assigned elsewhere
+    pub fn assign<V>(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a1: &AssignedCell<V, F>,
+        a2: &AssignedCell<V, F>,
+        b1: &AssignedCell<V, F>,
+        b2: &AssignedCell<V, F>,
+    ) -> Result<(), CircuitsError>
+    where
+        V: Clone,
+        for<'v> Assigned<F>: From<&'v V>,
+    {
+        let b1_lt_a2 = self.comparator.assign(layouter.namespace(|| "b1 < a2"), b1, a2)?;
+        let a1_lt_b2 = self.comparator.assign(layouter.namespace(|| "a1 < b2"), a1, b2)?;
+        layouter.assign_region(
+            || "assign disjoint",
+            |mut region| {
+                self.q_disjoint.enable(&mut region, 0)?;
+                b1_lt_a2.copy_advice(|| "b1 < a2", &mut region, self.comparator.a, 0)?;
+                a1_lt_b2.copy_advice(|| "a1 < b2", &mut region, self.comparator.b, 0)?;
+                Ok(())
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, value_u64, ExpectedFailure};
+
+    #[derive(Default)]
+    struct DisjointCircuit {
+        ends: [Value<Assigned<Fp>>; 4],
+    }
+
+    impl Circuit<Fp> for DisjointCircuit {
+        type Config = DisjointConfig<Fp, 16>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(3);
+            DisjointConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table().load(&mut layouter)?;
+            let [a1, a2, b1, b2] = layouter.assign_region(
+                || "assign ends",
+                |mut region| {
+                    let mut cells = vec![];
+                    for (offset, end) in self.ends.iter().enumerate() {
+                        cells.push(region.assign_advice(|| "end", config.comparator.a, offset, || *end)?);
+                    }
+                    Ok(cells)
+                },
+            )?.try_into().unwrap();
+            config.assign(layouter.namespace(|| "disjoint"), &a1, &a2, &b1, &b2)?;
+            Ok(())
+        }
+    }
+
+    fn prover(a: (u64, u64), b: (u64, u64)) -> MockProver<Fp> {
+        let k = 5;
+        let circuit = DisjointCircuit { ends: [a.0, a.1, b.0, b.1].map(value_u64) };
+        MockProver::run(k, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_disjoint() {
+        prover((0, 3), (8, 12)).assert_satisfied();
+        prover((8, 12), (0, 3)).assert_satisfied();
+        //touching ends, both ways round
+        prover((2, 5), (5, 9)).assert_satisfied();
+        prover((5, 9), (2, 5)).assert_satisfied();
+    }
+
+    #[test]
+    fn test_overlapping() {
+        let failure = [ExpectedFailure::constraint("disjoint", "a2 <= b1 or b2 <= a1", "assign disjoint", 0)];
+        expect_failures(&prover((2, 6), (5, 9)), &failure);
+        //one inside the other
+        expect_failures(&prover((0, 15), (3, 4)), &failure);
+    }
+}
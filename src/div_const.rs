@@ -0,0 +1,209 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use std::marker::PhantomData;
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, field::lower_128, report, rows::{self, RowBudget, TablePadding}};
+
+//divides x in 0..RANGE by the constant C by looking up (x, x / C, x % C) in a table of every
+//such triple. one lookup per division, against the gate, range lookup and q decomposition of
+//DivModByRangeConfig, at the cost of RANGE table rows. x = 0 gives (0, 0, 0), so rows off
+//the selector hit a real triple
+#[derive(Clone, Debug)]
+pub struct DivByConstTableConfig<F: FieldExt, const C: u64, const RANGE: usize> {
+    pub x: Column<Advice>,
+    pub q: Column<Advice>,
+    pub r: Column<Advice>,
+    pub q_enable: Selector,
+    pub table_x: TableColumn,
+    pub table_q: TableColumn,
+    pub table_r: TableColumn,
+    //what load_padded puts in the rows past the RANGE triples
+    pub padding: TablePadding,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const C: u64, const RANGE: usize> DivByConstTableConfig<F, C, RANGE> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, x: Column<Advice>, q: Column<Advice>, r: Column<Advice>) -> Self {
+        Self::configure_padded(cs, x, q, r, TablePadding::Unpadded)
+    }
+
+    pub fn configure_padded(cs: &mut ConstraintSystem<F>, x: Column<Advice>, q: Column<Advice>, r: Column<Advice>, padding: TablePadding) -> Self {
+        assert!(C > 0, "can't divide by 0");
+        //RANGE * C bounds every value the table holds, and has to be representable in the field
+        let bound = (RANGE as u128).checked_mul(C as u128);
+        assert!(RANGE > 0 && bound.map_or(false, |bound| bound < 1u128 << (F::NUM_BITS - 1).min(127)));
+        let q_enable = cs.complex_selector();
+        let table_x = cs.lookup_table_column();
+        let table_q = cs.lookup_table_column();
+        let table_r = cs.lookup_table_column();
+
+        cs.enable_equality(x);
+        cs.enable_equality(q);
+        cs.enable_equality(r);
+
+        let lookup_index = cs.lookup(|cs| {
+            let s = cs.query_selector(q_enable);
+            let x = cs.query_advice(x, Rotation::cur());
+            let q = cs.query_advice(q, Rotation::cur());
+            let r = cs.query_advice(r, Rotation::cur());
+            vec![(s.clone() * x, table_x), (s.clone() * q, table_q), (s * r, table_r)]
+        });
+        report::annotate_lookup("div_const", lookup_index);
+
+        Self {
+            x,
+            q,
+            r,
+            q_enable,
+            table_x,
+            table_q,
+            table_r,
+            padding: padding.for_rows(RANGE),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 3);
+        Self::configure(cs, columns[0], columns[1], columns[2])
+    }
+
+    fn rows() -> Vec<Vec<F>> {
+        (0..RANGE as u64).map(|x| vec![F::from(x), F::from(x / C), F::from(x % C)]).collect()
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), CircuitsError> {
+        self.load_rows(layouter, None)
+    }
+
+    //load that first checks the table fits in the budget, then fills the rest of its usable
+    //rows as the padding says
+    pub fn load_padded(&self, layouter: &mut impl Layouter<F>, budget: RowBudget) -> Result<(), CircuitsError> {
+        self.load_rows(layouter, Some(budget))
+    }
+
+    fn load_rows(&self, layouter: &mut impl Layouter<F>, budget: Option<RowBudget>) -> Result<(), CircuitsError> {
+        let columns = [("x", self.table_x), ("q", self.table_q), ("r", self.table_r)];
+        rows::load_table(layouter, &format!("div_const<{}, {}> table", C, RANGE), &columns, &Self::rows(), self.padding, budget)
+    }
+
+    //returns (q, r) for a cell assigned elsewhere, which is copied in as x
+    pub fn div_rem(&self, layouter: impl Layouter<F>, x: &AssignedCell<F, F>) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), CircuitsError> {
+        let value = x.value().map(|x| lower_128(x));
+        let q = value.map(|x| F::from_u128(x / C as u128));
+        let r = value.map(|x| F::from_u128(x % C as u128));
+        self.assign_with_qr(layouter, x, q, r)
+    }
+
+    fn assign_with_qr(&self, mut layouter: impl Layouter<F>, x: &AssignedCell<F, F>, q: Value<F>, r: Value<F>) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), CircuitsError> {
+        let offset = 0;
+        layouter.assign_region(
+            || "div rem by const",
+            |mut region| {
+                self.q_enable.enable(&mut region, offset)?;
+                x.copy_advice(|| "x", &mut region, self.x, offset)?;
+                let q = region.assign_advice(|| "q", self.q, offset, || q)?;
+                let r = region.assign_advice(|| "r", self.r, offset, || r)?;
+                Ok((q, r))
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, known, ExpectedFailure, Forged};
+
+    type Config = DivByConstTableConfig<Fp, 10, 64>;
+
+    #[derive(Default)]
+    struct DivConstCircuit {
+        xs: Vec<Value<Fp>>,
+        //witnessed as the one x's (q, r) instead of the real ones when forged
+        qr: Forged<Fp>,
+        //checked against each x's (q, r) cells
+        expected: Option<Vec<(u64, u64)>>,
+    }
+
+    impl Circuit<Fp> for DivConstCircuit {
+        type Config = (Config, Column<Advice>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { xs: vec![Value::unknown(); self.xs.len()], qr: self.qr.without_witnesses(), expected: None }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(3);
+            let values = pool.advice(cs);
+            cs.enable_equality(values);
+            (DivByConstTableConfig::configure_with_pool(cs, &mut pool), values)
+        }
+
+        fn synthesize(&self, (config, values): Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.load(&mut layouter)?;
+            let xs = layouter.assign_region(
+                || "assign xs",
+                |mut region| {
+                    self.xs.iter().enumerate().map(|(offset, x)| {
+                        region.assign_advice(|| "x", values, offset, || *x)
+                    }).collect::<Result<Vec<_>, Error>>()
+                },
+            )?;
+            for (i, cell) in xs.iter().enumerate() {
+                let (q, r) = match self.qr.pair() {
+                    Some((q, r)) => config.assign_with_qr(layouter.namespace(|| "div rem"), cell, q, r)?,
+                    None => config.div_rem(layouter.namespace(|| "div rem"), cell)?,
+                };
+                if let Some((expected_q, expected_r)) = self.expected.as_ref().map(|expected| expected[i]) {
+                    q.value().assert_if_known(|q| **q == Fp::from(expected_q));
+                    r.value().assert_if_known(|r| **r == Fp::from(expected_r));
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn prover(xs: &[u64], qr: Option<(u64, u64)>) -> MockProver<Fp> {
+        let k = 8;
+        let circuit = DivConstCircuit {
+            xs: xs.iter().map(|x| known(*x)).collect(),
+            qr: qr.map_or_else(Forged::honest, |(q, r)| Forged::from_u64s(&[q, r])),
+            expected: qr.is_none().then(|| xs.iter().map(|x| (x / 10, x % 10)).collect()),
+        };
+        MockProver::run(k, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_div_rem() {
+        let xs: Vec<u64> = (0..64).collect();
+        prover(&xs, None).assert_satisfied();
+    }
+
+    #[test]
+    fn test_forged_quotient() {
+        let failure = ExpectedFailure::lookup(0, "div rem by const", 0);
+        //47 = 3 * 10 + 17 adds up, but isn't the triple for 47
+        expect_failures(&prover(&[47], Some((3, 17))), &[failure.clone()]);
+        expect_failures(&prover(&[47], Some((5, 7))), &[failure.clone()]);
+        //past the table
+        expect_failures(&prover(&[64], None), &[failure]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_divisor() {
+        let mut cs = ConstraintSystem::<Fp>::default();
+        let mut pool = ColumnPool::new(3);
+        DivByConstTableConfig::<Fp, 0, 64>::configure_with_pool(&mut cs, &mut pool);
+    }
+}
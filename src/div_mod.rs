@@ -0,0 +1,200 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Selector},
+    poly::Rotation,
+};
+
+use crate::{
+    bit_decompose::BitDecomposeConfig, column_pool::ColumnPool, error::CircuitsError, field::lower_128,
+    range_lookup::RangeTableConfig, report,
+};
+
+//witnesses x = q * RANGE + r with r < RANGE, looked up in 0..RANGE, and q < 2^q_bits, by
+//bit decomposition. the two bounds make q and r unique as long as 2^q_bits * RANGE < p
+#[derive(Clone, Debug)]
+pub struct DivModByRangeConfig<F: FieldExt, const RANGE: usize> {
+    pub x: Column<Advice>,
+    pub q: Column<Advice>,
+    pub r: Column<Advice>,
+    pub q_enable: Selector,
+    pub table: RangeTableConfig<F, RANGE>,
+    pub bits: BitDecomposeConfig<F>,
+}
+
+impl<F: FieldExt, const RANGE: usize> DivModByRangeConfig<F, RANGE> {
+    pub fn configure(
+        cs: &mut ConstraintSystem<F>,
+        x: Column<Advice>,
+        q: Column<Advice>,
+        r: Column<Advice>,
+        bits: BitDecomposeConfig<F>,
+    ) -> Self {
+        assert!(RANGE > 1);
+        let q_enable = cs.complex_selector();
+        let table = RangeTableConfig::configure(cs);
+
+        cs.enable_equality(x);
+        cs.enable_equality(q);
+        cs.enable_equality(r);
+
+        cs.create_gate("div mod", |cs| {
+            let s = cs.query_selector(q_enable);
+            let x = cs.query_advice(x, Rotation::cur());
+            let q = cs.query_advice(q, Rotation::cur());
+            let r = cs.query_advice(r, Rotation::cur());
+            Constraints::with_selector(s, [("x = q * range + r", x - (q * F::from(RANGE as u64) + r))])
+        });
+        report::annotate_gate("div_mod", "div mod");
+
+        let lookup_index = cs.lookup(|cs| {
+            let s = cs.query_selector(q_enable);
+            let r = cs.query_advice(r, Rotation::cur());
+            vec![(s * r, table.value)]
+        });
+        report::annotate_lookup("div_mod", lookup_index);
+
+        Self {
+            x,
+            q,
+            r,
+            q_enable,
+            table,
+            bits,
+        }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 3);
+        let bits = BitDecomposeConfig::configure_with_pool(cs, pool);
+        Self::configure(cs, columns[0], columns[1], columns[2], bits)
+    }
+
+    //returns (q, r). the witness is computed from the low 128 bits of x
+    pub fn assign(&self, layouter: impl Layouter<F>, x: Value<F>, q_bits: usize) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), CircuitsError> {
+        let range = RANGE as u128;
+        let q = x.map(|x| F::from_u128(lower_128(&x) / range));
+        let r = x.map(|x| F::from_u128(lower_128(&x) % range));
+        self.assign_with_qr(layouter, x, None, q, r, q_bits)
+    }
+
+    //the same on a cell assigned elsewhere, which is copied in as x
+    pub fn assign_cell(&self, layouter: impl Layouter<F>, x: &AssignedCell<F, F>, q_bits: usize) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), CircuitsError> {
+        let range = RANGE as u128;
+        let value = x.value().copied();
+        let q = value.map(|x| F::from_u128(lower_128(&x) / range));
+        let r = value.map(|x| F::from_u128(lower_128(&x) % range));
+        self.assign_with_qr(layouter, value, Some(x), q, r, q_bits)
+    }
+
+    pub(crate) fn assign_with_qr(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: Value<F>,
+        x_cell: Option<&AssignedCell<F, F>>,
+        q: Value<F>,
+        r: Value<F>,
+        q_bits: usize,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), CircuitsError> {
+        //RANGE <= 2^range_bits, which keeps 2^q_bits * RANGE below 2^(F::NUM_BITS - 1) < p
+        let range_bits = (usize::BITS - (RANGE - 1).leading_zeros()) as usize;
+        assert!(q_bits + range_bits < F::NUM_BITS as usize);
+
+        let offset = 0;
+        let (q, r) = layouter.assign_region(
+            || "assign div mod",
+            |mut region| {
+                self.q_enable.enable(&mut region, offset)?;
+                match x_cell {
+                    Some(cell) => cell.copy_advice(|| "x", &mut region, self.x, offset)?,
+                    None => region.assign_advice(|| "x", self.x, offset, || x)?,
+                };
+                let q = region.assign_advice(|| "q", self.q, offset, || q)?;
+                let r = region.assign_advice(|| "r", self.r, offset, || r)?;
+                Ok((q, r))
+            },
+        )?;
+        self.bits.decompose_cell(layouter.namespace(|| "q bits"), &q, q_bits)?;
+        Ok((q, r))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{arithmetic::Field, circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, ExpectedFailure};
+
+    const Q_BITS: usize = 32;
+
+    #[derive(Default)]
+    struct DivModCircuit {
+        x: Value<Fp>,
+        //when set, witnesses this (q, r) instead of the real one
+        qr: Option<(Value<Fp>, Value<Fp>)>,
+        //checked against the (q, r) cells
+        expected: Option<(u64, u64)>,
+    }
+
+    impl Circuit<Fp> for DivModCircuit {
+        type Config = DivModByRangeConfig<Fp, 10>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(3);
+            DivModByRangeConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            let (q, r) = match self.qr {
+                Some((q, r)) => config.assign_with_qr(layouter.namespace(|| "div mod"), self.x, None, q, r, Q_BITS)?,
+                None => config.assign(layouter.namespace(|| "div mod"), self.x, Q_BITS)?,
+            };
+            if let Some((expected_q, expected_r)) = self.expected {
+                q.value().assert_if_known(|q| **q == Fp::from(expected_q));
+                r.value().assert_if_known(|r| **r == Fp::from(expected_r));
+            }
+            Ok(())
+        }
+    }
+
+    fn prover(circuit: DivModCircuit) -> MockProver<Fp> {
+        let k = 7;
+        MockProver::run(k, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_div_mod() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let inputs = (0..8).map(|_| rng.gen::<u32>() as u64).chain([0, 9, 10, u32::MAX as u64]);
+        for x in inputs {
+            let circuit = DivModCircuit { x: Value::known(Fp::from(x)), qr: None, expected: Some((x / 10, x % 10)) };
+            prover(circuit).assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_div_mod_sound() {
+        let x = Value::known(Fp::from(47));
+        let qr = |q: Fp, r: Fp| DivModCircuit { x, qr: Some((Value::known(q), Value::known(r))), expected: None };
+
+        //47 = 3 * 10 + 17, the relation holds but r is too large
+        expect_failures(&prover(qr(Fp::from(3), Fp::from(17))), &[ExpectedFailure::lookup(0, "assign div mod", 0)]);
+        //an in range r that doesn't match q
+        expect_failures(
+            &prover(qr(Fp::from(3), Fp::from(7))),
+            &[ExpectedFailure::constraint("div mod", "x = q * range + r", "assign div mod", 0)],
+        );
+        //r = 8 balanced by q = 39 / 10 in the field, which doesn't fit in 32 bits
+        let q = Fp::from(39) * Fp::from(10).invert().unwrap();
+        assert!(prover(qr(q, Fp::from(8))).verify().is_err());
+    }
+}
@@ -0,0 +1,172 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, report};
+
+//checks a value is one of the discriminants 0..N or the catch-all, and witnesses it one-hot,
+//one flag column per variant and one for the catch-all. the flags are boolean, sum to one and
+//weight to the value, with the catch-all flag weighted by the catch-all, so the flag that is
+//set is the value's and any other value has no flags at all
+#[derive(Clone, Debug)]
+pub struct EnumDiscriminantConfig<F: FieldExt, const N: usize> {
+    pub value: Column<Advice>,
+    pub flags: [Column<Advice>; N],
+    pub other: Column<Advice>,
+    pub catch_all: u64,
+    pub q_enable: Selector,
+    _marker: std::marker::PhantomData<F>,
+}
+
+impl<F: FieldExt, const N: usize> EnumDiscriminantConfig<F, N> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, value: Column<Advice>, flags: [Column<Advice>; N], other: Column<Advice>, catch_all: u64) -> Self {
+        assert!(N > 0);
+        //a catch-all inside 0..N would have two flags that can be set for it
+        assert!(catch_all >= N as u64);
+        let q_enable = cs.selector();
+
+        cs.enable_equality(value);
+        for flag in flags.into_iter().chain([other]) {
+            cs.enable_equality(flag);
+        }
+
+        cs.create_gate("enum discriminant", |cs| {
+            let q = cs.query_selector(q_enable);
+            let value = cs.query_advice(value, Rotation::cur());
+            let flags = flags.map(|flag| cs.query_advice(flag, Rotation::cur()));
+            let other = cs.query_advice(other, Rotation::cur());
+            let one = Expression::Constant(F::one());
+
+            let sum = flags.iter().fold(other.clone(), |sum, flag| sum + flag.clone());
+            let weighted = flags.iter().enumerate().fold(other.clone() * F::from(catch_all), |acc, (i, flag)| {
+                acc + flag.clone() * F::from(i as u64)
+            });
+            let booleans = flags.iter().chain([&other]).map(|flag| ("flag boolean", flag.clone() * (one.clone() - flag.clone())));
+            Constraints::with_selector(
+                q,
+                booleans
+                    .chain([("flags sum to one", sum - one.clone()), ("value = sum i * flag i + catch-all * other", value - weighted)])
+                    .collect::<Vec<_>>(),
+            )
+        });
+        report::annotate_gate("enum_discriminant", "enum discriminant");
+
+        Self { value, flags, other, catch_all, q_enable, _marker: std::marker::PhantomData }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>, catch_all: u64) -> Self {
+        let columns = pool.advice_many(cs, N + 2);
+        Self::configure(cs, columns[0], columns[1..=N].try_into().unwrap(), columns[N + 1], catch_all)
+    }
+
+    //returns the value cell and the flag cells, flag i set for discriminant i and the last
+    //flag, past the N variants, set for the catch-all
+    pub fn assign_discriminant(&self, layouter: impl Layouter<F>, value: Value<F>) -> Result<(AssignedCell<F, F>, Vec<AssignedCell<F, F>>), CircuitsError> {
+        let flags = (0..N as u64)
+            .chain([self.catch_all])
+            .map(|i| value.map(|v| F::from((v == F::from(i)) as u64)))
+            .collect::<Vec<_>>();
+        self.assign_with_flags(layouter, value, &flags)
+    }
+
+    fn assign_with_flags(&self, mut layouter: impl Layouter<F>, value: Value<F>, flags: &[Value<F>]) -> Result<(AssignedCell<F, F>, Vec<AssignedCell<F, F>>), CircuitsError> {
+        let offset = 0;
+        layouter.assign_region(
+            || "assign enum discriminant",
+            |mut region| {
+                self.q_enable.enable(&mut region, offset)?;
+                let value = region.assign_advice(|| "value", self.value, offset, || value)?;
+                let flags = self.flags.iter().chain([&self.other]).zip(flags).enumerate().map(|(i, (&column, flag))| {
+                    region.assign_advice(|| format!("flag {}", i), column, offset, || *flag)
+                }).collect::<Result<Vec<_>, Error>>()?;
+                Ok((value, flags))
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, ExpectedFailure};
+
+    const CATCH_ALL: u64 = 9;
+
+    #[derive(Default)]
+    struct DiscriminantCircuit {
+        value: Value<Fp>,
+        //when set, witnessed instead of the real flags, the catch-all flag last
+        flags: Option<[Value<Fp>; 5]>,
+        expected: Option<[u64; 5]>,
+    }
+
+    impl Circuit<Fp> for DiscriminantCircuit {
+        type Config = EnumDiscriminantConfig<Fp, 4>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { flags: self.flags.map(|_| [Value::unknown(); 5]), ..Self::default() }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(6);
+            EnumDiscriminantConfig::configure_with_pool(cs, &mut pool, CATCH_ALL)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let (_, flags) = match self.flags {
+                Some(flags) => config.assign_with_flags(layouter.namespace(|| "discriminant"), self.value, &flags)?,
+                None => config.assign_discriminant(layouter.namespace(|| "discriminant"), self.value)?,
+            };
+            if let Some(expected) = self.expected {
+                for (flag, expected) in flags.iter().zip(expected) {
+                    flag.value().assert_if_known(|flag| **flag == Fp::from(expected));
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn prover(value: u64, flags: Option<[u64; 5]>, expected: Option<[u64; 5]>) -> MockProver<Fp> {
+        let k = 4;
+        let circuit = DiscriminantCircuit {
+            value: Value::known(Fp::from(value)),
+            flags: flags.map(|flags| flags.map(|f| Value::known(Fp::from(f)))),
+            expected,
+        };
+        MockProver::run(k, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_one_hot() {
+        prover(2, None, Some([0, 0, 1, 0, 0])).assert_satisfied();
+        prover(0, None, Some([1, 0, 0, 0, 0])).assert_satisfied();
+    }
+
+    #[test]
+    fn test_catch_all() {
+        prover(CATCH_ALL, None, Some([0, 0, 0, 0, 1])).assert_satisfied();
+    }
+
+    #[test]
+    fn test_non_member() {
+        let failure = |constraint| ExpectedFailure::constraint("enum discriminant", constraint, "assign enum discriminant", 0);
+        let weighted = "value = sum i * flag i + catch-all * other";
+        //no flags are set for 4, past the variants, or for 8, just below the catch-all
+        for value in [4, 8] {
+            expect_failures(&prover(value, None, None), &[failure("flags sum to one"), failure(weighted)]);
+        }
+        //two flags can make up 4 but don't sum to one
+        expect_failures(&prover(4, Some([0, 1, 0, 1, 0]), None), &[failure("flags sum to one")]);
+        //neither can a non-boolean flag
+        expect_failures(&prover(4, Some([0, 0, 2, 0, 0]), None), &[failure("flag boolean"), failure("flags sum to one")]);
+        //the catch-all flag doesn't cover another value
+        expect_failures(&prover(5, Some([0, 0, 0, 0, 1]), None), &[failure(weighted)]);
+    }
+}
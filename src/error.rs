@@ -0,0 +1,59 @@
+#![allow(warnings, unused)]
+use halo2_proofs::plonk;
+
+use std::fmt;
+
+//what the gadget assign/load apis fail with. converts both ways with plonk::Error so
+//synthesize can keep using ? on gadget calls, at the cost of the context on the way back
+#[derive(Debug)]
+pub enum CircuitsError {
+    Plonk(plonk::Error),
+    RangeTooLarge { gadget: &'static str, range: u64, max: u64 },
+    TableNotLoaded { table: &'static str },
+    LengthMismatch { expected: usize, got: usize },
+    NotEnoughRows { what: String, rows: usize, usable: usize, k: u32 },
+    ProductOverflow { bound_a: u128, bound_b: u128 },
+    DuplicateTable { name: String },
+    TableNotFull { what: String, rows: usize, usable: usize, k: u32 },
+}
+
+impl From<plonk::Error> for CircuitsError {
+    fn from(error: plonk::Error) -> Self {
+        CircuitsError::Plonk(error)
+    }
+}
+
+impl From<CircuitsError> for plonk::Error {
+    fn from(error: CircuitsError) -> Self {
+        match error {
+            CircuitsError::Plonk(error) => error,
+            //plonk::Error has nowhere to put the context
+            _ => plonk::Error::Synthesis,
+        }
+    }
+}
+
+impl fmt::Display for CircuitsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircuitsError::Plonk(error) => write!(f, "{}", error),
+            CircuitsError::RangeTooLarge { gadget, range, max } => {
+                write!(f, "{} can't check a range of {}, at most {} is supported", gadget, range, max)
+            }
+            CircuitsError::TableNotLoaded { table } => write!(f, "{} was used before being loaded", table),
+            CircuitsError::LengthMismatch { expected, got } => write!(f, "expected {} values, got {}", expected, got),
+            CircuitsError::ProductOverflow { bound_a, bound_b } => {
+                write!(f, "a product of values below {} and {} can wrap around the field", bound_a, bound_b)
+            }
+            CircuitsError::NotEnoughRows { what, rows, usable, k } => {
+                write!(f, "{} needs {} rows but only {} are usable at k = {}", what, rows, usable, k)
+            }
+            CircuitsError::DuplicateTable { name } => write!(f, "a table named {} is already registered", name),
+            CircuitsError::TableNotFull { what, rows, usable, k } => {
+                write!(f, "{} fills {} of the {} usable rows at k = {} and can't be padded", what, rows, usable, k)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CircuitsError {}
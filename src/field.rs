@@ -0,0 +1,31 @@
+#![allow(warnings, unused)]
+//field helpers that only need ff::PrimeField, which the PSE fork of halo2 keeps while it
+//drops FieldExt. there is no pse backend switch: the gadgets are bound on FieldExt
+//throughout and this tree has no manifest to declare the feature or the PSE dependency in,
+//so only zcash halo2 is built and tested
+use halo2_proofs::{arithmetic::FieldExt, pasta::group::ff::PrimeField};
+
+//low 128 bits of the canonical representation, FieldExt::get_lower_128 without FieldExt.
+//PrimeField doesn't fix the repr endianness, the pasta curves use little endian
+pub fn lower_128<F: PrimeField>(v: &F) -> u128 {
+    let repr = v.to_repr();
+    repr.as_ref()
+        .iter()
+        .take(16)
+        .rev()
+        .fold(0u128, |acc, byte| (acc << 8) | *byte as u128)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::pasta::Fp;
+
+    #[test]
+    fn test_lower_128() {
+        assert_eq!(lower_128(&Fp::from(0x0102_0304)), 0x0102_0304);
+        let big = Fp::from(u64::MAX) * Fp::from(1 << 16);
+        assert_eq!(lower_128(&big), (u64::MAX as u128) << 16);
+        assert_eq!(lower_128(&-Fp::one()), (-Fp::one()).get_lower_128());
+    }
+}
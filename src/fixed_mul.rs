@@ -0,0 +1,184 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Selector},
+    poly::Rotation,
+};
+
+use crate::{bit_decompose::BitDecomposeConfig, column_pool::ColumnPool, error::CircuitsError, field::lower_128, range_lookup::RangeTableConfig, report};
+
+//checks c = (a * b) >> S for fixed point values with S fractional bits.
+//
+//the product is split as a * b = c * 2^S + low, with low decomposed into S bits and c looked
+//up in 0..RANGE. the two bounds make the split unique since RANGE * 2^S < p. it is the
+//product in the field that gets split, the caller keeps a * b below p by bounding a and b
+#[derive(Clone, Debug)]
+pub struct FixedMulConfig<F: FieldExt, const S: usize, const RANGE: usize> {
+    pub a: Column<Advice>,
+    pub b: Column<Advice>,
+    pub c: Column<Advice>,
+    pub low: Column<Advice>,
+    pub q_mul: Selector,
+    pub table: RangeTableConfig<F, RANGE>,
+    pub bits: BitDecomposeConfig<F>,
+}
+
+impl<F: FieldExt, const S: usize, const RANGE: usize> FixedMulConfig<F, S, RANGE> {
+    pub fn configure(
+        cs: &mut ConstraintSystem<F>,
+        a: Column<Advice>,
+        b: Column<Advice>,
+        c: Column<Advice>,
+        low: Column<Advice>,
+        bits: BitDecomposeConfig<F>,
+    ) -> Self {
+        let range_bits = (usize::BITS - (RANGE - 1).leading_zeros()) as usize;
+        //2^S and the split of the product are worked out in a u128
+        assert!(S > 0 && S < 128 && RANGE > 1 && S + range_bits < F::NUM_BITS as usize);
+        let q_mul = cs.complex_selector();
+        let table = RangeTableConfig::configure(cs);
+
+        cs.enable_equality(a);
+        cs.enable_equality(b);
+        cs.enable_equality(c);
+        cs.enable_equality(low);
+
+        cs.create_gate("fixed mul", |cs| {
+            let q = cs.query_selector(q_mul);
+            let a = cs.query_advice(a, Rotation::cur());
+            let b = cs.query_advice(b, Rotation::cur());
+            let c = cs.query_advice(c, Rotation::cur());
+            let low = cs.query_advice(low, Rotation::cur());
+            let scale = F::from_u128(1 << S);
+            Constraints::with_selector(q, [("a * b = c * 2^S + low", a * b - (c * scale + low))])
+        });
+        report::annotate_gate("fixed_mul", "fixed mul");
+
+        let lookup_index = cs.lookup(|cs| {
+            let q = cs.query_selector(q_mul);
+            let c = cs.query_advice(c, Rotation::cur());
+            vec![(q * c, table.value)]
+        });
+        report::annotate_lookup("fixed_mul", lookup_index);
+
+        Self { a, b, c, low, q_mul, table, bits }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 4);
+        let bits = BitDecomposeConfig::configure_with_pool(cs, pool);
+        Self::configure(cs, columns[0], columns[1], columns[2], columns[3], bits)
+    }
+
+    //returns c for cells a and b assigned elsewhere. the witness is computed from the low 128
+    //bits of the product
+    pub fn assign(&self, layouter: impl Layouter<F>, a: &AssignedCell<F, F>, b: &AssignedCell<F, F>) -> Result<AssignedCell<F, F>, CircuitsError> {
+        let product = a.value().copied() * b.value().copied();
+        let c = product.map(|p| F::from_u128(lower_128(&p) >> S));
+        let low = product.map(|p| F::from_u128(lower_128(&p) & ((1 << S) - 1)));
+        self.assign_with_split(layouter, a, b, c, low)
+    }
+
+    fn assign_with_split(&self, mut layouter: impl Layouter<F>, a: &AssignedCell<F, F>, b: &AssignedCell<F, F>, c: Value<F>, low: Value<F>) -> Result<AssignedCell<F, F>, CircuitsError> {
+        let offset = 0;
+        let (c, low) = layouter.assign_region(
+            || "assign fixed mul",
+            |mut region| {
+                self.q_mul.enable(&mut region, offset)?;
+                a.copy_advice(|| "a", &mut region, self.a, offset)?;
+                b.copy_advice(|| "b", &mut region, self.b, offset)?;
+                let c = region.assign_advice(|| "c", self.c, offset, || c)?;
+                let low = region.assign_advice(|| "low", self.low, offset, || low)?;
+                Ok((c, low))
+            },
+        )?;
+        self.bits.decompose_cell(layouter.namespace(|| "dropped bits"), &low, S)?;
+        Ok(c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, known, ExpectedFailure, Forged};
+
+    //8 fractional bits, results below 4.0
+    type Config = FixedMulConfig<Fp, 8, 1024>;
+
+    #[derive(Default)]
+    struct FixedMulCircuit {
+        a: Value<Fp>,
+        b: Value<Fp>,
+        //witnessed instead of the real (c, low) when forged
+        split: Forged<Fp>,
+    }
+
+    impl Circuit<Fp> for FixedMulCircuit {
+        type Config = Config;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { split: self.split.without_witnesses(), ..Self::default() }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(4);
+            FixedMulConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            let (a, b) = layouter.assign_region(
+                || "assign a and b",
+                |mut region| {
+                    let a = region.assign_advice(|| "a", config.a, 0, || self.a)?;
+                    let b = region.assign_advice(|| "b", config.b, 0, || self.b)?;
+                    Ok((a, b))
+                },
+            )?;
+            let c = match self.split.pair() {
+                Some((c, low)) => config.assign_with_split(layouter.namespace(|| "fixed mul"), &a, &b, c, low)?,
+                None => config.assign(layouter.namespace(|| "fixed mul"), &a, &b)?,
+            };
+            Ok(())
+        }
+    }
+
+    fn prover(a: u64, b: u64, split: Option<(u64, u64)>) -> MockProver<Fp> {
+        let k = 11;
+        let split = split.map_or_else(Forged::honest, |(c, low)| Forged::from_u64s(&[c, low]));
+        let circuit = FixedMulCircuit { a: known(a), b: known(b), split };
+        MockProver::run(k, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_fixed_mul() {
+        //1.5 * 2.25 = 3.375, exact
+        prover(384, 576, None).assert_satisfied();
+        assert_eq!(384 * 576 >> 8, 864);
+        //1.50390625 * 2.25 = 3.3837890625, rounded down to 3.3828125 with 64 / 256 dropped
+        prover(385, 576, None).assert_satisfied();
+        prover(385, 576, Some((866, 64))).assert_satisfied();
+        //3.99609375 is the largest result
+        prover(1023, 256, None).assert_satisfied();
+    }
+
+    #[test]
+    fn test_result_out_of_range() {
+        //2.0 * 2.0 = 4.0 doesn't fit
+        expect_failures(&prover(512, 512, None), &[ExpectedFailure::lookup(0, "assign fixed mul", 0)]);
+    }
+
+    #[test]
+    fn test_dropped_bits_sound() {
+        let permutation = |region: &str, row| ExpectedFailure { name: "permutation".to_string(), region: region.to_string(), row };
+        //one less in c and 256 more dropped keeps the gate happy, but 320 isn't 8 bits
+        expect_failures(
+            &prover(385, 576, Some((865, 320))),
+            &[permutation("assign fixed mul", 0), permutation("decompose cell", 7)],
+        );
+    }
+}
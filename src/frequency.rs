@@ -0,0 +1,288 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::{Field, FieldExt},
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{column_pool::ColumnPool, comparator::ComparatorConfig, error::CircuitsError, range_lookup::RangeTableConfig, report};
+
+//the compare-exchange pairs (i, j), i < j, of batcher's odd-even merge sort on n wires. the
+//network for the next power of two is pruned to the pairs inside 0..n, which still sorts
+//since the missing wires would hold values above every real one and never move
+pub fn sorting_network(n: usize) -> Vec<(usize, usize)> {
+    let size = n.next_power_of_two();
+    let mut pairs = vec![];
+    let mut p = 1;
+    while p < size {
+        let mut k = p;
+        while k > 0 {
+            let mut j = k % p;
+            while j + k < size {
+                for i in 0..k {
+                    if (i + j) / (2 * p) == (i + j + k) / (2 * p) && i + j + k < n {
+                        pairs.push((i + j, i + j + k));
+                    }
+                }
+                j += 2 * k;
+            }
+            k /= 2;
+        }
+        p *= 2;
+    }
+    pairs
+}
+
+//checks no value appears more than CAP times among n value cells in 0..RANGE.
+//
+//the values are sorted by a fixed sorting network, each compare-exchange taking lt = [x < y]
+//from the comparator and putting min = y + lt * (x - y) and max = x + y - min on a swap row.
+//equal values then sit in runs, and the count rows carry the multiplicity of each value
+//along its run, count = eq * prev + 1 with eq = [value == prev value] by the inverse trick.
+//count - 1 is looked up in 0..CAP on every row, so the last row of each run, which holds
+//(value, count of value), has count <= CAP. that takes O(n log^2 n) rows
+#[derive(Clone, Debug)]
+pub struct FrequencyConfig<F: FieldExt, const CAP: usize, const RANGE: usize> {
+    //swap rows
+    pub x: Column<Advice>,
+    pub y: Column<Advice>,
+    pub lt: Column<Advice>,
+    pub min: Column<Advice>,
+    pub max: Column<Advice>,
+    //count rows
+    pub value: Column<Advice>,
+    pub inv: Column<Advice>,
+    pub eq: Column<Advice>,
+    pub count: Column<Advice>,
+    pub q_swap: Selector,
+    pub q_first: Selector,
+    pub q_step: Selector,
+    pub q_cap: Selector,
+    pub table: RangeTableConfig<F, CAP>,
+    pub comparator: ComparatorConfig<F, RANGE>,
+}
+
+impl<F: FieldExt, const CAP: usize, const RANGE: usize> FrequencyConfig<F, CAP, RANGE> {
+    pub fn configure(
+        cs: &mut ConstraintSystem<F>,
+        [x, y, lt, min, max]: [Column<Advice>; 5],
+        [value, inv, eq, count]: [Column<Advice>; 4],
+        comparator: ComparatorConfig<F, RANGE>,
+    ) -> Self {
+        //a cap of 0 would reject every non-empty column
+        assert!(CAP > 0);
+        let q_swap = cs.selector();
+        let q_first = cs.selector();
+        let q_step = cs.selector();
+        let q_cap = cs.complex_selector();
+        let table = RangeTableConfig::configure(cs);
+
+        for column in [x, y, lt, min, max, value, count] {
+            cs.enable_equality(column);
+        }
+
+        //lt is copied from the comparator, which makes it boolean
+        cs.create_gate("frequency swap", |cs| {
+            let q = cs.query_selector(q_swap);
+            let x = cs.query_advice(x, Rotation::cur());
+            let y = cs.query_advice(y, Rotation::cur());
+            let lt = cs.query_advice(lt, Rotation::cur());
+            let min = cs.query_advice(min, Rotation::cur());
+            let max = cs.query_advice(max, Rotation::cur());
+            Constraints::with_selector(
+                q,
+                [
+                    ("min = y + lt * (x - y)", min.clone() - (y.clone() + lt * (x.clone() - y.clone()))),
+                    ("max = x + y - min", max - (x + y - min)),
+                ],
+            )
+        });
+        report::annotate_gate("frequency", "frequency swap");
+
+        cs.create_gate("frequency count first", |cs| {
+            let q = cs.query_selector(q_first);
+            let count = cs.query_advice(count, Rotation::cur());
+            Constraints::with_selector(q, [("count = 1", count - Expression::Constant(F::one()))])
+        });
+        report::annotate_gate("frequency", "frequency count first");
+
+        cs.create_gate("frequency count step", |cs| {
+            let q = cs.query_selector(q_step);
+            let prev_value = cs.query_advice(value, Rotation::prev());
+            let value = cs.query_advice(value, Rotation::cur());
+            let inv = cs.query_advice(inv, Rotation::cur());
+            let eq = cs.query_advice(eq, Rotation::cur());
+            let prev = cs.query_advice(count, Rotation::prev());
+            let count = cs.query_advice(count, Rotation::cur());
+            let one = Expression::Constant(F::one());
+            let diff = value - prev_value;
+            Constraints::with_selector(
+                q,
+                [
+                    ("eq = 1 - (value - prev) * inv", eq.clone() - (one.clone() - diff.clone() * inv)),
+                    ("(value - prev) * eq = 0", diff * eq.clone()),
+                    ("count = eq * prev + 1", count - (eq * prev + one)),
+                ],
+            )
+        });
+        report::annotate_gate("frequency", "frequency count step");
+
+        let lookup_index = cs.lookup(|cs| {
+            let q = cs.query_selector(q_cap);
+            let count = cs.query_advice(count, Rotation::cur());
+            vec![(q * (count - Expression::Constant(F::one())), table.value)]
+        });
+        report::annotate_lookup("frequency", lookup_index);
+
+        Self { x, y, lt, min, max, value, inv, eq, count, q_swap, q_first, q_step, q_cap, table, comparator }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let comparator = ComparatorConfig::configure_with_pool(cs, pool);
+        let swap = pool.advice_many(cs, 5).try_into().unwrap();
+        let count = pool.advice_many(cs, 4).try_into().unwrap();
+        Self::configure(cs, swap, count, comparator)
+    }
+
+    //sorts value cells assigned elsewhere and returns the (value, count) cells of the sorted
+    //rows. the last row of each run of equal values holds the count of that value
+    pub fn assign(&self, mut layouter: impl Layouter<F>, values: &[AssignedCell<F, F>]) -> Result<Vec<(AssignedCell<F, F>, AssignedCell<F, F>)>, CircuitsError> {
+        let mut wires = values.to_vec();
+        for (i, j) in sorting_network(values.len()) {
+            let lt = self.comparator.assign(layouter.namespace(|| format!("compare {} {}", i, j)), &wires[i], &wires[j])?;
+            let (min, max) = layouter.assign_region(
+                || "assign frequency swap",
+                |mut region| {
+                    let offset = 0;
+                    self.q_swap.enable(&mut region, offset)?;
+                    let x = wires[i].copy_advice(|| "x", &mut region, self.x, offset)?;
+                    let y = wires[j].copy_advice(|| "y", &mut region, self.y, offset)?;
+                    let lt = lt.copy_advice(|| "lt", &mut region, self.lt, offset)?;
+                    let min = lt.value().zip(x.value()).zip(y.value()).map(|((lt, x), y)| if lt.evaluate() == F::one() { *x } else { *y });
+                    let max = x.value().copied() + y.value().copied() - min;
+                    let min = region.assign_advice(|| "min", self.min, offset, || min)?;
+                    let max = region.assign_advice(|| "max", self.max, offset, || max)?;
+                    Ok((min, max))
+                },
+            )?;
+            wires[i] = min;
+            wires[j] = max;
+        }
+
+        layouter.assign_region(
+            || "assign frequency count",
+            |mut region| {
+                let mut rows: Vec<(AssignedCell<F, F>, AssignedCell<F, F>)> = vec![];
+                for (offset, wire) in wires.iter().enumerate() {
+                    self.q_cap.enable(&mut region, offset)?;
+                    let value = wire.copy_advice(|| format!("value {}", offset), &mut region, self.value, offset)?;
+                    let count = match rows.last() {
+                        None => {
+                            self.q_first.enable(&mut region, offset)?;
+                            Value::known(F::one())
+                        }
+                        Some((prev_value, prev)) => {
+                            self.q_step.enable(&mut region, offset)?;
+                            let diff = value.value().copied() - prev_value.value().copied();
+                            let inv = diff.map(|diff| diff.invert().unwrap_or(F::zero()));
+                            let eq = diff.zip(inv).map(|(diff, inv)| F::one() - diff * inv);
+                            region.assign_advice(|| "inv", self.inv, offset, || inv)?;
+                            region.assign_advice(|| "eq", self.eq, offset, || eq)?;
+                            eq * prev.value().copied() + Value::known(F::one())
+                        }
+                    };
+                    let count = region.assign_advice(|| "count", self.count, offset, || count)?;
+                    rows.push((value, count));
+                }
+                Ok(rows)
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, ExpectedFailure};
+
+    #[derive(Default)]
+    struct FrequencyCircuit {
+        values: Vec<Value<Fp>>,
+    }
+
+    impl Circuit<Fp> for FrequencyCircuit {
+        type Config = FrequencyConfig<Fp, 3, 16>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { values: vec![Value::unknown(); self.values.len()] }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(5);
+            FrequencyConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            config.comparator.table.load(&mut layouter)?;
+            let values = layouter.assign_region(
+                || "assign values",
+                |mut region| self.values.iter().enumerate().map(|(offset, value)| region.assign_advice(|| "value", config.value, offset, || *value)).collect::<Result<Vec<_>, _>>(),
+            )?;
+            let counts = config.assign(layouter.namespace(|| "frequency"), &values)?;
+            assert_eq!(counts.len(), self.values.len());
+            Ok(())
+        }
+    }
+
+    fn prover(values: &[u64]) -> MockProver<Fp> {
+        let k = 7;
+        let circuit = FrequencyCircuit { values: values.iter().map(|&v| Value::known(Fp::from(v))).collect() };
+        MockProver::run(k, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_sorting_network() {
+        //by the 0-1 principle a network sorting every 0/1 input sorts everything
+        for n in 1..=9 {
+            let pairs = sorting_network(n);
+            for input in 0..1u32 << n {
+                let mut wires: Vec<u32> = (0..n).map(|i| (input >> i) & 1).collect();
+                for &(i, j) in &pairs {
+                    if wires[i] > wires[j] {
+                        wires.swap(i, j);
+                    }
+                }
+                assert!(wires.windows(2).all(|w| w[0] <= w[1]), "n = {} input = {:b}", n, input);
+            }
+        }
+        assert!(sorting_network(1).is_empty());
+        assert_eq!(sorting_network(2), vec![(0, 1)]);
+    }
+
+    #[test]
+    fn test_frequency() {
+        //7 appears exactly CAP times
+        prover(&[7, 1, 7, 2, 7]).assert_satisfied();
+        prover(&[0, 1, 2, 3, 4]).assert_satisfied();
+        prover(&[15, 0, 15, 0, 0, 15]).assert_satisfied();
+        prover(&[5]).assert_satisfied();
+    }
+
+    #[test]
+    fn test_frequency_over_cap() {
+        //one 7 too many. sorted it is 1, 7, 7, 7, 7 and the run of 7s reaches 4 on the last row.
+        //the comparator's three lookups come first
+        expect_failures(&prover(&[7, 1, 7, 7, 7]), &[ExpectedFailure::lookup(3, "assign frequency count", 4)]);
+    }
+
+    #[test]
+    fn test_value_out_of_range() {
+        //16 doesn't fit the comparator
+        assert!(prover(&[16, 1, 2]).verify().is_err());
+    }
+}
@@ -0,0 +1,180 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use std::marker::PhantomData;
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, report, rows::{self, RowBudget, TablePadding}};
+
+//the pairs (key, f(key)) for key in 0..SIZE. f is fixed at configure time since the lookups
+//need f(0), see FunctionLookupConfig
+#[derive(Clone, Debug)]
+pub struct FunctionTableConfig<F: FieldExt, const SIZE: usize> {
+    pub key: TableColumn,
+    pub value: TableColumn,
+    pub f: fn(u64) -> u64,
+    //what load_padded puts in the rows past the SIZE pairs, a filler being a key
+    pub padding: TablePadding,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const SIZE: usize> FunctionTableConfig<F, SIZE> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, f: fn(u64) -> u64) -> Self {
+        Self::configure_padded(cs, f, TablePadding::Unpadded)
+    }
+
+    //padding is what load_padded fills the rows past the SIZE pairs with
+    pub fn configure_padded(cs: &mut ConstraintSystem<F>, f: fn(u64) -> u64, padding: TablePadding) -> Self {
+        assert!(SIZE > 0);
+        Self {
+            key: cs.lookup_table_column(),
+            value: cs.lookup_table_column(),
+            f,
+            padding: padding.for_rows(SIZE),
+            _marker: PhantomData,
+        }
+    }
+
+    //the key whose pair is repeated in the padding rows, if there are any
+    pub fn filler(&self) -> Option<u64> {
+        self.padding.filler_row(SIZE).map(|row| row as u64)
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), CircuitsError> {
+        self.load_rows(layouter, None)
+    }
+
+    //load that first checks the table fits in the budget, then fills the rest of its usable
+    //rows as the padding says
+    pub fn load_padded(&self, layouter: &mut impl Layouter<F>, budget: RowBudget) -> Result<(), CircuitsError> {
+        self.load_rows(layouter, Some(budget))
+    }
+
+    fn load_rows(&self, layouter: &mut impl Layouter<F>, budget: Option<RowBudget>) -> Result<(), CircuitsError> {
+        let rows: Vec<_> = (0..SIZE as u64).map(|key| vec![F::from(key), F::from((self.f)(key))]).collect();
+        rows::load_table(layouter, &format!("function_table<{}>", SIZE), &[("key", self.key), ("value", self.value)], &rows, self.padding, budget)
+    }
+}
+
+//checks output = f(key) with key in 0..SIZE by looking the pair up.
+//
+//rows off the selector have to look up a pair in the table, and (0, 0) is only one when
+//f(0) = 0. they look up (0, f(0)) instead, which always is
+#[derive(Clone, Debug)]
+pub struct FunctionLookupConfig<F: FieldExt, const SIZE: usize> {
+    pub key: Column<Advice>,
+    pub output: Column<Advice>,
+    pub q_enable: Selector,
+    pub table: FunctionTableConfig<F, SIZE>,
+}
+
+impl<F: FieldExt, const SIZE: usize> FunctionLookupConfig<F, SIZE> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, key: Column<Advice>, output: Column<Advice>, table: FunctionTableConfig<F, SIZE>) -> Self {
+        let q_enable = cs.complex_selector();
+
+        cs.enable_equality(key);
+        cs.enable_equality(output);
+
+        let at_zero = F::from((table.f)(0));
+        let lookup_index = cs.lookup(|cs| {
+            let q = cs.query_selector(q_enable);
+            let key = cs.query_advice(key, Rotation::cur());
+            let output = cs.query_advice(output, Rotation::cur());
+            let off = Expression::Constant(F::one()) - q.clone();
+            vec![(q.clone() * key, table.key), (q * output + off * at_zero, table.value)]
+        });
+        report::annotate_lookup("function_lookup", lookup_index);
+
+        Self { key, output, q_enable, table }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>, f: fn(u64) -> u64) -> Self {
+        let columns = pool.advice_many(cs, 2);
+        let table = FunctionTableConfig::configure(cs, f);
+        Self::configure(cs, columns[0], columns[1], table)
+    }
+
+    //returns the (key, output) cells
+    pub fn assign(&self, mut layouter: impl Layouter<F>, key: Value<F>, output: Value<F>) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), CircuitsError> {
+        let offset = 0;
+        layouter.assign_region(
+            || "assign function lookup",
+            |mut region| {
+                self.q_enable.enable(&mut region, offset)?;
+                let key = region.assign_advice(|| "key", self.key, offset, || key)?;
+                let output = region.assign_advice(|| "output", self.output, offset, || output)?;
+                Ok((key, output))
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, ExpectedFailure};
+
+    fn square(x: u64) -> u64 {
+        x * x
+    }
+
+    //f(0) isn't 0, so the rows off the selector need their own pair
+    fn shifted(x: u64) -> u64 {
+        x + 7
+    }
+
+    #[derive(Default)]
+    struct FunctionLookupCircuit<const SHIFTED: bool> {
+        pairs: Vec<(u64, u64)>,
+    }
+
+    impl<const SHIFTED: bool> Circuit<Fp> for FunctionLookupCircuit<SHIFTED> {
+        type Config = FunctionLookupConfig<Fp, 16>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { pairs: self.pairs.clone() }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(2);
+            FunctionLookupConfig::configure_with_pool(cs, &mut pool, if SHIFTED { shifted } else { square })
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            for &(key, output) in &self.pairs {
+                config.assign(layouter.namespace(|| "lookup"), Value::known(Fp::from(key)), Value::known(Fp::from(output)))?;
+            }
+            Ok(())
+        }
+    }
+
+    fn prover<const SHIFTED: bool>(pairs: &[(u64, u64)]) -> MockProver<Fp> {
+        let k = 5;
+        MockProver::run(k, &FunctionLookupCircuit::<SHIFTED> { pairs: pairs.to_vec() }, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_square_table() {
+        prover::<false>(&[(4, 16), (0, 0), (15, 225)]).assert_satisfied();
+        let failure = ExpectedFailure::lookup(0, "assign function lookup", 0);
+        expect_failures(&prover::<false>(&[(4, 15)]), &[failure.clone()]);
+        expect_failures(&prover::<false>(&[(16, 4)]), &[failure.clone()]);
+        //a right square of a key past the table
+        expect_failures(&prover::<false>(&[(16, 256)]), &[failure]);
+    }
+
+    #[test]
+    fn test_nonzero_at_zero() {
+        prover::<true>(&[(0, 7), (4, 11)]).assert_satisfied();
+        //the off selector pair isn't an input the gadget accepts
+        expect_failures(&prover::<true>(&[(0, 0)]), &[ExpectedFailure::lookup(0, "assign function lookup", 0)]);
+    }
+}
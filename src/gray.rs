@@ -0,0 +1,156 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{bit_decompose::BitDecomposeConfig, column_pool::ColumnPool, error::CircuitsError, report, running_sum::RunningSumConfig};
+
+//checks cur and next are NUM_BITS wide and differ in exactly one bit, i.e. they're neighbours
+//in the Gray code. both are decomposed, the bits are copied side by side with their xor
+//a + b - 2ab, and a running popcount of the xor has to end on 1. which neighbour next is
+//(the step direction) is not checked
+#[derive(Clone, Debug)]
+pub struct GrayConfig<F: FieldExt, const NUM_BITS: usize> {
+    pub decompose: BitDecomposeConfig<F>,
+    pub a: Column<Advice>,
+    pub b: Column<Advice>,
+    //the xors are the terms
+    pub count: RunningSumConfig<F>,
+    pub q_xor: Selector,
+}
+
+impl<F: FieldExt, const NUM_BITS: usize> GrayConfig<F, NUM_BITS> {
+    pub fn configure(
+        cs: &mut ConstraintSystem<F>,
+        decompose: BitDecomposeConfig<F>,
+        a: Column<Advice>,
+        b: Column<Advice>,
+        xor: Column<Advice>,
+        count: Column<Advice>,
+    ) -> Self {
+        assert!(NUM_BITS > 0);
+        let q_xor = cs.selector();
+        let count = RunningSumConfig::configure(cs, xor, count, 1);
+
+        cs.enable_equality(a);
+        cs.enable_equality(b);
+
+        cs.create_gate("gray xor", |cs| {
+            let q = cs.query_selector(q_xor);
+            let a = cs.query_advice(a, Rotation::cur());
+            let b = cs.query_advice(b, Rotation::cur());
+            let xor = cs.query_advice(xor, Rotation::cur());
+            Constraints::with_selector(q, [("xor = a + b - 2ab", xor - (a.clone() + b.clone() - a * b * F::from(2)))])
+        });
+        report::annotate_gate("gray", "gray xor");
+
+        Self { decompose, a, b, count, q_xor }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let decompose = BitDecomposeConfig::configure_with_pool(cs, pool);
+        let columns = pool.advice_many(cs, 4);
+        Self::configure(cs, decompose, columns[0], columns[1], columns[2], columns[3])
+    }
+
+    //for cells cur and next assigned elsewhere, returns the xor cells, little endian. they're a
+    //one-hot mask of the bit that flips
+    pub fn assign(&self, mut layouter: impl Layouter<F>, cur: &AssignedCell<F, F>, next: &AssignedCell<F, F>) -> Result<Vec<AssignedCell<F, F>>, CircuitsError> {
+        let cur_bits = self.decompose.decompose_cell(layouter.namespace(|| "decompose cur"), cur, NUM_BITS)?;
+        let next_bits = self.decompose.decompose_cell(layouter.namespace(|| "decompose next"), next, NUM_BITS)?;
+        layouter.assign_region(
+            || "assign gray step",
+            |mut region| {
+                let mut xors = vec![];
+                for (offset, (a, b)) in cur_bits.iter().zip(&next_bits).enumerate() {
+                    self.q_xor.enable(&mut region, offset)?;
+                    a.copy_advice(|| format!("cur bit {}", offset), &mut region, self.a, offset)?;
+                    b.copy_advice(|| format!("next bit {}", offset), &mut region, self.b, offset)?;
+                    xors.push(a.value().zip(b.value()).map(|(a, b)| *a + *b - *a * *b * F::from(2)));
+                }
+                //exactly one bit differs
+                let rows = self.count.assign_rows(&mut region, 0, &xors, Some(F::one()))?;
+                Ok(rows.into_iter().map(|(xor, _)| xor).collect())
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, ExpectedFailure};
+
+    #[derive(Default)]
+    struct GrayCircuit {
+        cur: Value<Fp>,
+        next: Value<Fp>,
+        //checked against the mask
+        flipped: Option<usize>,
+    }
+
+    impl Circuit<Fp> for GrayCircuit {
+        type Config = GrayConfig<Fp, 4>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(4);
+            GrayConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let (cur, next) = layouter.assign_region(
+                || "assign cur and next",
+                |mut region| {
+                    let cur = region.assign_advice(|| "cur", config.a, 0, || self.cur)?;
+                    let next = region.assign_advice(|| "next", config.b, 0, || self.next)?;
+                    Ok((cur, next))
+                },
+            )?;
+            let mask = config.assign(layouter.namespace(|| "gray"), &cur, &next)?;
+            if let Some(flipped) = self.flipped {
+                for (i, bit) in mask.iter().enumerate() {
+                    bit.value().assert_if_known(|bit| **bit == Fp::from((i == flipped) as u64));
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn prover(cur: u64, next: u64) -> MockProver<Fp> {
+        let k = 5;
+        let flipped = (cur ^ next).is_power_of_two().then(|| (cur ^ next).trailing_zeros() as usize);
+        let circuit = GrayCircuit { cur: Value::known(Fp::from(cur)), next: Value::known(Fp::from(next)), flipped };
+        MockProver::run(k, &circuit, vec![]).unwrap()
+    }
+
+    fn gray(n: u64) -> u64 {
+        n ^ (n >> 1)
+    }
+
+    #[test]
+    fn test_gray_step() {
+        //every step of the 4 bit counter, wrapping around at the end
+        for n in 0..16 {
+            prover(gray(n), gray((n + 1) % 16)).assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_gray_two_bits() {
+        let failure = [ExpectedFailure::constraint("running sum last", "acc = total", "assign gray step", 3)];
+        //0b0000 -> 0b0011
+        expect_failures(&prover(0, 3), &failure);
+        //no change at all
+        expect_failures(&prover(6, 6), &failure);
+    }
+}
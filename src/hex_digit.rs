@@ -0,0 +1,197 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use std::marker::PhantomData;
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, field::lower_128, report, rows::{self, RowBudget, TablePadding}};
+
+//the nibble of an ascii hex digit, either case, None for any other byte
+pub fn hex_nibble(byte: u8) -> Option<u8> {
+    match byte {
+        b'0'..=b'9' => Some(byte - b'0'),
+        b'A'..=b'F' => Some(byte - b'A' + 10),
+        b'a'..=b'f' => Some(byte - b'a' + 10),
+        _ => None,
+    }
+}
+
+//the pairs (byte, nibble) for the 22 hex digit bytes and nothing else. byte 0 isn't one, so
+//rows off the selector look up (b'0', 0) instead, like FunctionLookupConfig
+#[derive(Clone, Debug)]
+pub struct HexDigitTableConfig<F: FieldExt> {
+    pub byte: TableColumn,
+    pub nibble: TableColumn,
+    //what load_padded puts in the rows past the 22 pairs
+    pub padding: TablePadding,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> HexDigitTableConfig<F> {
+    pub fn configure(cs: &mut ConstraintSystem<F>) -> Self {
+        Self::configure_padded(cs, TablePadding::Unpadded)
+    }
+
+    pub fn configure_padded(cs: &mut ConstraintSystem<F>, padding: TablePadding) -> Self {
+        Self {
+            byte: cs.lookup_table_column(),
+            nibble: cs.lookup_table_column(),
+            padding: padding.for_rows(Self::rows().len()),
+            _marker: PhantomData,
+        }
+    }
+
+    fn rows() -> Vec<Vec<F>> {
+        (0..=255u8)
+            .filter_map(|byte| hex_nibble(byte).map(|nibble| vec![F::from(byte as u64), F::from(nibble as u64)]))
+            .collect()
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), CircuitsError> {
+        rows::load_table(layouter, "hex digit table", &[("byte", self.byte), ("nibble", self.nibble)], &Self::rows(), self.padding, None)
+    }
+
+    //load that first checks the table fits in the budget, then fills the rest of its usable
+    //rows as the padding says
+    pub fn load_padded(&self, layouter: &mut impl Layouter<F>, budget: RowBudget) -> Result<(), CircuitsError> {
+        rows::load_table(layouter, "hex digit table", &[("byte", self.byte), ("nibble", self.nibble)], &Self::rows(), self.padding, Some(budget))
+    }
+}
+
+//checks a byte is an ascii hex digit, 0-9, A-F or a-f, and decodes it to its nibble with a
+//single lookup of the pair
+#[derive(Clone, Debug)]
+pub struct HexDigitConfig<F: FieldExt> {
+    pub byte: Column<Advice>,
+    pub nibble: Column<Advice>,
+    pub q_enable: Selector,
+    pub table: HexDigitTableConfig<F>,
+}
+
+impl<F: FieldExt> HexDigitConfig<F> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, byte: Column<Advice>, nibble: Column<Advice>, table: HexDigitTableConfig<F>) -> Self {
+        let q_enable = cs.complex_selector();
+
+        cs.enable_equality(byte);
+        cs.enable_equality(nibble);
+
+        let lookup_index = cs.lookup(|cs| {
+            let q = cs.query_selector(q_enable);
+            let byte = cs.query_advice(byte, Rotation::cur());
+            let nibble = cs.query_advice(nibble, Rotation::cur());
+            let off = Expression::Constant(F::one()) - q.clone();
+            vec![(q.clone() * byte + off * F::from(b'0' as u64), table.byte), (q * nibble, table.nibble)]
+        });
+        report::annotate_lookup("hex_digit", lookup_index);
+
+        Self { byte, nibble, q_enable, table }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 2);
+        let table = HexDigitTableConfig::configure(cs);
+        Self::configure(cs, columns[0], columns[1], table)
+    }
+
+    //returns the (byte, nibble) cells
+    pub fn assign(&self, layouter: impl Layouter<F>, byte: Value<F>) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), CircuitsError> {
+        //a byte that isn't a digit gets 0, the lookup fails on it whatever the nibble is
+        let nibble = byte.map(|byte| F::from(hex_nibble(lower_128(&byte) as u8).unwrap_or(0) as u64));
+        self.assign_with_nibble(layouter, byte, nibble)
+    }
+
+    fn assign_with_nibble(&self, mut layouter: impl Layouter<F>, byte: Value<F>, nibble: Value<F>) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), CircuitsError> {
+        let offset = 0;
+        layouter.assign_region(
+            || "assign hex digit",
+            |mut region| {
+                self.q_enable.enable(&mut region, offset)?;
+                let byte = region.assign_advice(|| "byte", self.byte, offset, || byte)?;
+                let nibble = region.assign_advice(|| "nibble", self.nibble, offset, || nibble)?;
+                Ok((byte, nibble))
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, ExpectedFailure};
+
+    #[derive(Default)]
+    struct HexDigitCircuit {
+        k: u32,
+        byte: u64,
+        //when set, witnessed instead of the real nibble
+        nibble: Option<u64>,
+        expected: Option<u64>,
+    }
+
+    impl Circuit<Fp> for HexDigitCircuit {
+        type Config = HexDigitConfig<Fp>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { k: self.k, byte: self.byte, nibble: self.nibble, expected: self.expected }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            //padded with the last pair, so the not-digit tests also check the padding adds nothing
+            let columns = [cs.advice_column(), cs.advice_column()];
+            let table = HexDigitTableConfig::configure_padded(cs, TablePadding::RepeatMax);
+            HexDigitConfig::configure(cs, columns[0], columns[1], table)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table.load_padded(&mut layouter, RowBudget::for_circuit::<Fp, Self>(self.k))?;
+            let byte = Value::known(Fp::from(self.byte));
+            let (_, nibble) = match self.nibble {
+                Some(nibble) => config.assign_with_nibble(layouter.namespace(|| "hex digit"), byte, Value::known(Fp::from(nibble)))?,
+                None => config.assign(layouter.namespace(|| "hex digit"), byte)?,
+            };
+            if let Some(expected) = self.expected {
+                nibble.value().assert_if_known(|nibble| **nibble == Fp::from(expected));
+            }
+            Ok(())
+        }
+    }
+
+    fn prover(byte: u8, nibble: Option<u64>, expected: Option<u64>) -> MockProver<Fp> {
+        let k = 5;
+        MockProver::run(k, &HexDigitCircuit { k, byte: byte as u64, nibble, expected }, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_hex_digit() {
+        prover(b'A', None, Some(10)).assert_satisfied();
+        prover(b'9', None, Some(9)).assert_satisfied();
+        prover(b'0', None, Some(0)).assert_satisfied();
+        prover(b'f', None, Some(15)).assert_satisfied();
+    }
+
+    #[test]
+    fn test_not_hex_digit() {
+        let failure = [ExpectedFailure::lookup(0, "assign hex digit", 0)];
+        //just past either end of the three ranges, and the 0 rows off the selector would look
+        //up without the shift to b'0'
+        for byte in [b'G', b':', b'g', b'/', b'@', 0] {
+            expect_failures(&prover(byte, None, None), &failure);
+        }
+        //a digit with another digit's nibble
+        expect_failures(&prover(b'A', Some(11), None), &failure);
+    }
+
+    #[test]
+    fn test_hex_nibble() {
+        let digits: Vec<u8> = (0..=255u8).filter(|byte| hex_nibble(*byte).is_some()).collect();
+        assert_eq!(digits, b"0123456789ABCDEFabcdef");
+        assert_eq!(hex_nibble(b'c'), Some(12));
+    }
+}
@@ -0,0 +1,116 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{ConstraintSystem, Constraints, Error, Selector},
+    poly::Rotation,
+};
+
+use crate::{bit_decompose::BitDecomposeConfig, column_pool::ColumnPool, error::CircuitsError, report};
+
+//checks value < 2^NUM_BITS by decomposing it into all F::NUM_BITS bits and asserting bits
+//[NUM_BITS, F::NUM_BITS) are zero.
+//
+//the bits go most significant first, so after the high bits the running sum holds exactly
+//the high part, which the gate asserts is zero. a full width decomposition isn't unique
+//for values near p but one with a zero high part recomposes to less than 2^NUM_BITS < p,
+//so there is nothing to wrap around.
+#[derive(Clone, Debug)]
+pub struct HighZeroConfig<F: FieldExt, const NUM_BITS: usize> {
+    pub bits: BitDecomposeConfig<F>,
+    pub q_high: Selector,
+}
+
+impl<F: FieldExt, const NUM_BITS: usize> HighZeroConfig<F, NUM_BITS> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, bits: BitDecomposeConfig<F>) -> Self {
+        assert!(NUM_BITS > 0 && NUM_BITS < F::NUM_BITS as usize);
+        let q_high = cs.selector();
+
+        cs.create_gate("high zero", |cs| {
+            let q = cs.query_selector(q_high);
+            let high = cs.query_advice(bits.acc, Rotation::cur());
+            Constraints::with_selector(q, [("high bits", high)])
+        });
+        report::annotate_gate("high_zero", "high zero");
+
+        Self { bits, q_high }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let bits = BitDecomposeConfig::configure_with_pool(cs, pool);
+        Self::configure(cs, bits)
+    }
+
+    //number of bits that have to be zero. the high part is complete on row high_bits() - 1
+    fn high_bits() -> usize {
+        F::NUM_BITS as usize - NUM_BITS
+    }
+
+    //returns the value cell, one row per field bit
+    pub fn assign(&self, mut layouter: impl Layouter<F>, value: Value<F>) -> Result<AssignedCell<F, F>, CircuitsError> {
+        let num_bits = F::NUM_BITS as usize;
+        layouter.assign_region(
+            || "assign high zero",
+            |mut region| {
+                let (acc, _, _) = self.bits.assign_bits(&mut region, value, num_bits, false)?;
+                self.q_high.enable(&mut region, Self::high_bits() - 1)?;
+                let value = region.assign_advice(|| "value", self.bits.acc, num_bits, || value)?;
+                region.constrain_equal(acc.cell(), value.cell())?;
+                Ok(value)
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, ExpectedFailure};
+
+    #[derive(Default)]
+    struct HighZeroCircuit<F: FieldExt> {
+        value: Value<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for HighZeroCircuit<F> {
+        type Config = HighZeroConfig<F, 4>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let mut pool = ColumnPool::new(3);
+            HighZeroConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            config.assign(layouter.namespace(|| "high zero"), self.value)?;
+            Ok(())
+        }
+    }
+
+    fn prover(value: Fp) -> MockProver<Fp> {
+        let k = 9;
+        MockProver::run(k, &HighZeroCircuit { value: Value::known(value) }, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_high_zero() {
+        prover(Fp::from(0)).assert_satisfied();
+        prover(Fp::from(15)).assert_satisfied();
+    }
+
+    #[test]
+    fn test_high_bits_set() {
+        let row = HighZeroConfig::<Fp, 4>::high_bits() - 1;
+        let high_bits = [ExpectedFailure::constraint("high zero", "high bits", "assign high zero", row)];
+        //5 bits
+        expect_failures(&prover(Fp::from(16)), &high_bits);
+        //p - 1, high bits set all over
+        expect_failures(&prover(-Fp::one()), &high_bits);
+    }
+}
@@ -0,0 +1,223 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::{Field, FieldExt},
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Instance, Selector},
+    poly::Rotation,
+};
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, field::lower_128, range_lookup::RangeTableConfig, report, running_sum::RunningSumConfig};
+
+//counts values into NUM_BUCKETS buckets of WIDTH, bucket b holding b * WIDTH..(b + 1) * WIDTH.
+//
+//each value gets a row with its bucket and value = bucket * WIDTH + rem, rem looked up in
+//0..WIDTH. rem not wrapping below 0 is bucket * WIDTH <= value and rem < WIDTH is
+//value < (bucket + 1) * WIDTH. next to it every bucket b has an is-equal eq_b = [bucket == b]
+//by the inverse trick and a running sum counting eq_b down the rows. the eq flags have to sum
+//to 1, which also keeps bucket inside 0..NUM_BUCKETS
+#[derive(Clone, Debug)]
+pub struct HistogramConfig<F: FieldExt, const NUM_BUCKETS: usize, const WIDTH: usize> {
+    pub value: Column<Advice>,
+    pub bucket: Column<Advice>,
+    pub rem: Column<Advice>,
+    pub inv: [Column<Advice>; NUM_BUCKETS],
+    //the eq flags of each bucket are the terms
+    pub counts: [RunningSumConfig<F>; NUM_BUCKETS],
+    pub instance: Column<Instance>,
+    pub q_row: Selector,
+    pub table: RangeTableConfig<F, WIDTH>,
+}
+
+//the counts a native bucketing gives, values past the last bucket are dropped
+pub fn histogram<const NUM_BUCKETS: usize>(values: &[u64], width: u64) -> [u64; NUM_BUCKETS] {
+    let mut counts = [0; NUM_BUCKETS];
+    for value in values {
+        if let Some(count) = counts.get_mut((value / width) as usize) {
+            *count += 1;
+        }
+    }
+    counts
+}
+
+impl<F: FieldExt, const NUM_BUCKETS: usize, const WIDTH: usize> HistogramConfig<F, NUM_BUCKETS, WIDTH> {
+    //the counts go to rows 0..NUM_BUCKETS of instance
+    pub fn configure(
+        cs: &mut ConstraintSystem<F>,
+        [value, bucket, rem]: [Column<Advice>; 3],
+        inv: [Column<Advice>; NUM_BUCKETS],
+        eq: [Column<Advice>; NUM_BUCKETS],
+        count: [Column<Advice>; NUM_BUCKETS],
+        instance: Column<Instance>,
+    ) -> Self {
+        assert!(NUM_BUCKETS > 0 && WIDTH > 0);
+        let q_row = cs.complex_selector();
+        let counts: Vec<_> = eq.iter().zip(count).map(|(eq, count)| RunningSumConfig::configure(cs, *eq, count, 1)).collect();
+        let table = RangeTableConfig::configure(cs);
+
+        cs.enable_equality(value);
+        cs.enable_equality(instance);
+
+        cs.create_gate("histogram bucket", |cs| {
+            let q = cs.query_selector(q_row);
+            let value = cs.query_advice(value, Rotation::cur());
+            let bucket = cs.query_advice(bucket, Rotation::cur());
+            let rem = cs.query_advice(rem, Rotation::cur());
+            let one = Expression::Constant(F::one());
+            let mut constraints = vec![("value = bucket * WIDTH + rem", value - (bucket.clone() * Expression::Constant(F::from(WIDTH as u64)) + rem))];
+            let mut eq_sum = Expression::Constant(F::zero());
+            for b in 0..NUM_BUCKETS {
+                let diff = bucket.clone() - Expression::Constant(F::from(b as u64));
+                let inv = cs.query_advice(inv[b], Rotation::cur());
+                let eq = cs.query_advice(eq[b], Rotation::cur());
+                constraints.push(("eq = 1 - (bucket - b) * inv", eq.clone() - (one.clone() - diff.clone() * inv)));
+                constraints.push(("(bucket - b) * eq = 0", diff * eq.clone()));
+                eq_sum = eq_sum + eq;
+            }
+            constraints.push(("one bucket", eq_sum - one));
+            Constraints::with_selector(q, constraints)
+        });
+        report::annotate_gate("histogram", "histogram bucket");
+
+        let lookup_index = cs.lookup(|cs| {
+            let q = cs.query_selector(q_row);
+            let rem = cs.query_advice(rem, Rotation::cur());
+            vec![(q * rem, table.value)]
+        });
+        report::annotate_lookup("histogram", lookup_index);
+
+        Self { value, bucket, rem, inv, counts: counts.try_into().unwrap(), instance, q_row, table }
+    }
+
+    //every column is queried on the value rows, so they all have to be distinct
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>, instance: Column<Instance>) -> Self {
+        let columns = pool.advice_many(cs, 3 + 3 * NUM_BUCKETS);
+        let (row, buckets) = columns.split_at(3);
+        let bucket_columns = |i: usize| -> [Column<Advice>; NUM_BUCKETS] { buckets[i * NUM_BUCKETS..(i + 1) * NUM_BUCKETS].try_into().unwrap() };
+        Self::configure(cs, row.try_into().unwrap(), bucket_columns(0), bucket_columns(1), bucket_columns(2), instance)
+    }
+
+    //buckets the values and returns the count of each bucket
+    pub fn assign(&self, mut layouter: impl Layouter<F>, values: &[Value<F>]) -> Result<[AssignedCell<F, F>; NUM_BUCKETS], CircuitsError> {
+        if values.is_empty() {
+            return Err(CircuitsError::LengthMismatch { expected: 1, got: 0 });
+        }
+        layouter.assign_region(
+            || "assign histogram",
+            |mut region| {
+                let mut eqs = vec![vec![]; NUM_BUCKETS];
+                for (offset, value) in values.iter().enumerate() {
+                    self.q_row.enable(&mut region, offset)?;
+
+                    //a value past the last bucket still gets its quotient, the eq flags then
+                    //can't sum to 1
+                    let bucket = value.map(|v| F::from((lower_128(&v) / WIDTH as u128) as u64));
+                    let rem = *value - bucket.map(|b| b * F::from(WIDTH as u64));
+                    region.assign_advice(|| "value", self.value, offset, || *value)?;
+                    region.assign_advice(|| "bucket", self.bucket, offset, || bucket)?;
+                    region.assign_advice(|| "rem", self.rem, offset, || rem)?;
+
+                    for b in 0..NUM_BUCKETS {
+                        let diff = bucket.map(|bucket| bucket - F::from(b as u64));
+                        let inv = diff.map(|diff| diff.invert().unwrap_or(F::zero()));
+                        region.assign_advice(|| format!("inv {}", b), self.inv[b], offset, || inv)?;
+                        eqs[b].push(diff.zip(inv).map(|(diff, inv)| F::one() - diff * inv));
+                    }
+                }
+                let count_cells = self.counts.iter().zip(&eqs).map(|(count, eqs)| {
+                    let rows = count.assign_rows(&mut region, 0, eqs, None)?;
+                    Ok(rows[values.len() - 1].1.clone())
+                }).collect::<Result<Vec<_>, Error>>()?;
+                Ok(count_cells.try_into().unwrap())
+            },
+        ).map_err(CircuitsError::from)
+    }
+
+    //constrains the counts to the instance rows 0..NUM_BUCKETS
+    pub fn expose_counts(&self, mut layouter: impl Layouter<F>, counts: &[AssignedCell<F, F>; NUM_BUCKETS]) -> Result<(), CircuitsError> {
+        for (row, count) in counts.iter().enumerate() {
+            layouter.constrain_instance(count.cell(), self.instance, row)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, ExpectedFailure};
+
+    const NUM_BUCKETS: usize = 4;
+    const WIDTH: usize = 10;
+
+    #[derive(Default)]
+    struct HistogramCircuit {
+        values: Vec<Value<Fp>>,
+    }
+
+    impl Circuit<Fp> for HistogramCircuit {
+        type Config = HistogramConfig<Fp, NUM_BUCKETS, WIDTH>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { values: vec![Value::unknown(); self.values.len()] }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let instance = cs.instance_column();
+            let mut pool = ColumnPool::new(3 + 3 * NUM_BUCKETS);
+            HistogramConfig::configure_with_pool(cs, &mut pool, instance)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            let counts = config.assign(layouter.namespace(|| "histogram"), &self.values)?;
+            config.expose_counts(layouter.namespace(|| "expose counts"), &counts)?;
+            Ok(())
+        }
+    }
+
+    fn prover(values: &[u64], counts: [u64; NUM_BUCKETS]) -> MockProver<Fp> {
+        let k = 6;
+        let circuit = HistogramCircuit { values: values.iter().map(|&v| Value::known(Fp::from(v))).collect() };
+        MockProver::run(k, &circuit, vec![counts.iter().map(|&c| Fp::from(c)).collect()]).unwrap()
+    }
+
+    #[test]
+    fn test_histogram_random() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..4 {
+            let values: Vec<u64> = (0..16).map(|_| rng.gen_range(0..(NUM_BUCKETS * WIDTH) as u64)).collect();
+            let counts = histogram::<NUM_BUCKETS>(&values, WIDTH as u64);
+            assert_eq!(counts.iter().sum::<u64>(), 16);
+            prover(&values, counts).assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_histogram_edges() {
+        //the first and last value of each bucket
+        let values = [0, 9, 10, 19, 20, 29, 30, 39];
+        prover(&values, [2; NUM_BUCKETS]).assert_satisfied();
+    }
+
+    #[test]
+    fn test_miscounted_bucket() {
+        let values = [3, 15, 17, 38];
+        assert_eq!(histogram::<NUM_BUCKETS>(&values, WIDTH as u64), [1, 2, 0, 1]);
+        //a value moved from bucket 1 to bucket 2
+        assert!(prover(&values, [1, 1, 1, 1]).verify().is_err());
+        assert!(prover(&values, [1, 2, 0, 2]).verify().is_err());
+    }
+
+    #[test]
+    fn test_value_past_last_bucket() {
+        //40 would be bucket 4, which has no flag to set
+        expect_failures(
+            &prover(&[3, 40], [1, 0, 0, 0]),
+            &[ExpectedFailure::constraint("histogram bucket", "one bucket", "assign histogram", 1)],
+        );
+    }
+}
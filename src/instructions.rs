@@ -0,0 +1,144 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Chip, Layouter, Value},
+    plonk::{Assigned, Error, TableColumn},
+};
+
+use crate::{error::CircuitsError, RangeCheck, RangeConstrained};
+
+//what gadgets written against a range check chip get to use, so they can swap between the
+//expression and the lookup backend without other changes
+pub trait RangeCheckInstructions<F: FieldExt>: Chip<F> {
+    //no-op unless the backend has a table
+    fn load_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), CircuitsError>;
+
+    //assigns a fresh value and checks it
+    fn check(&self, layouter: impl Layouter<F>, value: Value<Assigned<F>>) -> Result<RangeConstrained<F>, CircuitsError>;
+
+    //copies an already assigned cell in and checks it
+    fn check_existing(&self, layouter: impl Layouter<F>, cell: &AssignedCell<Assigned<F>, F>) -> Result<RangeConstrained<F>, CircuitsError>;
+}
+
+//range checks backed by a lookup table, exposed so other lookups can share it
+pub trait LookupRangeCheckInstructions<F: FieldExt>: RangeCheckInstructions<F> {
+    fn table(&self) -> TableColumn;
+}
+
+//every chip over a RangeCheck config gets the instructions from it
+impl<F: FieldExt, C: Chip<F>> RangeCheckInstructions<F> for C
+where
+    C::Config: RangeCheck<F>,
+{
+    fn load_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), CircuitsError> {
+        self.config().load(layouter)
+    }
+
+    fn check(&self, layouter: impl Layouter<F>, value: Value<Assigned<F>>) -> Result<RangeConstrained<F>, CircuitsError> {
+        self.config().check_value(layouter, value)
+    }
+
+    fn check_existing(&self, layouter: impl Layouter<F>, cell: &AssignedCell<Assigned<F>, F>) -> Result<RangeConstrained<F>, CircuitsError> {
+        self.config().check_cell(layouter, cell)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, ConstraintSystem},
+    };
+
+    use super::*;
+    use crate::{range_check::RangeCheckChip, range_lookup::RangeLookupChip, test_utils::values_u64};
+
+    //how the generic circuit builds its chip, the instructions don't cover configuration
+    trait TestChip<F: FieldExt>: RangeCheckInstructions<F> + Sized {
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config;
+        fn construct(config: Self::Config) -> Self;
+    }
+
+    impl<F: FieldExt, const RANGE: usize> TestChip<F> for RangeCheckChip<F, RANGE> {
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let value = cs.advice_column();
+            RangeCheckChip::configure(cs, value)
+        }
+
+        fn construct(config: Self::Config) -> Self {
+            RangeCheckChip::construct(config)
+        }
+    }
+
+    impl<F: FieldExt, const RANGE: usize> TestChip<F> for RangeLookupChip<F, RANGE> {
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let value = cs.advice_column();
+            RangeLookupChip::configure(cs, value)
+        }
+
+        fn construct(config: Self::Config) -> Self {
+            RangeLookupChip::construct(config)
+        }
+    }
+
+    //only talks to the chip through the instructions
+    fn check_all<F: FieldExt, C: RangeCheckInstructions<F>>(
+        chip: &C,
+        mut layouter: impl Layouter<F>,
+        values: &[Value<Assigned<F>>],
+    ) -> Result<(), Error> {
+        chip.load_table(&mut layouter)?;
+        for value in values {
+            let checked = chip.check(layouter.namespace(|| "check"), *value)?;
+            chip.check_existing(layouter.namespace(|| "check existing"), checked.as_assigned().unwrap())?;
+        }
+        Ok(())
+    }
+
+    struct ChipCircuit<F: FieldExt, C> {
+        values: Vec<Value<Assigned<F>>>,
+        _chip: std::marker::PhantomData<C>,
+    }
+
+    impl<F: FieldExt, C: TestChip<F>> Circuit<F> for ChipCircuit<F, C> {
+        type Config = C::Config;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                values: vec![Value::unknown(); self.values.len()],
+                _chip: std::marker::PhantomData,
+            }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            C::configure(cs)
+        }
+
+        fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+            check_all(&C::construct(config), layouter, &self.values)
+        }
+    }
+
+    fn verify<C: TestChip<Fp>>(values: &[u64]) -> bool {
+        let k = 5;
+        let circuit = ChipCircuit::<Fp, C> {
+            values: values_u64(values),
+            _chip: std::marker::PhantomData,
+        };
+        MockProver::run(k, &circuit, vec![]).unwrap().verify().is_ok()
+    }
+
+    fn test_backend<C: TestChip<Fp>>() {
+        assert!(verify::<C>(&[0, 3, 7]));
+        assert!(!verify::<C>(&[3, 8]));
+    }
+
+    #[test]
+    fn test_backends() {
+        test_backend::<RangeCheckChip<Fp, 8>>();
+        test_backend::<RangeLookupChip<Fp, 8>>();
+    }
+}
@@ -0,0 +1,146 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::{Field, FieldExt},
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, report};
+
+//witnesses out = [value == 0] with the usual inverse trick. out = 1 - value * inv, and
+//value * out = 0 forces out to 0 whenever value isn't, whatever inv is
+#[derive(Clone, Debug)]
+pub struct IsZeroConfig<F: FieldExt> {
+    pub value: Column<Advice>,
+    pub inv: Column<Advice>,
+    pub out: Column<Advice>,
+    pub q_enable: Selector,
+    _marker: std::marker::PhantomData<F>,
+}
+
+impl<F: FieldExt> IsZeroConfig<F> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, value: Column<Advice>, inv: Column<Advice>, out: Column<Advice>) -> Self {
+        let q_enable = cs.selector();
+
+        cs.enable_equality(value);
+        cs.enable_equality(out);
+
+        cs.create_gate("is zero", |cs| {
+            let q = cs.query_selector(q_enable);
+            let value = cs.query_advice(value, Rotation::cur());
+            let inv = cs.query_advice(inv, Rotation::cur());
+            let out = cs.query_advice(out, Rotation::cur());
+            Constraints::with_selector(
+                q,
+                [
+                    ("out = 1 - value * inv", out.clone() - (Expression::Constant(F::one()) - value.clone() * inv)),
+                    ("value * out = 0", value * out),
+                ],
+            )
+        });
+        report::annotate_gate("is_zero", "is zero");
+
+        Self { value, inv, out, q_enable, _marker: std::marker::PhantomData }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 3);
+        Self::configure(cs, columns[0], columns[1], columns[2])
+    }
+
+    //returns the out cell for a value cell assigned elsewhere, 1 when value is zero and 0
+    //otherwise
+    pub fn assign(&self, layouter: impl Layouter<F>, value: &AssignedCell<F, F>) -> Result<AssignedCell<F, F>, CircuitsError> {
+        //zero has no inverse, any inv works for it so it gets zero
+        let value_inv = value.value().map(|v| v.invert().unwrap_or(F::zero()));
+        self.assign_with_inverse(layouter, value, value_inv)
+    }
+
+    //for callers that already have the inverse, e.g. from a batch inversion. it is only a
+    //witness, a wrong one fails the gate rather than giving a wrong out
+    pub fn assign_with_inverse(&self, mut layouter: impl Layouter<F>, value: &AssignedCell<F, F>, value_inv: Value<F>) -> Result<AssignedCell<F, F>, CircuitsError> {
+        let offset = 0;
+        let out = value.value().zip(value_inv).map(|(v, inv)| F::one() - *v * inv);
+        layouter.assign_region(
+            || "assign is zero",
+            |mut region| {
+                self.q_enable.enable(&mut region, offset)?;
+                value.copy_advice(|| "value", &mut region, self.value, offset)?;
+                region.assign_advice(|| "inv", self.inv, offset, || value_inv)?;
+                region.assign_advice(|| "out", self.out, offset, || out)
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, ExpectedFailure};
+
+    #[derive(Default)]
+    struct IsZeroCircuit {
+        value: Value<Fp>,
+        //when set, goes through assign_with_inverse with it
+        inv: Option<Value<Fp>>,
+        //unknown skips checking out
+        expected: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for IsZeroCircuit {
+        type Config = IsZeroConfig<Fp>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { inv: self.inv.map(|_| Value::unknown()), ..Self::default() }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(3);
+            IsZeroConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let value = layouter.assign_region(|| "assign value", |mut region| region.assign_advice(|| "value", config.value, 0, || self.value))?;
+            let out = match self.inv {
+                Some(inv) => config.assign_with_inverse(layouter.namespace(|| "is zero"), &value, inv)?,
+                None => config.assign(layouter.namespace(|| "is zero"), &value)?,
+            };
+            out.value().zip(self.expected).assert_if_known(|(out, expected)| *out == expected);
+            Ok(())
+        }
+    }
+
+    fn prover(value: u64, inv: Option<Fp>, expected: Value<Fp>) -> MockProver<Fp> {
+        let k = 4;
+        let circuit = IsZeroCircuit {
+            value: Value::known(Fp::from(value)),
+            inv: inv.map(Value::known),
+            expected,
+        };
+        MockProver::run(k, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_with_inverse_matches_auto() {
+        for value in [0u64, 1, 7, u64::MAX] {
+            let inv = Fp::from(value).invert().unwrap_or(Fp::zero());
+            let expected = Value::known(Fp::from((value == 0) as u64));
+            prover(value, None, expected).assert_satisfied();
+            prover(value, Some(inv), expected).assert_satisfied();
+        }
+        //zero takes any inverse
+        prover(0, Some(Fp::from(5)), Value::known(Fp::one())).assert_satisfied();
+    }
+
+    #[test]
+    fn test_wrong_inverse() {
+        //3 * 5 != 1 so out is neither 0 nor times zero
+        let failure = ExpectedFailure::constraint("is zero", "value * out = 0", "assign is zero", 0);
+        expect_failures(&prover(3, Some(Fp::from(5)), Value::unknown()), &[failure.clone()]);
+        expect_failures(&prover(3, Some(Fp::zero()), Value::unknown()), &[failure]);
+    }
+}
@@ -0,0 +1,195 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Selector},
+    poly::Rotation,
+};
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, range_lookup::RangeTableConfig, report, running_sum::RunningSumConfig};
+
+//splits value into N bytes, proving value < 2^(8 * N) on the way.
+//
+//like the bit decomposition the bytes go most significant first, as the terms of a base 256
+//running sum that ends on the value. each byte is looked up in 0..256
+#[derive(Clone, Debug)]
+pub struct LeBytesConfig<F: FieldExt, const N: usize> {
+    pub sum: RunningSumConfig<F>,
+    pub q_byte: Selector,
+    pub table: RangeTableConfig<F, 256>,
+}
+
+impl<F: FieldExt, const N: usize> LeBytesConfig<F, N> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, byte: Column<Advice>, acc: Column<Advice>) -> Self {
+        //the recomposition can't wrap around the modulus
+        assert!(N > 0 && 8 * N < F::NUM_BITS as usize);
+        let q_byte = cs.complex_selector();
+        let sum = RunningSumConfig::configure(cs, byte, acc, 256);
+        let table = RangeTableConfig::configure(cs);
+
+        let lookup_index = cs.lookup(|cs| {
+            let q = cs.query_selector(q_byte);
+            let byte = cs.query_advice(byte, Rotation::cur());
+            vec![(q * byte, table.value)]
+        });
+        report::annotate_lookup("le_bytes", lookup_index);
+
+        Self { sum, q_byte, table }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 2);
+        Self::configure(cs, columns[0], columns[1])
+    }
+
+    //the byte cells, least significant first, of a value cell assigned elsewhere
+    pub fn to_le_bytes(&self, layouter: impl Layouter<F>, value: &AssignedCell<F, F>) -> Result<[AssignedCell<F, F>; N], CircuitsError> {
+        let bytes = value.value().map(|v| {
+            let repr = v.to_repr();
+            let mut bytes = [F::zero(); N];
+            for (byte, b) in bytes.iter_mut().zip(repr.as_ref()) {
+                *byte = F::from(*b as u64);
+            }
+            bytes
+        });
+        self.assign_with_bytes(layouter, value, bytes)
+    }
+
+    //takes the bytes as a hint instead of deriving them from value, e.g. when the caller
+    //already has them from an earlier decomposition. the hint is only a witness, the lookups
+    //and the recomposition check it the same as derived bytes
+    pub fn assign_with_hint(&self, layouter: impl Layouter<F>, value: &AssignedCell<F, F>, limbs_hint: &[Value<F>]) -> Result<[AssignedCell<F, F>; N], CircuitsError> {
+        if limbs_hint.len() != N {
+            return Err(CircuitsError::LengthMismatch { expected: N, got: limbs_hint.len() });
+        }
+        let bytes = limbs_hint.iter().fold(Value::known(vec![]), |bytes, limb| {
+            bytes.zip(*limb).map(|(mut bytes, limb)| {
+                bytes.push(limb);
+                bytes
+            })
+        });
+        self.assign_with_bytes(layouter, value, bytes.map(|bytes| bytes.try_into().unwrap()))
+    }
+
+    //bytes are little endian
+    fn assign_with_bytes(&self, mut layouter: impl Layouter<F>, value: &AssignedCell<F, F>, bytes: Value<[F; N]>) -> Result<[AssignedCell<F, F>; N], CircuitsError> {
+        layouter.assign_region(
+            || "assign le bytes",
+            |mut region| {
+                //most significant byte first
+                let terms: Vec<_> = (0..N).map(|i| bytes.map(|bytes| bytes[N - 1 - i])).collect();
+                for offset in 0..N {
+                    self.q_byte.enable(&mut region, offset)?;
+                }
+                let rows = self.sum.assign_rows(&mut region, 0, &terms, None)?;
+                region.constrain_equal(rows[N - 1].1.cell(), value.cell())?;
+
+                let mut byte_cells: Vec<_> = rows.into_iter().map(|(byte, _)| byte).collect();
+                byte_cells.reverse();
+                Ok(byte_cells.try_into().unwrap())
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, ExpectedFailure};
+
+    #[derive(Default)]
+    struct LeBytesCircuit {
+        value: Value<Fp>,
+        //when set, witnesses these bytes instead of the value's own
+        bytes: Option<Value<[Fp; 2]>>,
+        //when set, passed as the hint to assign_with_hint
+        hint: Option<Vec<u64>>,
+        //checked against the byte cells
+        expected: Option<[u64; 2]>,
+    }
+
+    impl Circuit<Fp> for LeBytesCircuit {
+        type Config = LeBytesConfig<Fp, 2>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(2);
+            LeBytesConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            let value = layouter.assign_region(|| "assign value", |mut region| region.assign_advice(|| "value", config.sum.acc, 0, || self.value))?;
+            let bytes = match (self.bytes, &self.hint) {
+                (Some(bytes), _) => config.assign_with_bytes(layouter.namespace(|| "bytes"), &value, bytes)?,
+                (None, Some(hint)) => {
+                    let hint: Vec<_> = hint.iter().map(|limb| Value::known(Fp::from(*limb))).collect();
+                    config.assign_with_hint(layouter.namespace(|| "bytes"), &value, &hint)?
+                }
+                (None, None) => config.to_le_bytes(layouter.namespace(|| "bytes"), &value)?,
+            };
+            if let Some(expected) = self.expected {
+                for (cell, expected) in bytes.iter().zip(expected) {
+                    cell.value().assert_if_known(|v| **v == Fp::from(expected));
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn prover(circuit: LeBytesCircuit) -> MockProver<Fp> {
+        let k = 9;
+        MockProver::run(k, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_le_bytes() {
+        let circuit = LeBytesCircuit { value: Value::known(Fp::from(0x0102)), bytes: None, hint: None, expected: Some([0x02, 0x01]) };
+        prover(circuit).assert_satisfied();
+        let circuit = LeBytesCircuit { value: Value::known(Fp::from(0xFFFF)), bytes: None, hint: None, expected: Some([0xFF, 0xFF]) };
+        prover(circuit).assert_satisfied();
+    }
+
+    #[test]
+    fn test_le_bytes_sound() {
+        //the honest two bytes of 2^16 are zero and don't add back up to it
+        let circuit = LeBytesCircuit { value: Value::known(Fp::from(0x10000)), bytes: None, hint: None, expected: None };
+        assert!(prover(circuit).verify().is_err());
+
+        //256 * 256 + 0 adds up but 256 isn't a byte
+        let bytes = Value::known([Fp::from(0), Fp::from(256)]);
+        let circuit = LeBytesCircuit { value: Value::known(Fp::from(0x10000)), bytes: Some(bytes), hint: None, expected: None };
+        expect_failures(&prover(circuit), &[ExpectedFailure::lookup(0, "assign le bytes", 0)]);
+    }
+
+    fn hinted(value: u64, hint: &[u64], expected: Option<[u64; 2]>) -> LeBytesCircuit {
+        LeBytesCircuit { value: Value::known(Fp::from(value)), bytes: None, hint: Some(hint.to_vec()), expected }
+    }
+
+    #[test]
+    fn test_hint() {
+        //the hinted bytes end up in the same cells the derived ones do
+        for value in [0x0102, 0xFFFF, 0] {
+            let expected = [value & 0xFF, value >> 8];
+            prover(LeBytesCircuit { value: Value::known(Fp::from(value)), bytes: None, hint: None, expected: Some(expected) }).assert_satisfied();
+            prover(hinted(value, &expected, Some(expected))).assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_wrong_hint() {
+        //bytes of another value don't add up to this one
+        let permutation = |region: &str, row| ExpectedFailure { name: "permutation".to_string(), region: region.to_string(), row };
+        expect_failures(&prover(hinted(0x0102, &[0x03, 0x01], None)), &[permutation("assign le bytes", 1), permutation("assign value", 0)]);
+        //258 + 256 * 0 adds up, but 258 isn't a byte
+        expect_failures(&prover(hinted(0x0102, &[0x102, 0], None)), &[ExpectedFailure::lookup(0, "assign le bytes", 1)]);
+        //a hint with the wrong number of limbs doesn't get as far as the circuit
+        assert!(matches!(MockProver::run(9, &hinted(0x0102, &[0x02], None), vec![]), Err(Error::Synthesis)));
+    }
+}
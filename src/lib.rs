@@ -1,2 +1,181 @@
 #![allow(warnings, unused)]
-pub mod range_lookup;
\ No newline at end of file
+pub mod range_check;
+pub mod range_lookup;
+pub mod window;
+pub mod params_cache;
+pub mod proving;
+pub mod prime;
+pub mod and_range;
+pub mod sort;
+pub mod report;
+pub mod bit_decompose;
+pub mod bounds;
+pub mod sat_add;
+pub mod instructions;
+pub mod column_pool;
+pub mod date;
+pub mod field;
+pub mod error;
+pub mod checked_sub;
+pub mod high_zero;
+pub mod utf8;
+pub mod le_bytes;
+pub mod div_mod;
+pub mod residue;
+pub mod range_schedule;
+pub mod comparator;
+pub mod order_preserving;
+pub mod is_zero;
+pub mod rows;
+pub mod matrix_range;
+pub mod delta_range;
+pub mod monotonic;
+pub mod checksum;
+pub mod enum_discriminant;
+pub mod balance_proof;
+pub mod disjoint;
+pub mod age;
+pub mod all_distinct;
+pub mod sudoku;
+pub mod frequency;
+pub mod vote_tally;
+pub mod ram_range;
+pub mod budget;
+pub mod histogram;
+pub mod gray;
+pub mod timestamp_window;
+pub mod non_zero_range;
+pub mod luhn;
+pub mod fixed_mul;
+pub mod password;
+pub mod reserves;
+pub mod bsearch_step;
+pub mod dice;
+pub mod sbox;
+pub mod char_class;
+pub mod rgb;
+pub mod function_lookup;
+pub mod div_const;
+pub mod rle;
+pub mod popcount;
+pub mod bcd;
+pub mod spread;
+pub mod addition_chain;
+pub mod table_registry;
+pub mod wrap_inc;
+pub mod byte_op;
+pub mod carry_range;
+pub mod base_convert;
+pub mod port;
+pub mod not;
+pub mod one_hot;
+pub mod shr_const;
+pub mod hex_digit;
+pub mod shr_var;
+pub mod max_reduce;
+pub mod byte_swap;
+pub mod running_sum;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Cell, Layouter, Region, Value},
+    plonk::{Advice, Assigned, Column, Error},
+};
+
+use crate::{error::CircuitsError, field::lower_128};
+
+//a cell some gadget has constrained to be in its range
+#[derive(Clone, Debug)]
+pub enum RangeConstrained<F: FieldExt> {
+    //from the assign apis, keeps the deferred inversion
+    Assigned(AssignedCell<Assigned<F>, F>),
+    //from the assign_simple apis, the form halo2_gadgets chips take
+    Simple(AssignedCell<F, F>),
+}
+
+impl<F: FieldExt> RangeConstrained<F> {
+    pub fn cell(&self) -> Cell {
+        match self {
+            RangeConstrained::Assigned(cell) => cell.cell(),
+            RangeConstrained::Simple(cell) => cell.cell(),
+        }
+    }
+
+    //the same cell with its value evaluated, inverting once if it was still deferred. no
+    //region is needed, the cell itself is unchanged
+    pub fn evaluate(&self) -> AssignedCell<F, F> {
+        match self {
+            RangeConstrained::Assigned(cell) => cell.clone().evaluate(),
+            RangeConstrained::Simple(cell) => cell.clone(),
+        }
+    }
+
+    //the Assigned form, None for cells from the simple path. AsRef doesn't fit since the
+    //enum doesn't always hold one
+    pub fn as_assigned(&self) -> Option<&AssignedCell<Assigned<F>, F>> {
+        match self {
+            RangeConstrained::Assigned(cell) => Some(cell),
+            RangeConstrained::Simple(_) => None,
+        }
+    }
+
+    //low 64 bits of the value, for debugging
+    pub fn value_u64(&self) -> Value<u64> {
+        self.evaluate().value().map(|v| lower_128(v) as u64)
+    }
+
+    //copy constrains the cell into column at offset. the copy equals a checked value so it
+    //is returned as range constrained in the same form
+    pub fn copy_advice_to<A, AR>(&self, annotation: A, region: &mut Region<'_, F>, column: Column<Advice>, offset: usize) -> Result<Self, Error>
+    where
+        A: Fn() -> AR,
+        AR: Into<String>,
+    {
+        match self {
+            RangeConstrained::Assigned(cell) => cell.copy_advice(annotation, region, column, offset).map(RangeConstrained::Assigned),
+            RangeConstrained::Simple(cell) => cell.copy_advice(annotation, region, column, offset).map(RangeConstrained::Simple),
+        }
+    }
+
+    //witnesses for the assign apis. there's no From<u64> for RangeConstrained itself, it
+    //only comes out of a gadget that has assigned and checked the cell
+    pub fn witness_u64(value: u64) -> Value<Assigned<F>> {
+        Value::known(F::from(value).into())
+    }
+
+    pub fn witness_bool(value: bool) -> Value<Assigned<F>> {
+        Self::witness_u64(value as u64)
+    }
+}
+
+//a range constrained cell with the exclusive bound of its range, for arithmetic on checked
+//cells that has to know the result can't wrap around the field
+#[derive(Clone, Debug)]
+pub struct Bounded<F: FieldExt> {
+    pub cell: RangeConstrained<F>,
+    pub bound: u128,
+}
+
+//two results are equal when they are the same cell, not just the same value. Cell has no
+//PartialEq in this halo2 version and its fields are private, its Debug form has them all
+impl<F: FieldExt> PartialEq for RangeConstrained<F> {
+    fn eq(&self, other: &Self) -> bool {
+        format!("{:?}", self.cell()) == format!("{:?}", other.cell())
+    }
+}
+
+//common interface over the range gadgets so they can be swapped and composed
+pub trait RangeCheck<F: FieldExt> {
+    //assigns whatever fixed data the check needs, e.g. its lookup table
+    fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), CircuitsError> {
+        Ok(())
+    }
+
+    //assigns a fresh value in the gadget and checks it
+    fn check_value(&self, layouter: impl Layouter<F>, value: Value<Assigned<F>>) -> Result<RangeConstrained<F>, CircuitsError>;
+
+    //copies an already assigned cell into the gadget and checks it
+    fn check_cell(&self, layouter: impl Layouter<F>, cell: &AssignedCell<Assigned<F>, F>) -> Result<RangeConstrained<F>, CircuitsError>;
+}
@@ -0,0 +1,195 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    pasta::Fp,
+    plonk::{Advice, Assigned, Circuit, Column, ConstraintSystem, Constraints, Error, Instance, Selector},
+    poly::Rotation,
+};
+
+use crate::{column_pool::ColumnPool, div_mod::DivModByRangeConfig, proving::Instances, range_lookup::RangeCheckLookupConfig, report, rows::RowBudget, running_sum::RunningSumConfig};
+
+pub const CARD_DIGITS: usize = 16;
+pub const BIN_DIGITS: usize = 6;
+//k the circuit fits in
+pub const LUHN_K: u32 = 7;
+
+//the luhn sum is at most 16 * 9 = 144, so its quotient by 10 fits in 4 bits
+const SUM_Q_BITS: usize = 4;
+
+//the digits of a card number, None unless it is CARD_DIGITS decimal digits
+pub fn card_digits(number: &str) -> Option<[u64; CARD_DIGITS]> {
+    let digits: Vec<u64> = number.chars().map(|c| c.to_digit(10).map(u64::from)).collect::<Option<_>>()?;
+    digits.try_into().ok()
+}
+
+//counting from the right, every second digit is doubled. for 16 digits that is the even
+//positions from the left
+fn doubled(i: usize) -> bool {
+    (CARD_DIGITS - 1 - i) % 2 == 1
+}
+
+#[derive(Clone, Debug)]
+pub struct LuhnConfig {
+    //each digit in 0..10, against the div mod table
+    pub digits: RangeCheckLookupConfig<Fp, 10>,
+    pub digit: Column<Advice>,
+    pub double: Column<Advice>,
+    pub instance: Column<Instance>,
+    pub q_double: Selector,
+    //the first BIN_DIGITS digits read as a decimal number
+    pub bin: RunningSumConfig<Fp>,
+    //the luhn sum of the parts each digit adds, the decimal digits of a doubled digit or the
+    //digit itself
+    pub sum: RunningSumConfig<Fp>,
+    //splits a doubled digit into its decimal digits, and reduces the sum mod 10
+    pub div_mod: DivModByRangeConfig<Fp, 10>,
+}
+
+//proves a private 16 digit card number passes the luhn check and starts with a public BIN,
+//the only instance value
+#[derive(Clone, Debug, Default)]
+pub struct LuhnCircuit {
+    pub digits: [Value<Fp>; CARD_DIGITS],
+}
+
+impl LuhnCircuit {
+    pub fn from_card_number(number: &str) -> Option<Self> {
+        card_digits(number).map(|digits| Self { digits: digits.map(|digit| Value::known(Fp::from(digit))) })
+    }
+
+    pub fn public_inputs(bin: u64) -> Instances {
+        vec![vec![Fp::from(bin)]]
+    }
+}
+
+impl Circuit<Fp> for LuhnCircuit {
+    type Config = LuhnConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let mut pool = ColumnPool::new(4);
+        let div_mod = DivModByRangeConfig::configure_with_pool(cs, &mut pool);
+        let digit_values = pool.advice(cs);
+        let digits = RangeCheckLookupConfig::configure_with_table(cs, digit_values, Some(div_mod.table.value));
+        let [digit, double]: [Column<Advice>; 2] = pool.advice_many(cs, 2).try_into().unwrap();
+        let bin = RunningSumConfig::configure_with_pool(cs, &mut pool, 10);
+        let sum = RunningSumConfig::configure_with_pool(cs, &mut pool, 1);
+        let instance = cs.instance_column();
+        let constants = cs.fixed_column();
+        let q_double = cs.selector();
+
+        cs.enable_equality(digit);
+        cs.enable_equality(double);
+        cs.enable_equality(instance);
+        cs.enable_constant(constants);
+
+        cs.create_gate("luhn double", |cs| {
+            let q = cs.query_selector(q_double);
+            let digit = cs.query_advice(digit, Rotation::cur());
+            let double = cs.query_advice(double, Rotation::cur());
+            Constraints::with_selector(q, [("double = 2 * digit", double - digit * Fp::from(2))])
+        });
+        report::annotate_gate("luhn", "luhn double");
+
+        LuhnConfig { digits, digit, double, instance, q_double, bin, sum, div_mod }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        config.div_mod.table.load(&mut layouter)?;
+
+        let digits: Vec<Value<Assigned<Fp>>> = self.digits.iter().map(|digit| digit.map(Assigned::from)).collect();
+        let checked = config.digits.assign_lookup_batch(layouter.namespace(|| "digits"), RowBudget::for_circuit::<Fp, Self>(LUHN_K), &digits)?;
+
+        let (digits, doubles) = layouter.assign_region(
+            || "assign digits",
+            |mut region| {
+                let mut digits = vec![];
+                let mut doubles = vec![];
+                for (offset, checked) in checked.iter().enumerate() {
+                    let digit = checked.copy_advice_to(|| format!("digit {}", offset), &mut region, config.digit, offset)?.evaluate();
+                    if doubled(offset) {
+                        config.q_double.enable(&mut region, offset)?;
+                        let double = digit.value().map(|digit| *digit * Fp::from(2));
+                        doubles.push(Some(region.assign_advice(|| "double", config.double, offset, || double)?));
+                    } else {
+                        doubles.push(None);
+                    }
+                    digits.push(digit);
+                }
+                Ok((digits, doubles))
+            },
+        )?;
+        let bin = config.bin.assign(layouter.namespace(|| "bin"), &digits[..BIN_DIGITS], None)?;
+        layouter.constrain_instance(bin[BIN_DIGITS - 1].cell(), config.instance, 0)?;
+
+        //a doubled digit is at most 18, so q is 0 or 1 and q + r is the sum of its digits
+        let mut parts = vec![];
+        for (i, (digit, double)) in digits.iter().zip(&doubles).enumerate() {
+            match double {
+                Some(double) => {
+                    let (q, r) = config.div_mod.assign_cell(layouter.namespace(|| format!("split double {}", i)), double, 1)?;
+                    parts.extend([q, r]);
+                }
+                None => parts.push(digit.clone()),
+            }
+        }
+        let sums = config.sum.assign(layouter.namespace(|| "luhn sum"), &parts, None)?;
+        let sum = sums.last().unwrap();
+
+        let (_, r) = config.div_mod.assign_cell(layouter.namespace(|| "reduce luhn sum"), sum, SUM_Q_BITS)?;
+        layouter.assign_region(
+            || "check luhn sum",
+            |mut region| {
+                let zero = region.assign_advice_from_constant(|| "zero", config.sum.acc, 0, Fp::zero())?;
+                region.constrain_equal(zero.cell(), r.cell())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::dev::MockProver;
+
+    use super::*;
+
+    fn verify(number: &str, bin: u64) -> bool {
+        let circuit = LuhnCircuit::from_card_number(number).unwrap();
+        let prover = MockProver::run(LUHN_K, &circuit, LuhnCircuit::public_inputs(bin)).unwrap();
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn test_card_digits() {
+        assert_eq!(card_digits("4111111111111111"), Some([4, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1]));
+        assert_eq!(card_digits("411111111111111"), None);
+        assert_eq!(card_digits("4111-11111111111"), None);
+    }
+
+    #[test]
+    fn test_valid_numbers() {
+        assert!(verify("4111111111111111", 411111));
+        assert!(verify("4532015112830366", 453201));
+        assert!(verify("5500005555555559", 550000));
+    }
+
+    #[test]
+    fn test_invalid_numbers() {
+        //the last digit off by one
+        assert!(!verify("4111111111111112", 411111));
+        assert!(!verify("4532015112830367", 453201));
+        //two adjacent digits swapped, which luhn catches
+        assert!(!verify("4111111111111121", 411111));
+    }
+
+    #[test]
+    fn test_bin_mismatch() {
+        //a valid number checked against someone else's BIN
+        assert!(!verify("4532015112830366", 411111));
+        assert!(!verify("4111111111111111", 411112));
+    }
+}
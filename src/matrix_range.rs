@@ -0,0 +1,99 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Value},
+    plonk::{Advice, Assigned, Column, ConstraintSystem, Error},
+};
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, range_lookup::{RangeCheckLookupConfig, RangeTableConfig}, RangeConstrained};
+
+//checks every entry of a ROWS x COLS grid is in 0..RANGE. each grid row is one circuit row
+//across COLS columns under a single selector, every column looks up into the same table
+#[derive(Clone, Debug)]
+pub struct MatrixRangeConfig<F: FieldExt, const ROWS: usize, const COLS: usize, const RANGE: usize> {
+    pub columns: [Column<Advice>; COLS],
+    pub lookup: RangeCheckLookupConfig<F, RANGE>,
+}
+
+impl<F: FieldExt, const ROWS: usize, const COLS: usize, const RANGE: usize> MatrixRangeConfig<F, ROWS, COLS, RANGE> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, columns: [Column<Advice>; COLS]) -> Self {
+        Self { columns, lookup: RangeCheckLookupConfig::configure_parallel(cs, &columns) }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, COLS);
+        Self::configure(cs, columns.try_into().unwrap())
+    }
+
+    pub fn table(&self) -> &RangeTableConfig<F, RANGE> {
+        &self.lookup.table
+    }
+
+    //the whole grid in one region, grid row i on offset i
+    pub fn assign(&self, mut layouter: impl Layouter<F>, grid: [[Value<Assigned<F>>; COLS]; ROWS]) -> Result<Vec<Vec<RangeConstrained<F>>>, CircuitsError> {
+        layouter.assign_region(
+            || format!("assign matrix {}x{}", ROWS, COLS),
+            |mut region| {
+                grid.iter().enumerate().map(|(offset, row)| {
+                    self.lookup.q_enable.enable(&mut region, offset)?;
+                    self.columns.iter().zip(row).enumerate().map(|(col, (&column, value))| {
+                        region.assign_advice(|| format!("entry {} {}", offset, col), column, offset, || *value)
+                            .map(RangeConstrained::Assigned)
+                    }).collect::<Result<Vec<_>, Error>>()
+                }).collect()
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, value_u64, ExpectedFailure};
+
+    #[derive(Default)]
+    struct MatrixCircuit {
+        grid: [[Value<Assigned<Fp>>; 2]; 2],
+    }
+
+    impl Circuit<Fp> for MatrixCircuit {
+        type Config = MatrixRangeConfig<Fp, 2, 2, 16>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(2);
+            MatrixRangeConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table().load(&mut layouter)?;
+            let cells = config.assign(layouter.namespace(|| "matrix"), self.grid)?;
+            assert_eq!(cells.iter().map(Vec::len).collect::<Vec<_>>(), vec![2, 2]);
+            Ok(())
+        }
+    }
+
+    fn prover(grid: [[u64; 2]; 2]) -> MockProver<Fp> {
+        let k = 5;
+        let circuit = MatrixCircuit { grid: grid.map(|row| row.map(value_u64)) };
+        MockProver::run(k, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_matrix_range() {
+        prover([[0, 15], [7, 3]]).assert_satisfied();
+    }
+
+    #[test]
+    fn test_matrix_range_sound() {
+        //the entry at grid row 1, column 0 only fails the first column's lookup
+        expect_failures(&prover([[0, 15], [16, 3]]), &[ExpectedFailure::lookup(0, "assign matrix 2x2", 1)]);
+        expect_failures(&prover([[0, 15], [7, 20]]), &[ExpectedFailure::lookup(1, "assign matrix 2x2", 1)]);
+    }
+}
@@ -0,0 +1,176 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{column_pool::ColumnPool, comparator::ComparatorConfig, error::CircuitsError, field::lower_128, range_lookup::RangeTableConfig, report, running_sum::RunningSumConfig};
+
+//checks m = max(values) for values in 0..RANGE.
+//
+//row i is a comparator row with a = m and b = values[i], its lt pinned to 0 so m >= values[i],
+//and a boolean flag with flag * (m - values[i]) = 0. the flags are the terms of a running
+//sum that has to end at 1, so m is one of the values. the value cells are copied onto the
+//comparator's b column in this gadget's region, and the m cells of every row are copies of
+//the first
+#[derive(Clone, Debug)]
+pub struct MaxReduceConfig<F: FieldExt, const RANGE: usize> {
+    pub comparator: ComparatorConfig<F, RANGE>,
+    pub flags: RunningSumConfig<F>,
+    pub q_max: Selector,
+}
+
+impl<F: FieldExt, const RANGE: usize> MaxReduceConfig<F, RANGE> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, comparator: ComparatorConfig<F, RANGE>, flag: Column<Advice>, sum: Column<Advice>) -> Self {
+        let q_max = cs.selector();
+        let flags = RunningSumConfig::configure(cs, flag, sum, 1);
+
+        cs.create_gate("max reduce", |cs| {
+            let q = cs.query_selector(q_max);
+            let m = cs.query_advice(comparator.a, Rotation::cur());
+            let value = cs.query_advice(comparator.b, Rotation::cur());
+            let lt = cs.query_advice(comparator.lt, Rotation::cur());
+            let flag = cs.query_advice(flag, Rotation::cur());
+            let one = Expression::Constant(F::one());
+            Constraints::with_selector(
+                q,
+                [
+                    ("m >= value", lt),
+                    ("flag boolean", flag.clone() * (one - flag.clone())),
+                    ("flag * (m - value) = 0", flag * (m - value)),
+                ],
+            )
+        });
+        report::annotate_gate("max_reduce", "max reduce");
+
+        Self { comparator, flags, q_max }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let comparator = ComparatorConfig::configure_with_pool(cs, pool);
+        let columns = pool.advice_many(cs, 2);
+        Self::configure(cs, comparator, columns[0], columns[1])
+    }
+
+    pub fn table(&self) -> &RangeTableConfig<F, RANGE> {
+        &self.comparator.table
+    }
+
+    //returns the m cell for value cells assigned elsewhere
+    pub fn assign(&self, layouter: impl Layouter<F>, values: &[AssignedCell<F, F>]) -> Result<AssignedCell<F, F>, CircuitsError> {
+        let max = values.iter().fold(Value::known(F::zero()), |max, value| {
+            max.zip(value.value().copied()).map(|(max, value)| if lower_128(&value) > lower_128(&max) { value } else { max })
+        });
+        //the flag goes on the first value equal to the max
+        let mut found = Value::known(false);
+        let flags = values.iter().map(|value| {
+            let flag = found.zip(max).zip(value.value().copied()).map(|((found, max), value)| !found && value == max);
+            found = found.zip(flag).map(|(found, flag)| found || flag);
+            flag.map(|flag| F::from(flag as u64))
+        }).collect::<Vec<_>>();
+        self.assign_with_max(layouter, values, max, &flags)
+    }
+
+    fn assign_with_max(&self, mut layouter: impl Layouter<F>, values: &[AssignedCell<F, F>], m: Value<F>, flags: &[Value<F>]) -> Result<AssignedCell<F, F>, CircuitsError> {
+        if values.is_empty() {
+            return Err(CircuitsError::LengthMismatch { expected: 1, got: 0 });
+        }
+        layouter.assign_region(
+            || "assign max reduce",
+            |mut region| {
+                let mut m_cell: Option<AssignedCell<F, F>> = None;
+                for (offset, value) in values.iter().enumerate() {
+                    self.comparator.q_enable.enable(&mut region, offset)?;
+                    self.q_max.enable(&mut region, offset)?;
+                    let cell = region.assign_advice(|| "m", self.comparator.a, offset, || m)?;
+                    match &m_cell {
+                        Some(first) => region.constrain_equal(first.cell(), cell.cell())?,
+                        None => m_cell = Some(cell),
+                    }
+                    value.copy_advice(|| "value", &mut region, self.comparator.b, offset)?;
+                    region.assign_advice(|| "lt", self.comparator.lt, offset, || Value::known(F::zero()))?;
+                }
+                self.flags.assign_rows(&mut region, 0, flags, Some(F::one()))?;
+                Ok(m_cell.unwrap())
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, known, ExpectedFailure, Forged};
+
+    #[derive(Default)]
+    struct MaxCircuit {
+        values: Vec<Value<Fp>>,
+        //witnessed as m then the flags instead of the real ones when forged
+        forged: Forged<Fp>,
+        expected: Option<u64>,
+    }
+
+    impl Circuit<Fp> for MaxCircuit {
+        type Config = MaxReduceConfig<Fp, 16>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { values: vec![Value::unknown(); self.values.len()], forged: self.forged.without_witnesses(), expected: None }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(5);
+            MaxReduceConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table().load(&mut layouter)?;
+            let values = layouter.assign_region(
+                || "assign values",
+                |mut region| {
+                    self.values.iter().enumerate().map(|(offset, v)| region.assign_advice(|| "value", config.comparator.b, offset, || *v)).collect::<Result<Vec<_>, _>>()
+                },
+            )?;
+            let m = match self.forged.values().and_then(|forged| forged.split_first()) {
+                Some((m, flags)) => config.assign_with_max(layouter.namespace(|| "max"), &values, *m, flags)?,
+                None => config.assign(layouter.namespace(|| "max"), &values)?,
+            };
+            if let Some(expected) = self.expected {
+                m.value().assert_if_known(|m| **m == Fp::from(expected));
+            }
+            Ok(())
+        }
+    }
+
+    fn prover(values: &[u64], forged: Option<(u64, &[u64])>, expected: Option<u64>) -> MockProver<Fp> {
+        let k = 5;
+        let forged = forged.map_or_else(Forged::honest, |(m, flags)| Forged::from_u64s(&[&[m][..], flags].concat()));
+        let circuit = MaxCircuit { values: values.iter().map(|v| known(*v)).collect(), forged, expected };
+        MockProver::run(k, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_max() {
+        prover(&[3, 7, 2, 5], None, Some(7)).assert_satisfied();
+        //a tie only flags the first
+        prover(&[7, 3, 7], None, Some(7)).assert_satisfied();
+        prover(&[0], None, Some(0)).assert_satisfied();
+        prover(&[15, 0, 15, 15], None, Some(15)).assert_satisfied();
+    }
+
+    #[test]
+    fn test_max_sound() {
+        let values = [3, 7, 2, 5];
+        let at = |constraint, row| ExpectedFailure::constraint("max reduce", constraint, "assign max reduce", row);
+        //8 is above every value but isn't one of them, whichever flag is set
+        expect_failures(&prover(&values, Some((8, &[0, 1, 0, 0])), None), &[at("flag * (m - value) = 0", 1)]);
+        let last = ExpectedFailure::constraint("running sum last", "acc = total", "assign max reduce", 3);
+        expect_failures(&prover(&values, Some((8, &[0, 0, 0, 0])), None), &[last]);
+        //5 is one of them but 7 is above it, m - value = -2 misses the table
+        expect_failures(&prover(&values, Some((5, &[0, 0, 0, 1])), None), &[ExpectedFailure::lookup(2, "assign max reduce", 1)]);
+    }
+}
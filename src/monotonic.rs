@@ -0,0 +1,176 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{bit_decompose::BitDecomposeConfig, column_pool::ColumnPool, error::CircuitsError, report};
+
+//checks a column is non-decreasing, or increasing when strict, with steps up to 2^NUM_BITS.
+//
+//each step witnesses diff = next - cur (- 1 when strict) and decomposes it into NUM_BITS
+//bits. a step down wraps diff around to something near p, which has no such decomposition
+//as long as NUM_BITS < F::NUM_BITS - 1
+#[derive(Clone, Debug)]
+pub struct MonotonicConfig<F: FieldExt, const NUM_BITS: usize> {
+    pub value: Column<Advice>,
+    pub diff: Column<Advice>,
+    pub q_step: Selector,
+    pub strict: bool,
+    pub bits: BitDecomposeConfig<F>,
+}
+
+impl<F: FieldExt, const NUM_BITS: usize> MonotonicConfig<F, NUM_BITS> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, value: Column<Advice>, diff: Column<Advice>, bits: BitDecomposeConfig<F>, strict: bool) -> Self {
+        assert!(NUM_BITS > 0 && NUM_BITS < F::NUM_BITS as usize - 1);
+        let q_step = cs.selector();
+
+        cs.enable_equality(value);
+        cs.enable_equality(diff);
+
+        cs.create_gate("monotonic", |cs| {
+            let q = cs.query_selector(q_step);
+            let cur = cs.query_advice(value, Rotation::cur());
+            let next = cs.query_advice(value, Rotation::next());
+            let diff = cs.query_advice(diff, Rotation::cur());
+            let gap = Expression::Constant(if strict { F::one() } else { F::zero() });
+            Constraints::with_selector(q, [("diff = next - cur - strict", diff - (next - cur - gap))])
+        });
+        report::annotate_gate("monotonic", "monotonic");
+
+        Self { value, diff, q_step, strict, bits }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>, strict: bool) -> Self {
+        let columns = pool.advice_many(cs, 2);
+        let bits = BitDecomposeConfig::configure_with_pool(cs, pool);
+        Self::configure(cs, columns[0], columns[1], bits, strict)
+    }
+
+    pub fn assign_column(&self, layouter: impl Layouter<F>, values: &[Value<F>]) -> Result<Vec<AssignedCell<F, F>>, CircuitsError> {
+        let gap = if self.strict { F::one() } else { F::zero() };
+        let diffs = values.windows(2).map(|w| w[0].zip(w[1]).map(|(cur, next)| next - cur - gap)).collect::<Vec<_>>();
+        self.assign_with_diffs(layouter, values, None, &diffs)
+    }
+
+    //the same on cells assigned elsewhere, which are copied into the column in order
+    pub fn assign_cells(&self, layouter: impl Layouter<F>, cells: &[AssignedCell<F, F>]) -> Result<Vec<AssignedCell<F, F>>, CircuitsError> {
+        let values = cells.iter().map(|cell| cell.value().copied()).collect::<Vec<_>>();
+        let gap = if self.strict { F::one() } else { F::zero() };
+        let diffs = values.windows(2).map(|w| w[0].zip(w[1]).map(|(cur, next)| next - cur - gap)).collect::<Vec<_>>();
+        self.assign_with_diffs(layouter, &values, Some(cells), &diffs)
+    }
+
+    fn assign_with_diffs(
+        &self,
+        mut layouter: impl Layouter<F>,
+        values: &[Value<F>],
+        cells: Option<&[AssignedCell<F, F>]>,
+        diffs: &[Value<F>],
+    ) -> Result<Vec<AssignedCell<F, F>>, CircuitsError> {
+        let (cells, diffs) = layouter.assign_region(
+            || "assign monotonic column",
+            |mut region| {
+                let cells = values.iter().enumerate().map(|(offset, value)| match cells {
+                    Some(cells) => cells[offset].copy_advice(|| format!("value {}", offset), &mut region, self.value, offset),
+                    None => region.assign_advice(|| format!("value {}", offset), self.value, offset, || *value),
+                }).collect::<Result<Vec<_>, Error>>()?;
+                let diffs = diffs.iter().enumerate().map(|(offset, diff)| {
+                    self.q_step.enable(&mut region, offset)?;
+                    region.assign_advice(|| format!("diff {}", offset), self.diff, offset, || *diff)
+                }).collect::<Result<Vec<_>, Error>>()?;
+                Ok((cells, diffs))
+            },
+        )?;
+        for diff in &diffs {
+            self.bits.decompose_cell(layouter.namespace(|| "diff bits"), diff, NUM_BITS)?;
+        }
+        Ok(cells)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, ExpectedFailure};
+
+    #[derive(Default)]
+    struct MonotonicCircuit<const STRICT: bool> {
+        values: Vec<Value<Fp>>,
+        //when set, witnessed instead of the real differences
+        diffs: Option<Vec<Value<Fp>>>,
+    }
+
+    impl<const STRICT: bool> Circuit<Fp> for MonotonicCircuit<STRICT> {
+        type Config = MonotonicConfig<Fp, 16>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                values: vec![Value::unknown(); self.values.len()],
+                diffs: self.diffs.as_ref().map(|diffs| vec![Value::unknown(); diffs.len()]),
+            }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(5);
+            MonotonicConfig::configure_with_pool(cs, &mut pool, STRICT)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            match &self.diffs {
+                Some(diffs) => config.assign_with_diffs(layouter.namespace(|| "monotonic"), &self.values, None, diffs)?,
+                None => config.assign_column(layouter.namespace(|| "monotonic"), &self.values)?,
+            };
+            Ok(())
+        }
+    }
+
+    fn prover<const STRICT: bool>(values: &[u64]) -> MockProver<Fp> {
+        let k = 9;
+        let circuit = MonotonicCircuit::<STRICT> { values: values.iter().map(|v| Value::known(Fp::from(*v))).collect(), diffs: None };
+        MockProver::run(k, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_equal_neighbours() {
+        let values = [3, 3, 1 << 15, (1 << 16) + (1 << 15) - 1];
+        prover::<false>(&values).assert_satisfied();
+        //strict only fails on the repeated 3, where diff is -1
+        expect_failures(
+            &prover::<true>(&values),
+            &[
+                ExpectedFailure { name: "permutation".to_string(), region: "assign monotonic column".to_string(), row: 0 },
+                ExpectedFailure { name: "permutation".to_string(), region: "decompose cell".to_string(), row: 15 },
+            ],
+        );
+        prover::<true>(&[3, 4, 1 << 15]).assert_satisfied();
+    }
+
+    #[test]
+    fn test_out_of_order_deep() {
+        //wide steps, with the value at index 20 dropping below the one before it
+        let mut values: Vec<u64> = (0..24).map(|i| i * 20_000).collect();
+        values[20] = values[19] - 1;
+        expect_failures(
+            &prover::<false>(&values),
+            &[
+                ExpectedFailure { name: "permutation".to_string(), region: "assign monotonic column".to_string(), row: 19 },
+                ExpectedFailure { name: "permutation".to_string(), region: "decompose cell".to_string(), row: 15 },
+            ],
+        );
+    }
+
+    #[test]
+    fn test_diff_tied_to_values() {
+        //an in range diff that doesn't match the step is caught by the gate
+        let values = [5, 2].map(|v| Value::known(Fp::from(v))).to_vec();
+        let circuit = MonotonicCircuit::<false> { values, diffs: Some(vec![Value::known(Fp::from(3))]) };
+        let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+        expect_failures(&prover, &[ExpectedFailure::constraint("monotonic", "diff = next - cur - strict", "assign monotonic column", 0)]);
+    }
+}
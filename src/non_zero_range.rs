@@ -0,0 +1,117 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::{Field, FieldExt},
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, range_lookup::RangeTableConfig, report};
+
+//checks value is in 1..RANGE. value is looked up in 0..RANGE like the plain lookup gadget,
+//and value * inv = 1 rules out the 0, which has no inverse
+#[derive(Clone, Debug)]
+pub struct NonZeroRangeConfig<F: FieldExt, const RANGE: usize> {
+    pub value: Column<Advice>,
+    pub inv: Column<Advice>,
+    pub q_enable: Selector,
+    pub table: RangeTableConfig<F, RANGE>,
+}
+
+impl<F: FieldExt, const RANGE: usize> NonZeroRangeConfig<F, RANGE> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, value: Column<Advice>, inv: Column<Advice>) -> Self {
+        //only 0 is below 1
+        assert!(RANGE > 1);
+        let q_enable = cs.complex_selector();
+        let table = RangeTableConfig::configure(cs);
+
+        cs.enable_equality(value);
+
+        cs.create_gate("non zero", |cs| {
+            let q = cs.query_selector(q_enable);
+            let value = cs.query_advice(value, Rotation::cur());
+            let inv = cs.query_advice(inv, Rotation::cur());
+            Constraints::with_selector(q, [("value * inv = 1", value * inv - Expression::Constant(F::one()))])
+        });
+        report::annotate_gate("non_zero_range", "non zero");
+
+        let lookup_index = cs.lookup(|cs| {
+            let q = cs.query_selector(q_enable);
+            let value = cs.query_advice(value, Rotation::cur());
+            vec![(q * value, table.value)]
+        });
+        report::annotate_lookup("non_zero_range", lookup_index);
+
+        Self { value, inv, q_enable, table }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 2);
+        Self::configure(cs, columns[0], columns[1])
+    }
+
+    pub fn assign(&self, mut layouter: impl Layouter<F>, value: Value<F>) -> Result<AssignedCell<F, F>, CircuitsError> {
+        let offset = 0;
+        //0 gets 0, the gate fails on it whatever inv is
+        let inv = value.map(|v| v.invert().unwrap_or(F::zero()));
+        layouter.assign_region(
+            || "assign non zero range",
+            |mut region| {
+                self.q_enable.enable(&mut region, offset)?;
+                region.assign_advice(|| "inv", self.inv, offset, || inv)?;
+                region.assign_advice(|| "value", self.value, offset, || value)
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, ExpectedFailure};
+
+    #[derive(Default)]
+    struct NonZeroRangeCircuit {
+        value: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for NonZeroRangeCircuit {
+        type Config = NonZeroRangeConfig<Fp, 16>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(2);
+            NonZeroRangeConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            config.assign(layouter.namespace(|| "non zero range"), self.value)?;
+            Ok(())
+        }
+    }
+
+    fn prover(value: u64) -> MockProver<Fp> {
+        let k = 5;
+        MockProver::run(k, &NonZeroRangeCircuit { value: Value::known(Fp::from(value)) }, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_non_zero_range() {
+        prover(1).assert_satisfied();
+        prover(15).assert_satisfied();
+    }
+
+    #[test]
+    fn test_boundaries() {
+        //0 is in the table, only the inverse catches it
+        expect_failures(&prover(0), &[ExpectedFailure::constraint("non zero", "value * inv = 1", "assign non zero range", 0)]);
+        expect_failures(&prover(16), &[ExpectedFailure::lookup(0, "assign non zero range", 0)]);
+    }
+}
@@ -0,0 +1,185 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use std::marker::PhantomData;
+
+use crate::{bit_decompose::BitDecomposeConfig, column_pool::ColumnPool, error::CircuitsError, report};
+
+//bitwise not of a NUM_BITS wide word, out = (2^NUM_BITS - 1) - x.
+//
+//the subtraction is only the complement when x < 2^NUM_BITS, a wider x just makes out
+//negative, so x is decomposed into NUM_BITS bits and so is out. there is also a bit level
+//not for words that are already decomposed, one out = 1 - bit row per bit
+#[derive(Clone, Debug)]
+pub struct NotConfig<F: FieldExt, const NUM_BITS: usize> {
+    pub x: Column<Advice>,
+    pub out: Column<Advice>,
+    pub q_not: Selector,
+    pub q_not_bit: Selector,
+    pub decompose: BitDecomposeConfig<F>,
+}
+
+impl<F: FieldExt, const NUM_BITS: usize> NotConfig<F, NUM_BITS> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, x: Column<Advice>, out: Column<Advice>, decompose: BitDecomposeConfig<F>) -> Self {
+        //the mask is built from a u128 and out can't wrap around the modulus
+        assert!(NUM_BITS > 0 && NUM_BITS < 128 && NUM_BITS < F::NUM_BITS as usize);
+        let q_not = cs.selector();
+        let q_not_bit = cs.selector();
+
+        cs.enable_equality(x);
+        cs.enable_equality(out);
+
+        cs.create_gate("not", |cs| {
+            let q = cs.query_selector(q_not);
+            let x = cs.query_advice(x, Rotation::cur());
+            let out = cs.query_advice(out, Rotation::cur());
+            Constraints::with_selector(q, [("out = mask - x", out - (Expression::Constant(Self::mask()) - x))])
+        });
+        report::annotate_gate("not", "not");
+
+        //the bits come from a decomposition, so they are already boolean and so is 1 - bit
+        cs.create_gate("not bit", |cs| {
+            let q = cs.query_selector(q_not_bit);
+            let bit = cs.query_advice(x, Rotation::cur());
+            let out = cs.query_advice(out, Rotation::cur());
+            Constraints::with_selector(q, [("out = 1 - bit", out - (Expression::Constant(F::one()) - bit))])
+        });
+        report::annotate_gate("not", "not bit");
+
+        Self { x, out, q_not, q_not_bit, decompose }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 2);
+        let decompose = BitDecomposeConfig::configure_with_pool(cs, pool);
+        Self::configure(cs, columns[0], columns[1], decompose)
+    }
+
+    fn mask() -> F {
+        F::from_u128((1 << NUM_BITS) - 1)
+    }
+
+    //returns the out cell for a cell x assigned elsewhere
+    pub fn not(&self, mut layouter: impl Layouter<F>, x: &AssignedCell<F, F>) -> Result<AssignedCell<F, F>, CircuitsError> {
+        let offset = 0;
+        let (x, out) = layouter.assign_region(
+            || "assign not",
+            |mut region| {
+                self.q_not.enable(&mut region, offset)?;
+                let x = x.copy_advice(|| "x", &mut region, self.x, offset)?;
+                let out = region.assign_advice(|| "out", self.out, offset, || x.value().map(|x| Self::mask() - x))?;
+                Ok((x, out))
+            },
+        ).map_err(CircuitsError::from)?;
+        self.decompose.decompose_cell(layouter.namespace(|| "x bits"), &x, NUM_BITS)?;
+        self.decompose.decompose_cell(layouter.namespace(|| "out bits"), &out, NUM_BITS)?;
+        Ok(out)
+    }
+
+    //not of each bit cell, in the same order. the bits have to be boolean already, e.g. from
+    //BitDecomposeConfig
+    pub fn not_bits(&self, mut layouter: impl Layouter<F>, bits: &[AssignedCell<F, F>]) -> Result<Vec<AssignedCell<F, F>>, CircuitsError> {
+        if bits.len() != NUM_BITS {
+            return Err(CircuitsError::LengthMismatch { expected: NUM_BITS, got: bits.len() });
+        }
+        layouter.assign_region(
+            || "assign not bits",
+            |mut region| {
+                bits.iter().enumerate().map(|(offset, bit)| {
+                    self.q_not_bit.enable(&mut region, offset)?;
+                    let bit = bit.copy_advice(|| "bit", &mut region, self.x, offset)?;
+                    region.assign_advice(|| "out", self.out, offset, || bit.value().map(|bit| F::one() - bit))
+                }).collect::<Result<Vec<_>, Error>>()
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct NotCircuit<const NUM_BITS: usize> {
+        x: u64,
+        //not each bit of x instead of the whole word
+        bitwise: bool,
+        expected: Option<u64>,
+    }
+
+    impl<const NUM_BITS: usize> Circuit<Fp> for NotCircuit<NUM_BITS> {
+        type Config = NotConfig<Fp, NUM_BITS>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { x: self.x, bitwise: self.bitwise, expected: self.expected }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(5);
+            NotConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let x = Value::known(Fp::from(self.x));
+            if self.bitwise {
+                let (_, bits) = config.decompose.decompose(layouter.namespace(|| "x"), x, NUM_BITS)?;
+                let out = config.not_bits(layouter.namespace(|| "not"), &bits)?;
+                if let Some(expected) = self.expected {
+                    for (i, bit) in out.iter().enumerate() {
+                        bit.value().assert_if_known(|bit| **bit == Fp::from((expected >> i) & 1));
+                    }
+                }
+            } else {
+                let x = layouter.assign_region(|| "assign x", |mut region| region.assign_advice(|| "x", config.x, 0, || x))?;
+                let out = config.not(layouter.namespace(|| "not"), &x)?;
+                if let Some(expected) = self.expected {
+                    out.value().assert_if_known(|out| **out == Fp::from(expected));
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn native<const NUM_BITS: usize>(x: u64) -> u64 {
+        let mask = if NUM_BITS == 64 { u64::MAX } else { (1 << NUM_BITS) - 1 };
+        !x & mask
+    }
+
+    fn prover<const NUM_BITS: usize>(x: u64, bitwise: bool, expected: Option<u64>) -> MockProver<Fp> {
+        //the two 64 bit decompositions take 130 rows
+        let k = 8;
+        MockProver::run(k, &NotCircuit::<NUM_BITS> { x, bitwise, expected }, vec![]).unwrap()
+    }
+
+    fn check<const NUM_BITS: usize>(xs: &[u64]) {
+        for &x in xs {
+            prover::<NUM_BITS>(x, false, Some(native::<NUM_BITS>(x))).assert_satisfied();
+            prover::<NUM_BITS>(x, true, Some(native::<NUM_BITS>(x))).assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_not() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut xs = |mask: u64| [0, mask, 1, mask - 1].into_iter().chain((0..4).map(|_| rng.gen::<u64>() & mask)).collect::<Vec<_>>();
+        check::<8>(&xs(0xFF));
+        check::<32>(&xs(0xFFFF_FFFF));
+        check::<64>(&xs(u64::MAX));
+    }
+
+    #[test]
+    fn test_x_too_wide() {
+        //out = 255 - 256 is -1, and neither x nor out fits in 8 bits
+        assert!(prover::<8>(256, false, None).verify().is_err());
+        assert!(prover::<32>(1 << 32, false, None).verify().is_err());
+    }
+}
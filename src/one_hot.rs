@@ -0,0 +1,130 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, report};
+
+//checks exactly one of N flags is set, e.g. the states of a state machine. the flags sit on
+//one row, one column each, and are boolean and sum to one. EnumDiscriminantConfig is the
+//same with the flags weighted to a value
+#[derive(Clone, Debug)]
+pub struct OneHotConfig<F: FieldExt, const N: usize> {
+    pub flags: [Column<Advice>; N],
+    pub q_enable: Selector,
+    _marker: std::marker::PhantomData<F>,
+}
+
+impl<F: FieldExt, const N: usize> OneHotConfig<F, N> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, flags: [Column<Advice>; N]) -> Self {
+        assert!(N > 0);
+        let q_enable = cs.selector();
+
+        for flag in flags {
+            cs.enable_equality(flag);
+        }
+
+        cs.create_gate("one hot", |cs| {
+            let q = cs.query_selector(q_enable);
+            let flags = flags.map(|flag| cs.query_advice(flag, Rotation::cur()));
+            let one = Expression::Constant(F::one());
+
+            let sum = flags.iter().fold(Expression::Constant(F::zero()), |sum, flag| sum + flag.clone());
+            let booleans = flags.iter().map(|flag| ("flag boolean", flag.clone() * (one.clone() - flag.clone())));
+            Constraints::with_selector(q, booleans.chain([("flags sum to one", sum - one.clone())]).collect::<Vec<_>>())
+        });
+        report::annotate_gate("one_hot", "one hot");
+
+        Self { flags, q_enable, _marker: std::marker::PhantomData }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, N);
+        Self::configure(cs, columns.try_into().unwrap())
+    }
+
+    //returns the flag cells, in the same order
+    pub fn assign(&self, mut layouter: impl Layouter<F>, flags: [Value<F>; N]) -> Result<[AssignedCell<F, F>; N], CircuitsError> {
+        let offset = 0;
+        layouter.assign_region(
+            || "assign one hot",
+            |mut region| {
+                self.q_enable.enable(&mut region, offset)?;
+                let flags = self.flags.iter().zip(flags).enumerate().map(|(i, (&column, flag))| {
+                    region.assign_advice(|| format!("flag {}", i), column, offset, || flag)
+                }).collect::<Result<Vec<_>, Error>>()?;
+                Ok(flags.try_into().unwrap())
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{arithmetic::Field, circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, ExpectedFailure};
+
+    #[derive(Default)]
+    struct OneHotCircuit {
+        flags: [Value<Fp>; 4],
+    }
+
+    impl Circuit<Fp> for OneHotCircuit {
+        type Config = OneHotConfig<Fp, 4>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(4);
+            OneHotConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.assign(layouter.namespace(|| "one hot"), self.flags)?;
+            Ok(())
+        }
+    }
+
+    fn prover(flags: [Fp; 4]) -> MockProver<Fp> {
+        let k = 4;
+        MockProver::run(k, &OneHotCircuit { flags: flags.map(Value::known) }, vec![]).unwrap()
+    }
+
+    fn flags(flags: [u64; 4]) -> [Fp; 4] {
+        flags.map(Fp::from)
+    }
+
+    fn failure(constraint: &str) -> ExpectedFailure {
+        ExpectedFailure::constraint("one hot", constraint, "assign one hot", 0)
+    }
+
+    #[test]
+    fn test_one_hot() {
+        prover(flags([0, 0, 1, 0])).assert_satisfied();
+        prover(flags([1, 0, 0, 0])).assert_satisfied();
+        prover(flags([0, 0, 0, 1])).assert_satisfied();
+    }
+
+    #[test]
+    fn test_not_one_hot() {
+        expect_failures(&prover(flags([0, 0, 0, 0])), &[failure("flags sum to one")]);
+        expect_failures(&prover(flags([0, 1, 0, 1])), &[failure("flags sum to one")]);
+    }
+
+    #[test]
+    fn test_flags_boolean() {
+        //2 and -1 sum to one, only the booleans catch them
+        expect_failures(&prover([Fp::from(2), -Fp::one(), Fp::zero(), Fp::zero()]), &[failure("flag boolean"), failure("flag boolean")]);
+        //so does a half, twice
+        let half = Fp::from(2).invert().unwrap();
+        expect_failures(&prover([half, half, Fp::zero(), Fp::zero()]), &[failure("flag boolean"), failure("flag boolean")]);
+    }
+}
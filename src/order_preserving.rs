@@ -0,0 +1,115 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Assigned, ConstraintSystem, Error},
+};
+
+use crate::{comparator::ComparatorConfig, column_pool::ColumnPool, error::CircuitsError, range_lookup::RangeTableConfig};
+
+//checks a pair of values and their encodings are in the same order, [a < b] = [enc(a) < enc(b)].
+//
+//the inputs and the encodings each go through the comparator, and the encodings' comparison
+//copies its result from the inputs' one
+#[derive(Clone, Debug)]
+pub struct OrderPreservingConfig<F: FieldExt, const RANGE: usize> {
+    pub comparator: ComparatorConfig<F, RANGE>,
+}
+
+impl<F: FieldExt, const RANGE: usize> OrderPreservingConfig<F, RANGE> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, comparator: ComparatorConfig<F, RANGE>) -> Self {
+        Self { comparator }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let comparator = ComparatorConfig::configure_with_pool(cs, pool);
+        Self::configure(cs, comparator)
+    }
+
+    pub fn table(&self) -> &RangeTableConfig<F, RANGE> {
+        &self.comparator.table
+    }
+
+    //returns [a < b] for cells assigned elsewhere, which the encodings are bound to agree with
+    pub fn assign<V>(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<V, F>,
+        b: &AssignedCell<V, F>,
+        enc_a: &AssignedCell<V, F>,
+        enc_b: &AssignedCell<V, F>,
+    ) -> Result<AssignedCell<Assigned<F>, F>, CircuitsError>
+    where
+        V: Clone,
+        for<'v> Assigned<F>: From<&'v V>,
+    {
+        let lt = self.comparator.assign(layouter.namespace(|| "inputs"), a, b)?;
+        self.comparator.assign_with_lt(layouter.namespace(|| "encodings"), enc_a, enc_b, &lt)?;
+        Ok(lt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, value_u64, ExpectedFailure};
+
+    #[derive(Default)]
+    struct OrderCircuit {
+        values: [Value<Assigned<Fp>>; 4],
+    }
+
+    impl Circuit<Fp> for OrderCircuit {
+        type Config = OrderPreservingConfig<Fp, 32>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(3);
+            OrderPreservingConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table().load(&mut layouter)?;
+            let [a, b, enc_a, enc_b] = layouter.assign_region(
+                || "assign values",
+                |mut region| {
+                    let mut cells = vec![];
+                    for (offset, value) in self.values.iter().enumerate() {
+                        cells.push(region.assign_advice(|| "value", config.comparator.a, offset, || *value)?);
+                    }
+                    Ok(cells)
+                },
+            )?.try_into().unwrap();
+            config.assign(layouter.namespace(|| "order"), &a, &b, &enc_a, &enc_b)?;
+            Ok(())
+        }
+    }
+
+    fn prover(a: u64, b: u64, enc_a: u64, enc_b: u64) -> MockProver<Fp> {
+        let k = 6;
+        let circuit = OrderCircuit { values: [a, b, enc_a, enc_b].map(value_u64) };
+        MockProver::run(k, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_order_preserved() {
+        let enc = |x: u64| 2 * x + 1;
+        for (a, b) in [(2, 5), (5, 2), (7, 7), (0, 15)] {
+            prover(a, b, enc(a), enc(b)).assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_order_broken() {
+        //2 < 5 but 9 > 4, the copied result can't be squared with the encodings
+        expect_failures(&prover(2, 5, 9, 4), &[ExpectedFailure::lookup(2, "assign comparison", 0)]);
+        //swapping the encodings of a and b
+        expect_failures(&prover(4, 6, 13, 9), &[ExpectedFailure::lookup(2, "assign comparison", 0)]);
+    }
+}
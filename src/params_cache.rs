@@ -0,0 +1,204 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{pasta::EqAffine, poly::commitment::Params};
+
+use std::{
+    collections::HashMap,
+    fs,
+    io,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+//header written in front of persisted params: k, payload length then payload hash
+const HEADER_LEN: usize = 24;
+
+type Slot = Arc<Mutex<Option<Arc<Params<EqAffine>>>>>;
+
+//memoizes params per k, optionally persisting them to a directory
+#[derive(Debug, Default)]
+pub struct ParamsCache {
+    dir: Option<PathBuf>,
+    //one slot per k so callers asking for different k don't wait on each other
+    slots: Mutex<HashMap<u32, Slot>>,
+    generated: AtomicUsize,
+}
+
+impl ParamsCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_dir(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: Some(dir.into()),
+            ..Self::default()
+        }
+    }
+
+    pub fn get_or_create(&self, k: u32) -> Arc<Params<EqAffine>> {
+        let slot = self.slots.lock().unwrap().entry(k).or_default().clone();
+        //holding the per-k lock while generating keeps concurrent callers from doing it twice
+        let mut slot = slot.lock().unwrap();
+        if let Some(params) = slot.as_ref() {
+            return params.clone();
+        }
+
+        let params = match self.read(k) {
+            Some(params) => params,
+            None => {
+                let params = Params::new(k);
+                self.generated.fetch_add(1, Ordering::SeqCst);
+                //persisting is best effort, a failed write just means regenerating next time
+                let _ = self.write(k, &params);
+                params
+            }
+        };
+        let params = Arc::new(params);
+        *slot = Some(params.clone());
+        params
+    }
+
+    //how many times params had to be generated rather than found in memory or on disk
+    pub fn generated(&self) -> usize {
+        self.generated.load(Ordering::SeqCst)
+    }
+
+    pub fn path(&self, k: u32) -> Option<PathBuf> {
+        self.dir.as_ref().map(|dir| dir.join(format!("params_k{}.bin", k)))
+    }
+
+    //returns None for missing files and for files failing the size or hash check
+    fn read(&self, k: u32) -> Option<Params<EqAffine>> {
+        let bytes = fs::read(self.path(k)?).ok()?;
+        if bytes.len() < HEADER_LEN {
+            return None;
+        }
+        let (header, payload) = bytes.split_at(HEADER_LEN);
+        let field = |i: usize| u64::from_le_bytes(header[8 * i..8 * (i + 1)].try_into().unwrap());
+        //a file copied or renamed from another k is as stale as a corrupted one
+        if field(0) != k as u64 || field(1) != payload.len() as u64 || field(2) != digest(payload) {
+            return None;
+        }
+        Params::read(&mut &payload[..]).ok()
+    }
+
+    fn write(&self, k: u32, params: &Params<EqAffine>) -> io::Result<()> {
+        let path = match self.path(k) {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let mut payload = vec![];
+        params.write(&mut payload)?;
+
+        let mut bytes = Vec::with_capacity(HEADER_LEN + payload.len());
+        bytes.extend_from_slice(&(k as u64).to_le_bytes());
+        bytes.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&digest(&payload).to_le_bytes());
+        bytes.extend_from_slice(&payload);
+
+        fs::create_dir_all(path.parent().unwrap())?;
+        //write to a temp file first so a crash never leaves a half written cache entry
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, bytes)?;
+        fs::rename(tmp, path)
+    }
+}
+
+//64 bit fnv-1a. the files outlive the process, so unlike DefaultHasher the hash can't change
+//between toolchains
+fn digest(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("halo2-circuits-params-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_cache_hit() {
+        let dir = temp_dir("hit");
+        let cache = ParamsCache::with_dir(&dir);
+        let a = cache.get_or_create(4);
+        let b = cache.get_or_create(4);
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(cache.generated(), 1);
+
+        //a fresh cache pointed at the same directory loads from disk
+        let cache = ParamsCache::with_dir(&dir);
+        cache.get_or_create(4);
+        assert_eq!(cache.generated(), 0);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_corrupted_file_regenerated() {
+        let dir = temp_dir("corrupt");
+        let cache = ParamsCache::with_dir(&dir);
+        let mut expected = vec![];
+        cache.get_or_create(4).write(&mut expected).unwrap();
+
+        let path = cache.path(4).unwrap();
+        let mut bytes = fs::read(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        fs::write(&path, bytes).unwrap();
+
+        let cache = ParamsCache::with_dir(&dir);
+        let mut actual = vec![];
+        cache.get_or_create(4).write(&mut actual).unwrap();
+        assert_eq!(cache.generated(), 1);
+        assert_eq!(actual.len(), expected.len());
+
+        //and the regenerated params were written back
+        let cache = ParamsCache::with_dir(&dir);
+        cache.get_or_create(4);
+        assert_eq!(cache.generated(), 0);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_digest_stable() {
+        //the published fnv-1a test vectors
+        assert_eq!(digest(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(digest(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(digest(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn test_file_for_other_k_regenerated() {
+        let dir = temp_dir("other-k");
+        let cache = ParamsCache::with_dir(&dir);
+        cache.get_or_create(4);
+        fs::copy(cache.path(4).unwrap(), cache.path(5).unwrap()).unwrap();
+
+        let cache = ParamsCache::with_dir(&dir);
+        cache.get_or_create(5);
+        assert_eq!(cache.generated(), 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_concurrent_access() {
+        let cache = Arc::new(ParamsCache::new());
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let cache = cache.clone();
+                thread::spawn(move || cache.get_or_create(4))
+            })
+            .collect();
+        let params: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+        assert_eq!(cache.generated(), 1);
+        assert!(params.iter().all(|p| Arc::ptr_eq(p, &params[0])));
+    }
+}
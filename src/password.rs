@@ -0,0 +1,311 @@
+#![allow(warnings, unused)]
+use halo2_gadgets::poseidon::{
+    primitives::{self as poseidon, ConstantLength, P128Pow5T3},
+    Hash, Pow5Chip, Pow5Config,
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    pasta::Fp,
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Constraints, Error, Expression, Instance, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use crate::{field::lower_128, proving::Instances, report, rows::{self, RowBudget, TablePadding}, running_sum::RunningSumConfig};
+
+pub const MAX_PASSWORD_LEN: usize = 64;
+pub const MIN_PASSWORD_LEN: u64 = 12;
+//k the byte class table and the hash fit in
+pub const PASSWORD_K: u32 = 9;
+
+//the bytes are hashed as big endian 16 byte chunks
+const CHUNK_LEN: usize = 16;
+const CHUNKS: usize = MAX_PASSWORD_LEN / CHUNK_LEN;
+
+//class tags. 0 is the padding byte, which doesn't count towards the length
+pub const CLASS_PADDING: u64 = 0;
+pub const CLASS_DIGIT: u64 = 1;
+pub const CLASS_UPPER: u64 = 2;
+pub const CLASS_LOWER: u64 = 3;
+pub const CLASS_OTHER: u64 = 4;
+const CLASSES: usize = 4;
+
+//at least this many bytes of each class, and MIN_PASSWORD_LEN non-padding bytes overall
+const MIN_COUNTS: [u64; CLASSES] = [1, 1, 1, MIN_PASSWORD_LEN];
+
+pub fn byte_class(byte: u8) -> u64 {
+    match byte {
+        0 => CLASS_PADDING,
+        b'0'..=b'9' => CLASS_DIGIT,
+        b'A'..=b'Z' => CLASS_UPPER,
+        b'a'..=b'z' => CLASS_LOWER,
+        _ => CLASS_OTHER,
+    }
+}
+
+//the bytes zero padded to MAX_PASSWORD_LEN, None if the password is too long or has a 0
+//byte that would read as padding
+pub fn padded_bytes(password: &[u8]) -> Option<[u8; MAX_PASSWORD_LEN]> {
+    if password.len() > MAX_PASSWORD_LEN || password.contains(&0) {
+        return None;
+    }
+    let mut bytes = [0; MAX_PASSWORD_LEN];
+    bytes[..password.len()].copy_from_slice(password);
+    Some(bytes)
+}
+
+//the public hash of a password, poseidon over its padded bytes in CHUNKS chunks
+pub fn hash(password: &[u8]) -> Option<Fp> {
+    let bytes = padded_bytes(password)?;
+    let chunks = [0, 1, 2, 3].map(|i| Fp::from_u128(u128::from_be_bytes(bytes[i * CHUNK_LEN..(i + 1) * CHUNK_LEN].try_into().unwrap())));
+    Some(poseidon::Hash::<_, P128Pow5T3, ConstantLength<CHUNKS>, 3, 2>::init().hash(chunks))
+}
+
+//maps every byte to its class tag
+#[derive(Clone, Debug)]
+pub struct ByteClassTable {
+    pub byte: TableColumn,
+    pub class: TableColumn,
+    //what load_padded puts in the rows past the 256 pairs
+    pub padding: TablePadding,
+}
+
+impl ByteClassTable {
+    pub fn configure(cs: &mut ConstraintSystem<Fp>) -> Self {
+        Self::configure_padded(cs, TablePadding::Unpadded)
+    }
+
+    pub fn configure_padded(cs: &mut ConstraintSystem<Fp>, padding: TablePadding) -> Self {
+        Self { byte: cs.lookup_table_column(), class: cs.lookup_table_column(), padding: padding.for_rows(256) }
+    }
+
+    fn load_rows(&self, layouter: &mut impl Layouter<Fp>, budget: Option<RowBudget>) -> Result<(), Error> {
+        let rows: Vec<_> = (0..=255u8).map(|byte| vec![Fp::from(byte as u64), Fp::from(byte_class(byte))]).collect();
+        rows::load_table(layouter, "byte class table", &[("byte", self.byte), ("class", self.class)], &rows, self.padding, budget).map_err(Error::from)
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<Fp>) -> Result<(), Error> {
+        self.load_rows(layouter, None)
+    }
+
+    //load that first checks the table fits in the budget, then fills the rest of its usable
+    //rows as the padding says
+    pub fn load_padded(&self, layouter: &mut impl Layouter<Fp>, budget: RowBudget) -> Result<(), Error> {
+        self.load_rows(layouter, Some(budget))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PasswordConfig {
+    //one-hot flags for digit, upper, lower and other, all 0 on padding
+    pub flags: [Column<Advice>; CLASSES],
+    //running counts of the digit, upper and lower flags, and of a non-padding flag, the sum of
+    //all four, for the length
+    pub counts: [RunningSumConfig<Fp>; CLASSES],
+    //the bytes of each chunk read as a big endian number
+    pub chunk: RunningSumConfig<Fp>,
+    pub class: Column<Advice>,
+    pub instance: Column<Instance>,
+    pub q_byte: Selector,
+    pub q_policy: Selector,
+    pub table: ByteClassTable,
+    pub poseidon: Pow5Config<Fp, 3, 2>,
+}
+
+//proves a private password of at most MAX_PASSWORD_LEN bytes has a digit, an uppercase and a
+//lowercase letter and at least MIN_PASSWORD_LEN bytes, and hashes to the public value, the
+//only instance value
+#[derive(Clone, Debug)]
+pub struct PasswordCircuit {
+    pub bytes: [Value<Fp>; MAX_PASSWORD_LEN],
+}
+
+impl Default for PasswordCircuit {
+    fn default() -> Self {
+        Self { bytes: [Value::unknown(); MAX_PASSWORD_LEN] }
+    }
+}
+
+impl PasswordCircuit {
+    pub fn from_password(password: &[u8]) -> Option<Self> {
+        padded_bytes(password).map(|bytes| Self { bytes: bytes.map(|byte| Value::known(Fp::from(byte as u64))) })
+    }
+
+    pub fn public_inputs(hash: Fp) -> Instances {
+        vec![vec![hash]]
+    }
+}
+
+impl Circuit<Fp> for PasswordCircuit {
+    type Config = PasswordConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let byte = cs.advice_column();
+        let class = cs.advice_column();
+        let flags = [(); CLASSES].map(|_| cs.advice_column());
+        let nonpadding = cs.advice_column();
+        let terms = [flags[0], flags[1], flags[2], nonpadding];
+        let counts = terms.map(|term| {
+            let count = cs.advice_column();
+            RunningSumConfig::configure(cs, term, count, 1)
+        });
+        let chunk_acc = cs.advice_column();
+        let chunk = RunningSumConfig::configure(cs, byte, chunk_acc, 256);
+        let instance = cs.instance_column();
+        let q_byte = cs.complex_selector();
+        let q_policy = cs.complex_selector();
+        let table = ByteClassTable::configure(cs);
+
+        cs.enable_equality(instance);
+
+        cs.create_gate("password class", |cs| {
+            let q = cs.query_selector(q_byte);
+            let class = cs.query_advice(class, Rotation::cur());
+            let flags = flags.map(|flag| cs.query_advice(flag, Rotation::cur()));
+            let one = Expression::Constant(Fp::one());
+            let boolean = |e: Expression<Fp>| e.clone() * (one.clone() - e);
+            let mut constraints: Vec<_> = flags.iter().map(|flag| ("flag boolean", boolean(flag.clone()))).collect();
+            let any = flags.iter().fold(Expression::Constant(Fp::zero()), |sum, flag| sum + flag.clone());
+            constraints.push(("at most one flag", boolean(any.clone())));
+            constraints.push(("nonpadding = flag sum", cs.query_advice(nonpadding, Rotation::cur()) - any));
+            let tagged = flags.iter().zip(1..).fold(Expression::Constant(Fp::zero()), |sum, (flag, tag)| sum + flag.clone() * Fp::from(tag));
+            constraints.push(("class = flagged tag", class - tagged));
+            Constraints::with_selector(q, constraints)
+        });
+        report::annotate_gate("password", "password class");
+
+        let lookup_index = cs.lookup(|cs| {
+            let q = cs.query_selector(q_byte);
+            let byte = cs.query_advice(byte, Rotation::cur());
+            let class = cs.query_advice(class, Rotation::cur());
+            vec![(q.clone() * byte, table.byte), (q * class, table.class)]
+        });
+        report::annotate_lookup("password", lookup_index);
+
+        //count - minimum in 0..256 on the last row. the counts are at most 64, a count short
+        //of its minimum wraps around and misses the byte column
+        for (i, min) in MIN_COUNTS.iter().enumerate() {
+            let lookup_index = cs.lookup(|cs| {
+                let q = cs.query_selector(q_policy);
+                let count = cs.query_advice(counts[i].acc, Rotation::cur());
+                vec![(q * (count - Expression::Constant(Fp::from(*min))), table.byte)]
+            });
+            report::annotate_lookup("password", lookup_index);
+        }
+
+        let state = [cs.advice_column(), cs.advice_column(), cs.advice_column()];
+        let partial_sbox = cs.advice_column();
+        let rc_a = [cs.fixed_column(), cs.fixed_column(), cs.fixed_column()];
+        let rc_b = [cs.fixed_column(), cs.fixed_column(), cs.fixed_column()];
+        cs.enable_constant(rc_b[0]);
+        let poseidon = Pow5Chip::configure::<P128Pow5T3>(cs, state, partial_sbox, rc_a, rc_b);
+
+        PasswordConfig { flags, counts, chunk, class, instance, q_byte, q_policy, table, poseidon }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        config.table.load(&mut layouter)?;
+
+        let chunks = layouter.assign_region(
+            || "assign password",
+            |mut region| {
+                let mut terms = vec![vec![]; CLASSES];
+                for (offset, byte) in self.bytes.iter().enumerate() {
+                    config.q_byte.enable(&mut region, offset)?;
+                    if offset == MAX_PASSWORD_LEN - 1 {
+                        config.q_policy.enable(&mut region, offset)?;
+                    }
+
+                    let class = byte.map(|byte| byte_class(lower_128(&byte) as u8));
+                    region.assign_advice(|| "class", config.class, offset, || class.map(Fp::from))?;
+                    let flags = [CLASS_DIGIT, CLASS_UPPER, CLASS_LOWER, CLASS_OTHER].map(|tag| class.map(|class| Fp::from((class == tag) as u64)));
+                    region.assign_advice(|| "flag", config.flags[CLASSES - 1], offset, || flags[CLASSES - 1])?;
+                    let nonpadding = flags.iter().fold(Value::known(Fp::zero()), |sum, flag| sum + *flag);
+                    for (i, term) in [flags[0], flags[1], flags[2], nonpadding].into_iter().enumerate() {
+                        terms[i].push(term);
+                    }
+                }
+                for (count, terms) in config.counts.iter().zip(&terms) {
+                    count.assign_rows(&mut region, 0, terms, None)?;
+                }
+
+                //the byte cells are the terms of the chunk sums
+                self.bytes.chunks(CHUNK_LEN).enumerate().map(|(i, bytes)| {
+                    let rows = config.chunk.assign_rows(&mut region, i * CHUNK_LEN, bytes, None)?;
+                    Ok(rows[CHUNK_LEN - 1].1.clone())
+                }).collect::<Result<Vec<_>, Error>>()
+            },
+        )?;
+
+        let chunks: [AssignedCell<Fp, Fp>; CHUNKS] = chunks.try_into().unwrap();
+        let hasher = Hash::<_, _, P128Pow5T3, ConstantLength<CHUNKS>, 3, 2>::init(
+            Pow5Chip::construct(config.poseidon),
+            layouter.namespace(|| "init hash"),
+        )?;
+        let hash = hasher.hash(layouter.namespace(|| "hash"), chunks)?;
+        layouter.constrain_instance(hash.cell(), config.instance, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::dev::MockProver;
+
+    use super::*;
+
+    fn verify(password: &[u8], hash: Fp) -> bool {
+        let circuit = PasswordCircuit::from_password(password).unwrap();
+        let prover = MockProver::run(PASSWORD_K, &circuit, PasswordCircuit::public_inputs(hash)).unwrap();
+        prover.verify().is_ok()
+    }
+
+    fn verify_own_hash(password: &[u8]) -> bool {
+        verify(password, hash(password).unwrap())
+    }
+
+    #[test]
+    fn test_padded_bytes() {
+        assert!(padded_bytes(&[b'a'; 64]).is_some());
+        assert_eq!(padded_bytes(&[b'a'; 65]), None);
+        assert_eq!(padded_bytes(b"abc\0def"), None);
+        assert_eq!(byte_class(b'7'), CLASS_DIGIT);
+        assert_eq!(byte_class(b'!'), CLASS_OTHER);
+    }
+
+    #[test]
+    fn test_compliant() {
+        assert!(verify_own_hash(b"correctHorse9battery"));
+        assert!(verify_own_hash(b"Tr0ub4dor&3-and-more-symbols!"));
+        //the longest password
+        let mut longest = [b'x'; 64];
+        longest[..3].copy_from_slice(b"A1b");
+        assert!(verify_own_hash(&longest));
+    }
+
+    #[test]
+    fn test_non_compliant() {
+        //no digit, no uppercase, no lowercase
+        assert!(!verify_own_hash(b"correctHorsebattery"));
+        assert!(!verify_own_hash(b"correcthorse9battery"));
+        assert!(!verify_own_hash(b"CORRECTHORSE9BATTERY"));
+        //symbols count towards the length but not as any of the three
+        assert!(!verify_own_hash(b"!@#$%^&*()_+"));
+    }
+
+    #[test]
+    fn test_minimum_length() {
+        assert!(verify_own_hash(b"Abcdefghij12"));
+        assert!(!verify_own_hash(b"Abcdefghij1"));
+    }
+
+    #[test]
+    fn test_hash_mismatch() {
+        //a compliant password against the hash of a different one
+        assert!(!verify(b"correctHorse9battery", hash(b"correctHorse9batterz").unwrap()));
+    }
+}
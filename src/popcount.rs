@@ -0,0 +1,208 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use std::marker::PhantomData;
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, field::lower_128, report, running_sum::RunningSumConfig, rows::{self, RowBudget, TablePadding}};
+
+//the pairs (byte, popcount(byte)). byte 0 has popcount 0, so rows off the selector looking
+//up (0, 0) hit a real row
+#[derive(Clone, Debug)]
+pub struct PopcountTableConfig<F: FieldExt> {
+    pub byte: TableColumn,
+    pub count: TableColumn,
+    //what load_padded puts in the rows past the 256 pairs
+    pub padding: TablePadding,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> PopcountTableConfig<F> {
+    pub fn configure(cs: &mut ConstraintSystem<F>) -> Self {
+        Self::configure_padded(cs, TablePadding::Unpadded)
+    }
+
+    pub fn configure_padded(cs: &mut ConstraintSystem<F>, padding: TablePadding) -> Self {
+        Self {
+            byte: cs.lookup_table_column(),
+            count: cs.lookup_table_column(),
+            padding: padding.for_rows(256),
+            _marker: PhantomData,
+        }
+    }
+
+    fn rows() -> Vec<Vec<F>> {
+        (0..=255u8).map(|byte| vec![F::from(byte as u64), F::from(byte.count_ones() as u64)]).collect()
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), CircuitsError> {
+        rows::load_table(layouter, "popcount table", &[("byte", self.byte), ("count", self.count)], &Self::rows(), self.padding, None)
+    }
+
+    //load that first checks the table fits in the budget, then fills the rest of its usable
+    //rows as the padding says
+    pub fn load_padded(&self, layouter: &mut impl Layouter<F>, budget: RowBudget) -> Result<(), CircuitsError> {
+        rows::load_table(layouter, "popcount table", &[("byte", self.byte), ("count", self.count)], &Self::rows(), self.padding, Some(budget))
+    }
+}
+
+//counts the set bits of a word given as byte cells. each byte's count is looked up next to
+//it and the counts are the terms of a running sum, the last row holding the total. the
+//lookup also range checks the bytes, so the caller doesn't have to
+#[derive(Clone, Debug)]
+pub struct PopcountConfig<F: FieldExt> {
+    pub byte: Column<Advice>,
+    pub sum: RunningSumConfig<F>,
+    pub q_lookup: Selector,
+    pub table: PopcountTableConfig<F>,
+}
+
+impl<F: FieldExt> PopcountConfig<F> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, byte: Column<Advice>, count: Column<Advice>, sum: Column<Advice>, table: PopcountTableConfig<F>) -> Self {
+        let q_lookup = cs.complex_selector();
+        let sum = RunningSumConfig::configure(cs, count, sum, 1);
+
+        cs.enable_equality(byte);
+
+        let lookup_index = cs.lookup(|cs| {
+            let q = cs.query_selector(q_lookup);
+            let byte = cs.query_advice(byte, Rotation::cur());
+            let count = cs.query_advice(count, Rotation::cur());
+            vec![(q.clone() * byte, table.byte), (q * count, table.count)]
+        });
+        report::annotate_lookup("popcount", lookup_index);
+
+        Self { byte, sum, q_lookup, table }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 3);
+        let table = PopcountTableConfig::configure(cs);
+        Self::configure(cs, columns[0], columns[1], columns[2], table)
+    }
+
+    //returns the cell holding the total count of set bits of the bytes, 4 of them for a 32 bit
+    //word and 8 for a 64 bit one. the byte order doesn't matter
+    pub fn popcount_word(&self, layouter: impl Layouter<F>, bytes: &[AssignedCell<F, F>]) -> Result<AssignedCell<F, F>, CircuitsError> {
+        let counts = bytes.iter().map(|byte| byte.value().map(|byte| F::from(lower_128(byte).count_ones() as u64))).collect();
+        self.assign_with_counts(layouter, bytes, counts)
+    }
+
+    fn assign_with_counts(&self, mut layouter: impl Layouter<F>, bytes: &[AssignedCell<F, F>], counts: Vec<Value<F>>) -> Result<AssignedCell<F, F>, CircuitsError> {
+        if bytes.is_empty() {
+            return Err(CircuitsError::LengthMismatch { expected: 1, got: 0 });
+        }
+        if counts.len() != bytes.len() {
+            return Err(CircuitsError::LengthMismatch { expected: bytes.len(), got: counts.len() });
+        }
+        layouter.assign_region(
+            || "assign popcount",
+            |mut region| {
+                for (offset, byte) in bytes.iter().enumerate() {
+                    self.q_lookup.enable(&mut region, offset)?;
+                    byte.copy_advice(|| format!("byte {}", offset), &mut region, self.byte, offset)?;
+                }
+                let rows = self.sum.assign_rows(&mut region, 0, &counts, None)?;
+                Ok(rows[bytes.len() - 1].1.clone())
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, known, ExpectedFailure, Forged};
+
+    #[derive(Default)]
+    struct PopcountCircuit {
+        //the little endian bytes of the word
+        bytes: Vec<Value<Fp>>,
+        //witnessed as the per-byte counts instead of the real ones when forged
+        counts: Forged<Fp>,
+        expected: Option<u64>,
+    }
+
+    impl Circuit<Fp> for PopcountCircuit {
+        type Config = PopcountConfig<Fp>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { bytes: vec![Value::unknown(); self.bytes.len()], counts: self.counts.without_witnesses(), expected: None }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(3);
+            PopcountConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            let bytes = layouter.assign_region(
+                || "assign bytes",
+                |mut region| {
+                    self.bytes.iter().enumerate().map(|(offset, byte)| {
+                        region.assign_advice(|| "byte", config.byte, offset, || *byte)
+                    }).collect::<Result<Vec<_>, Error>>()
+                },
+            )?;
+            let total = match self.counts.values() {
+                Some(counts) => config.assign_with_counts(layouter.namespace(|| "popcount"), &bytes, counts.to_vec())?,
+                None => config.popcount_word(layouter.namespace(|| "popcount"), &bytes)?,
+            };
+            if let Some(expected) = self.expected {
+                total.value().assert_if_known(|total| **total == Fp::from(expected));
+            }
+            Ok(())
+        }
+    }
+
+    fn prover(bytes: Vec<u8>, expected: u64, counts: Option<Vec<u64>>) -> MockProver<Fp> {
+        let k = 9;
+        let circuit = PopcountCircuit {
+            bytes: bytes.iter().map(|byte| known(*byte as u64)).collect(),
+            expected: counts.is_none().then(|| expected),
+            counts: counts.map_or_else(Forged::honest, |counts| Forged::from_u64s(&counts)),
+        };
+        MockProver::run(k, &circuit, vec![]).unwrap()
+    }
+
+    fn prover_u64(word: u64) -> MockProver<Fp> {
+        prover(word.to_le_bytes().to_vec(), word.count_ones() as u64, None)
+    }
+
+    fn prover_u32(word: u32) -> MockProver<Fp> {
+        prover(word.to_le_bytes().to_vec(), word.count_ones() as u64, None)
+    }
+
+    #[test]
+    fn test_popcount() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..8 {
+            prover_u64(rng.gen()).assert_satisfied();
+            prover_u32(rng.gen()).assert_satisfied();
+        }
+        prover_u64(0).assert_satisfied();
+        prover_u64(u64::MAX).assert_satisfied();
+        prover_u32(0).assert_satisfied();
+        prover_u32(u32::MAX).assert_satisfied();
+    }
+
+    #[test]
+    fn test_forged_counts() {
+        let word = 0xf00f_0001u32;
+        //a count moved from one byte to another keeps the total but not the pairs
+        let failures = [ExpectedFailure::lookup(0, "assign popcount", 0), ExpectedFailure::lookup(0, "assign popcount", 1)];
+        expect_failures(&prover(word.to_le_bytes().to_vec(), 9, Some(vec![0, 2, 4, 4])), &failures);
+        //one byte undercounted
+        let failure = ExpectedFailure::lookup(0, "assign popcount", 3);
+        expect_failures(&prover(word.to_le_bytes().to_vec(), 8, Some(vec![1, 0, 4, 3])), &[failure]);
+    }
+}
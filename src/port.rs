@@ -0,0 +1,121 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::{Field, FieldExt},
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, le_bytes::LeBytesConfig, range_lookup::RangeTableConfig, report};
+
+//checks a TCP/UDP port, value in 1..65536.
+//
+//2^16 rows is more than a table should take, so the upper bound is the two byte
+//decomposition of LeBytesConfig of the port cell. the port is copied into one more row with
+//its inverse, which rules out the 0 like NonZeroRangeConfig
+#[derive(Clone, Debug)]
+pub struct PortConfig<F: FieldExt> {
+    pub bytes: LeBytesConfig<F, 2>,
+    pub port: Column<Advice>,
+    pub inv: Column<Advice>,
+    pub q_port: Selector,
+}
+
+impl<F: FieldExt> PortConfig<F> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, bytes: LeBytesConfig<F, 2>, port: Column<Advice>, inv: Column<Advice>) -> Self {
+        let q_port = cs.selector();
+
+        cs.enable_equality(port);
+
+        cs.create_gate("port", |cs| {
+            let q = cs.query_selector(q_port);
+            let port = cs.query_advice(port, Rotation::cur());
+            let inv = cs.query_advice(inv, Rotation::cur());
+            Constraints::with_selector(q, [("port * inv = 1", port * inv - Expression::Constant(F::one()))])
+        });
+        report::annotate_gate("port", "port");
+
+        Self { bytes, port, inv, q_port }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let bytes = LeBytesConfig::configure_with_pool(cs, pool);
+        let columns = pool.advice_many(cs, 2);
+        Self::configure(cs, bytes, columns[0], columns[1])
+    }
+
+    pub fn table(&self) -> &RangeTableConfig<F, 256> {
+        &self.bytes.table
+    }
+
+    //for a port cell assigned elsewhere
+    pub fn assign(&self, mut layouter: impl Layouter<F>, port: &AssignedCell<F, F>) -> Result<(), CircuitsError> {
+        self.bytes.to_le_bytes(layouter.namespace(|| "port bytes"), port)?;
+        let offset = 0;
+        layouter.assign_region(
+            || "assign port",
+            |mut region| {
+                self.q_port.enable(&mut region, offset)?;
+                port.copy_advice(|| "port", &mut region, self.port, offset)?;
+                //0 gets 0, the gate fails on it whatever inv is
+                region.assign_advice(|| "inv", self.inv, offset, || port.value().map(|v| v.invert().unwrap_or(F::zero())))?;
+                Ok(())
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, ExpectedFailure};
+
+    #[derive(Default)]
+    struct PortCircuit {
+        port: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for PortCircuit {
+        type Config = PortConfig<Fp>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(4);
+            PortConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table().load(&mut layouter)?;
+            let port = layouter.assign_region(|| "assign port cell", |mut region| region.assign_advice(|| "port", config.port, 0, || self.port))?;
+            config.assign(layouter.namespace(|| "port"), &port)?;
+            Ok(())
+        }
+    }
+
+    fn prover(port: u64) -> MockProver<Fp> {
+        //the byte table takes 256 rows
+        let k = 9;
+        MockProver::run(k, &PortCircuit { port: Value::known(Fp::from(port)) }, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_port() {
+        for port in [1, 80, 443, 0x1234, 65535] {
+            prover(port).assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_port_bounds() {
+        //0 decomposes fine, only the inverse catches it
+        expect_failures(&prover(0), &[ExpectedFailure::constraint("port", "port * inv = 1", "assign port", 0)]);
+        //the two honest bytes of 65536 are 0 and don't add up to the port
+        assert!(prover(65536).verify().is_err());
+    }
+}
@@ -0,0 +1,165 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Assigned, Column, ConstraintSystem, Error, Expression, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use std::marker::PhantomData;
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, rows::{self, RowBudget, TablePadding}};
+
+//sieve of eratosthenes over 0..n. this runs natively every time the table is loaded, so
+//synthesis pays O(n log log n) time and O(n) memory on top of assigning the table rows
+pub fn primes_below(n: usize) -> Vec<u64> {
+    let mut is_prime = vec![true; n];
+    let mut primes = vec![];
+    for i in 2..n {
+        if is_prime[i] {
+            primes.push(i as u64);
+            let mut j = i * i;
+            while j < n {
+                is_prime[j] = false;
+                j += i;
+            }
+        }
+    }
+    primes
+}
+
+//lookup table holding every prime below N
+#[derive(Clone, Debug)]
+pub struct PrimeTableConfig<F: FieldExt, const N: usize> {
+    pub value: TableColumn,
+    //what load_padded puts in the rows past the primes
+    pub padding: TablePadding,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const N: usize> PrimeTableConfig<F, N> {
+    pub fn configure(cs: &mut ConstraintSystem<F>) -> Self {
+        Self::configure_padded(cs, TablePadding::Unpadded)
+    }
+
+    //a filler is the index of a prime, not the prime
+    pub fn configure_padded(cs: &mut ConstraintSystem<F>, padding: TablePadding) -> Self {
+        assert!(N > 2, "there are no primes below {}", N);
+        Self {
+            value: cs.lookup_table_column(),
+            padding: padding.for_rows(primes_below(N).len()),
+            _marker: PhantomData,
+        }
+    }
+
+    fn rows() -> Vec<Vec<F>> {
+        primes_below(N).into_iter().map(|p| vec![F::from(p)]).collect()
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), CircuitsError> {
+        rows::load_table(layouter, "prime table", &[("assign prime table value", self.value)], &Self::rows(), self.padding, None)
+    }
+
+    //load that first checks the table fits in the budget, then fills the rest of its usable
+    //rows as the padding says
+    pub fn load_padded(&self, layouter: &mut impl Layouter<F>, budget: RowBudget) -> Result<(), CircuitsError> {
+        rows::load_table(layouter, "prime table", &[("assign prime table value", self.value)], &Self::rows(), self.padding, Some(budget))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PrimeCheckConfig<F: FieldExt, const N: usize> {
+    pub value: Column<Advice>,
+    pub q_enable: Selector,
+    pub table: PrimeTableConfig<F, N>,
+}
+
+impl<F: FieldExt, const N: usize> PrimeCheckConfig<F, N> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, value: Column<Advice>) -> Self {
+        let q_enable = cs.complex_selector();
+        let table = PrimeTableConfig::configure(cs);
+        cs.lookup(|cs| {
+            let q = cs.query_selector(q_enable);
+            let v = cs.query_advice(value, Rotation::cur());
+            //0 isn't prime so disabled rows look up 2 instead
+            let not_q = Expression::Constant(F::one()) - q.clone();
+            vec![(q * v + not_q * Expression::Constant(F::from(2)), table.value)]
+        });
+
+        Self { value, q_enable, table }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let value = pool.advice(cs);
+        Self::configure(cs, value)
+    }
+
+    pub fn assign(&self, mut layouter: impl Layouter<F>, val: Value<Assigned<F>>) -> Result<AssignedCell<Assigned<F>, F>, CircuitsError> {
+        let offset = 0;
+        layouter.assign_region(
+            || "assign prime",
+            |mut region| {
+                self.q_enable.enable(&mut region, offset)?;
+                region.assign_advice(|| "prime", self.value, offset, || val)
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::value_u64;
+
+    #[derive(Default)]
+    struct PrimeCircuit<F: FieldExt, const N: usize> {
+        value: Value<Assigned<F>>,
+    }
+
+    impl<F: FieldExt, const N: usize> Circuit<F> for PrimeCircuit<F, N> {
+        type Config = PrimeCheckConfig<F, N>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let value = cs.advice_column();
+            PrimeCheckConfig::configure(cs, value)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            config.assign(layouter.namespace(|| "prime"), self.value)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_primes_below() {
+        assert_eq!(primes_below(20), vec![2, 3, 5, 7, 11, 13, 17, 19]);
+    }
+
+    #[test]
+    fn test_complete() {
+        let k = 4;
+        let circuit = PrimeCircuit::<Fp, 20> {
+            value: value_u64(7),
+        };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_sound() {
+        let k = 4;
+        let circuit = PrimeCircuit::<Fp, 20> {
+            value: value_u64(8),
+        };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
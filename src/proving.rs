@@ -0,0 +1,125 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    pasta::{EqAffine, Fp},
+    plonk::{self, keygen_pk, keygen_vk, BatchVerifier, Circuit, Error, ProvingKey, SingleVerifier, VerifyingKey},
+    poly::commitment::Params,
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+};
+use rand_core::OsRng;
+
+use std::sync::Arc;
+
+use crate::params_cache::ParamsCache;
+
+//serialized proof bytes as produced by the transcript
+pub type Proof = Vec<u8>;
+//one vec of values per instance column
+pub type Instances = Vec<Vec<Fp>>;
+
+#[derive(Debug)]
+pub enum VerifyError {
+    //the proof at index was rejected
+    Rejected { index: usize, error: Error },
+}
+
+//params come from the cache so repeated setups at the same k only pay for keygen
+pub fn keygen<C: Circuit<Fp>>(
+    cache: &ParamsCache,
+    k: u32,
+    circuit: &C,
+) -> Result<(Arc<Params<EqAffine>>, ProvingKey<EqAffine>), Error> {
+    let params = cache.get_or_create(k);
+    let vk = keygen_vk(&params, circuit)?;
+    let pk = keygen_pk(&params, vk, circuit)?;
+    Ok((params, pk))
+}
+
+pub fn create_proof<C: Circuit<Fp>>(
+    params: &Params<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    circuit: C,
+    instances: &Instances,
+) -> Result<Proof, Error> {
+    let instances: Vec<&[Fp]> = instances.iter().map(|col| &col[..]).collect();
+    let mut transcript = Blake2bWrite::<_, EqAffine, Challenge255<_>>::init(vec![]);
+    plonk::create_proof(params, pk, &[circuit], &[&instances[..]], OsRng, &mut transcript)?;
+    Ok(transcript.finalize())
+}
+
+pub fn verify_proof(
+    params: &Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    proof: &Proof,
+    instances: &Instances,
+) -> Result<(), Error> {
+    let instances: Vec<&[Fp]> = instances.iter().map(|col| &col[..]).collect();
+    let strategy = SingleVerifier::new(params);
+    let mut transcript = Blake2bRead::<_, EqAffine, Challenge255<_>>::init(&proof[..]);
+    plonk::verify_proof(params, vk, strategy, &[&instances[..]], &mut transcript)
+}
+
+//verifies all proofs with one batched msm. the batch only says yes or no, so when it's
+//rejected we fall back to checking the proofs one by one to find the first bad index
+pub fn verify_batch(
+    params: &Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    proofs: &[(Proof, Instances)],
+) -> Result<(), VerifyError> {
+    let mut batch = BatchVerifier::new();
+    for (proof, instances) in proofs {
+        batch.add_proof(vec![instances.clone()], proof.clone());
+    }
+    if batch.finalize(params, vk) {
+        return Ok(());
+    }
+
+    for (index, (proof, instances)) in proofs.iter().enumerate() {
+        verify_proof(params, vk, proof, instances)
+            .map_err(|error| VerifyError::Rejected { index, error })?;
+    }
+    //every proof passed on its own, which only happens if the batch randomness was unlucky
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::range_lookup::RangeCheckLookupCircuit;
+
+    const RANGE: usize = 8;
+
+    fn setup(k: u32) -> (Arc<Params<EqAffine>>, ProvingKey<EqAffine>) {
+        let circuit = RangeCheckLookupCircuit::<Fp, RANGE>::from_values(&[0]).without_witnesses();
+        keygen(&ParamsCache::new(), k, &circuit).unwrap()
+    }
+
+    fn proofs(params: &Params<EqAffine>, pk: &ProvingKey<EqAffine>, n: usize) -> Vec<(Proof, Instances)> {
+        (0..n)
+            .map(|i| {
+                let circuit = RangeCheckLookupCircuit::<Fp, RANGE>::from_values(&[(i % RANGE) as u64]);
+                (create_proof(params, pk, circuit, &vec![]).unwrap(), vec![])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let (params, pk) = setup(4);
+        let proofs = proofs(&params, &pk, 10);
+        verify_batch(&params, pk.get_vk(), &proofs).unwrap();
+    }
+
+    #[test]
+    fn test_verify_batch_reports_index() {
+        let (params, pk) = setup(4);
+        let mut proofs = proofs(&params, &pk, 10);
+        let proof = &mut proofs[7].0;
+        let mid = proof.len() / 2;
+        proof[mid] ^= 1;
+
+        match verify_batch(&params, pk.get_vk(), &proofs) {
+            Err(VerifyError::Rejected { index, .. }) => assert_eq!(index, 7),
+            Ok(()) => panic!("corrupted batch was accepted"),
+        }
+    }
+}
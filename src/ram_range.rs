@@ -0,0 +1,109 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Selector},
+    poly::Rotation,
+};
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, range_lookup::RangeTableConfig, report};
+
+//an (address, value) row of a memory trace with the value looked up in 0..RANGE. the address
+//is only carried along, whatever ties addresses to each other (sorting, a permutation into
+//the execution trace) is up to the caller, which gets both cells back to wire up
+#[derive(Clone, Debug)]
+pub struct RamRangeConfig<F: FieldExt, const RANGE: usize> {
+    pub address: Column<Advice>,
+    pub value: Column<Advice>,
+    pub q_value: Selector,
+    pub table: RangeTableConfig<F, RANGE>,
+}
+
+impl<F: FieldExt, const RANGE: usize> RamRangeConfig<F, RANGE> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, address: Column<Advice>, value: Column<Advice>) -> Self {
+        let q_value = cs.complex_selector();
+        let table = RangeTableConfig::configure(cs);
+
+        cs.enable_equality(address);
+        cs.enable_equality(value);
+
+        let lookup_index = cs.lookup(|cs| {
+            let q = cs.query_selector(q_value);
+            let value = cs.query_advice(value, Rotation::cur());
+            vec![(q * value, table.value)]
+        });
+        report::annotate_lookup("ram_range", lookup_index);
+
+        Self { address, value, q_value, table }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 2);
+        Self::configure(cs, columns[0], columns[1])
+    }
+
+    //returns (address cell, value cell)
+    pub fn assign(&self, mut layouter: impl Layouter<F>, address: Value<F>, value: Value<F>) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), CircuitsError> {
+        let offset = 0;
+        layouter.assign_region(
+            || "assign ram entry",
+            |mut region| {
+                self.q_value.enable(&mut region, offset)?;
+                let address = region.assign_advice(|| "address", self.address, offset, || address)?;
+                let value = region.assign_advice(|| "value", self.value, offset, || value)?;
+                Ok((address, value))
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, ExpectedFailure};
+
+    #[derive(Default)]
+    struct RamCircuit {
+        entries: Vec<(Value<Fp>, Value<Fp>)>,
+    }
+
+    impl Circuit<Fp> for RamCircuit {
+        type Config = RamRangeConfig<Fp, 16>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { entries: vec![(Value::unknown(), Value::unknown()); self.entries.len()] }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(2);
+            RamRangeConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            for (address, value) in &self.entries {
+                config.assign(layouter.namespace(|| "ram entry"), *address, *value)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn prover(entries: &[(u64, u64)]) -> MockProver<Fp> {
+        let k = 6;
+        let entries = entries.iter().map(|&(a, v)| (Value::known(Fp::from(a)), Value::known(Fp::from(v)))).collect();
+        MockProver::run(k, &RamCircuit { entries }, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_ram_range() {
+        //addresses are unconstrained, only values are checked
+        prover(&[(0, 0), (1, 15), (1 << 40, 7)]).assert_satisfied();
+
+        for address in [0, 3, 1 << 40] {
+            expect_failures(&prover(&[(1, 2), (address, 16)]), &[ExpectedFailure::lookup(0, "assign ram entry", 0)]);
+        }
+    }
+}
@@ -3,14 +3,65 @@ use std::marker::PhantomData;
 
 use halo2_proofs::{
     arithmetic::FieldExt,
-    circuit::{Value, AssignedCell, Layouter},
-    plonk::{Advice, Assigned, Column, ConstraintSystem, Constraints, Expression, Selector, Error}, poly::Rotation,
+    circuit::{Value, AssignedCell, Chip, Layouter, SimpleFloorPlanner},
+    plonk::{Advice, Assigned, Circuit, Column, ConstraintSystem, Constraints, Expression, Selector, Error}, poly::Rotation,
 };
 
+use halo2_proofs::{dev::CircuitCost, pasta::{Eq, Fp}};
 
+use std::fmt;
 
-#[derive(Debug, Clone)]
-struct RangeConstrained<F: FieldExt>(AssignedCell<Assigned<F>, F>);
+use crate::{column_pool::ColumnPool, error::CircuitsError, report::{self, ConfigDescription}, RangeCheck, RangeConstrained};
+
+
+//check that value is in range by multiplying its differences with every value
+//one of them has to be zero if it is in the range. the expression has degree range
+pub fn range_check_expression<F: FieldExt>(range: usize, value: Expression<F>) -> Expression<F> {
+    assert!(range > 0, "range check needs at least one value in the range");
+    (1..range).fold(
+        value.clone(),
+        |expr, i|{
+        expr * (Expression::Constant(F::from(i as u64)) - value.clone())
+    })
+}
+
+//largest RANGE_SIZE the expression check accepts
+pub const MAX_RANGE_SIZE: usize = 32;
+
+//the "range" gate for range, on the rows of the selector it returns
+fn configure_gate<F: FieldExt>(cs: &mut ConstraintSystem<F>, value: Column<Advice>, range: usize) -> Selector {
+    //make selector columns
+    let q_select = cs.selector();
+    //so checked cells can be copied in from other gadgets
+    cs.enable_equality(value);
+    //make advice column to put the value(s) in
+    cs.create_gate(
+        "range",
+        |cs| {
+            //query the value of the selector
+            let q_select = cs.query_selector(q_select);
+
+            //query the value at the current position
+            let value = cs.query_advice(value, Rotation::cur());
+
+            //a range of one value only accepts zero, say so instead of the degree 1 product
+            if range == 1 {
+                return Constraints::with_selector(q_select, [("value is zero", value)]);
+            }
+            Constraints::with_selector(q_select, [("range check", range_check_expression(range, value))])
+        }
+    );
+    report::annotate_gate("range_check", "range");
+    q_select
+}
+
+//the gate degree grows with the range, past MAX_RANGE_SIZE the lookup gadget is the better fit
+fn check_range_size(range: usize) -> Result<(), CircuitsError>{
+    if range > MAX_RANGE_SIZE {
+        return Err(CircuitsError::RangeTooLarge { gadget: "range_check", range: range as u64, max: MAX_RANGE_SIZE as u64 });
+    }
+    Ok(())
+}
 
 #[derive(Debug, Clone)]
 pub struct RangeCheckCircuitConfig<F: FieldExt, const RANGE_SIZE: usize>{
@@ -18,7 +69,8 @@ pub struct RangeCheckCircuitConfig<F: FieldExt, const RANGE_SIZE: usize>{
     pub value: Column<Advice>,
     //selector to enable/disable some values from being checked
     pub q_enable: Selector,
-    //number of elements in range
+    //put in front of the region names when embedded in a bigger circuit
+    pub name_prefix: Option<&'static str>,
 
     _marker: PhantomData<F>,
 }
@@ -26,67 +78,280 @@ pub struct RangeCheckCircuitConfig<F: FieldExt, const RANGE_SIZE: usize>{
 
 impl <F: FieldExt, const RANGE_SIZE: usize> RangeCheckCircuitConfig<F, RANGE_SIZE>{
     pub fn configure(cs: &mut ConstraintSystem<F>, value: Column<Advice>) -> Self{
-        //make selector columns
-        let q_select = cs.selector();
-        //make advice column to put the value(s) in
-        cs.create_gate(
-            "range",
-            |cs| {
-                //query the value of the selector
-                let q_select = cs.query_selector(q_select);
-
-                //query the value at the current position
-                let value = cs.query_advice(value, Rotation::cur());
-
-                //check that value is in range by multiplying its differences with every value
-                //one of them has to be zero if it is in the range
-
-                let range_check = |range: usize, value: Expression<F>| {
-                    assert!(range > 0);
-                    (1..range).fold(
-                        value.clone(),
-                        |expr, i|{
-                        expr * (Expression::Constant(F::from(i as u64)) - value.clone())
-                    })
-                };
-                Constraints::with_selector(q_select, [("range check", range_check(RANGE_SIZE, value))])    
+        //an empty range can't be checked by anything, catch it here rather than inside the
+        //gate closure during keygen
+        assert!(RANGE_SIZE > 0, "range_check: RANGE_SIZE must be at least 1, got 0");
+        let q_select = configure_gate(cs, value, RANGE_SIZE);
+        Self { value: value, q_enable: q_select, name_prefix: None, _marker:PhantomData::<F> }
+    }
+
+    //like configure but the regions are named "name_prefix range_check<N> ..."
+    pub fn configure_named(cs: &mut ConstraintSystem<F>, value: Column<Advice>, name_prefix: &'static str) -> Self{
+        Self { name_prefix: Some(name_prefix), ..Self::configure(cs, value) }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self{
+        let value = pool.advice(cs);
+        Self::configure(cs, value)
+    }
+
+    //the assign paths run in a namespace with this name and put it in front of their
+    //default region names, so checks with different bounds can be told apart. MockProver
+    //ignores namespaces, only the region names show up in its failures
+    fn instance_name(&self) -> String{
+        match self.name_prefix {
+            Some(prefix) => format!("{} range_check<{}>", prefix, RANGE_SIZE),
+            None => format!("range_check<{}>", RANGE_SIZE),
+        }
+    }
+
+    //takes anything that converts into Assigned, e.g. Value<F> or Value<Assigned<F>>
+    pub fn assign<V: Into<Assigned<F>>>(&self, layouter: impl Layouter<F>, value: Value<V>) -> Result<RangeConstrained<F>, CircuitsError>{
+        self.assign_annotated(layouter, format!("{} assign range val", self.instance_name()), value)
+    }
+
+    //label names the region and the cell, so failures point at what the value was
+    pub fn assign_annotated<V: Into<Assigned<F>>>(&self, mut layouter: impl Layouter<F>, label: impl Into<String>, value: Value<V>) -> Result<RangeConstrained<F>, CircuitsError>{
+        let offset = 0;
+        let label = label.into();
+        let value = value.map(Into::into);
+        check_range_size(RANGE_SIZE)?;
+        let mut layouter = layouter.namespace(|| self.instance_name());
+        layouter.assign_region(
+            || label.as_str(),
+            |mut region| {
+                self.q_enable.enable(&mut region, offset)?;
+                region.assign_advice(|| label.as_str(), self.value, offset, ||value)
+                .map(RangeConstrained::Assigned)
+
             }
-        );
-        Self { value: value, q_enable: q_select, _marker:PhantomData::<F> }
+        ).map_err(CircuitsError::from)
+
     }
 
-    pub fn assign(&self, mut layouter: impl Layouter<F>, value: Value<Assigned<F>>) -> Result<RangeConstrained<F>, Error>{
+    //the same check on a plain field value, for callers that never need Assigned
+    pub fn assign_simple(&self, mut layouter: impl Layouter<F>, value: Value<F>) -> Result<RangeConstrained<F>, CircuitsError>{
         let offset = 0;
+        let name = format!("{} assign range val", self.instance_name());
+        check_range_size(RANGE_SIZE)?;
+        let mut layouter = layouter.namespace(|| self.instance_name());
         layouter.assign_region(
-            || "assign range val",
+            || name.as_str(),
             |mut region| {
                 self.q_enable.enable(&mut region, offset)?;
-                region.assign_advice(||"value", self.value, offset, ||value)
-                .map(RangeConstrained)
+                region.assign_advice(|| name.as_str(), self.value, offset, ||value)
+                .map(RangeConstrained::Simple)
+            }
+        ).map_err(CircuitsError::from)
+    }
 
+    //for values borrowed from a slice. the element is copied once, into the cell, rather than
+    //into an owned Value first
+    pub fn assign_ref(&self, layouter: impl Layouter<F>, val: Value<&F>) -> Result<RangeConstrained<F>, CircuitsError>{
+        self.assign(layouter, val.map(|v| Assigned::from(*v)))
+    }
+
+    pub fn describe(&self) -> ConfigDescription {
+        ConfigDescription::new("range_check")
+            .advice("value", self.value)
+            .selector("q_enable", self.q_enable)
+    }
+
+    pub fn check_u64(&self, layouter: impl Layouter<F>, value: u64) -> Result<RangeConstrained<F>, CircuitsError>{
+        self.assign(layouter, RangeConstrained::witness_u64(value))
+    }
+
+    //a bool is 0 or 1, so this only rejects anything when RANGE_SIZE is 1
+    pub fn check_bool(&self, layouter: impl Layouter<F>, value: bool) -> Result<RangeConstrained<F>, CircuitsError>{
+        self.assign(layouter, RangeConstrained::witness_bool(value))
+    }
+}
+
+impl<F: FieldExt, const RANGE_SIZE: usize> fmt::Display for RangeCheckCircuitConfig<F, RANGE_SIZE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.describe())
+    }
+}
+
+impl<F: FieldExt, const RANGE_SIZE: usize> RangeCheck<F> for RangeCheckCircuitConfig<F, RANGE_SIZE> {
+    fn check_value(&self, layouter: impl Layouter<F>, value: Value<Assigned<F>>) -> Result<RangeConstrained<F>, CircuitsError>{
+        self.assign(layouter, value)
+    }
+
+    fn check_cell(&self, mut layouter: impl Layouter<F>, cell: &AssignedCell<Assigned<F>, F>) -> Result<RangeConstrained<F>, CircuitsError>{
+        let offset = 0;
+        check_range_size(RANGE_SIZE)?;
+        let mut layouter = layouter.namespace(|| self.instance_name());
+        layouter.assign_region(
+            || format!("{} check range val", self.instance_name()),
+            |mut region| {
+                self.q_enable.enable(&mut region, offset)?;
+                cell.copy_advice(||"value", &mut region, self.value, offset)
+                .map(RangeConstrained::Assigned)
             }
-        )
+        ).map_err(CircuitsError::from)
+    }
+}
+
+//chip wrapper, RangeCheckInstructions comes from the config's RangeCheck impl
+#[derive(Debug, Clone)]
+pub struct RangeCheckChip<F: FieldExt, const RANGE_SIZE: usize>{
+    config: RangeCheckCircuitConfig<F, RANGE_SIZE>,
+}
+
+impl<F: FieldExt, const RANGE_SIZE: usize> RangeCheckChip<F, RANGE_SIZE> {
+    pub fn construct(config: RangeCheckCircuitConfig<F, RANGE_SIZE>) -> Self{
+        Self { config }
+    }
 
-    } 
+    pub fn configure(cs: &mut ConstraintSystem<F>, value: Column<Advice>) -> RangeCheckCircuitConfig<F, RANGE_SIZE>{
+        RangeCheckCircuitConfig::configure(cs, value)
+    }
+}
+
+impl<F: FieldExt, const RANGE_SIZE: usize> Chip<F> for RangeCheckChip<F, RANGE_SIZE> {
+    type Config = RangeCheckCircuitConfig<F, RANGE_SIZE>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config{
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded{
+        &()
+    }
+}
+
+//checks every value in its own region, the batch size is part of the circuit shape
+#[derive(Clone, Default)]
+pub struct RangeCheckCircuit<F: FieldExt, const RANGE_SIZE: usize>{
+    pub values: Vec<Value<Assigned<F>>>,
+}
+
+impl<F: FieldExt, const RANGE_SIZE: usize> RangeCheckCircuit<F, RANGE_SIZE> {
+    pub fn from_values(values: &[u64]) -> Self{
+        Self { values: values.iter().map(|v| RangeConstrained::witness_u64(*v)).collect() }
+    }
+}
+
+impl<F: FieldExt, const RANGE_SIZE: usize> Circuit<F> for RangeCheckCircuit<F, RANGE_SIZE> {
+    type Config = RangeCheckCircuitConfig<F, RANGE_SIZE>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self{
+        Self { values: vec![Value::unknown(); self.values.len()] }
+    }
+
+    fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config{
+        //create advice column to give to configuration
+        let value = cs.advice_column();
+        Self::Config::configure(cs, value)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        for value in &self.values {
+            config.assign(layouter.namespace(
+                ||"Assign value to range check circ"), 
+                *value
+            )?;
+        }
+        Ok(())
+    }
+}
+
+//estimates proof size and prover cost of checking num_values values at k without proving,
+//only the shape is measured so the values are left unknown
+pub fn estimate_cost<const RANGE_SIZE: usize>(k: u32, num_values: usize) -> CircuitCost<Eq, RangeCheckCircuit<Fp, RANGE_SIZE>> {
+    let circuit = RangeCheckCircuit::<Fp, RANGE_SIZE> { values: vec![Value::unknown(); num_values] };
+    CircuitCost::measure(k as usize, &circuit)
 }
 
 #[cfg(test)]
 mod tests{
     use halo2_proofs::{
-        circuit::SimpleFloorPlanner,
         dev::{FailureLocation, MockProver, VerifyFailure},
         pasta::Fp,
-        plonk::{Any, Circuit},
+        plonk::Any,
     }; 
     use super::*;
+    use crate::{params_cache::ParamsCache, proving};
+    use crate::test_utils::{boundary_values, expect_failures, in_range_values, out_of_range_values, value_near, value_u64, values_u64, ExpectedFailure};
+
+    use proptest::prelude::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_range_check_complete(){
+        let k = 4;
+        const range_size: usize = 8;
+
+
+        //check that prover produces circuit that gets acccepted when the value is in range\
+        for i in (0..range_size){
+            let circuit = RangeCheckCircuit::<Fp, range_size>::from_values(&[i as u64]);
+            
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            prover.assert_satisfied();
+        }
+
+    
+    }
+    #[test]
+    fn test_range_check_sound(){
+        let k = 4;
+        const range_size: usize = 8;
+        let circuit = RangeCheckCircuit::<Fp, range_size>::from_values(&[range_size as u64]);
+        
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        expect_failures(&prover, &[ExpectedFailure::constraint("range", "range check", "range_check<8> assign range val", 0)]);
+    }
+
+    #[test]
+    fn test_range_check_generated(){
+        let k = 4;
+        const range_size: usize = 8;
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let mut values = in_range_values(range_size as u64, 4, &mut rng);
+        values.extend(boundary_values(range_size as u64));
+        let circuit = RangeCheckCircuit::<Fp, range_size> { values };
+        MockProver::run(k, &circuit, vec![]).unwrap().assert_satisfied();
+
+        for value in out_of_range_values(range_size as u64, 4, &mut rng) {
+            let circuit = RangeCheckCircuit::<Fp, range_size> { values: vec![value] };
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
 
+    #[test]
+    fn test_range_check_failure_location(){
+        let k = 4;
+        const range_size: usize = 8;
+        //only the second value is out of range, each value gets its own region
+        let circuit = RangeCheckCircuit::<Fp, range_size>::from_values(&[3, range_size as u64]);
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(
+            prover.verify(),
+            Err(vec![VerifyFailure::ConstraintNotSatisfied {
+                constraint: ((0, "range").into(), 0, "range check").into(),
+                location: FailureLocation::InRegion {
+                    region: (1, "range_check<8> assign range val").into(),
+                    offset: 0,
+                },
+                cell_values: vec![(((Any::Advice, 0).into(), 0).into(), "0x8".to_string())],
+            }])
+        );
+    }
+
+    //assigns a value then copies it into a second check, asserting on the returned cells
     #[derive(Default)]
-    struct RangeCheckCircuit<F: FieldExt, const RANGE_SIZE: usize>{
-        pub value: Value<Assigned<F>>,
+    struct WiringCircuit<F: FieldExt>{
+        value: Value<Assigned<F>>,
     }
 
-    impl<F: FieldExt, const RANGE_SIZE: usize> Circuit<F> for RangeCheckCircuit<F, RANGE_SIZE> {
-        type Config = RangeCheckCircuitConfig<F, RANGE_SIZE>;
+    impl<F: FieldExt> Circuit<F> for WiringCircuit<F> {
+        type Config = RangeCheckCircuitConfig<F, 8>;
         type FloorPlanner = SimpleFloorPlanner;
 
         fn without_witnesses(&self) -> Self{
@@ -94,47 +359,377 @@ mod tests{
         }
 
         fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config{
-            //create advice column to give to configuration
             let value = cs.advice_column();
             Self::Config::configure(cs, value)
         }
 
         fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
-            config.assign(layouter.namespace(
-                ||"Assign value to test circ"), 
-                self.value
-            )?;
+            let assigned = config.assign(layouter.namespace(||"assign"), self.value)?;
+            let checked = config.check_cell(layouter.namespace(||"check"), assigned.as_assigned().unwrap())?;
+
+            assert_eq!(assigned, assigned.clone());
+            assert_eq!(format!("{:?}", assigned.cell()), format!("{:?}", assigned.evaluate().cell()));
+            //the copy is a different cell holding the same value
+            assert_ne!(assigned, checked);
             Ok(())
         }
     }
+
     #[test]
-    fn test_range_check_complete(){
+    fn test_range_constrained_cells(){
         let k = 4;
-        const range_size: usize = 8;
+        let circuit = WiringCircuit::<Fp>{ value: value_u64(3) };
+        MockProver::run(k, &circuit, vec![]).unwrap().assert_satisfied();
+    }
 
+    //a checked value feeding a doubling gate and a squaring gate in separate regions
+    #[derive(Clone, Debug)]
+    struct DownstreamConfig {
+        range: RangeCheckCircuitConfig<Fp, 8>,
+        input: Column<Advice>,
+        output: Column<Advice>,
+        q_double: Selector,
+        q_square: Selector,
+    }
 
-        //check that prover produces circuit that gets acccepted when the value is in range\
-        for i in (0..range_size){
-            let circuit = RangeCheckCircuit::<Fp, range_size>{
-                value: Value::known(Fp::from(i as u64).into())
-            };
-            
-            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
-            prover.assert_satisfied();
+    #[derive(Default)]
+    struct DownstreamCircuit {
+        value: Value<Assigned<Fp>>,
+        //witnessed outputs of the two gates
+        double: Value<Assigned<Fp>>,
+        square: Value<Assigned<Fp>>,
+    }
+
+    impl Circuit<Fp> for DownstreamCircuit {
+        type Config = DownstreamConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self{
+            Self::default()
         }
 
-    
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config{
+            let value = cs.advice_column();
+            let input = cs.advice_column();
+            let output = cs.advice_column();
+            cs.enable_equality(input);
+            let q_double = cs.selector();
+            let q_square = cs.selector();
+            cs.create_gate("double", |cs| {
+                let q = cs.query_selector(q_double);
+                let i = cs.query_advice(input, Rotation::cur());
+                let o = cs.query_advice(output, Rotation::cur());
+                Constraints::with_selector(q, [("double", o - i * Fp::from(2))])
+            });
+            cs.create_gate("square", |cs| {
+                let q = cs.query_selector(q_square);
+                let i = cs.query_advice(input, Rotation::cur());
+                let o = cs.query_advice(output, Rotation::cur());
+                Constraints::with_selector(q, [("square", o - i.clone() * i)])
+            });
+            DownstreamConfig { range: RangeCheckCircuitConfig::configure(cs, value), input, output, q_double, q_square }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let checked = config.range.assign(layouter.namespace(||"assign"), self.value)?;
+            let checked_again = checked.clone();
+            assert_eq!(checked, checked_again);
+            let downstream = [(config.q_double, self.double, "double"), (config.q_square, self.square, "square")];
+            let [double, square] = downstream.map(|(q, output, name)| layouter.assign_region(|| name, |mut region| {
+                q.enable(&mut region, 0)?;
+                region.assign_advice(|| "output", config.output, 0, || output)?;
+                checked.copy_advice_to(|| "input", &mut region, config.input, 0)
+            }));
+            let (double, square) = (double?, square?);
+            //both copies hold the value in their own cell
+            assert_ne!(double, checked);
+            assert_ne!(double, square);
+            double.value_u64().zip(checked.value_u64()).assert_if_known(|(copy, value)| copy == value);
+            Ok(())
+        }
     }
+
     #[test]
-    #[should_panic]
-    fn test_range_check_sound(){
+    fn test_copy_to_downstream_gates(){
         let k = 4;
-        const range_size: usize = 8;
-        let circuit = RangeCheckCircuit::<Fp, range_size>{
-            value: Value::known(Fp::from(range_size as u64).into())
-        };
-        
+        let circuit = DownstreamCircuit { value: value_u64(3), double: value_u64(6), square: value_u64(9) };
+        MockProver::run(k, &circuit, vec![]).unwrap().assert_satisfied();
+
+        //a wrong output fails only the gate it feeds
+        let circuit = DownstreamCircuit { value: value_u64(3), double: value_u64(6), square: value_u64(6) };
         let prover = MockProver::run(k, &circuit, vec![]).unwrap();
-        prover.verify().unwrap();
+        expect_failures(&prover, &[ExpectedFailure::constraint("square", "square", "square", 0)]);
+    }
+
+    //plain u64, bool and field witnesses through the same config
+    struct PlainWitnessCircuit{
+        value: u64,
+        flag: bool,
+    }
+
+    impl Circuit<Fp> for PlainWitnessCircuit {
+        type Config = RangeCheckCircuitConfig<Fp, 4>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self{
+            Self { value: self.value, flag: self.flag }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config{
+            let value = cs.advice_column();
+            Self::Config::configure(cs, value)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.check_u64(layouter.namespace(||"u64"), self.value)?;
+            config.check_bool(layouter.namespace(||"bool"), self.flag)?;
+            config.assign(layouter.namespace(||"field"), Value::known(Fp::from(3)))?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_plain_witnesses(){
+        let k = 4;
+        MockProver::run(k, &PlainWitnessCircuit { value: 2, flag: true }, vec![]).unwrap().assert_satisfied();
+        let prover = MockProver::run(k, &PlainWitnessCircuit { value: 4, flag: false }, vec![]).unwrap();
+        expect_failures(&prover, &[ExpectedFailure::constraint("range", "range check", "range_check<4> assign range val", 0)]);
+    }
+
+    //assigns straight from a borrowed slice
+    struct RefCircuit{
+        values: Vec<Value<Fp>>,
+    }
+
+    impl Circuit<Fp> for RefCircuit {
+        type Config = RangeCheckCircuitConfig<Fp, 4>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self{
+            Self { values: vec![Value::unknown(); self.values.len()] }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config{
+            let value = cs.advice_column();
+            Self::Config::configure(cs, value)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            for value in self.values.iter() {
+                config.assign_ref(layouter.namespace(||"ref"), value.as_ref())?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_assign_ref(){
+        let k = 4;
+        let values: Vec<Value<Fp>> = [0, 3, 1].iter().map(|&v| Value::known(Fp::from(v))).collect();
+        MockProver::run(k, &RefCircuit { values }, vec![]).unwrap().assert_satisfied();
+        let prover = MockProver::run(k, &RefCircuit { values: vec![Value::known(Fp::from(2)), Value::known(Fp::from(4))] }, vec![]).unwrap();
+        expect_failures(&prover, &[ExpectedFailure::constraint("range", "range check", "range_check<4> assign range val", 0)]);
+    }
+
+    #[test]
+    fn test_range_too_large(){
+        const range_size: usize = MAX_RANGE_SIZE + 1;
+        let mut cs = ConstraintSystem::<Fp>::default();
+        let value = cs.advice_column();
+        RangeCheckCircuitConfig::<Fp, range_size>::configure(&mut cs, value);
+        let err = check_range_size(range_size).unwrap_err();
+        assert!(matches!(
+            err,
+            CircuitsError::RangeTooLarge { gadget: "range_check", range: 33, max: 32 }
+        ));
+        //assign fails on it, which synthesize sees as a synthesis error
+        let circuit = RangeCheckCircuit::<Fp, range_size>::from_values(&[3]);
+        assert!(matches!(MockProver::run(4, &circuit, vec![]), Err(Error::Synthesis)));
+        //the largest supported range is still accepted
+        assert!(check_range_size(MAX_RANGE_SIZE).is_ok());
+    }
+
+    //two checks with the same bound, told apart by their prefix
+    struct NamedCircuit{
+        values: [Value<Assigned<Fp>>; 2],
     }
-}
\ No newline at end of file
+
+    #[derive(Clone, Debug)]
+    struct NamedConfig{
+        inputs: RangeCheckCircuitConfig<Fp, 8>,
+        outputs: RangeCheckCircuitConfig<Fp, 8>,
+    }
+
+    impl Circuit<Fp> for NamedCircuit {
+        type Config = NamedConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self{
+            Self { values: [Value::unknown(); 2] }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config{
+            let input = cs.advice_column();
+            let output = cs.advice_column();
+            NamedConfig {
+                inputs: RangeCheckCircuitConfig::configure_named(cs, input, "inputs"),
+                outputs: RangeCheckCircuitConfig::configure_named(cs, output, "outputs"),
+            }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.inputs.assign(layouter.namespace(||"input"), self.values[0])?;
+            config.outputs.assign(layouter.namespace(||"output"), self.values[1])?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_name_prefix(){
+        let k = 4;
+        let prover = MockProver::run(k, &NamedCircuit { values: [value_u64(3), value_u64(9)] }, vec![]).unwrap();
+        expect_failures(&prover, &[ExpectedFailure::constraint("range", "range check", "outputs range_check<8> assign range val", 0)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "range_check: RANGE_SIZE must be at least 1, got 0")]
+    fn test_range_size_zero(){
+        let mut cs = ConstraintSystem::<Fp>::default();
+        let value = cs.advice_column();
+        RangeCheckCircuitConfig::<Fp, 0>::configure(&mut cs, value);
+    }
+
+    #[test]
+    fn test_range_size_one_and_two(){
+        let k = 4;
+        MockProver::run(k, &RangeCheckCircuit::<Fp, 1>::from_values(&[0]), vec![]).unwrap().assert_satisfied();
+        let prover = MockProver::run(k, &RangeCheckCircuit::<Fp, 1>::from_values(&[1]), vec![]).unwrap();
+        expect_failures(&prover, &[ExpectedFailure::constraint("range", "value is zero", "range_check<1> assign range val", 0)]);
+
+        MockProver::run(k, &RangeCheckCircuit::<Fp, 2>::from_values(&[0, 1]), vec![]).unwrap().assert_satisfied();
+        let prover = MockProver::run(k, &RangeCheckCircuit::<Fp, 2>::from_values(&[2]), vec![]).unwrap();
+        expect_failures(&prover, &[ExpectedFailure::constraint("range", "range check", "range_check<2> assign range val", 0)]);
+    }
+
+    #[test]
+    fn test_range_check_expression(){
+        //evaluate with the value substituted by a constant, only constants can show up
+        let eval = |expr: Expression<Fp>| expr.evaluate(
+            &|c| c,
+            &|_| unreachable!(),
+            &|_, _, _| unreachable!(),
+            &|_, _, _| unreachable!(),
+            &|_, _, _| unreachable!(),
+            &|a| -a,
+            &|a, b| a + b,
+            &|a, b| a * b,
+            &|a, f| a * f,
+        );
+
+        for range in 1..=6u64 {
+            for v in 0..10u64 {
+                let value = Fp::from(v);
+                let actual = eval(range_check_expression(range as usize, Expression::Constant(value)));
+                let expected = (1..range).fold(value, |acc, i| acc * (Fp::from(i) - value));
+                assert_eq!(actual, expected);
+                assert_eq!(actual == Fp::zero(), v < range);
+            }
+        }
+    }
+
+    //the range is picked at runtime, so one circuit type covers every range the property
+    //test draws. configure sets up a gate per range on a shared column and synthesize uses
+    //the one asked for. the gate has degree range + 1 so the ranges stay small
+    const MAX_TEST_RANGE: usize = 8;
+
+    //RangeCheckCircuitConfig with the range a runtime value instead of a const generic, so
+    //the property test can build a gate per range. the gate and the region names are the same
+    #[derive(Debug, Clone)]
+    struct RangeBoundCheckConfig {
+        value: Column<Advice>,
+        q_enable: Selector,
+        range: usize,
+    }
+
+    impl RangeBoundCheckConfig {
+        fn configure(cs: &mut ConstraintSystem<Fp>, value: Column<Advice>, range: usize) -> Self{
+            let q_enable = configure_gate(cs, value, range);
+            Self { value, q_enable, range }
+        }
+
+        fn assign(&self, mut layouter: impl Layouter<Fp>, value: Value<Assigned<Fp>>) -> Result<RangeConstrained<Fp>, CircuitsError>{
+            let name = format!("range_check<{}> assign range val", self.range);
+            check_range_size(self.range)?;
+            let mut layouter = layouter.namespace(|| format!("range_check<{}>", self.range));
+            layouter.assign_region(
+                || name.as_str(),
+                |mut region| {
+                    self.q_enable.enable(&mut region, 0)?;
+                    region.assign_advice(|| name.as_str(), self.value, 0, ||value)
+                    .map(RangeConstrained::Assigned)
+                }
+            ).map_err(CircuitsError::from)
+        }
+    }
+
+    struct RangeBoundCircuit {
+        range: usize,
+        values: Vec<Value<Assigned<Fp>>>,
+    }
+
+    impl Circuit<Fp> for RangeBoundCircuit {
+        type Config = Vec<RangeBoundCheckConfig>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self{
+            Self { range: self.range, values: vec![Value::unknown(); self.values.len()] }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config{
+            let value = cs.advice_column();
+            (1..=MAX_TEST_RANGE).map(|range| RangeBoundCheckConfig::configure(cs, value, range)).collect()
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let config = &config[self.range - 1];
+            for value in &self.values {
+                config.assign(layouter.namespace(|| "value"), *value)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn accepts_range(range: usize, values: &[u64]) -> bool{
+        let k = 5;
+        let circuit = RangeBoundCircuit { range, values: values_u64(values) };
+        MockProver::run(k, &circuit, vec![]).unwrap().verify().is_ok()
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn prop_range_check_matches_native(
+            (range, values) in (1..=MAX_TEST_RANGE).prop_flat_map(|range| (Just(range), prop::collection::vec(value_near(range), 1..8)))
+        ){
+            let expected = values.iter().all(|v| *v < range as u64);
+            prop_assert_eq!(accepts_range(range, &values), expected);
+        }
+    }
+
+    #[test]
+    fn test_estimate_cost(){
+        let cost = estimate_cost::<8>(5, 3);
+        let proof_size: usize = cost.proof_size(0).into();
+        //a few dozen commitments and evaluations of 32 bytes each
+        assert!((500..10_000).contains(&proof_size), "{}", proof_size);
+        //and close to what the prover actually produces
+        let circuit = RangeCheckCircuit::<Fp, 8>::from_values(&[1, 2, 3]);
+        let (params, pk) = proving::keygen(&ParamsCache::new(), 5, &circuit.without_witnesses()).unwrap();
+        let proof = proving::create_proof(&params, &pk, circuit, &vec![]).unwrap();
+        assert!(proof.len() <= 2 * proof_size && proof_size <= 2 * proof.len(), "estimated {}, proved {}", proof_size, proof.len());
+        //more values at the same k only add rows, not columns
+        let more: usize = estimate_cost::<8>(5, 10).proof_size(0).into();
+        assert_eq!(more, proof_size);
+    }
+}
@@ -1,95 +1,663 @@
 #![allow(warnings, unused)]
 use halo2_proofs::{
-    arithmetic::FieldExt, circuit::{AssignedCell, Layouter, Value}, dev::metadata::Constraint, plonk::{Advice, Assigned, Column, ConstraintSystem, Constraints, Error, Expression, Selector, TableColumn}, poly::Rotation
+    arithmetic::FieldExt, circuit::{AssignedCell, Chip, Layouter, Region, SimpleFloorPlanner, Table, Value}, dev::metadata::Constraint, plonk::{Advice, Assigned, Circuit, Column, ConstraintSystem, Constraints, Error, Expression, Selector, TableColumn}, poly::Rotation
 };
 
-use std::marker::PhantomData;
+use halo2_proofs::{dev::CircuitCost, pasta::{Eq, Fp}};
 
-#[derive(Clone, Debug)]
-pub struct RangeConstrained<F: FieldExt>(AssignedCell<Assigned<F>, F>);
+use std::{fmt, marker::PhantomData, sync::{atomic::{AtomicBool, Ordering}, Arc}};
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, instructions::LookupRangeCheckInstructions, report::{self, ConfigDescription}, rows::{self, RowBudget, TablePadding}, Bounded, RangeCheck, RangeConstrained};
 
 #[derive(Clone, Debug)]
 pub struct RangeTableConfig<F: FieldExt, const RANGE: usize>{
     pub value: TableColumn,
     pub _marker: PhantomData<F>,
+    //shared between clones of the config so gadgets can tell the table was never loaded
+    loaded: Arc<AtomicBool>,
+    //what load_padded puts in the rows past 0..RANGE
+    pub padding: TablePadding,
+    //the k load checks the table fits at before assigning it, when it's known at configure time
+    pub k: Option<u32>,
 }
 
 impl<F: FieldExt, const RANGE: usize> RangeTableConfig<F, RANGE>{
     pub fn configure(cs: &mut ConstraintSystem<F>) -> Self {
+        Self::configure_padded(cs, TablePadding::Unpadded)
+    }
+
+    //for a circuit proved at k, so load fails with the numbers instead of halo2's bare
+    //NotEnoughRowsAvailable when RANGE doesn't fit
+    pub fn configure_at(cs: &mut ConstraintSystem<F>, k: u32) -> Self {
+        Self { k: Some(k), ..Self::configure(cs) }
+    }
+
+    //padding is what load_padded fills the rows past 0..RANGE with
+    pub fn configure_padded(cs: &mut ConstraintSystem<F>, padding: TablePadding) -> Self {
         let values = cs.lookup_table_column();
 
         Self{
             value: values,
-            _marker: PhantomData::<F>
+            _marker: PhantomData::<F>,
+            loaded: Arc::new(AtomicBool::new(false)),
+            padding: padding.for_rows(RANGE),
+            k: None,
         }
     }
 
-    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
-        
-        layouter.assign_table(||"assign table", |mut table| {
-            for i in (0..RANGE) {
-                table.assign_cell(||"assign lookup table value", self.value, i, || Value::known(F::from(i as u64)))?;
-            }
+    //the value repeated in the padding rows, if there are any
+    pub fn filler(&self) -> Option<u64> {
+        self.padding.filler_row(RANGE).map(|row| row as u64)
+    }
+
+    //wraps a column someone else owns and has filled with 0..RANGE. it's taken as loaded,
+    //loading it is up to the owner
+    pub fn from_column(value: TableColumn) -> Self {
+        Self{
+            value,
+            _marker: PhantomData::<F>,
+            loaded: Arc::new(AtomicBool::new(true)),
+            padding: TablePadding::Unpadded,
+            k: None,
+        }
+    }
+
+    fn rows() -> Vec<Vec<F>> {
+        (0..RANGE).map(|i| vec![F::from(i as u64)]).collect()
+    }
+
+    //marks the table loaded before assigning it rather than from inside assign_table, the
+    //V1 floor planner skips the table closures on its measurement pass
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), CircuitsError> {
+        let what = format!("range_table<{}>", RANGE);
+        if let Some(k) = self.k {
+            RowBudget::at(k).table().ensure(what.as_str(), RANGE)?;
+        }
+        self.loaded.store(true, Ordering::Relaxed);
+        rows::load_table(layouter, &what, &[("assign lookup table value", self.value)], &Self::rows(), self.padding, None)
+    }
+
+    //load that first checks the table fits in the budget, halo2's own error for it doesn't
+    //say which table or by how much. then fills the rest of the usable rows as the padding
+    //says, so the table holds the same set whatever k ends up being
+    pub fn load_padded(&self, layouter: &mut impl Layouter<F>, budget: RowBudget) -> Result<(), CircuitsError> {
+        self.loaded.store(true, Ordering::Relaxed);
+        rows::load_table(layouter, &format!("range_table<{}>", RANGE), &[("assign lookup table value", self.value)], &Self::rows(), self.padding, Some(budget))
+    }
+
+    //assigns only rows [from, to), so the table can be grown in stages. the floor planners
+    //reject a table column assigned from a second assign_table call, so every stage has to
+    //run inside the same one
+    pub fn extend(&self, table: &mut Table<'_, F>, from: usize, to: usize) -> Result<(), Error> {
+        for i in (from..to) {
+            table.assign_cell(||"assign lookup table value", self.value, i, || Value::known(F::from(i as u64)))?;
+        }
+        self.loaded.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    pub fn describe(&self) -> ConfigDescription {
+        ConfigDescription::new("range_table").table("value", self.value)
+    }
+
+    pub fn ensure_loaded(&self) -> Result<(), CircuitsError> {
+        if self.loaded.load(Ordering::Relaxed) {
             Ok(())
-        })
+        } else {
+            Err(CircuitsError::TableNotLoaded { table: "range table" })
+        }
+    }
+}
+impl<F: FieldExt, const RANGE: usize> fmt::Display for RangeTableConfig<F, RANGE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.describe())
+    }
+}
+
+//an arbitrary allowed set of SIZE values instead of 0..RANGE, e.g. from a const array, loaded
+//with load_from_array. the set doesn't have to hold 0, so like SBoxConfig the rows are
+//tagged: the set is loaded with tag 1 and the single row (0, 0) is what rows off the
+//selector look up
+#[derive(Clone, Debug)]
+pub struct ArrayTableConfig<F: FieldExt, const SIZE: usize> {
+    pub tag: TableColumn,
+    pub value: TableColumn,
+    //what load_padded puts in the rows past the set
+    pub padding: TablePadding,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const SIZE: usize> ArrayTableConfig<F, SIZE> {
+    pub fn configure(cs: &mut ConstraintSystem<F>) -> Self {
+        Self::configure_padded(cs, TablePadding::Unpadded)
+    }
+
+    //padding counts the (0, 0) row, a filler of 1 repeats the first entry of the array
+    pub fn configure_padded(cs: &mut ConstraintSystem<F>, padding: TablePadding) -> Self {
+        Self {
+            tag: cs.lookup_table_column(),
+            value: cs.lookup_table_column(),
+            padding: padding.for_rows(SIZE + 1),
+            _marker: PhantomData,
+        }
+    }
+
+    //what RangeTableConfig::load_from_array would be. a single column range table has to hold
+    //the 0 rows off the selector look up, which would make 0 a member of every set, so an
+    //allowed set gets a tagged table of its own
+    pub fn load_from_array(&self, layouter: &mut impl Layouter<F>, arr: &[u64]) -> Result<(), CircuitsError> {
+        self.load_rows(layouter, arr, None)
+    }
+
+    //load_from_array that first checks the table fits in the budget, then fills the rest of
+    //its usable rows as the padding says
+    pub fn load_from_array_padded(&self, layouter: &mut impl Layouter<F>, arr: &[u64], budget: RowBudget) -> Result<(), CircuitsError> {
+        self.load_rows(layouter, arr, Some(budget))
+    }
+
+    fn load_rows(&self, layouter: &mut impl Layouter<F>, arr: &[u64], budget: Option<RowBudget>) -> Result<(), CircuitsError> {
+        if arr.len() != SIZE {
+            return Err(CircuitsError::LengthMismatch { expected: SIZE, got: arr.len() });
+        }
+        let rows: Vec<_> = std::iter::once((0, 0)).chain(arr.iter().map(|v| (1, *v))).map(|(tag, value)| vec![F::from(tag), F::from(value)]).collect();
+        rows::load_table(layouter, &format!("array_table<{}>", SIZE), &[("tag", self.tag), ("value", self.value)], &rows, self.padding, budget)
+    }
+
+    pub fn describe(&self) -> ConfigDescription {
+        ConfigDescription::new("array_table").table("tag", self.tag).table("value", self.value)
     }
 }
+
+//checks values are in the set an ArrayTableConfig is loaded with, one row per value
+#[derive(Clone, Debug)]
+pub struct ArrayLookupConfig<F: FieldExt, const SIZE: usize> {
+    pub value: Column<Advice>,
+    pub q_enable: Selector,
+    pub table: ArrayTableConfig<F, SIZE>,
+}
+
+impl<F: FieldExt, const SIZE: usize> ArrayLookupConfig<F, SIZE> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, value: Column<Advice>, table: ArrayTableConfig<F, SIZE>) -> Self {
+        let q_enable = cs.complex_selector();
+
+        cs.enable_equality(value);
+
+        let lookup_index = cs.lookup(|cs| {
+            let q = cs.query_selector(q_enable);
+            let value = cs.query_advice(value, Rotation::cur());
+            vec![(q.clone(), table.tag), (q * value, table.value)]
+        });
+        report::annotate_lookup("range_lookup", lookup_index);
+
+        Self { value, q_enable, table }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let value = pool.advice(cs);
+        let table = ArrayTableConfig::configure(cs);
+        Self::configure(cs, value, table)
+    }
+
+    pub fn assign_lookup(&self, mut layouter: impl Layouter<F>, value: Value<F>) -> Result<AssignedCell<F, F>, CircuitsError> {
+        let offset = 0;
+        layouter.assign_region(
+            || format!("array_lookup<{}> assign value", SIZE),
+            |mut region| {
+                self.q_enable.enable(&mut region, offset)?;
+                region.assign_advice(|| "value", self.value, offset, || value)
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct RangeCheckLookupConfig<F: FieldExt, const RANGE: usize>{
     pub values: Column<Advice>,
+    //further columns checked on the same rows as values, empty unless configured parallel
+    pub parallel: Vec<Column<Advice>>,
     pub q_enable: Selector,
+    //sum = a + b over three rows of values, for add_and_check. configured by a
+    //RangeComposeConfig over this lookup, none otherwise
+    pub q_add: Option<Selector>,
     pub table: RangeTableConfig<F, RANGE>,
+    //one cs.lookup per checked column
+    lookup_indices: Vec<usize>,
+    //put in front of the region names when embedded in a bigger circuit
+    pub name_prefix: Option<&'static str>,
 }
 
 impl<F: FieldExt, const RANGE: usize> RangeCheckLookupConfig<F, RANGE> {
     pub fn configure(cs: &mut ConstraintSystem<F>, values: Column<Advice>) -> Self{
-        let q_enable = cs.complex_selector();
-        let table = RangeTableConfig::configure(cs);
-        cs.lookup(|cs| {
-            let q_lookup = cs.query_selector(q_enable);
-            let v = cs.query_advice(values, Rotation::cur());
-            vec![(q_lookup * v, table.value)]
-        });
+        Self::configure_with_table(cs, values, None)
+    }
+
+    //like configure but the regions are named "name_prefix range_lookup<N> ..."
+    pub fn configure_named(cs: &mut ConstraintSystem<F>, values: Column<Advice>, name_prefix: &'static str) -> Self{
+        Self { name_prefix: Some(name_prefix), ..Self::configure(cs, values) }
+    }
+
+    //reuses table when given, so several gadgets checking 0..RANGE can share one column
+    pub fn configure_with_table(cs: &mut ConstraintSystem<F>, values: Column<Advice>, table: Option<TableColumn>) -> Self{
+        Self::configure_columns(cs, &[values], table, None)
+    }
+
+    //looks values up on the rows q is enabled on instead of allocating q_enable, for a check
+    //that always fires alongside another gate. the caller guarantees q means the same thing
+    //for both
+    pub fn configure_shared_selector(cs: &mut ConstraintSystem<F>, values: Column<Advice>, q: Selector) -> Self{
+        //a simple selector can't be queried in a lookup, keygen would only fail much later
+        assert!(!q.is_simple(), "the shared selector has to be a complex selector");
+        Self::configure_columns(cs, &[values], None, Some(q))
+    }
+
+    //checks a value in every column on each enabled row, which costs a lookup per column
+    pub fn configure_parallel(cs: &mut ConstraintSystem<F>, columns: &[Column<Advice>]) -> Self{
+        Self::configure_columns(cs, columns, None, None)
+    }
+
+    fn configure_columns(cs: &mut ConstraintSystem<F>, columns: &[Column<Advice>], table: Option<TableColumn>, q_enable: Option<Selector>) -> Self{
+        assert!(!columns.is_empty());
+        let q_enable = q_enable.unwrap_or_else(|| cs.complex_selector());
+        let table = match table {
+            Some(column) => RangeTableConfig::from_column(column),
+            None => RangeTableConfig::configure(cs),
+        };
+        let lookup_indices = columns.iter().map(|&column| {
+            cs.enable_equality(column);
+            let lookup_index = cs.lookup(|cs| {
+                let q_lookup = cs.query_selector(q_enable);
+                let v = cs.query_advice(column, Rotation::cur());
+                vec![(q_lookup * v, table.value)]
+            });
+            report::annotate_lookup("range_lookup", lookup_index);
+            lookup_index
+        }).collect();
 
         Self {
-            values: values,
+            values: columns[0],
+            parallel: columns[1..].to_vec(),
             q_enable: q_enable,
+            q_add: None,
             table: table,
+            lookup_indices,
+            name_prefix: None,
+        }
+    }
+
+    pub fn describe(&self) -> ConfigDescription {
+        let description = self.parallel.iter().enumerate().fold(
+            ConfigDescription::new("range_lookup").advice("values", self.values),
+            |description, (i, column)| description.advice(format!("parallel {}", i), *column),
+        );
+        description
+            .selector("q_enable", self.q_enable)
+            .extend(self.table.describe())
+    }
+
+    //how many lookup arguments configure registered, for budgeting the constraint system
+    pub fn num_lookups(&self) -> usize {
+        self.lookup_indices.len()
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self{
+        let values = pool.advice(cs);
+        Self::configure(cs, values)
+    }
+
+    //the assign paths run in a namespace with this name and put it in front of their
+    //default region names, so lookups into different tables can be told apart. MockProver
+    //ignores namespaces, only the region names show up in its failures
+    fn instance_name(&self) -> String{
+        match self.name_prefix {
+            Some(prefix) => format!("{} range_lookup<{}>", prefix, RANGE),
+            None => format!("range_lookup<{}>", RANGE),
+        }
+    }
+
+    //takes anything that converts into Assigned, e.g. Value<F> or Value<Assigned<F>>
+    pub fn assign_lookup<V: Into<Assigned<F>>>(&self, layouter: impl Layouter<F>, val: Value<V>) -> Result<RangeConstrained<F>, CircuitsError>{
+        self.assign_lookup_annotated(layouter, format!("{} assign value", self.instance_name()), val)
+    }
+
+    //label names the region and the cell, so failures point at what the value was
+    pub fn assign_lookup_annotated<V: Into<Assigned<F>>>(&self, mut layouter: impl Layouter<F>, label: impl Into<String>, val: Value<V>) -> Result<RangeConstrained<F>, CircuitsError>{
+        let offset = 0;
+        let label = label.into();
+        let val = val.map(Into::into);
+        self.table.ensure_loaded()?;
+        let mut layouter = layouter.namespace(|| self.instance_name());
+        layouter.assign_region(|| label.as_str(), |mut region| {
+            self.q_enable.enable(&mut region, offset)?;
+            region.assign_advice(|| label.as_str(), self.values, offset, ||val)
+                  .map(RangeConstrained::Assigned)
+        }).map_err(CircuitsError::from)
+    }
+
+    //for values borrowed from a slice. the element is copied once, into the cell, rather than
+    //into an owned Value first
+    pub fn assign_ref(&self, layouter: impl Layouter<F>, val: Value<&F>) -> Result<RangeConstrained<F>, CircuitsError>{
+        self.assign_lookup(layouter, val.map(|v| Assigned::from(*v)))
+    }
+
+    //fills one row across values and the parallel columns, vals in that order
+    pub fn assign_lookup_row(&self, mut layouter: impl Layouter<F>, vals: &[Value<Assigned<F>>]) -> Result<Vec<RangeConstrained<F>>, CircuitsError>{
+        let offset = 0;
+        let columns: Vec<_> = std::iter::once(self.values).chain(self.parallel.iter().copied()).collect();
+        if vals.len() != columns.len() {
+            return Err(CircuitsError::LengthMismatch { expected: columns.len(), got: vals.len() });
+        }
+        self.table.ensure_loaded()?;
+        //the row length is part of the name, parallel configs of different widths differ
+        let name = format!("{} x{}", self.instance_name(), vals.len());
+        let mut layouter = layouter.namespace(|| name.as_str());
+        layouter.assign_region(|| format!("{} assign row", name), |mut region| {
+            self.q_enable.enable(&mut region, offset)?;
+            columns.iter().zip(vals).map(|(&column, val)| {
+                region.assign_advice(|| "advice", column, offset, || *val)
+                      .map(RangeConstrained::Assigned)
+            }).collect::<Result<Vec<_>, Error>>()
+        }).map_err(CircuitsError::from)
+    }
+
+    //copies a and b in on consecutive rows of values and checks their sum on the row after,
+    //so the sum is in 0..RANGE like its inputs. it comes back in the form of a. the add gate
+    //is configured by RangeComposeConfig, so this is for the lookup of one
+    pub fn add_and_check(&self, mut layouter: impl Layouter<F>, a: &RangeConstrained<F>, b: &RangeConstrained<F>) -> Result<RangeConstrained<F>, CircuitsError>{
+        let q_add = self.q_add.expect("add_and_check needs the add gate of a RangeComposeConfig");
+        self.table.ensure_loaded()?;
+        let sum = a.evaluate().value().copied() + b.evaluate().value().copied();
+        let mut layouter = layouter.namespace(|| self.instance_name());
+        layouter.assign_region(|| format!("{} add and check", self.instance_name()), |mut region| {
+            q_add.enable(&mut region, 0)?;
+            a.copy_advice_to(|| "a", &mut region, self.values, 0)?;
+            b.copy_advice_to(|| "b", &mut region, self.values, 1)?;
+            self.q_enable.enable(&mut region, 2)?;
+            match a {
+                RangeConstrained::Assigned(_) => region.assign_advice(|| "sum", self.values, 2, || sum.map(Assigned::from)).map(RangeConstrained::Assigned),
+                RangeConstrained::Simple(_) => region.assign_advice(|| "sum", self.values, 2, || sum).map(RangeConstrained::Simple),
+            }
+        }).map_err(CircuitsError::from)
+    }
+
+    //the same check on a plain field value, for callers that never need Assigned
+    pub fn assign_lookup_simple(&self, mut layouter: impl Layouter<F>, val: Value<F>) -> Result<RangeConstrained<F>, CircuitsError>{
+        let offset = 0;
+        let name = format!("{} assign value", self.instance_name());
+        self.table.ensure_loaded()?;
+        let mut layouter = layouter.namespace(|| self.instance_name());
+        layouter.assign_region(|| name.as_str(), |mut region| {
+            self.q_enable.enable(&mut region, offset)?;
+            region.assign_advice(|| name.as_str(), self.values, offset, ||val)
+                  .map(RangeConstrained::Simple)
+        }).map_err(CircuitsError::from)
+    }
+
+    //lays the values out from offset inside a region the caller already opened and
+    //returns the next free offset so further gadgets can be packed after them
+    pub fn assign_lookup_many(&self, region: &mut Region<'_, F>, offset: usize, vals: &[Value<Assigned<F>>]) -> Result<(Vec<RangeConstrained<F>>, usize), Error>{
+        self.assign_lookup_many_annotated(region, offset, format!("{} x{}", self.instance_name(), vals.len()), vals)
+    }
+
+    //assign_lookup_many in a region of its own, checking up front that the values fit in
+    //the budget
+    pub fn assign_lookup_batch(&self, mut layouter: impl Layouter<F>, budget: RowBudget, vals: &[Value<Assigned<F>>]) -> Result<Vec<RangeConstrained<F>>, CircuitsError>{
+        let name = format!("{} x{}", self.instance_name(), vals.len());
+        budget.ensure(name.as_str(), vals.len())?;
+        self.table.ensure_loaded()?;
+        //no region for no values, MockProver can't locate a failure once there's an empty one
+        if vals.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut layouter = layouter.namespace(|| self.instance_name());
+        layouter.assign_region(|| format!("{} assign batch", name), |mut region| {
+            self.assign_lookup_many(&mut region, 0, vals).map(|(cells, _)| cells)
+        }).map_err(CircuitsError::from)
+    }
+
+    //the caller owns the region name, the cells are annotated "label i"
+    pub fn assign_lookup_many_annotated(&self, region: &mut Region<'_, F>, offset: usize, label: impl Into<String>, vals: &[Value<Assigned<F>>]) -> Result<(Vec<RangeConstrained<F>>, usize), Error>{
+        let label = label.into();
+        let cells = vals.iter().enumerate().map(|(i, val)| {
+            self.q_enable.enable(region, offset + i)?;
+            region.assign_advice(|| format!("{} {}", label, i), self.values, offset + i, ||*val)
+                  .map(RangeConstrained::Assigned)
+        }).collect::<Result<Vec<_>, Error>>()?;
+        Ok((cells, offset + vals.len()))
+    }
+}
+
+impl<F: FieldExt, const RANGE: usize> fmt::Display for RangeCheckLookupConfig<F, RANGE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.describe())
+    }
+}
+
+//add_and_check and mul_and_check on top of a RangeCheckLookupConfig. the gates read three
+//rows of the lookup's values column, which widens the rotation set, so they live in a config
+//of their own that only circuits composing ranges configure
+#[derive(Clone, Debug)]
+pub struct RangeComposeConfig<F: FieldExt, const RANGE: usize> {
+    pub lookup: RangeCheckLookupConfig<F, RANGE>,
+    //sum = a + b over three rows of values, for add_and_check
+    pub q_add: Selector,
+    //out = a * b on the same layout, for mul_and_check
+    pub q_mul: Selector,
+}
+
+impl<F: FieldExt, const RANGE: usize> RangeComposeConfig<F, RANGE> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, lookup: RangeCheckLookupConfig<F, RANGE>) -> Self {
+        let values = lookup.values;
+
+        let q_add = cs.selector();
+        cs.create_gate("range_lookup add", |cs| {
+            let q = cs.query_selector(q_add);
+            let a = cs.query_advice(values, Rotation::cur());
+            let b = cs.query_advice(values, Rotation::next());
+            let sum = cs.query_advice(values, Rotation(2));
+            Constraints::with_selector(q, [("sum = a + b", sum - (a + b))])
+        });
+        report::annotate_gate("range_lookup", "range_lookup add");
+
+        let q_mul = cs.selector();
+        cs.create_gate("range_lookup mul", |cs| {
+            let q = cs.query_selector(q_mul);
+            let a = cs.query_advice(values, Rotation::cur());
+            let b = cs.query_advice(values, Rotation::next());
+            let out = cs.query_advice(values, Rotation(2));
+            Constraints::with_selector(q, [("out = a * b", out - a * b)])
+        });
+        report::annotate_gate("range_lookup", "range_lookup mul");
+
+        let lookup = RangeCheckLookupConfig { q_add: Some(q_add), ..lookup };
+        Self { lookup, q_add, q_mul }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let lookup = RangeCheckLookupConfig::configure_with_pool(cs, pool);
+        Self::configure(cs, lookup)
+    }
+
+    pub fn describe(&self) -> ConfigDescription {
+        self.lookup.describe().selector("q_add", self.q_add).selector("q_mul", self.q_mul)
+    }
+
+    //a cell checked by the lookup, with the table's bound
+    pub fn bounded(&self, cell: RangeConstrained<F>) -> Bounded<F> {
+        Bounded { cell, bound: RANGE as u128 }
+    }
+
+    //lookup.add_and_check, with the table's bound on the sum
+    pub fn add_and_check(&self, layouter: impl Layouter<F>, a: &RangeConstrained<F>, b: &RangeConstrained<F>) -> Result<Bounded<F>, CircuitsError>{
+        self.lookup.add_and_check(layouter, a, b).map(|sum| self.bounded(sum))
+    }
+
+    //copies a and b in like add_and_check and checks out = a * b is in 0..RANGE, for a < a.bound
+    //and b < b.bound, from this config's bounded or checked by other gadgets. the lookup only
+    //means anything if a * b can't wrap around into 0..RANGE, so a.bound * b.bound has to be
+    //below p. this is checked on bit lengths, so a product of up to F::NUM_BITS - 1 bits
+    pub fn mul_and_check(&self, mut layouter: impl Layouter<F>, a: &Bounded<F>, b: &Bounded<F>) -> Result<Bounded<F>, CircuitsError>{
+        let (bound_a, bound_b) = (a.bound, b.bound);
+        let bits = |bound: u128| (u128::BITS - bound.saturating_sub(1).leading_zeros()) as usize;
+        if bits(bound_a) + bits(bound_b) >= F::NUM_BITS as usize {
+            return Err(CircuitsError::ProductOverflow { bound_a, bound_b });
         }
+        self.lookup.table.ensure_loaded()?;
+        let (a, b) = (&a.cell, &b.cell);
+        let out = a.evaluate().value().copied() * b.evaluate().value().copied();
+        let name = self.lookup.instance_name();
+        let mut layouter = layouter.namespace(|| name.as_str());
+        layouter.assign_region(|| format!("{} mul and check", name), |mut region| {
+            self.q_mul.enable(&mut region, 0)?;
+            a.copy_advice_to(|| "a", &mut region, self.lookup.values, 0)?;
+            b.copy_advice_to(|| "b", &mut region, self.lookup.values, 1)?;
+            self.lookup.q_enable.enable(&mut region, 2)?;
+            match a {
+                RangeConstrained::Assigned(_) => region.assign_advice(|| "out", self.lookup.values, 2, || out.map(Assigned::from)).map(RangeConstrained::Assigned),
+                RangeConstrained::Simple(_) => region.assign_advice(|| "out", self.lookup.values, 2, || out).map(RangeConstrained::Simple),
+            }
+        }).map(|out| self.bounded(out)).map_err(CircuitsError::from)
+    }
+
+}
+
+impl<F: FieldExt, const RANGE: usize> RangeCheck<F> for RangeCheckLookupConfig<F, RANGE> {
+    fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), CircuitsError> {
+        self.table.load(layouter)
     }
 
-    pub fn assign_lookup(&self, mut layouter: impl Layouter<F>, val: Value<Assigned<F>>) -> Result<RangeConstrained<F>, Error>{
+    fn check_value(&self, layouter: impl Layouter<F>, value: Value<Assigned<F>>) -> Result<RangeConstrained<F>, CircuitsError>{
+        self.assign_lookup(layouter, value)
+    }
+
+    fn check_cell(&self, mut layouter: impl Layouter<F>, cell: &AssignedCell<Assigned<F>, F>) -> Result<RangeConstrained<F>, CircuitsError>{
         let offset = 0;
-        layouter.assign_region(|| "assign value", |mut region| {
+        self.table.ensure_loaded()?;
+        let mut layouter = layouter.namespace(|| self.instance_name());
+        layouter.assign_region(|| format!("{} check value", self.instance_name()), |mut region| {
             self.q_enable.enable(&mut region, offset)?;
-            region.assign_advice(||"advice", self.values, offset, ||val)
-                  .map(RangeConstrained)
-        })
+            cell.copy_advice(||"advice", &mut region, self.values, offset)
+                .map(RangeConstrained::Assigned)
+        }).map_err(CircuitsError::from)
+    }
+}
+
+//chip wrapper, RangeCheckInstructions comes from the config's RangeCheck impl
+#[derive(Clone, Debug)]
+pub struct RangeLookupChip<F: FieldExt, const RANGE: usize>{
+    config: RangeCheckLookupConfig<F, RANGE>,
+}
+
+impl<F: FieldExt, const RANGE: usize> RangeLookupChip<F, RANGE> {
+    pub fn construct(config: RangeCheckLookupConfig<F, RANGE>) -> Self {
+        Self { config }
+    }
+
+    pub fn configure(cs: &mut ConstraintSystem<F>, values: Column<Advice>) -> RangeCheckLookupConfig<F, RANGE> {
+        RangeCheckLookupConfig::configure(cs, values)
+    }
+}
+
+impl<F: FieldExt, const RANGE: usize> Chip<F> for RangeLookupChip<F, RANGE> {
+    type Config = RangeCheckLookupConfig<F, RANGE>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt, const RANGE: usize> LookupRangeCheckInstructions<F> for RangeLookupChip<F, RANGE> {
+    fn table(&self) -> TableColumn {
+        self.config.table.value
     }
 }
 
+//range checks every value against the table, each in its own region
+#[derive(Clone, Default)]
+pub struct RangeCheckLookupCircuit<F: FieldExt, const RANGE: usize> {
+    pub lookup_values: Vec<Value<Assigned<F>>>,
+
+}
+impl<F: FieldExt, const RANGE: usize> RangeCheckLookupCircuit<F, RANGE> {
+    pub fn from_values(values: &[u64]) -> Self {
+        Self { lookup_values: values.iter().map(|v| RangeConstrained::witness_u64(*v)).collect() }
+    }
+}
+impl<F: FieldExt, const RANGE: usize> From<Vec<u64>> for RangeCheckLookupCircuit<F, RANGE> {
+    fn from(values: Vec<u64>) -> Self {
+        Self::from_values(&values)
+    }
+}
+impl<F: FieldExt, const RANGE: usize> Circuit<F> for RangeCheckLookupCircuit<F, RANGE> {
+    type Config = RangeCheckLookupConfig<F, RANGE>;
+    type FloorPlanner = SimpleFloorPlanner;
+    fn without_witnesses(&self) -> Self {
+        Self { lookup_values: vec![Value::unknown(); self.lookup_values.len()] }
+    }
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let value = meta.advice_column();
+        RangeCheckLookupConfig::configure(meta, value)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        config.table.load(&mut layouter)?;
+        self.assign_values(&config, layouter)?;
+        Ok(())
+    }
+}
+impl<F: FieldExt, const RANGE: usize> RangeCheckLookupCircuit<F, RANGE> {
+    //the checked cells in order, for circuits wiring them further. see ExposedLookupCircuit
+    //in the tests for copying one out to an instance
+    pub fn assign_values(&self, config: &RangeCheckLookupConfig<F, RANGE>, mut layouter: impl Layouter<F>) -> Result<Vec<RangeConstrained<F>>, Error> {
+        self.lookup_values.iter().map(|v| {
+            config.assign_lookup(layouter.namespace(||"layout"), *v).map_err(Error::from)
+        }).collect()
+    }
+}
+
+//estimates proof size and prover cost of checking num_values values at k without proving,
+//only the shape is measured so the values are left unknown
+pub fn estimate_cost<const RANGE: usize>(k: u32, num_values: usize) -> CircuitCost<Eq, RangeCheckLookupCircuit<Fp, RANGE>> {
+    let circuit = RangeCheckLookupCircuit::<Fp, RANGE> { lookup_values: vec![Value::unknown(); num_values] };
+    CircuitCost::measure(k as usize, &circuit)
+}
+
 #[cfg(test)]
 mod tests{
     use halo2_proofs::{
-        dev::MockProver,
+        dev::{FailureLocation, MockProver, VerifyFailure},
         pasta::Fp,
-        circuit::{Value, SimpleFloorPlanner},
-        plonk::{Any, Circuit, Assigned, ConstraintSystem},
+        plonk::{Any, Instance},
+    };
+    use halo2_gadgets::poseidon::{
+        primitives::{self as poseidon, ConstantLength, P128Pow5T3},
+        Hash, Pow5Chip, Pow5Config,
     };
 
     use super::*;
+    use crate::{params_cache::ParamsCache, proving};
+    use crate::test_utils::{boundary_values, expect_failures, in_range_values, out_of_range_values, value_near, value_u64, values_u64, ExpectedFailure};
+    use proptest::prelude::*;
+    use rand::{rngs::StdRng, SeedableRng};
 
-    //do it for multiple values not just one
+    //two batches packed back to back in one region
     #[derive(Default)]
-    pub struct RangeCheckLookupCircuit<F: FieldExt, const RANGE: usize> {
-        pub lookup_values: Vec<Value<Assigned<F>>>,
-
+    pub struct RangeCheckLookupManyCircuit<F: FieldExt, const RANGE: usize> {
+        pub first: Vec<Value<Assigned<F>>>,
+        pub second: Vec<Value<Assigned<F>>>,
     }
-    impl<F: FieldExt, const RANGE: usize> Circuit<F> for RangeCheckLookupCircuit<F, RANGE> {
+    impl<F: FieldExt, const RANGE: usize> Circuit<F> for RangeCheckLookupManyCircuit<F, RANGE> {
         type Config = RangeCheckLookupConfig<F, RANGE>;
         type FloorPlanner = SimpleFloorPlanner;
         fn without_witnesses(&self) -> Self {
-            Self::default()
+            Self{
+                first: vec![Value::unknown(); self.first.len()],
+                second: vec![Value::unknown(); self.second.len()],
+            }
         }
         fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
             let value = meta.advice_column();
@@ -99,18 +667,628 @@ mod tests{
         fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
             config.table.load(&mut layouter)?;
 
-            self.lookup_values.iter().for_each(|v| {
-                config.assign_lookup(layouter.namespace(||"layout"), *v).unwrap();
+            layouter.assign_region(||"assign batches", |mut region| {
+                let (first, offset) = config.assign_lookup_many(&mut region, 0, &self.first)?;
+                assert_eq!(offset, self.first.len());
+                let (second, offset) = config.assign_lookup_many(&mut region, offset, &self.second)?;
+                assert_eq!(offset, self.first.len() + self.second.len());
+                Ok(())
+            })
+        }
+    }
+
+    //loads the table in two stages before checking the values
+    #[derive(Default)]
+    struct StagedTableCircuit<F: FieldExt> {
+        values: Vec<Value<Assigned<F>>>,
+    }
+    impl<F: FieldExt> Circuit<F> for StagedTableCircuit<F> {
+        type Config = RangeCheckLookupConfig<F, 9>;
+        type FloorPlanner = SimpleFloorPlanner;
+        fn without_witnesses(&self) -> Self {
+            Self{ values: vec![Value::unknown(); self.values.len()] }
+        }
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let value = meta.advice_column();
+            RangeCheckLookupConfig::configure(meta, value)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            layouter.assign_table(||"staged table", |mut table| {
+                config.table.extend(&mut table, 0, 5)?;
+                //later, once the full range is known
+                config.table.extend(&mut table, 5, 9)
+            })?;
+            for v in &self.values {
+                config.assign_lookup(layouter.namespace(||"layout"), *v)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_extend(){
+        let k = 5;
+        //values from both stages
+        let circuit = StagedTableCircuit::<Fp> { values: values_u64(&[0, 4, 5, 8]) };
+        MockProver::run(k, &circuit, vec![]).unwrap().assert_satisfied();
+
+        let circuit = StagedTableCircuit::<Fp> { values: values_u64(&[9]) };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        expect_failures(&prover, &[ExpectedFailure::lookup(0, "range_lookup<9> assign value", 0)]);
+    }
+
+    //checks two values in their own regions, then their sum
+    struct AddCircuit {
+        a: Value<Assigned<Fp>>,
+        //goes through the simple path, so the inputs differ in form too
+        b: Value<Fp>,
+    }
+    impl Circuit<Fp> for AddCircuit {
+        type Config = RangeComposeConfig<Fp, 16>;
+        type FloorPlanner = SimpleFloorPlanner;
+        fn without_witnesses(&self) -> Self {
+            Self{ a: Value::unknown(), b: Value::unknown() }
+        }
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(1);
+            RangeComposeConfig::configure_with_pool(meta, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.lookup.table.load(&mut layouter)?;
+            let a = config.lookup.assign_lookup(layouter.namespace(||"a"), self.a)?;
+            let b = config.lookup.assign_lookup_simple(layouter.namespace(||"b"), self.b)?;
+            let sum = config.lookup.add_and_check(layouter.namespace(||"add"), &a, &b)?;
+            assert!(sum.as_assigned().is_some());
+            sum.value_u64().zip(a.value_u64()).zip(b.value_u64()).assert_if_known(|((sum, a), b)| *sum == a + b);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_add_and_check(){
+        let k = 5;
+        let prover = |a: u64, b: u64| {
+            MockProver::run(k, &AddCircuit { a: value_u64(a), b: Value::known(Fp::from(b)) }, vec![]).unwrap()
+        };
+        prover(7, 8).assert_satisfied();
+        prover(0, 15).assert_satisfied();
+        //both inputs are in range, their sum isn't
+        expect_failures(&prover(8, 8), &[ExpectedFailure::lookup(0, "range_lookup<16> add and check", 2)]);
+    }
+
+    //multiplies two checked values, optionally claiming wider input bounds than the table's
+    struct MulCircuit {
+        a: u64,
+        b: u64,
+        bounds: Option<(u128, u128)>,
+    }
+    impl Circuit<Fp> for MulCircuit {
+        type Config = RangeComposeConfig<Fp, 16>;
+        type FloorPlanner = SimpleFloorPlanner;
+        fn without_witnesses(&self) -> Self {
+            Self{ a: self.a, b: self.b, bounds: self.bounds }
+        }
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(1);
+            RangeComposeConfig::configure_with_pool(meta, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.lookup.table.load(&mut layouter)?;
+            let a = config.lookup.assign_lookup(layouter.namespace(||"a"), value_u64(self.a))?;
+            let b = config.lookup.assign_lookup(layouter.namespace(||"b"), value_u64(self.b))?;
+            let (a, b) = match self.bounds {
+                Some((bound_a, bound_b)) => (Bounded { cell: a, bound: bound_a }, Bounded { cell: b, bound: bound_b }),
+                None => (config.bounded(a), config.bounded(b)),
+            };
+            let out = config.mul_and_check(layouter.namespace(||"mul"), &a, &b);
+            //the precondition fails before anything is assigned
+            out.map(|out| assert_eq!(out.bound, 16)).map_err(|err| {
+                assert!(matches!(err, CircuitsError::ProductOverflow { .. }), "{:?}", err);
+                err.into()
+            })
+        }
+    }
+
+    #[test]
+    fn test_mul_and_check(){
+        let k = 5;
+        let prover = |a: u64, b: u64| MockProver::run(k, &MulCircuit { a, b, bounds: None }, vec![]).unwrap();
+        //15 is the largest value in the table
+        prover(3, 5).assert_satisfied();
+        prover(15, 1).assert_satisfied();
+        expect_failures(&prover(4, 4), &[ExpectedFailure::lookup(0, "range_lookup<16> mul and check", 2)]);
+
+        //254 bits of product still fit below p, 256 don't
+        let bounded = |bounds| MockProver::run(k, &MulCircuit { a: 3, b: 5, bounds: Some(bounds) }, vec![]);
+        bounded((1 << 127, 1 << 127)).unwrap().assert_satisfied();
+        assert!(matches!(bounded((u128::MAX, u128::MAX)), Err(Error::Synthesis)));
+    }
+
+    //the allowed set comes from an array instead of 0..RANGE
+    struct ArrayTableCircuit {
+        values: Vec<u64>,
+    }
+    impl Circuit<Fp> for ArrayTableCircuit {
+        type Config = ArrayLookupConfig<Fp, 4>;
+        type FloorPlanner = SimpleFloorPlanner;
+        fn without_witnesses(&self) -> Self {
+            Self{ values: vec![0; self.values.len()] }
+        }
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(1);
+            ArrayLookupConfig::configure_with_pool(meta, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            const ALLOWED: [u64; 4] = [2, 4, 8, 16];
+            config.table.load_from_array(&mut layouter, &ALLOWED)?;
+            for v in &self.values {
+                config.assign_lookup(layouter.namespace(||"layout"), Value::known(Fp::from(*v)))?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_load_from_array(){
+        let k = 4;
+        let circuit = ArrayTableCircuit { values: vec![8, 2, 16] };
+        MockProver::run(k, &circuit, vec![]).unwrap().assert_satisfied();
+
+        //6 is inside 0..16 but not in the set, and 0 is only in the row off the selector
+        for rejected in [6, 0] {
+            let circuit = ArrayTableCircuit { values: vec![8, rejected] };
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            expect_failures(&prover, &[ExpectedFailure::lookup(0, "array_lookup<4> assign value", 0)]);
+        }
+    }
+
+    //a set with a value missing
+    struct ShortArrayCircuit;
+    impl Circuit<Fp> for ShortArrayCircuit {
+        type Config = ArrayLookupConfig<Fp, 4>;
+        type FloorPlanner = SimpleFloorPlanner;
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(1);
+            ArrayLookupConfig::configure_with_pool(meta, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let err = config.table.load_from_array(&mut layouter, &[2, 4, 8]).unwrap_err();
+            assert!(matches!(err, CircuitsError::LengthMismatch { expected: 4, got: 3 }), "{:?}", err);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_array_length_mismatch(){
+        MockProver::run(4, &ShortArrayCircuit, vec![]).unwrap();
+    }
+
+    //assigns straight from a borrowed slice
+    struct RefCircuit {
+        values: Vec<Value<Fp>>,
+    }
+    impl Circuit<Fp> for RefCircuit {
+        type Config = RangeCheckLookupConfig<Fp, 8>;
+        type FloorPlanner = SimpleFloorPlanner;
+        fn without_witnesses(&self) -> Self {
+            Self{ values: vec![Value::unknown(); self.values.len()] }
+        }
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let value = meta.advice_column();
+            RangeCheckLookupConfig::configure(meta, value)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            for v in self.values.iter() {
+                config.assign_ref(layouter.namespace(||"ref"), v.as_ref())?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_assign_ref(){
+        let k = 4;
+        let values: Vec<Value<Fp>> = [7, 0, 5].iter().map(|&v| Value::known(Fp::from(v))).collect();
+        MockProver::run(k, &RefCircuit { values }, vec![]).unwrap().assert_satisfied();
+        let prover = MockProver::run(k, &RefCircuit { values: vec![Value::known(Fp::from(8))] }, vec![]).unwrap();
+        expect_failures(&prover, &[ExpectedFailure::lookup(0, "range_lookup<8> assign value", 0)]);
+    }
+
+    //sum = a + b with the sum range checked, the gate and the lookup on one selector
+    struct SharedSelectorCircuit {
+        rows: Vec<(u64, u64, u64)>,
+    }
+    #[derive(Clone, Debug)]
+    struct SharedSelectorConfig {
+        a: Column<Advice>,
+        b: Column<Advice>,
+        q_sum: Selector,
+        range: RangeCheckLookupConfig<Fp, 8>,
+    }
+    impl Circuit<Fp> for SharedSelectorCircuit {
+        type Config = SharedSelectorConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        fn without_witnesses(&self) -> Self {
+            Self{ rows: self.rows.clone() }
+        }
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            let sum = meta.advice_column();
+            let q_sum = meta.complex_selector();
+            meta.create_gate("shared add", |cs| {
+                let q = cs.query_selector(q_sum);
+                let a = cs.query_advice(a, Rotation::cur());
+                let b = cs.query_advice(b, Rotation::cur());
+                let sum = cs.query_advice(sum, Rotation::cur());
+                Constraints::with_selector(q, [("sum = a + b", sum - (a + b))])
             });
-            
+            let range = RangeCheckLookupConfig::configure_shared_selector(meta, sum, q_sum);
+            assert_eq!(range.q_enable, q_sum);
+            SharedSelectorConfig { a, b, q_sum, range }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.range.table.load(&mut layouter)?;
+            layouter.assign_region(||"assign sums", |mut region| {
+                for (offset, &(a, b, sum)) in self.rows.iter().enumerate() {
+                    config.q_sum.enable(&mut region, offset)?;
+                    region.assign_advice(|| "a", config.a, offset, || value_u64::<Fp>(a))?;
+                    region.assign_advice(|| "b", config.b, offset, || value_u64::<Fp>(b))?;
+                    region.assign_advice(|| "sum", config.range.values, offset, || value_u64::<Fp>(sum))?;
+                }
+                Ok(())
+            })
+        }
+    }
+
+    #[test]
+    fn test_shared_selector(){
+        let k = 4;
+        let prover = |rows: Vec<(u64, u64, u64)>| MockProver::run(k, &SharedSelectorCircuit { rows }, vec![]).unwrap();
+        prover(vec![(3, 4, 7), (0, 0, 0), (5, 2, 7)]).assert_satisfied();
+        //a correct sum out of range fails the lookup only
+        expect_failures(&prover(vec![(3, 4, 7), (5, 3, 8)]), &[ExpectedFailure::lookup(0, "assign sums", 1)]);
+        //a wrong sum in range fails the gate only
+        expect_failures(&prover(vec![(3, 4, 6)]), &[ExpectedFailure::constraint("shared add", "sum = a + b", "assign sums", 0)]);
+    }
+
+    #[test]
+    fn test_shared_selector_allocates_none(){
+        let mut cs = ConstraintSystem::<Fp>::default();
+        let value = cs.advice_column();
+        let q = cs.complex_selector();
+        RangeCheckLookupConfig::<Fp, 8>::configure_shared_selector(&mut cs, value, q);
+        //halo2 only shows the counts through the pinned constraint system
+        let pinned = format!("{:?}", cs.pinned());
+        assert!(pinned.contains("num_selectors: 1,"));
+        assert!(pinned.contains("gates: [],"));
+    }
+
+    #[test]
+    #[should_panic(expected = "complex selector")]
+    fn test_shared_selector_simple(){
+        let mut cs = ConstraintSystem::<Fp>::default();
+        let value = cs.advice_column();
+        let q = cs.selector();
+        RangeCheckLookupConfig::<Fp, 8>::configure_shared_selector(&mut cs, value, q);
+    }
+
+    //the lookup circuit with its first checked value exposed as the only instance value
+    struct ExposedLookupCircuit(RangeCheckLookupCircuit<Fp, 8>);
+    impl Circuit<Fp> for ExposedLookupCircuit {
+        type Config = (RangeCheckLookupConfig<Fp, 8>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+        fn without_witnesses(&self) -> Self {
+            Self(self.0.without_witnesses())
+        }
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let value = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (RangeCheckLookupConfig::configure(meta, value), instance)
+        }
+
+        fn synthesize(&self, (config, instance): Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            let cells = self.0.assign_values(&config, layouter.namespace(||"values"))?;
+            assert_eq!(cells.len(), self.0.lookup_values.len());
+            layouter.constrain_instance(cells[0].cell(), instance, 0)
+        }
+    }
+
+    #[test]
+    fn test_expose_first(){
+        let k = 4;
+        let circuit = ExposedLookupCircuit(RangeCheckLookupCircuit::from_values(&[5, 2, 7]));
+        MockProver::run(k, &circuit, vec![vec![Fp::from(5)]]).unwrap().assert_satisfied();
+        //another checked value, and the first one off by one
+        for exposed in [2, 6] {
+            let prover = MockProver::run(k, &circuit, vec![vec![Fp::from(exposed)]]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    //forgets to load the table before assigning
+    #[derive(Default)]
+    struct UnloadedTableCircuit;
+    impl Circuit<Fp> for UnloadedTableCircuit {
+        type Config = RangeCheckLookupConfig<Fp, 8>;
+        type FloorPlanner = SimpleFloorPlanner;
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let value = meta.advice_column();
+            RangeCheckLookupConfig::configure(meta, value)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let err = config.assign_lookup(layouter.namespace(||"layout"), value_u64(3)).unwrap_err();
+            assert!(matches!(err, CircuitsError::TableNotLoaded { table: "range table" }), "{:?}", err);
+            //still surfaces as a synthesis error through ?
+            assert!(matches!(Error::from(err), Error::Synthesis));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_table_not_loaded(){
+        let k = 4;
+        MockProver::run(k, &UnloadedTableCircuit, vec![]).unwrap();
+    }
+
+    //one labelled value and a labelled batch
+    #[derive(Default)]
+    struct AnnotatedCircuit<F: FieldExt> {
+        value: Value<Assigned<F>>,
+        batch: Vec<Value<Assigned<F>>>,
+    }
+    impl<F: FieldExt> Circuit<F> for AnnotatedCircuit<F> {
+        type Config = RangeCheckLookupConfig<F, 9>;
+        type FloorPlanner = SimpleFloorPlanner;
+        fn without_witnesses(&self) -> Self {
+            Self{ value: Value::unknown(), batch: vec![Value::unknown(); self.batch.len()] }
+        }
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let value = meta.advice_column();
+            RangeCheckLookupConfig::configure(meta, value)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            config.assign_lookup_annotated(layouter.namespace(||"layout"), "deposit amount", self.value)?;
+            layouter.assign_region(||"withdrawals", |mut region| {
+                config.assign_lookup_many_annotated(&mut region, 0, "withdrawal", &self.batch)?;
+                Ok(())
+            })
+        }
+    }
+
+    #[test]
+    fn test_annotated_failures(){
+        let k = 4;
+        let circuit = AnnotatedCircuit::<Fp> { value: value_u64(12), batch: values_u64(&[1, 10]) };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        expect_failures(
+            &prover,
+            &[ExpectedFailure::lookup(0, "deposit amount", 0), ExpectedFailure::lookup(0, "withdrawals", 1)],
+        );
+    }
+
+    //two configs looking up into one table column the circuit loads itself
+    #[derive(Default)]
+    struct SharedTableCircuit<F: FieldExt> {
+        a: Value<Assigned<F>>,
+        b: Value<Assigned<F>>,
+    }
+    impl<F: FieldExt> Circuit<F> for SharedTableCircuit<F> {
+        type Config = (TableColumn, RangeCheckLookupConfig<F, 9>, RangeCheckLookupConfig<F, 9>);
+        type FloorPlanner = SimpleFloorPlanner;
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let table = meta.lookup_table_column();
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            (
+                table,
+                RangeCheckLookupConfig::configure_with_table(meta, a, Some(table)),
+                RangeCheckLookupConfig::configure_with_table(meta, b, Some(table)),
+            )
+        }
+
+        fn synthesize(&self, (table, a, b): Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            layouter.assign_table(||"identity table", |mut t| {
+                for i in 0..9 {
+                    t.assign_cell(||"identity", table, i, || Value::known(F::from(i as u64)))?;
+                }
+                Ok(())
+            })?;
+            a.assign_lookup(layouter.namespace(||"a"), self.a)?;
+            b.assign_lookup(layouter.namespace(||"b"), self.b)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_shared_table(){
+        let k = 4;
+        let mut cs = ConstraintSystem::<Fp>::default();
+        SharedTableCircuit::<Fp>::configure(&mut cs);
+        //one table column, selectors only turn into fixed columns at keygen
+        assert!(format!("{:?}", cs.pinned()).contains("num_fixed_columns: 1,"));
+
+        let circuit = SharedTableCircuit::<Fp> { a: value_u64(8), b: value_u64(0) };
+        MockProver::run(k, &circuit, vec![]).unwrap().assert_satisfied();
+
+        let circuit = SharedTableCircuit::<Fp> { a: value_u64(3), b: value_u64(9) };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        expect_failures(&prover, &[ExpectedFailure::lookup(1, "range_lookup<9> assign value", 0)]);
+    }
+
+    //range checked inputs copied into a poseidon hash, whose output goes to the instance
+    #[derive(Default)]
+    struct PoseidonInputCircuit {
+        message: [Value<Fp>; 2],
+    }
+    impl Circuit<Fp> for PoseidonInputCircuit {
+        type Config = (RangeCheckLookupConfig<Fp, 16>, Pow5Config<Fp, 3, 2>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let state = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+            let partial_sbox = meta.advice_column();
+            let rc_a = [meta.fixed_column(), meta.fixed_column(), meta.fixed_column()];
+            let rc_b = [meta.fixed_column(), meta.fixed_column(), meta.fixed_column()];
+            meta.enable_constant(rc_b[0]);
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (
+                RangeCheckLookupConfig::configure(meta, state[0]),
+                Pow5Chip::configure::<P128Pow5T3>(meta, state, partial_sbox, rc_a, rc_b),
+                instance,
+            )
+        }
+
+        fn synthesize(&self, (range, poseidon, instance): Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            range.table.load(&mut layouter)?;
+            let a = range.assign_lookup_simple(layouter.namespace(||"a"), self.message[0])?;
+            let b = range.assign_lookup_simple(layouter.namespace(||"b"), self.message[1])?;
+            let hasher = Hash::<_, _, P128Pow5T3, ConstantLength<2>, 3, 2>::init(
+                Pow5Chip::construct(poseidon),
+                layouter.namespace(||"init"),
+            )?;
+            let output = hasher.hash(layouter.namespace(||"hash"), [a.evaluate(), b.evaluate()])?;
+            layouter.constrain_instance(output.cell(), instance, 0)
+        }
+    }
+
+    #[test]
+    fn test_simple_into_poseidon(){
+        let k = 7;
+        let hash = |message: [Fp; 2]| poseidon::Hash::<_, P128Pow5T3, ConstantLength<2>, 3, 2>::init().hash(message);
+        let message = [Fp::from(3), Fp::from(15)];
+        let circuit = PoseidonInputCircuit { message: message.map(Value::known) };
+        MockProver::run(k, &circuit, vec![vec![hash(message)]]).unwrap().assert_satisfied();
+
+        //the hash is bound to the checked cells, a different input changes it
+        let other = PoseidonInputCircuit { message: [Value::known(Fp::from(4)), Value::known(Fp::from(15))] };
+        assert!(MockProver::run(k, &other, vec![vec![hash(message)]]).unwrap().verify().is_err());
+
+        //and an out of range input still fails its lookup
+        let message = [Fp::from(16), Fp::from(15)];
+        let circuit = PoseidonInputCircuit { message: message.map(Value::known) };
+        let prover = MockProver::run(k, &circuit, vec![vec![hash(message)]]).unwrap();
+        expect_failures(&prover, &[ExpectedFailure::lookup(0, "range_lookup<16> assign value", 0)]);
+    }
+
+    //three values checked side by side on one row
+    #[derive(Default)]
+    struct ParallelCircuit<F: FieldExt> {
+        row: Vec<Value<Assigned<F>>>,
+    }
+    impl<F: FieldExt> Circuit<F> for ParallelCircuit<F> {
+        type Config = RangeCheckLookupConfig<F, 9>;
+        type FloorPlanner = SimpleFloorPlanner;
+        fn without_witnesses(&self) -> Self {
+            Self { row: vec![Value::unknown(); self.row.len()] }
+        }
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let columns = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+            RangeCheckLookupConfig::configure_parallel(meta, &columns)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            config.assign_lookup_row(layouter.namespace(||"row"), &self.row)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_num_lookups(){
+        let mut cs = ConstraintSystem::<Fp>::default();
+        let value = cs.advice_column();
+        assert_eq!(RangeCheckLookupConfig::<Fp, 9>::configure(&mut cs, value).num_lookups(), 1);
+        assert_eq!(ParallelCircuit::<Fp>::configure(&mut cs).num_lookups(), 3);
+        //which is what the constraint system ends up with
+        assert!(format!("{:?}", cs.pinned()).matches("input_expressions").count() == 4);
+    }
+
+    #[test]
+    fn test_parallel(){
+        let k = 4;
+        let circuit = ParallelCircuit::<Fp> { row: values_u64(&[0, 4, 8]) };
+        MockProver::run(k, &circuit, vec![]).unwrap().assert_satisfied();
+
+        //only the third column's lookup fails
+        let circuit = ParallelCircuit::<Fp> { row: values_u64(&[0, 4, 9]) };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        expect_failures(&prover, &[ExpectedFailure::lookup(2, "range_lookup<9> x3 assign row", 0)]);
+
+        let circuit = ParallelCircuit::<Fp> { row: values_u64(&[0, 4]) };
+        assert!(matches!(MockProver::run(k, &circuit, vec![]), Err(Error::Synthesis)));
+    }
+
+    //a 4-bit and an 8-bit check side by side
+    #[derive(Default)]
+    struct TwoBoundsCircuit {
+        small: Value<Assigned<Fp>>,
+        large: Value<Assigned<Fp>>,
+    }
+    impl Circuit<Fp> for TwoBoundsCircuit {
+        type Config = (RangeCheckLookupConfig<Fp, 16>, RangeCheckLookupConfig<Fp, 256>);
+        type FloorPlanner = SimpleFloorPlanner;
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let small = meta.advice_column();
+            let large = meta.advice_column();
+            (RangeCheckLookupConfig::configure(meta, small), RangeCheckLookupConfig::configure(meta, large))
+        }
+
+        fn synthesize(&self, (small, large): Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            small.table.load(&mut layouter)?;
+            large.table.load(&mut layouter)?;
+            small.assign_lookup(layouter.namespace(||"small"), self.small)?;
+            large.assign_lookup(layouter.namespace(||"large"), self.large)?;
             Ok(())
         }
     }
 
+    #[test]
+    fn test_region_names_carry_bound(){
+        let k = 9;
+        let circuit = TwoBoundsCircuit { small: value_u64(16), large: value_u64(256) };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        expect_failures(
+            &prover,
+            &[
+                ExpectedFailure::lookup(0, "range_lookup<16> assign value", 0),
+                ExpectedFailure::lookup(1, "range_lookup<256> assign value", 0),
+            ],
+        );
+    }
+
     #[test]
     fn test_complete(){
         let k = 4;
-        let lookup_values = vec![Value::known(Fp::from(2 as u64)).into(),Value::known(Fp::from(5 as u64).into())];
+        let lookup_values = vec![value_u64(2), value_u64(5)];
         const RANGE: usize = 9;
         let circuit = RangeCheckLookupCircuit::<Fp, RANGE> {
             lookup_values: lookup_values
@@ -120,15 +1298,230 @@ mod tests{
     }
     
     #[test]
-    #[should_panic]
+    fn test_from_u64s(){
+        let k = 4;
+        let circuit: RangeCheckLookupCircuit<Fp, 9> = vec![0, 3, 8].into();
+        MockProver::run(k, &circuit, vec![]).unwrap().assert_satisfied();
+
+        let circuit: RangeCheckLookupCircuit<Fp, 9> = vec![9].into();
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        expect_failures(&prover, &[ExpectedFailure::lookup(0, "range_lookup<9> assign value", 0)]);
+    }
+
+    #[test]
     fn test_sound(){
         let k = 4;
-        let lookup_values = vec![Value::known(Fp::from(25 as u64)).into(),Value::known(Fp::from(24 as u64).into())];
+        let lookup_values = vec![value_u64(25), value_u64(24)];
         const RANGE: usize = 9;
         let circuit = RangeCheckLookupCircuit::<Fp, RANGE> {
             lookup_values: lookup_values
         };
         let prover =MockProver::run(k, &circuit, vec![]).unwrap();
+        expect_failures(
+            &prover,
+            &[ExpectedFailure::lookup(0, "range_lookup<9> assign value", 0), ExpectedFailure::lookup(0, "range_lookup<9> assign value", 0)],
+        );
+    }
+
+    #[test]
+    fn test_generated(){
+        let k = 4;
+        const RANGE: usize = 9;
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let mut lookup_values = in_range_values(RANGE as u64, 4, &mut rng);
+        lookup_values.extend(boundary_values(RANGE as u64));
+        let circuit = RangeCheckLookupCircuit::<Fp, RANGE> { lookup_values };
+        MockProver::run(k, &circuit, vec![]).unwrap().assert_satisfied();
+
+        let lookup_values = out_of_range_values(RANGE as u64, 4, &mut rng);
+        let circuit = RangeCheckLookupCircuit::<Fp, RANGE> { lookup_values };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        //every value is rejected on its own
+        expect_failures(&prover, &vec![ExpectedFailure::lookup(0, "range_lookup<9> assign value", 0); 4]);
+    }
+
+    #[test]
+    fn test_assign_lookup_many_chained(){
+        let k = 4;
+        const RANGE: usize = 9;
+        let circuit = RangeCheckLookupManyCircuit::<Fp, RANGE> {
+            first: vec![value_u64(1), value_u64(8)],
+            second: vec![value_u64(0), value_u64(3), value_u64(7)],
+        };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
         prover.assert_satisfied();
+
+        //the second batch is still checked when placed at the returned offset
+        let circuit = RangeCheckLookupManyCircuit::<Fp, RANGE> {
+            first: vec![value_u64(1)],
+            second: vec![value_u64(3), value_u64(9)],
+        };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    //lookup failures aren't reported in region order
+    fn assert_failures(actual: Result<(), Vec<VerifyFailure>>, expected: Vec<VerifyFailure>){
+        let actual = actual.unwrap_err();
+        assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+        for failure in expected {
+            assert!(actual.contains(&failure), "missing {:?} in {:?}", failure, actual);
+        }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_sound_failure_locations(){
+        let k = 4;
+        const RANGE: usize = 9;
+        let circuit = RangeCheckLookupCircuit::<Fp, RANGE>::from_values(&[25, 3, 24]);
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        //the table is region 0, then every value gets a region of its own
+        assert_failures(
+            prover.verify(),
+            vec![
+                VerifyFailure::Lookup {
+                    lookup_index: 0,
+                    location: FailureLocation::InRegion { region: (1, "range_lookup<9> assign value").into(), offset: 0 },
+                },
+                VerifyFailure::Lookup {
+                    lookup_index: 0,
+                    location: FailureLocation::InRegion { region: (3, "range_lookup<9> assign value").into(), offset: 0 },
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn test_assign_lookup_many_failure_locations(){
+        let k = 4;
+        const RANGE: usize = 9;
+        let circuit = RangeCheckLookupManyCircuit::<Fp, RANGE> {
+            first: vec![value_u64(1), value_u64(9)],
+            second: vec![value_u64(3), value_u64(10)],
+        };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        //both batches share one region, offsets continue across the two calls
+        assert_failures(
+            prover.verify(),
+            vec![
+                VerifyFailure::Lookup {
+                    lookup_index: 0,
+                    location: FailureLocation::InRegion { region: (1, "assign batches").into(), offset: 1 },
+                },
+                VerifyFailure::Lookup {
+                    lookup_index: 0,
+                    location: FailureLocation::InRegion { region: (1, "assign batches").into(), offset: 3 },
+                },
+            ],
+        );
+    }
+
+    //the bounds the property test draws from
+    const TEST_BOUNDS: [usize; 9] = [1, 2, 3, 4, 7, 8, 9, 15, 16];
+
+    //the bound is picked at runtime, so one circuit type covers every bound the property
+    //test draws. configure sets up a lookup per bound on a shared column and synthesize
+    //uses the one asked for
+    //RangeCheckLookupConfig with the bound a runtime value instead of a const generic, so the
+    //property test can build a lookup per bound. the table holding 0..bound is its own
+    #[derive(Clone, Debug)]
+    struct RangeBoundLookupConfig {
+        values: Column<Advice>,
+        q_enable: Selector,
+        table: TableColumn,
+        bound: usize,
+    }
+
+    impl RangeBoundLookupConfig {
+        fn configure(cs: &mut ConstraintSystem<Fp>, values: Column<Advice>, bound: usize) -> Self {
+            let q_enable = cs.complex_selector();
+            let table = cs.lookup_table_column();
+            cs.lookup(|cs| {
+                let q = cs.query_selector(q_enable);
+                let v = cs.query_advice(values, Rotation::cur());
+                vec![(q * v, table)]
+            });
+            Self { values, q_enable, table, bound }
+        }
+
+        fn load(&self, layouter: &mut impl Layouter<Fp>) -> Result<(), CircuitsError> {
+            let rows: Vec<_> = (0..self.bound as u64).map(|i| vec![Fp::from(i)]).collect();
+            rows::load_table(layouter, &format!("range_table<{}>", self.bound), &[("assign lookup table value", self.table)], &rows, TablePadding::Unpadded, None)
+        }
+
+        fn assign_lookup(&self, mut layouter: impl Layouter<Fp>, val: Value<Assigned<Fp>>) -> Result<RangeConstrained<Fp>, CircuitsError> {
+            let name = format!("range_lookup<{}> assign value", self.bound);
+            layouter.assign_region(|| name.as_str(), |mut region| {
+                self.q_enable.enable(&mut region, 0)?;
+                region.assign_advice(|| name.as_str(), self.values, 0, || val)
+                      .map(RangeConstrained::Assigned)
+            }).map_err(CircuitsError::from)
+        }
+    }
+
+    struct RangeBoundCircuit {
+        bound: usize,
+        values: Vec<Value<Assigned<Fp>>>,
+    }
+
+    impl Circuit<Fp> for RangeBoundCircuit {
+        type Config = Vec<RangeBoundLookupConfig>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { bound: self.bound, values: vec![Value::unknown(); self.values.len()] }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let value = cs.advice_column();
+            TEST_BOUNDS.iter().map(|&bound| RangeBoundLookupConfig::configure(cs, value, bound)).collect()
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            for config in &config {
+                config.load(&mut layouter)?;
+            }
+            let config = config.iter().find(|config| config.bound == self.bound).unwrap();
+            for value in &self.values {
+                config.assign_lookup(layouter.namespace(|| "value"), *value)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn accepts_range(bound: usize, values: &[u64]) -> bool{
+        let k = 5;
+        let circuit = RangeBoundCircuit { bound, values: values_u64(values) };
+        MockProver::run(k, &circuit, vec![]).unwrap().verify().is_ok()
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn prop_lookup_matches_native(
+            (range, values) in prop::sample::select(TEST_BOUNDS.to_vec())
+                .prop_flat_map(|range| (Just(range), prop::collection::vec(value_near(range), 1..8)))
+        ){
+            let expected = values.iter().all(|v| *v < range as u64);
+            prop_assert_eq!(accepts_range(range, &values), expected);
+        }
+    }
+
+    #[test]
+    fn test_estimate_cost(){
+        let cost = estimate_cost::<8>(5, 3);
+        let proof_size: usize = cost.proof_size(0).into();
+        //a few dozen commitments and evaluations of 32 bytes each
+        assert!((500..10_000).contains(&proof_size), "{}", proof_size);
+        //and close to what the prover actually produces
+        let circuit = RangeCheckLookupCircuit::<Fp, 8>::from_values(&[1, 2, 3]);
+        let (params, pk) = proving::keygen(&ParamsCache::new(), 5, &circuit.without_witnesses()).unwrap();
+        let proof = proving::create_proof(&params, &pk, circuit, &vec![]).unwrap();
+        assert!(proof.len() <= 2 * proof_size && proof_size <= 2 * proof.len(), "estimated {}, proved {}", proof_size, proof.len());
+        //more values at the same k only add rows, not columns
+        let more: usize = estimate_cost::<8>(5, 10).proof_size(0).into();
+        assert_eq!(more, proof_size);
+    }
+}
@@ -0,0 +1,163 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Value},
+    plonk::{Advice, Assigned, Column, ConstraintSystem, Error, Fixed, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use std::marker::PhantomData;
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, report, rows::{self, RowBudget, TablePadding}, RangeConstrained};
+
+//range check where row i has its own bound, bounds[i], fixed at keygen.
+//
+//row i carries the tag i + 1 in a fixed column and (tag, value) is looked up in a table
+//holding (i + 1, v) for every v < bounds[i]. tag 0 is kept for disabled rows, which look
+//up (0, 0)
+#[derive(Clone, Debug)]
+pub struct ScheduledRangeLookupConfig<F: FieldExt> {
+    pub values: Column<Advice>,
+    pub tag: Column<Fixed>,
+    pub q_enable: Selector,
+    pub table_tag: TableColumn,
+    pub table_value: TableColumn,
+    pub bounds: Vec<u64>,
+    //what load_padded puts in the rows past the schedule
+    pub padding: TablePadding,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> ScheduledRangeLookupConfig<F> {
+    pub fn configure_with_schedule(cs: &mut ConstraintSystem<F>, values: Column<Advice>, bounds: &[u64]) -> Self {
+        Self::configure_padded(cs, values, bounds, TablePadding::Unpadded)
+    }
+
+    pub fn configure_padded(cs: &mut ConstraintSystem<F>, values: Column<Advice>, bounds: &[u64], padding: TablePadding) -> Self {
+        assert!(!bounds.is_empty());
+        let q_enable = cs.complex_selector();
+        let tag = cs.fixed_column();
+        let table_tag = cs.lookup_table_column();
+        let table_value = cs.lookup_table_column();
+
+        cs.enable_equality(values);
+
+        let lookup_index = cs.lookup(|cs| {
+            let q = cs.query_selector(q_enable);
+            let tag = cs.query_fixed(tag, Rotation::cur());
+            let value = cs.query_advice(values, Rotation::cur());
+            vec![(q.clone() * tag, table_tag), (q * value, table_value)]
+        });
+        report::annotate_lookup("range_schedule", lookup_index);
+
+        Self {
+            values,
+            tag,
+            q_enable,
+            table_tag,
+            table_value,
+            bounds: bounds.to_vec(),
+            padding: padding.for_rows(1 + bounds.iter().sum::<u64>() as usize),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>, bounds: &[u64]) -> Self {
+        let values = pool.advice(cs);
+        Self::configure_with_schedule(cs, values, bounds)
+    }
+
+    fn rows(&self) -> Vec<Vec<F>> {
+        std::iter::once((0, 0))
+            .chain(self.bounds.iter().enumerate().flat_map(|(i, bound)| (0..*bound).map(move |v| (i as u64 + 1, v))))
+            .map(|(tag, value)| vec![F::from(tag), F::from(value)])
+            .collect()
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), CircuitsError> {
+        rows::load_table(layouter, "range schedule table", &[("tag", self.table_tag), ("value", self.table_value)], &self.rows(), self.padding, None)
+    }
+
+    //load that first checks the table fits in the budget, then fills the rest of its usable
+    //rows as the padding says
+    pub fn load_padded(&self, layouter: &mut impl Layouter<F>, budget: RowBudget) -> Result<(), CircuitsError> {
+        rows::load_table(layouter, "range schedule table", &[("tag", self.table_tag), ("value", self.table_value)], &self.rows(), self.padding, Some(budget))
+    }
+
+    //one value per bound, in schedule order. the tags are assigned here along with them
+    pub fn assign(&self, mut layouter: impl Layouter<F>, values: &[Value<Assigned<F>>]) -> Result<Vec<RangeConstrained<F>>, CircuitsError> {
+        if values.len() != self.bounds.len() {
+            return Err(CircuitsError::LengthMismatch { expected: self.bounds.len(), got: values.len() });
+        }
+        layouter.assign_region(
+            || "assign range schedule",
+            |mut region| {
+                values.iter().enumerate().map(|(offset, value)| {
+                    self.q_enable.enable(&mut region, offset)?;
+                    region.assign_fixed(|| "tag", self.tag, offset, || Value::known(F::from(offset as u64 + 1)))?;
+                    region.assign_advice(|| "value", self.values, offset, || *value)
+                        .map(RangeConstrained::Assigned)
+                }).collect::<Result<Vec<_>, Error>>()
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, values_u64, ExpectedFailure};
+
+    const SCHEDULE: [u64; 3] = [16, 256, 4];
+
+    #[derive(Default)]
+    struct ScheduleCircuit {
+        values: Vec<Value<Assigned<Fp>>>,
+    }
+
+    impl Circuit<Fp> for ScheduleCircuit {
+        type Config = ScheduledRangeLookupConfig<Fp>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { values: vec![Value::unknown(); self.values.len()] }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let values = cs.advice_column();
+            ScheduledRangeLookupConfig::configure_with_schedule(cs, values, &SCHEDULE)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.load(&mut layouter)?;
+            config.assign(layouter.namespace(|| "schedule"), &self.values)?;
+            Ok(())
+        }
+    }
+
+    fn prover(values: &[u64]) -> MockProver<Fp> {
+        let k = 9;
+        MockProver::run(k, &ScheduleCircuit { values: values_u64(values) }, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_schedule() {
+        prover(&[15, 255, 3]).assert_satisfied();
+        prover(&[0, 0, 0]).assert_satisfied();
+    }
+
+    #[test]
+    fn test_schedule_violations() {
+        let at = |row| [ExpectedFailure::lookup(0, "assign range schedule", row)];
+        expect_failures(&prover(&[16, 0, 0]), &at(0));
+        //within the second bound, but row 0 only allows 0..16
+        expect_failures(&prover(&[200, 0, 0]), &at(0));
+        expect_failures(&prover(&[0, 256, 0]), &at(1));
+        expect_failures(&prover(&[0, 0, 4]), &at(2));
+
+        let circuit = ScheduleCircuit { values: values_u64(&[0, 0]) };
+        assert!(matches!(MockProver::run(9, &circuit, vec![]), Err(Error::Synthesis)));
+    }
+}
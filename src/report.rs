@@ -0,0 +1,392 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    dev::{FailureLocation, MockProver, VerifyFailure},
+    plonk::{Advice, Any, Circuit, Column, ColumnType, Error, Selector, TableColumn},
+};
+
+use std::{cell::RefCell, collections::BTreeMap, collections::HashMap, fmt};
+
+//which gadget owns which gate/lookup. configs write into this during configure, and
+//check_circuit clears it before running the prover so only the checked circuit is in it
+#[derive(Debug, Default)]
+struct Registry {
+    gates: HashMap<&'static str, &'static str>,
+    lookups: HashMap<usize, &'static str>,
+}
+
+thread_local! {
+    static REGISTRY: RefCell<Registry> = RefCell::new(Registry::default());
+}
+
+pub fn annotate_gate(gadget: &'static str, gate: &'static str) {
+    REGISTRY.with(|r| r.borrow_mut().gates.insert(gate, gadget));
+}
+
+pub fn annotate_lookup(gadget: &'static str, lookup_index: usize) {
+    REGISTRY.with(|r| r.borrow_mut().lookups.insert(lookup_index, gadget));
+}
+
+const UNKNOWN: &str = "unknown";
+
+fn gadget_for_gate(gate: &str) -> String {
+    REGISTRY.with(|r| r.borrow().gates.get(gate).copied().unwrap_or(UNKNOWN).to_string())
+}
+
+fn gadget_for_lookup(lookup_index: usize) -> String {
+    REGISTRY.with(|r| r.borrow().lookups.get(&lookup_index).copied().unwrap_or(UNKNOWN).to_string())
+}
+
+//the metadata fields are private so names are read back from the display forms, e.g.
+//"Constraint 0 ('name') in gate 0 ('gate')" gives ["name", "gate"]
+pub(crate) fn quoted_names(display: &str) -> Vec<&str> {
+    display
+        .split("('")
+        .skip(1)
+        .filter_map(|s| s.split_once("')").map(|(name, _)| name))
+        .collect()
+}
+
+//the gate name of "Gate 0 ('gate')" or "Constraint 0 ('name') in gate 0 ('gate')" is the
+//last quoted name
+fn gate_name(display: &str) -> &str {
+    quoted_names(display).last().copied().unwrap_or(UNKNOWN)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FailureRow {
+    pub gadget: String,
+    //region name, or "outside any region"
+    pub region: String,
+    //offset within the region, or the absolute row outside regions
+    pub row: usize,
+    pub constraint: String,
+    pub cell_values: Vec<(String, String)>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CircuitReport {
+    pub failures: Vec<FailureRow>,
+}
+
+impl CircuitReport {
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    pub fn by_gadget(&self) -> BTreeMap<&str, Vec<&FailureRow>> {
+        let mut groups: BTreeMap<&str, Vec<&FailureRow>> = BTreeMap::new();
+        for failure in &self.failures {
+            groups.entry(&failure.gadget).or_default().push(failure);
+        }
+        groups
+    }
+}
+
+impl fmt::Display for CircuitReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ok() {
+            return writeln!(f, "all constraints satisfied");
+        }
+        writeln!(f, "{:<16} {:<32} {:>5}  {:<40} {}", "gadget", "region", "row", "constraint", "cells")?;
+        for (gadget, failures) in self.by_gadget() {
+            for failure in failures {
+                let cells: Vec<String> = failure.cell_values.iter().map(|(cell, value)| format!("{} = {}", cell, value)).collect();
+                writeln!(
+                    f,
+                    "{:<16} {:<32} {:>5}  {:<40} {}",
+                    gadget,
+                    failure.region,
+                    failure.row,
+                    failure.constraint,
+                    cells.join(", ")
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn location(location: &FailureLocation) -> (String, usize) {
+    match location {
+        FailureLocation::InRegion { region, offset } => (region.to_string(), *offset),
+        FailureLocation::OutsideRegion { row } => ("outside any region".to_string(), *row),
+    }
+}
+
+fn row(failure: &VerifyFailure) -> FailureRow {
+    match failure {
+        VerifyFailure::ConstraintNotSatisfied { constraint, location: loc, cell_values } => {
+            let constraint = constraint.to_string();
+            let (region, row) = location(loc);
+            FailureRow {
+                gadget: gadget_for_gate(gate_name(&constraint)),
+                region,
+                row,
+                constraint,
+                cell_values: cell_values.iter().map(|(cell, value)| (cell.to_string(), value.clone())).collect(),
+            }
+        }
+        VerifyFailure::Lookup { lookup_index, location: loc } => {
+            let (region, row) = location(loc);
+            FailureRow {
+                gadget: gadget_for_lookup(*lookup_index),
+                region,
+                row,
+                constraint: format!("lookup {}", lookup_index),
+                cell_values: vec![],
+            }
+        }
+        VerifyFailure::CellNotAssigned { gate, region, offset, column, .. } => {
+            let gate = gate.to_string();
+            FailureRow {
+                gadget: gadget_for_gate(gate_name(&gate)),
+                region: region.to_string(),
+                row: (*offset).max(0) as usize,
+                constraint: format!("{} cell not assigned", gate),
+                cell_values: vec![(format!("{:?}", column), "unassigned".to_string())],
+            }
+        }
+        VerifyFailure::Permutation { column, location: loc } => {
+            let (region, row) = location(loc);
+            FailureRow {
+                gadget: "permutation".to_string(),
+                region,
+                row,
+                constraint: format!("copy constraint on {}", column),
+                cell_values: vec![],
+            }
+        }
+        other => FailureRow {
+            gadget: UNKNOWN.to_string(),
+            region: UNKNOWN.to_string(),
+            row: 0,
+            constraint: other.to_string(),
+            cell_values: vec![],
+        },
+    }
+}
+
+//what a config's columns are for, so composed circuits can dump who owns which column
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnDescription {
+    pub role: String,
+    //advice, fixed, instance, selector or table
+    pub kind: &'static str,
+    //None when halo2 doesn't give the index away
+    pub index: Option<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigDescription {
+    pub gadget: &'static str,
+    pub columns: Vec<ColumnDescription>,
+}
+
+//halo2 keeps every index accessor pub(crate), so the index is read from the Debug forms
+//"Column { index: 1, .. }", "Selector(2, true)" and "TableColumn { inner: Column { index: 0, .. } }"
+fn debug_index(debug: &str) -> Option<usize> {
+    let digits = match debug.split_once("index: ") {
+        Some((_, rest)) => rest,
+        None => debug.split_once('(')?.1,
+    };
+    digits.chars().take_while(|c| c.is_ascii_digit()).collect::<String>().parse().ok()
+}
+
+impl ConfigDescription {
+    pub fn new(gadget: &'static str) -> Self {
+        Self { gadget, columns: vec![] }
+    }
+
+    fn push(mut self, role: impl Into<String>, kind: &'static str, index: Option<usize>) -> Self {
+        self.columns.push(ColumnDescription { role: role.into(), kind, index });
+        self
+    }
+
+    pub fn column<C: ColumnType>(self, role: impl Into<String>, column: Column<C>) -> Self {
+        let any: Any = (*column.column_type()).into();
+        let kind = match any {
+            Any::Advice => "advice",
+            Any::Fixed => "fixed",
+            Any::Instance => "instance",
+        };
+        self.push(role, kind, debug_index(&format!("{:?}", column)))
+    }
+
+    pub fn advice(self, role: impl Into<String>, column: Column<Advice>) -> Self {
+        self.column(role, column)
+    }
+
+    pub fn selector(self, role: impl Into<String>, selector: Selector) -> Self {
+        self.push(role, "selector", debug_index(&format!("{:?}", selector)))
+    }
+
+    pub fn table(self, role: impl Into<String>, column: TableColumn) -> Self {
+        self.push(role, "table", debug_index(&format!("{:?}", column)))
+    }
+
+    //appends a sub config's columns, e.g. a gadget's lookup table, roles prefixed with
+    //the sub config's gadget
+    pub fn extend(mut self, other: ConfigDescription) -> Self {
+        self.columns.extend(other.columns.into_iter().map(|column| ColumnDescription {
+            role: format!("{} {}", other.gadget, column.role),
+            ..column
+        }));
+        self
+    }
+}
+
+impl fmt::Display for ConfigDescription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.gadget)?;
+        for column in &self.columns {
+            let index = column.index.map_or("-".to_string(), |index| index.to_string());
+            writeln!(f, "  {:<16} {:<8} {:>3}", column.role, column.kind, index)?;
+        }
+        Ok(())
+    }
+}
+
+//(kind, index) -> every "gadget role" using it. anything with more than one owner is shared.
+//columns without an index are left out
+pub fn column_owners(descriptions: &[ConfigDescription]) -> BTreeMap<(&'static str, usize), Vec<String>> {
+    let mut owners: BTreeMap<(&'static str, usize), Vec<String>> = BTreeMap::new();
+    for description in descriptions {
+        for column in &description.columns {
+            let index = match column.index {
+                Some(index) => index,
+                None => continue,
+            };
+            owners
+                .entry((column.kind, index))
+                .or_default()
+                .push(format!("{} {}", description.gadget, column.role));
+        }
+    }
+    owners
+}
+
+//runs the mock prover and maps its failures back to the gadgets that produced them,
+//printing the table when anything failed
+pub fn check_circuit<F: FieldExt, C: Circuit<F>>(k: u32, circuit: &C, instances: Vec<Vec<F>>) -> Result<CircuitReport, Error> {
+    REGISTRY.with(|r| *r.borrow_mut() = Registry::default());
+    let prover = MockProver::run(k, circuit, instances)?;
+    let failures = prover.verify().err().unwrap_or_default();
+    let report = CircuitReport {
+        failures: failures.iter().map(row).collect(),
+    };
+    if !report.is_ok() {
+        println!("{}", report);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        pasta::Fp,
+        plonk::{Assigned, ConstraintSystem},
+    };
+
+    use super::*;
+    use crate::test_utils::value_u64;
+    use crate::{range_check::RangeCheckCircuitConfig, range_lookup::RangeCheckLookupConfig};
+
+    #[derive(Default)]
+    struct ComposedCircuit {
+        gate_value: Value<Assigned<Fp>>,
+        lookup_value: Value<Assigned<Fp>>,
+    }
+
+    impl Circuit<Fp> for ComposedCircuit {
+        type Config = (RangeCheckCircuitConfig<Fp, 4>, RangeCheckLookupConfig<Fp, 8>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = cs.advice_column();
+            let b = cs.advice_column();
+            (RangeCheckCircuitConfig::configure(cs, a), RangeCheckLookupConfig::configure(cs, b))
+        }
+
+        fn synthesize(&self, (gate, lookup): Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            lookup.table.load(&mut layouter)?;
+            gate.assign(layouter.namespace(|| "gate"), self.gate_value)?;
+            lookup.assign_lookup(layouter.namespace(|| "lookup"), self.lookup_value)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_report_groups_by_gadget() {
+        let circuit = ComposedCircuit {
+            gate_value: value_u64(5),
+            lookup_value: value_u64(9),
+        };
+        let report = check_circuit(4, &circuit, vec![]).unwrap();
+
+        let groups = report.by_gadget();
+        assert_eq!(groups.keys().copied().collect::<Vec<_>>(), vec!["range_check", "range_lookup"]);
+
+        let gate = groups["range_check"][0];
+        assert_eq!(gate.region, "Region 1 ('range_check<4> assign range val')");
+        assert_eq!(gate.row, 0);
+        assert_eq!(gate.cell_values[0].1, "0x5");
+        assert!(gate.constraint.contains("range check"));
+
+        let lookup = groups["range_lookup"][0];
+        assert_eq!(lookup.region, "Region 2 ('range_lookup<8> assign value')");
+        assert_eq!(lookup.constraint, "lookup 0");
+
+        let table = report.to_string();
+        assert!(table.contains("range_check") && table.contains("range_lookup"));
+    }
+
+    #[test]
+    fn test_describe_composed() {
+        let mut cs = ConstraintSystem::<Fp>::default();
+        let (gate, lookup) = ComposedCircuit::configure(&mut cs);
+        let descriptions = [gate.describe(), lookup.describe()];
+
+        let index = |description: &ConfigDescription, role: &str| {
+            description.columns.iter().find(|c| c.role == role).unwrap().index
+        };
+        assert_ne!(index(&descriptions[0], "value"), index(&descriptions[1], "values"));
+        //every column has a single owner
+        assert!(column_owners(&descriptions).values().all(|owners| owners.len() == 1));
+
+        //columns take their kind from halo2, every index is read from Debug
+        let mut fresh = ConstraintSystem::<Fp>::default();
+        fresh.fixed_column();
+        let fixed = fresh.fixed_column();
+        assert_eq!(ConfigDescription::new("fixed").column("c", fixed).columns[0], ColumnDescription { role: "c".into(), kind: "fixed", index: Some(1) });
+        assert!(descriptions[1].columns.iter().all(|c| c.index.is_some()));
+
+        let dump = lookup.to_string();
+        assert!(dump.starts_with("range_lookup\n"));
+        assert!(dump.contains("values") && dump.contains("advice") && dump.contains("range_table value"));
+    }
+
+    #[test]
+    fn test_shared_column_owners() {
+        let mut cs = ConstraintSystem::<Fp>::default();
+        let value = cs.advice_column();
+        let a = RangeCheckCircuitConfig::<Fp, 4>::configure(&mut cs, value);
+        let b = RangeCheckLookupConfig::<Fp, 8>::configure(&mut cs, value);
+        let owners = column_owners(&[a.describe(), b.describe()]);
+        assert_eq!(owners[&("advice", 0)], vec!["range_check value", "range_lookup values"]);
+    }
+
+    #[test]
+    fn test_report_ok() {
+        let circuit = ComposedCircuit {
+            gate_value: value_u64(3),
+            lookup_value: value_u64(7),
+        };
+        assert!(check_circuit(4, &circuit, vec![]).unwrap().is_ok());
+    }
+}
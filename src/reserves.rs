@@ -0,0 +1,315 @@
+#![allow(warnings, unused)]
+use halo2_gadgets::poseidon::{
+    primitives::{self as poseidon, ConstantLength, P128Pow5T3},
+    Hash, Pow5Chip, Pow5Config,
+};
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    pasta::Fp,
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Constraints, Error, Instance, Selector},
+    poly::Rotation,
+};
+
+use crate::{column_pool::ColumnPool, monotonic::MonotonicConfig, proving::Instances, report, running_sum::RunningSumConfig};
+
+//balances and the liability are u64s
+pub const BALANCE_BITS: usize = 64;
+//the total of up to 256 balances, and the steps between leaf indices, fit in this many bits
+const TOTAL_BITS: usize = BALANCE_BITS + 8;
+//k the circuit fits in for a handful of paths into a small tree
+pub const RESERVES_K: u32 = 10;
+
+//instance rows
+const ROOT_ROW: usize = 0;
+const LIABILITY_ROW: usize = 1;
+
+fn hash_pair(left: Fp, right: Fp) -> Fp {
+    poseidon::Hash::<_, P128Pow5T3, ConstantLength<2>, 3, 2>::init().hash([left, right])
+}
+
+//a leaf commits to an account id and its balance
+pub fn leaf_hash(id: u64, balance: u64) -> Fp {
+    hash_pair(Fp::from(id), Fp::from(balance))
+}
+
+//every level of the tree over the leaves, from the leaves up to the root. None unless there
+//are 2^depth leaves for some depth
+pub fn tree_levels(leaves: &[Fp]) -> Option<Vec<Vec<Fp>>> {
+    if !leaves.len().is_power_of_two() {
+        return None;
+    }
+    let mut levels = vec![leaves.to_vec()];
+    while levels.last().unwrap().len() > 1 {
+        let level = levels.last().unwrap().chunks(2).map(|pair| hash_pair(pair[0], pair[1])).collect();
+        levels.push(level);
+    }
+    Some(levels)
+}
+
+#[derive(Clone, Debug)]
+pub struct ReservesConfig {
+    //ids, balances and leaf indices, and the liability copied from the instance
+    pub private: Column<Advice>,
+    //one merkle level per row, the node and its sibling put in order by the index bit
+    pub node: Column<Advice>,
+    pub sibling: Column<Advice>,
+    pub bit: Column<Advice>,
+    pub left: Column<Advice>,
+    pub right: Column<Advice>,
+    pub q_swap: Selector,
+    //running sum of the balances
+    pub total: RunningSumConfig<Fp>,
+    pub instance: Column<Instance>,
+    //strictly increasing leaf indices, so no leaf is counted twice, and liability < total.
+    //its bit decomposition also range checks the balances, indices and liability
+    pub increasing: MonotonicConfig<Fp, TOTAL_BITS>,
+    pub poseidon: Pow5Config<Fp, 3, 2>,
+}
+
+//proves the balances of K distinct leaves of a public merkle root add up to more than a
+//public liability. the instance column holds [root, liability]
+#[derive(Clone, Debug)]
+pub struct ReservesCircuit<const K: usize, const DEPTH: usize> {
+    pub ids: [Value<Fp>; K],
+    pub balances: [Value<Fp>; K],
+    pub indices: [Value<Fp>; K],
+    //siblings from the leaf level up
+    pub siblings: [[Value<Fp>; DEPTH]; K],
+}
+
+impl<const K: usize, const DEPTH: usize> Default for ReservesCircuit<K, DEPTH> {
+    fn default() -> Self {
+        Self {
+            ids: [Value::unknown(); K],
+            balances: [Value::unknown(); K],
+            indices: [Value::unknown(); K],
+            siblings: [[Value::unknown(); DEPTH]; K],
+        }
+    }
+}
+
+impl<const K: usize, const DEPTH: usize> ReservesCircuit<K, DEPTH> {
+    //accounts are (id, balance) pairs, all 2^DEPTH leaves of the tree. the circuit opens the
+    //leaves at indices, None if one is out of the tree
+    pub fn from_accounts(accounts: &[(u64, u64)], indices: [usize; K]) -> Option<Self> {
+        if accounts.len() != 1 << DEPTH || indices.iter().any(|&index| index >= accounts.len()) {
+            return None;
+        }
+        let leaves: Vec<Fp> = accounts.iter().map(|&(id, balance)| leaf_hash(id, balance)).collect();
+        let levels = tree_levels(&leaves)?;
+        let known = |v: u64| Value::known(Fp::from(v));
+        Some(Self {
+            ids: indices.map(|index| known(accounts[index].0)),
+            balances: indices.map(|index| known(accounts[index].1)),
+            indices: indices.map(|index| known(index as u64)),
+            siblings: indices.map(|index| {
+                let mut siblings = [Value::unknown(); DEPTH];
+                for (level, sibling) in siblings.iter_mut().enumerate() {
+                    *sibling = Value::known(levels[level][(index >> level) ^ 1]);
+                }
+                siblings
+            }),
+        })
+    }
+
+    //[root, liability], None unless there are 2^DEPTH accounts
+    pub fn public_inputs(accounts: &[(u64, u64)], liability: u64) -> Option<Instances> {
+        if accounts.len() != 1 << DEPTH {
+            return None;
+        }
+        let leaves: Vec<Fp> = accounts.iter().map(|&(id, balance)| leaf_hash(id, balance)).collect();
+        let root = tree_levels(&leaves)?.last()?[0];
+        Some(vec![vec![root, Fp::from(liability)]])
+    }
+}
+
+fn hash_cells(config: &ReservesConfig, mut layouter: impl Layouter<Fp>, cells: [AssignedCell<Fp, Fp>; 2]) -> Result<AssignedCell<Fp, Fp>, Error> {
+    let hasher = Hash::<_, _, P128Pow5T3, ConstantLength<2>, 3, 2>::init(
+        Pow5Chip::construct(config.poseidon.clone()),
+        layouter.namespace(|| "init hash"),
+    )?;
+    hasher.hash(layouter.namespace(|| "hash"), cells)
+}
+
+impl<const K: usize, const DEPTH: usize> Circuit<Fp> for ReservesCircuit<K, DEPTH> {
+    type Config = ReservesConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+        assert!(K > 0 && K <= 1 << (TOTAL_BITS - BALANCE_BITS) && DEPTH > 0);
+        let mut pool = ColumnPool::new(8);
+        let private = pool.advice(cs);
+        let [node, sibling, bit, left, right]: [Column<Advice>; 5] = pool.advice_many(cs, 5).try_into().unwrap();
+        let total = RunningSumConfig::configure_with_pool(cs, &mut pool, 1);
+        let instance = cs.instance_column();
+        let q_swap = cs.selector();
+
+        for column in [private, node, bit, left, right] {
+            cs.enable_equality(column);
+        }
+        cs.enable_equality(instance);
+
+        cs.create_gate("reserves swap", |cs| {
+            let q = cs.query_selector(q_swap);
+            let node = cs.query_advice(node, Rotation::cur());
+            let sibling = cs.query_advice(sibling, Rotation::cur());
+            let bit = cs.query_advice(bit, Rotation::cur());
+            let left = cs.query_advice(left, Rotation::cur());
+            let right = cs.query_advice(right, Rotation::cur());
+            //bit is copied from the index decomposition, which already makes it boolean
+            Constraints::with_selector(
+                q,
+                [
+                    ("left", left.clone() - (node.clone() + bit * (sibling.clone() - node.clone()))),
+                    ("right", right - (node + sibling - left)),
+                ],
+            )
+        });
+        report::annotate_gate("reserves", "reserves swap");
+
+        let increasing = MonotonicConfig::configure_with_pool(cs, &mut pool, true);
+
+        let state = [cs.advice_column(), cs.advice_column(), cs.advice_column()];
+        let partial_sbox = cs.advice_column();
+        let rc_a = [cs.fixed_column(), cs.fixed_column(), cs.fixed_column()];
+        let rc_b = [cs.fixed_column(), cs.fixed_column(), cs.fixed_column()];
+        cs.enable_constant(rc_b[0]);
+        let poseidon = Pow5Chip::configure::<P128Pow5T3>(cs, state, partial_sbox, rc_a, rc_b);
+
+        ReservesConfig {
+            private,
+            node,
+            sibling,
+            bit,
+            left,
+            right,
+            q_swap,
+            total,
+            instance,
+            increasing,
+            poseidon,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        let bits = &config.increasing.bits;
+        let mut balances = vec![];
+        let mut indices = vec![];
+        for path in 0..K {
+            let (id, balance, index) = layouter.assign_region(
+                || format!("load account {}", path),
+                |mut region| {
+                    let id = region.assign_advice(|| "id", config.private, 0, || self.ids[path])?;
+                    let balance = region.assign_advice(|| "balance", config.private, 1, || self.balances[path])?;
+                    let index = region.assign_advice(|| "index", config.private, 2, || self.indices[path])?;
+                    Ok((id, balance, index))
+                },
+            )?;
+            bits.decompose_cell(layouter.namespace(|| "balance bits"), &balance, BALANCE_BITS)?;
+            let index_bits = bits.decompose_cell(layouter.namespace(|| "index bits"), &index, DEPTH)?;
+
+            let mut node = hash_cells(&config, layouter.namespace(|| format!("leaf {}", path)), [id, balance.clone()])?;
+            for (level, index_bit) in index_bits.iter().enumerate() {
+                let (left, right) = layouter.assign_region(
+                    || "merkle level",
+                    |mut region| {
+                        let offset = 0;
+                        config.q_swap.enable(&mut region, offset)?;
+                        let node = node.copy_advice(|| "node", &mut region, config.node, offset)?;
+                        let sibling = self.siblings[path][level];
+                        region.assign_advice(|| "sibling", config.sibling, offset, || sibling)?;
+                        let bit = index_bit.copy_advice(|| "bit", &mut region, config.bit, offset)?;
+                        let swapped = bit.value().copied().zip(node.value().copied()).zip(sibling);
+                        let left = swapped.map(|((bit, node), sibling)| if bit == Fp::one() { sibling } else { node });
+                        let right = swapped.map(|((bit, node), sibling)| if bit == Fp::one() { node } else { sibling });
+                        let left = region.assign_advice(|| "left", config.left, offset, || left)?;
+                        let right = region.assign_advice(|| "right", config.right, offset, || right)?;
+                        Ok((left, right))
+                    },
+                )?;
+                node = hash_cells(&config, layouter.namespace(|| format!("path {} level {}", path, level)), [left, right])?;
+            }
+            layouter.constrain_instance(node.cell(), config.instance, ROOT_ROW)?;
+
+            balances.push(balance);
+            indices.push(index);
+        }
+        if K > 1 {
+            config.increasing.assign_cells(layouter.namespace(|| "distinct leaves"), &indices)?;
+        }
+
+        let totals = config.total.assign(layouter.namespace(|| "sum balances"), &balances, None)?;
+        let total = totals[K - 1].clone();
+
+        let liability = layouter.assign_region(
+            || "load liability",
+            |mut region| region.assign_advice_from_instance(|| "liability", config.instance, LIABILITY_ROW, config.private, 0),
+        )?;
+        //the total is bounded by the balances' decompositions. the liability is decomposed too,
+        //or one near the modulus could wrap around to a small step up to the total
+        bits.decompose_cell(layouter.namespace(|| "liability bits"), &liability, BALANCE_BITS)?;
+        config.increasing.assign_cells(layouter.namespace(|| "liability < total"), &[liability, total])?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::dev::MockProver;
+
+    use super::*;
+
+    //(id, balance) for the four leaves of a depth 2 tree
+    const ACCOUNTS: [(u64, u64); 4] = [(11, 500), (12, 1200), (13, 40), (14, 3000)];
+
+    fn verify<const K: usize>(circuit: &ReservesCircuit<K, 2>, instances: Instances) -> bool {
+        let prover = MockProver::run(RESERVES_K, circuit, instances).unwrap();
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn test_tree_levels() {
+        let leaves: Vec<Fp> = ACCOUNTS.iter().map(|&(id, balance)| leaf_hash(id, balance)).collect();
+        let levels = tree_levels(&leaves).unwrap();
+        assert_eq!(levels.len(), 3);
+        assert_eq!(levels[2][0], hash_pair(hash_pair(leaves[0], leaves[1]), hash_pair(leaves[2], leaves[3])));
+        assert_eq!(tree_levels(&leaves[..3]), None);
+        assert!(ReservesCircuit::<2, 2>::from_accounts(&ACCOUNTS, [0, 4]).is_none());
+    }
+
+    #[test]
+    fn test_sufficient_reserves() {
+        let circuit = ReservesCircuit::<3, 2>::from_accounts(&ACCOUNTS, [0, 1, 3]).unwrap();
+        //4700 held
+        assert!(verify(&circuit, ReservesCircuit::<3, 2>::public_inputs(&ACCOUNTS, 4699).unwrap()));
+        assert!(verify(&circuit, ReservesCircuit::<3, 2>::public_inputs(&ACCOUNTS, 0).unwrap()));
+        let circuit = ReservesCircuit::<1, 2>::from_accounts(&ACCOUNTS, [2]).unwrap();
+        assert!(verify(&circuit, ReservesCircuit::<1, 2>::public_inputs(&ACCOUNTS, 39).unwrap()));
+    }
+
+    #[test]
+    fn test_insufficient_reserves() {
+        let circuit = ReservesCircuit::<3, 2>::from_accounts(&ACCOUNTS, [0, 1, 3]).unwrap();
+        //the total has to exceed the liability, matching it isn't enough
+        assert!(!verify(&circuit, ReservesCircuit::<3, 2>::public_inputs(&ACCOUNTS, 4700).unwrap()));
+        assert!(!verify(&circuit, ReservesCircuit::<3, 2>::public_inputs(&ACCOUNTS, u64::MAX).unwrap()));
+    }
+
+    #[test]
+    fn test_forged_leaves() {
+        let instances = ReservesCircuit::<2, 2>::public_inputs(&ACCOUNTS, 3500).unwrap();
+        //3040 held, short of the liability unless the large balance is counted twice
+        let circuit = ReservesCircuit::<2, 2>::from_accounts(&ACCOUNTS, [2, 3]).unwrap();
+        assert!(!verify(&circuit, instances.clone()));
+        let circuit = ReservesCircuit::<2, 2>::from_accounts(&ACCOUNTS, [3, 3]).unwrap();
+        assert!(!verify(&circuit, instances.clone()));
+        //or a balance inflated past what the tree commits to
+        let mut circuit = ReservesCircuit::<2, 2>::from_accounts(&ACCOUNTS, [2, 3]).unwrap();
+        circuit.balances[0] = Value::known(Fp::from(1000));
+        assert!(!verify(&circuit, instances));
+    }
+}
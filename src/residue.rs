@@ -0,0 +1,141 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{bit_decompose::BitDecomposeConfig, column_pool::ColumnPool, error::CircuitsError, field::lower_128, report};
+
+//width of the quotient. without a bound on q every field element is q * M + R for some q
+pub const QUOTIENT_BITS: usize = 64;
+
+//checks value = q * M + R with q < 2^QUOTIENT_BITS, i.e. value ≡ R (mod M) as an integer
+//below 2^QUOTIENT_BITS * M
+#[derive(Clone, Debug)]
+pub struct ResidueConfig<F: FieldExt, const M: usize, const R: usize> {
+    pub value: Column<Advice>,
+    pub q: Column<Advice>,
+    pub q_enable: Selector,
+    pub bits: BitDecomposeConfig<F>,
+}
+
+impl<F: FieldExt, const M: usize, const R: usize> ResidueConfig<F, M, R> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, value: Column<Advice>, q: Column<Advice>, bits: BitDecomposeConfig<F>) -> Self {
+        assert!(R < M);
+        let q_enable = cs.selector();
+
+        cs.enable_equality(value);
+        cs.enable_equality(q);
+
+        cs.create_gate("residue", |cs| {
+            let s = cs.query_selector(q_enable);
+            let value = cs.query_advice(value, Rotation::cur());
+            let q = cs.query_advice(q, Rotation::cur());
+            let m = Expression::Constant(F::from(M as u64));
+            let r = Expression::Constant(F::from(R as u64));
+            Constraints::with_selector(s, [("value = q * m + r", value - (q * m + r))])
+        });
+        report::annotate_gate("residue", "residue");
+
+        Self { value, q, q_enable, bits }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 2);
+        let bits = BitDecomposeConfig::configure_with_pool(cs, pool);
+        Self::configure(cs, columns[0], columns[1], bits)
+    }
+
+    //returns the quotient cell for a value cell assigned elsewhere. the witness is computed
+    //from the low 128 bits of value
+    pub fn assign(&self, layouter: impl Layouter<F>, value: &AssignedCell<F, F>) -> Result<AssignedCell<F, F>, CircuitsError> {
+        //a value below R has no quotient, 0 makes the gate fail on it
+        let q = value.value().map(|v| {
+            let q = lower_128(v).checked_sub(R as u128).map_or(0, |v| v / M as u128);
+            F::from_u128(q)
+        });
+        self.assign_with_q(layouter, value, q)
+    }
+
+    fn assign_with_q(&self, mut layouter: impl Layouter<F>, value: &AssignedCell<F, F>, q: Value<F>) -> Result<AssignedCell<F, F>, CircuitsError> {
+        let offset = 0;
+        let q = layouter.assign_region(
+            || "assign residue",
+            |mut region| {
+                self.q_enable.enable(&mut region, offset)?;
+                value.copy_advice(|| "value", &mut region, self.value, offset)?;
+                region.assign_advice(|| "q", self.q, offset, || q)
+            },
+        )?;
+        self.bits.decompose_cell(layouter.namespace(|| "quotient bits"), &q, QUOTIENT_BITS)?;
+        Ok(q)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{arithmetic::Field, circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, ExpectedFailure};
+
+    #[derive(Default)]
+    struct ResidueCircuit {
+        value: Value<Fp>,
+        //when set, witnesses this quotient instead of the real one
+        q: Option<Value<Fp>>,
+    }
+
+    impl Circuit<Fp> for ResidueCircuit {
+        type Config = ResidueConfig<Fp, 5, 3>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(3);
+            ResidueConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let value = layouter.assign_region(|| "assign value", |mut region| region.assign_advice(|| "value", config.value, 0, || self.value))?;
+            match self.q {
+                Some(q) => config.assign_with_q(layouter.namespace(|| "residue"), &value, q)?,
+                None => config.assign(layouter.namespace(|| "residue"), &value)?,
+            };
+            Ok(())
+        }
+    }
+
+    fn prover(value: u64, q: Option<Fp>) -> MockProver<Fp> {
+        let k = 7;
+        let circuit = ResidueCircuit { value: Value::known(Fp::from(value)), q: q.map(Value::known) };
+        MockProver::run(k, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_residue() {
+        prover(13, None).assert_satisfied();
+        prover(3, None).assert_satisfied();
+        let gate = [ExpectedFailure::constraint("residue", "value = q * m + r", "assign residue", 0)];
+        expect_failures(&prover(14, None), &gate);
+        //below R
+        expect_failures(&prover(2, None), &gate);
+    }
+
+    #[test]
+    fn test_quotient_not_forgeable() {
+        //q = 11 / 5 in the field satisfies the gate for 14, but it's no 64 bit integer
+        let q = Fp::from(11) * Fp::from(5).invert().unwrap();
+        assert!(prover(14, Some(q)).verify().is_err());
+        //a small q that's simply wrong
+        expect_failures(
+            &prover(13, Some(Fp::from(3))),
+            &[ExpectedFailure::constraint("residue", "value = q * m + r", "assign residue", 0)],
+        );
+    }
+}
@@ -0,0 +1,140 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Selector},
+    poly::Rotation,
+};
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, range_lookup::RangeTableConfig, report};
+
+//packs an rgb triple into packed = r * 65536 + g * 256 + b, with each channel looked up in
+//0..256 so the packing is unique
+#[derive(Clone, Debug)]
+pub struct RgbConfig<F: FieldExt> {
+    pub r: Column<Advice>,
+    pub g: Column<Advice>,
+    pub b: Column<Advice>,
+    pub packed: Column<Advice>,
+    pub q_enable: Selector,
+    pub table: RangeTableConfig<F, 256>,
+}
+
+impl<F: FieldExt> RgbConfig<F> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, r: Column<Advice>, g: Column<Advice>, b: Column<Advice>, packed: Column<Advice>) -> Self {
+        let q_enable = cs.complex_selector();
+        let table = RangeTableConfig::configure(cs);
+
+        for column in [r, g, b, packed] {
+            cs.enable_equality(column);
+        }
+
+        cs.create_gate("rgb pack", |cs| {
+            let q = cs.query_selector(q_enable);
+            let r = cs.query_advice(r, Rotation::cur());
+            let g = cs.query_advice(g, Rotation::cur());
+            let b = cs.query_advice(b, Rotation::cur());
+            let packed = cs.query_advice(packed, Rotation::cur());
+            Constraints::with_selector(q, [("packed = r * 65536 + g * 256 + b", packed - (r * F::from(65536) + g * F::from(256) + b))])
+        });
+        report::annotate_gate("rgb", "rgb pack");
+
+        for channel in [r, g, b] {
+            let lookup_index = cs.lookup(|cs| {
+                let q = cs.query_selector(q_enable);
+                let v = cs.query_advice(channel, Rotation::cur());
+                vec![(q * v, table.value)]
+            });
+            report::annotate_lookup("rgb", lookup_index);
+        }
+
+        Self { r, g, b, packed, q_enable, table }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 4);
+        Self::configure(cs, columns[0], columns[1], columns[2], columns[3])
+    }
+
+    //returns the packed cell and the [r, g, b] cells
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        r: Value<F>,
+        g: Value<F>,
+        b: Value<F>,
+    ) -> Result<(AssignedCell<F, F>, [AssignedCell<F, F>; 3]), CircuitsError> {
+        let offset = 0;
+        let packed = r * Value::known(F::from(65536)) + g * Value::known(F::from(256)) + b;
+        layouter.assign_region(
+            || "assign rgb",
+            |mut region| {
+                self.q_enable.enable(&mut region, offset)?;
+                let r = region.assign_advice(|| "r", self.r, offset, || r)?;
+                let g = region.assign_advice(|| "g", self.g, offset, || g)?;
+                let b = region.assign_advice(|| "b", self.b, offset, || b)?;
+                let packed = region.assign_advice(|| "packed", self.packed, offset, || packed)?;
+                Ok((packed, [r, g, b]))
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, ExpectedFailure};
+
+    #[derive(Default)]
+    struct RgbCircuit {
+        rgb: [Value<Fp>; 3],
+        //checked against the packed cell
+        expected: Option<u64>,
+    }
+
+    impl Circuit<Fp> for RgbCircuit {
+        type Config = RgbConfig<Fp>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(4);
+            RgbConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            let [r, g, b] = self.rgb;
+            let (packed, _) = config.assign(layouter.namespace(|| "rgb"), r, g, b)?;
+            if let Some(expected) = self.expected {
+                packed.value().assert_if_known(|v| **v == Fp::from(expected));
+            }
+            Ok(())
+        }
+    }
+
+    fn prover(rgb: [u64; 3], expected: Option<u64>) -> MockProver<Fp> {
+        let k = 9;
+        MockProver::run(k, &RgbCircuit { rgb: rgb.map(|v| Value::known(Fp::from(v))), expected }, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_rgb() {
+        prover([255, 128, 0], Some(0xff8000)).assert_satisfied();
+        prover([0, 0, 0], Some(0)).assert_satisfied();
+        prover([255, 255, 255], Some(0xffffff)).assert_satisfied();
+    }
+
+    #[test]
+    fn test_channel_out_of_range() {
+        //(0, 256, 0) packs to the same 65536 as (1, 0, 0), only the lookup on g tells them apart
+        expect_failures(&prover([0, 256, 0], Some(0x010000)), &[ExpectedFailure::lookup(1, "assign rgb", 0)]);
+        expect_failures(&prover([256, 0, 0], None), &[ExpectedFailure::lookup(0, "assign rgb", 0)]);
+        expect_failures(&prover([0, 0, 300], None), &[ExpectedFailure::lookup(2, "assign rgb", 0)]);
+    }
+}
@@ -0,0 +1,128 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Assigned, Column, ConstraintSystem, Error, Fixed},
+};
+
+use crate::{bounds::BoundsConfig, column_pool::ColumnPool, error::CircuitsError};
+
+//a run length encoded pair (value, run) with run in [1, MAX_RUN].
+//
+//the run goes through an interval check with the bounds pinned to the constants 1 and
+//MAX_RUN. they're MAX_RUN - 1 apart, so a table of MAX_RUN rows covers them
+#[derive(Clone, Debug)]
+pub struct RleConfig<F: FieldExt, const MAX_RUN: usize> {
+    pub value: Column<Advice>,
+    pub bounds: BoundsConfig<F, MAX_RUN>,
+}
+
+impl<F: FieldExt, const MAX_RUN: usize> RleConfig<F, MAX_RUN> {
+    pub fn configure(
+        cs: &mut ConstraintSystem<F>,
+        value: Column<Advice>,
+        run: Column<Advice>,
+        min: Column<Advice>,
+        max: Column<Advice>,
+        constants: Column<Fixed>,
+    ) -> Self {
+        assert!(MAX_RUN > 0, "runs are nonzero");
+        cs.enable_equality(value);
+        cs.enable_equality(min);
+        cs.enable_equality(max);
+        cs.enable_constant(constants);
+        Self {
+            value,
+            bounds: BoundsConfig::configure(cs, run, min, max),
+        }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>, constants: Column<Fixed>) -> Self {
+        let columns = pool.advice_many(cs, 4);
+        Self::configure(cs, columns[0], columns[1], columns[2], columns[3], constants)
+    }
+
+    //returns the run cell
+    pub fn assign(&self, layouter: impl Layouter<F>, value: Value<Assigned<F>>, run: Value<Assigned<F>>) -> Result<AssignedCell<Assigned<F>, F>, CircuitsError> {
+        self.assign_pair(layouter, value, run).map(|(_, run)| run)
+    }
+
+    //returns the (value, run) cells
+    pub fn assign_pair(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<Assigned<F>>,
+        run: Value<Assigned<F>>,
+    ) -> Result<(AssignedCell<Assigned<F>, F>, AssignedCell<Assigned<F>, F>), CircuitsError> {
+        let min = Value::known(F::one().into());
+        let max = Value::known(F::from(MAX_RUN as u64).into());
+        let (run, min, max) = self.bounds.assign_with_bounds(layouter.namespace(|| "run length"), run, min, max)?;
+        let value = layouter.assign_region(
+            || "assign rle pair",
+            |mut region| {
+                let fixed_min = region.assign_advice_from_constant(|| "min", self.bounds.min, 0, Assigned::from(F::one()))?;
+                let fixed_max = region.assign_advice_from_constant(|| "max", self.bounds.max, 0, Assigned::from(F::from(MAX_RUN as u64)))?;
+                region.constrain_equal(fixed_min.cell(), min.cell())?;
+                region.constrain_equal(fixed_max.cell(), max.cell())?;
+                region.assign_advice(|| "value", self.value, 0, || value)
+            },
+        )?;
+        Ok((value, run))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, value_u64, ExpectedFailure};
+
+    const MAX_RUN: usize = 16;
+
+    #[derive(Default)]
+    struct RleCircuit<F: FieldExt> {
+        value: Value<Assigned<F>>,
+        run: Value<Assigned<F>>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for RleCircuit<F> {
+        type Config = RleConfig<F, MAX_RUN>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let constants = cs.fixed_column();
+            let mut pool = ColumnPool::new(4);
+            RleConfig::configure_with_pool(cs, &mut pool, constants)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            config.bounds.table.load(&mut layouter)?;
+            config.assign(layouter.namespace(|| "rle"), self.value, self.run)?;
+            Ok(())
+        }
+    }
+
+    fn prover(value: u64, run: u64) -> MockProver<Fp> {
+        let k = 6;
+        MockProver::run(k, &RleCircuit { value: value_u64(value), run: value_u64(run) }, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_run_in_range() {
+        prover(0x41, 1).assert_satisfied();
+        prover(0x41, MAX_RUN as u64).assert_satisfied();
+    }
+
+    #[test]
+    fn test_run_out_of_range() {
+        //the interval is as wide as the table, so a run just outside it fails both lookups
+        let both = [ExpectedFailure::lookup(0, "assign bounds", 0), ExpectedFailure::lookup(1, "assign bounds", 0)];
+        expect_failures(&prover(0x41, 0), &both);
+        expect_failures(&prover(0x41, MAX_RUN as u64 + 1), &both);
+    }
+}
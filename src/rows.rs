@@ -0,0 +1,349 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::{Field, FieldExt},
+    circuit::{Layouter, Value},
+    pasta::Fp,
+    plonk::{Circuit, ConstraintSystem, TableColumn},
+};
+
+use std::ops::Range;
+
+use crate::error::CircuitsError;
+
+//the rows a circuit can assign at k. halo2 keeps the last blinding_factors + 1 rows for
+//blinding and l_last, and the blinding factors grow with the most queried advice column,
+//so it's taken from the circuit's own constraint system once everything is configured
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RowBudget {
+    pub k: u32,
+    pub usable: usize,
+}
+
+impl RowBudget {
+    pub fn new<F: Field>(cs: &ConstraintSystem<F>, k: u32) -> Self {
+        let reserved = cs.blinding_factors() + 1;
+        Self { k, usable: (1usize << k).saturating_sub(reserved) }
+    }
+
+    //the budget at k of a circuit querying no column at more than 3 rotations, which is most
+    //of them. the field doesn't change the blinding rows, so it's probed over pasta's
+    pub fn at(k: u32) -> Self {
+        Self::new(&ConstraintSystem::<Fp>::default(), k)
+    }
+
+    //the budget of circuit C at k, configuring it again for its constraint system. for
+    //synthesize, which doesn't get to see the one keygen built
+    pub fn for_circuit<F: Field, C: Circuit<F>>(k: u32) -> Self {
+        let mut cs = ConstraintSystem::default();
+        C::configure(&mut cs);
+        Self::new(&cs, k)
+    }
+
+    //the rows a table can fill. the floor planner fills a table column from its first
+    //unassigned row to the end with row 0's value and fails when there's no such row, so
+    //a table has to leave it the last usable row
+    pub fn table(&self) -> Self {
+        Self { k: self.k, usable: self.usable.saturating_sub(1) }
+    }
+
+    //fails with the numbers when what needs more than the usable rows
+    pub fn ensure(&self, what: impl Into<String>, rows: usize) -> Result<(), CircuitsError> {
+        if rows > self.usable {
+            return Err(CircuitsError::NotEnoughRows { what: what.into(), rows, usable: self.usable, k: self.k });
+        }
+        Ok(())
+    }
+}
+
+//the rows a circuit can assign at k, see RowBudget::at. a budget from the circuit's own
+//constraint system is exact
+pub fn usable_rows(k: u32) -> usize {
+    RowBudget::at(k).usable
+}
+
+//what a padded load does with the table rows past the contents, up to the ones it can fill.
+//halo2 fills them itself otherwise, with whatever its layouter picks. table configs take it
+//at configure time, so keygen and proving pad the same way
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TablePadding {
+    Unpadded,
+    //repeat the last row
+    RepeatMax,
+    //repeat this row of the contents. for a range table row i holds i, so it's the value
+    Filler(u64),
+    //fail unless the contents fill the table rows exactly
+    Strict,
+}
+
+impl Default for TablePadding {
+    fn default() -> Self {
+        TablePadding::Unpadded
+    }
+}
+
+impl TablePadding {
+    //the padding for a table of rows rows, panicking on a filler row it doesn't have
+    pub fn for_rows(self, rows: usize) -> Self {
+        if let TablePadding::Filler(row) = self {
+            assert!(row < rows as u64, "the filler has to be in the table");
+        }
+        self
+    }
+
+    //the row repeated in the padding of a table of rows rows, if there is one
+    pub fn filler_row(self, rows: usize) -> Option<usize> {
+        match self {
+            TablePadding::RepeatMax => rows.checked_sub(1),
+            TablePadding::Filler(row) => Some(row as usize),
+            TablePadding::Unpadded | TablePadding::Strict => None,
+        }
+    }
+}
+
+//the rows a padded load of a rows-long table fills, empty when there's nothing to pad
+pub fn padding_rows(padding: TablePadding, what: impl Into<String>, rows: usize, budget: RowBudget) -> Result<Range<usize>, CircuitsError> {
+    let what = what.into();
+    let budget = budget.table();
+    budget.ensure(what.clone(), rows)?;
+    let RowBudget { k, usable } = budget;
+    match padding {
+        TablePadding::Unpadded => Ok(rows..rows),
+        TablePadding::Strict if rows < usable => Err(CircuitsError::TableNotFull { what, rows, usable, k }),
+        TablePadding::Strict => Ok(rows..rows),
+        TablePadding::RepeatMax | TablePadding::Filler(_) => Ok(rows..usable),
+    }
+}
+
+//assigns a table row by row, each row one value per column. with a budget the contents are
+//checked to fit before anything is assigned and the rows past them are padded up to the
+//last one a table can fill as padding says, without one they're left to halo2. every table config loads
+//through here so they all check and pad the same way
+pub fn load_table<F: FieldExt>(
+    layouter: &mut impl Layouter<F>,
+    what: &str,
+    columns: &[(&'static str, TableColumn)],
+    rows: &[Vec<F>],
+    padding: TablePadding,
+    budget: Option<RowBudget>,
+) -> Result<(), CircuitsError> {
+    let (fillers, filler) = match budget {
+        Some(budget) => (padding_rows(padding, what, rows.len(), budget)?, padding.filler_row(rows.len())),
+        None => (rows.len()..rows.len(), None),
+    };
+    layouter.assign_table(
+        || format!("assign {}", what),
+        |mut table| {
+            let offsets = (0..rows.len()).map(|row| (row, row));
+            let padded = filler.into_iter().flat_map(|row| fillers.clone().map(move |offset| (offset, row)));
+            for (offset, row) in offsets.chain(padded) {
+                for ((name, column), value) in columns.iter().zip(&rows[row]) {
+                    table.assign_cell(|| *name, *column, offset, || Value::known(*value))?;
+                }
+            }
+            Ok(())
+        },
+    ).map_err(CircuitsError::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Error, Expression},
+        poly::Rotation,
+    };
+
+    use super::*;
+    use crate::{range_lookup::RangeTableConfig, test_utils::{expect_failures, value_u64, values_u64, ExpectedFailure}, range_lookup::RangeCheckLookupConfig};
+
+    #[test]
+    fn test_usable_rows() {
+        assert_eq!(usable_rows(4), 10);
+        assert_eq!(usable_rows(8), 250);
+        assert_eq!(usable_rows(1), 0);
+
+        let cs = ConstraintSystem::<Fp>::default();
+        assert_eq!(RowBudget::new(&cs, 4).usable, 10);
+        assert_eq!(RowBudget::new(&cs, 8).usable, 250);
+        assert_eq!(RowBudget::new(&cs, 1).usable, 0);
+
+        let budget = RowBudget::new(&cs, 4);
+        assert!(budget.ensure("table", 10).is_ok());
+        let err = budget.ensure("table", 11).unwrap_err();
+        assert_eq!(err.to_string(), "table needs 11 rows but only 10 are usable at k = 4");
+
+        //a column queried at 5 rotations takes two more blinding rows than the 3 the
+        //permutation argument needs anyway
+        let mut cs = ConstraintSystem::<Fp>::default();
+        let a = cs.advice_column();
+        cs.create_gate("wide", |cs| vec![(0..5).fold(Expression::Constant(Fp::zero()), |acc, i| acc + cs.query_advice(a, Rotation(i)))]);
+        assert_eq!(RowBudget::new(&cs, 4).usable, 8);
+    }
+
+    //loads a 16 row table at k = 4. CHECK 0 loads it unchecked, 1 with the k it was configured
+    //at and 2 padded to the circuit's budget
+    struct TableCircuit<const CHECK: usize>;
+
+    impl<const CHECK: usize> Circuit<Fp> for TableCircuit<CHECK> {
+        type Config = RangeTableConfig<Fp, 16>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            match CHECK {
+                1 => RangeTableConfig::configure_at(cs, 4),
+                _ => RangeTableConfig::configure(cs),
+            }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let result = match CHECK {
+                2 => config.load_padded(&mut layouter, RowBudget::for_circuit::<Fp, Self>(4)),
+                _ => config.load(&mut layouter),
+            };
+            if CHECK > 0 {
+                let err = result.unwrap_err();
+                assert!(matches!(err, CircuitsError::NotEnoughRows { rows: 16, usable: 9, k: 4, .. }), "{:?}", err);
+                return Err(err.into());
+            }
+            result.map_err(Error::from)
+        }
+    }
+
+    #[test]
+    fn test_load_pre_check() {
+        //halo2 only notices once it tries to assign row 10
+        let unchecked = MockProver::run(4, &TableCircuit::<0>, vec![]);
+        assert!(matches!(unchecked, Err(Error::NotEnoughRowsAvailable { current_k: 4 })));
+        for checked in [MockProver::run(4, &TableCircuit::<1>, vec![]), MockProver::run(4, &TableCircuit::<2>, vec![])] {
+            assert!(matches!(checked, Err(Error::Synthesis)));
+        }
+    }
+
+    #[derive(Default)]
+    struct BatchCircuit {
+        k: u32,
+        values: usize,
+    }
+
+    impl Circuit<Fp> for BatchCircuit {
+        type Config = RangeCheckLookupConfig<Fp, 8>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { k: self.k, values: self.values }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let value = cs.advice_column();
+            RangeCheckLookupConfig::configure(cs, value)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            let values = values_u64(&vec![3; self.values]);
+            let budget = RowBudget::for_circuit::<Fp, Self>(self.k);
+            match config.assign_lookup_batch(layouter.namespace(|| "batch"), budget, &values) {
+                Err(CircuitsError::NotEnoughRows { rows, usable, .. }) => {
+                    assert_eq!((rows, usable), (self.values, budget.usable));
+                    Err(Error::Synthesis)
+                }
+                result => result.map(|_| ()).map_err(Error::from),
+            }
+        }
+    }
+
+    #[test]
+    fn test_batch_pre_check() {
+        let k = 4;
+        MockProver::run(k, &BatchCircuit { k, values: 10 }, vec![]).unwrap().assert_satisfied();
+        assert!(matches!(MockProver::run(k, &BatchCircuit { k, values: 11 }, vec![]), Err(Error::Synthesis)));
+    }
+
+    //the padding of PaddedCircuit by its const, configure has no self to read it from
+    fn padding(kind: usize) -> TablePadding {
+        match kind {
+            0 => TablePadding::RepeatMax,
+            1 => TablePadding::Filler(3),
+            _ => TablePadding::Strict,
+        }
+    }
+
+    //values checked against a 9 row table loaded with padding at k
+    struct PaddedCircuit<const PADDING: usize> {
+        k: u32,
+        values: Vec<u64>,
+    }
+
+    impl<const PADDING: usize> Circuit<Fp> for PaddedCircuit<PADDING> {
+        type Config = (RangeCheckLookupConfig<Fp, 9>, RangeTableConfig<Fp, 9>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { k: self.k, values: self.values.clone() }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let value = cs.advice_column();
+            let table = RangeTableConfig::configure_padded(cs, padding(PADDING));
+            (RangeCheckLookupConfig::configure_with_table(cs, value, Some(table.value)), table)
+        }
+
+        fn synthesize(&self, (config, table): Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            if let Err(err) = table.load_padded(&mut layouter, RowBudget::for_circuit::<Fp, Self>(self.k)) {
+                assert!(matches!(err, CircuitsError::TableNotFull { rows: 9, .. }), "{:?}", err);
+                return Err(err.into());
+            }
+            for v in &self.values {
+                config.assign_lookup(layouter.namespace(|| "value"), value_u64(*v))?;
+            }
+            Ok(())
+        }
+    }
+
+    fn padded_prover<const PADDING: usize>(k: u32, values: &[u64]) -> Result<MockProver<Fp>, Error> {
+        MockProver::run(k, &PaddedCircuit::<PADDING> { k, values: values.to_vec() }, vec![])
+    }
+
+    #[test]
+    fn test_padded_table() {
+        let mut cs = ConstraintSystem::<Fp>::default();
+        assert_eq!(RangeTableConfig::<Fp, 9>::configure(&mut cs).filler(), None);
+        assert_eq!(RangeTableConfig::<Fp, 9>::configure_padded(&mut cs, TablePadding::RepeatMax).filler(), Some(8));
+        assert_eq!(RangeTableConfig::<Fp, 9>::configure_padded(&mut cs, TablePadding::Filler(3)).filler(), Some(3));
+
+        //the padding only repeats members, so growing k doesn't change the accepted set
+        let failure = [ExpectedFailure::lookup(0, "range_lookup<9> assign value", 0)];
+        for k in [4, 5, 8] {
+            padded_prover::<0>(k, &[0, 3, 8]).unwrap().assert_satisfied();
+            expect_failures(&padded_prover::<0>(k, &[9]).unwrap(), &failure);
+            padded_prover::<1>(k, &[0, 3, 8]).unwrap().assert_satisfied();
+            expect_failures(&padded_prover::<1>(k, &[9]).unwrap(), &failure);
+        }
+        let budget = RowBudget::new(&ConstraintSystem::<Fp>::default(), 5);
+        assert_eq!(padding_rows(TablePadding::RepeatMax, "table", 10, budget).unwrap(), 10..budget.usable - 1);
+        assert_eq!(padding_rows(TablePadding::Unpadded, "table", 10, budget).unwrap(), 10..10);
+    }
+
+    #[test]
+    #[should_panic(expected = "the filler has to be in the table")]
+    fn test_filler_outside_table() {
+        let mut cs = ConstraintSystem::<Fp>::default();
+        RangeTableConfig::<Fp, 10>::configure_padded(&mut cs, TablePadding::Filler(10));
+    }
+
+    #[test]
+    fn test_strict_padding() {
+        //9 rows are exactly the ones a table can fill at k = 4
+        padded_prover::<2>(4, &[0, 8]).unwrap().assert_satisfied();
+        assert!(matches!(padded_prover::<2>(5, &[0, 8]), Err(Error::Synthesis)));
+        let budget = RowBudget::new(&ConstraintSystem::<Fp>::default(), 5);
+        let err = padding_rows(TablePadding::Strict, "table", 10, budget).unwrap_err();
+        assert_eq!(err.to_string(), "table fills 10 of the 25 usable rows at k = 5 and can't be padded");
+    }
+}
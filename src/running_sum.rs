@@ -0,0 +1,219 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Fixed, Selector},
+    poly::Rotation,
+};
+
+use std::marker::PhantomData;
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, report};
+
+//acc_0 = term_0 and acc_i = base * acc_{i-1} + term_i down the rows. base 1 is a plain sum,
+//a larger base packs the terms as digits, most significant first.
+//
+//the last row can pin acc to a constant in the total column, e.g. a count of flags that
+//has to come to exactly 1. gadgets with gates of their own on the term rows assign the sum
+//inside their region, the others copy the term cells in
+#[derive(Clone, Debug)]
+pub struct RunningSumConfig<F: FieldExt> {
+    pub term: Column<Advice>,
+    pub acc: Column<Advice>,
+    pub total: Column<Fixed>,
+    pub base: u64,
+    pub q_first: Selector,
+    pub q_step: Selector,
+    pub q_last: Selector,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> RunningSumConfig<F> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, term: Column<Advice>, acc: Column<Advice>, base: u64) -> Self {
+        assert!(base > 0);
+        let q_first = cs.selector();
+        let q_step = cs.selector();
+        let q_last = cs.selector();
+        let total = cs.fixed_column();
+
+        cs.enable_equality(term);
+        cs.enable_equality(acc);
+
+        cs.create_gate("running sum first", |cs| {
+            let q = cs.query_selector(q_first);
+            let term = cs.query_advice(term, Rotation::cur());
+            let acc = cs.query_advice(acc, Rotation::cur());
+            Constraints::with_selector(q, [("acc = term", acc - term)])
+        });
+        report::annotate_gate("running_sum", "running sum first");
+
+        cs.create_gate("running sum step", |cs| {
+            let q = cs.query_selector(q_step);
+            let term = cs.query_advice(term, Rotation::cur());
+            let prev = cs.query_advice(acc, Rotation::prev());
+            let acc = cs.query_advice(acc, Rotation::cur());
+            Constraints::with_selector(q, [("acc = base * prev + term", acc - (prev * F::from(base) + term))])
+        });
+        report::annotate_gate("running_sum", "running sum step");
+
+        cs.create_gate("running sum last", |cs| {
+            let q = cs.query_selector(q_last);
+            let acc = cs.query_advice(acc, Rotation::cur());
+            let total = cs.query_fixed(total, Rotation::cur());
+            Constraints::with_selector(q, [("acc = total", acc - total)])
+        });
+        report::annotate_gate("running_sum", "running sum last");
+
+        Self { term, acc, total, base, q_first, q_step, q_last, _marker: PhantomData }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>, base: u64) -> Self {
+        let columns = pool.advice_many(cs, 2);
+        Self::configure(cs, columns[0], columns[1], base)
+    }
+
+    //the running sums of the terms without assigning anything
+    pub fn sums(&self, terms: &[Value<F>]) -> Vec<Value<F>> {
+        let mut acc = Value::known(F::zero());
+        terms.iter().map(|term| {
+            acc = acc.zip(*term).map(|(acc, term)| acc * F::from(self.base) + term);
+            acc
+        }).collect()
+    }
+
+    //assigns the terms and their sums on rows offset.. of a region the caller has gates of its
+    //own in, and pins the last sum to total when there is one. returns the (term, acc) cells
+    pub fn assign_rows(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        terms: &[Value<F>],
+        total: Option<F>,
+    ) -> Result<Vec<(AssignedCell<F, F>, AssignedCell<F, F>)>, Error> {
+        self.assign_rows_with_sums(region, offset, terms, &self.sums(terms), total)
+    }
+
+    fn assign_rows_with_sums(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        terms: &[Value<F>],
+        sums: &[Value<F>],
+        total: Option<F>,
+    ) -> Result<Vec<(AssignedCell<F, F>, AssignedCell<F, F>)>, Error> {
+        let last = offset + terms.len() - 1;
+        terms.iter().zip(sums).enumerate().map(|(i, (term, acc))| {
+            let row = offset + i;
+            if i == 0 {
+                self.q_first.enable(region, row)?;
+            } else {
+                self.q_step.enable(region, row)?;
+            }
+            if let (Some(total), true) = (total, row == last) {
+                self.q_last.enable(region, row)?;
+                region.assign_fixed(|| "total", self.total, row, || Value::known(total))?;
+            }
+            let term = region.assign_advice(|| "term", self.term, row, || *term)?;
+            let acc = region.assign_advice(|| "acc", self.acc, row, || *acc)?;
+            Ok((term, acc))
+        }).collect()
+    }
+
+    //returns the acc cells for term cells assigned elsewhere
+    pub fn assign(&self, mut layouter: impl Layouter<F>, terms: &[AssignedCell<F, F>], total: Option<F>) -> Result<Vec<AssignedCell<F, F>>, CircuitsError> {
+        if terms.is_empty() {
+            return Err(CircuitsError::LengthMismatch { expected: 1, got: 0 });
+        }
+        layouter.assign_region(
+            || "assign running sum",
+            |mut region| {
+                let values: Vec<_> = terms.iter().map(|term| term.value().copied()).collect();
+                let rows = self.assign_rows(&mut region, 0, &values, total)?;
+                rows.into_iter().zip(terms).map(|((copy, acc), term)| {
+                    region.constrain_equal(term.cell(), copy.cell())?;
+                    Ok(acc)
+                }).collect()
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, ExpectedFailure};
+
+    #[derive(Default)]
+    struct SumCircuit {
+        terms: Vec<Value<Fp>>,
+        total: Option<u64>,
+        //when set, witnessed as the sums instead of the real ones
+        forged: Option<Vec<Value<Fp>>>,
+        expected: Option<u64>,
+    }
+
+    impl Circuit<Fp> for SumCircuit {
+        type Config = RunningSumConfig<Fp>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                terms: vec![Value::unknown(); self.terms.len()],
+                total: self.total,
+                forged: self.forged.as_ref().map(|forged| vec![Value::unknown(); forged.len()]),
+                expected: None,
+            }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(2);
+            RunningSumConfig::configure_with_pool(cs, &mut pool, 10)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let total = self.total.map(Fp::from);
+            if let Some(forged) = &self.forged {
+                layouter.assign_region(|| "assign running sum", |mut region| config.assign_rows_with_sums(&mut region, 0, &self.terms, forged, total))?;
+                return Ok(());
+            }
+            let terms = layouter.assign_region(
+                || "assign terms",
+                |mut region| self.terms.iter().enumerate().map(|(offset, term)| region.assign_advice(|| "term", config.term, offset, || *term)).collect::<Result<Vec<_>, _>>(),
+            )?;
+            let sums = config.assign(layouter.namespace(|| "sum"), &terms, total)?;
+            if let Some(expected) = self.expected {
+                sums.last().unwrap().value().assert_if_known(|acc| **acc == Fp::from(expected));
+            }
+            Ok(())
+        }
+    }
+
+    fn values(values: &[u64]) -> Vec<Value<Fp>> {
+        values.iter().map(|v| Value::known(Fp::from(*v))).collect()
+    }
+
+    fn prover(terms: &[u64], total: Option<u64>, forged: Option<&[u64]>) -> MockProver<Fp> {
+        let k = 4;
+        let expected = terms.iter().fold(0, |acc, term| acc * 10 + term);
+        let circuit = SumCircuit { terms: values(terms), total, forged: forged.map(values), expected: Some(expected) };
+        MockProver::run(k, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_running_sum() {
+        prover(&[1, 2, 3], None, None).assert_satisfied();
+        prover(&[1, 2, 3], Some(123), None).assert_satisfied();
+        prover(&[7], Some(7), None).assert_satisfied();
+    }
+
+    #[test]
+    fn test_running_sum_sound() {
+        let at = |gate, constraint, row| ExpectedFailure::constraint(gate, constraint, "assign running sum", row);
+        expect_failures(&prover(&[1, 2, 3], Some(124), None), &[at("running sum last", "acc = total", 2)]);
+        expect_failures(&prover(&[1, 2, 3], None, Some(&[1, 13, 123])), &[at("running sum step", "acc = base * prev + term", 1), at("running sum step", "acc = base * prev + term", 2)]);
+        expect_failures(&prover(&[1, 2, 3], None, Some(&[2, 22, 223])), &[at("running sum first", "acc = term", 0)]);
+        assert!(matches!(MockProver::run(4, &SumCircuit::default(), vec![]), Err(Error::Synthesis)));
+    }
+}
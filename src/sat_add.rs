@@ -0,0 +1,242 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Assigned, Column, ConstraintSystem, Constraints, Error, Expression, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use std::marker::PhantomData;
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, field::lower_128, rows::{self, RowBudget, TablePadding}};
+
+//out = min(a + b, MAX) for a, b in [0, MAX].
+//
+//the flag picks the branch and is checked by looking up a + b - MAX - 1 when set and
+//MAX - (a + b) when not, both land in [0, MAX] exactly when the flag is honest. the inputs
+//and the output are looked up in the same table.
+#[derive(Clone, Debug)]
+pub struct SatAddConfig<F: FieldExt, const MAX: usize> {
+    pub a: Column<Advice>,
+    pub b: Column<Advice>,
+    pub out: Column<Advice>,
+    pub saturated: Column<Advice>,
+    pub q_enable: Selector,
+    //holds 0..=MAX
+    pub table: TableColumn,
+    //what load_padded puts in the rows past 0..=MAX
+    pub padding: TablePadding,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const MAX: usize> SatAddConfig<F, MAX> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, a: Column<Advice>, b: Column<Advice>, out: Column<Advice>) -> Self {
+        Self::configure_padded(cs, a, b, out, TablePadding::Unpadded)
+    }
+
+    pub fn configure_padded(cs: &mut ConstraintSystem<F>, a: Column<Advice>, b: Column<Advice>, out: Column<Advice>, padding: TablePadding) -> Self {
+        let saturated = cs.advice_column();
+        Self::configure_columns(cs, a, b, out, saturated, padding)
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 4);
+        Self::configure_columns(cs, columns[0], columns[1], columns[2], columns[3], TablePadding::Unpadded)
+    }
+
+    fn configure_columns(
+        cs: &mut ConstraintSystem<F>,
+        a: Column<Advice>,
+        b: Column<Advice>,
+        out: Column<Advice>,
+        saturated: Column<Advice>,
+        padding: TablePadding,
+    ) -> Self {
+        let q_enable = cs.complex_selector();
+        let table = cs.lookup_table_column();
+
+        cs.enable_equality(a);
+        cs.enable_equality(b);
+        cs.enable_equality(out);
+        cs.enable_equality(saturated);
+
+        let max = Expression::Constant(F::from(MAX as u64));
+        let one = Expression::Constant(F::one());
+
+        cs.create_gate("saturating add", |cs| {
+            let q = cs.query_selector(q_enable);
+            let a = cs.query_advice(a, Rotation::cur());
+            let b = cs.query_advice(b, Rotation::cur());
+            let out = cs.query_advice(out, Rotation::cur());
+            let s = cs.query_advice(saturated, Rotation::cur());
+            Constraints::with_selector(
+                q,
+                [
+                    ("flag boolean", s.clone() * (one.clone() - s.clone())),
+                    ("select output", out - (s.clone() * max.clone() + (one.clone() - s) * (a + b))),
+                ],
+            )
+        });
+
+        for column in [a, b, out] {
+            cs.lookup(|cs| {
+                let q = cs.query_selector(q_enable);
+                let v = cs.query_advice(column, Rotation::cur());
+                vec![(q * v, table)]
+            });
+        }
+
+        cs.lookup(|cs| {
+            let q = cs.query_selector(q_enable);
+            let a = cs.query_advice(a, Rotation::cur());
+            let b = cs.query_advice(b, Rotation::cur());
+            let s = cs.query_advice(saturated, Rotation::cur());
+            let sum = a + b;
+            let over = sum.clone() - max.clone() - one.clone();
+            let under = max - sum;
+            vec![(q * (s.clone() * over + (one - s) * under), table)]
+        });
+
+        Self {
+            a,
+            b,
+            out,
+            saturated,
+            q_enable,
+            table,
+            padding: padding.for_rows(MAX + 1),
+            _marker: PhantomData,
+        }
+    }
+
+    fn rows() -> Vec<Vec<F>> {
+        (0..=MAX as u64).map(|i| vec![F::from(i)]).collect()
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), CircuitsError> {
+        rows::load_table(layouter, "sat add table", &[("assign sat add table value", self.table)], &Self::rows(), self.padding, None)
+    }
+
+    //load that first checks the table fits in the budget, then fills the rest of its usable
+    //rows as the padding says
+    pub fn load_padded(&self, layouter: &mut impl Layouter<F>, budget: RowBudget) -> Result<(), CircuitsError> {
+        rows::load_table(layouter, "sat add table", &[("assign sat add table value", self.table)], &Self::rows(), self.padding, Some(budget))
+    }
+
+    //returns (out, saturated)
+    pub fn assign(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Value<Assigned<F>>,
+        b: Value<Assigned<F>>,
+    ) -> Result<(AssignedCell<Assigned<F>, F>, AssignedCell<Assigned<F>, F>), CircuitsError> {
+        let saturated = a
+            .zip(b)
+            .map(|(a, b)| lower_128(&(a.evaluate() + b.evaluate())) > MAX as u128);
+        self.assign_with_flag(layouter, a, b, saturated)
+    }
+
+    fn assign_with_flag(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Value<Assigned<F>>,
+        b: Value<Assigned<F>>,
+        saturated: Value<bool>,
+    ) -> Result<(AssignedCell<Assigned<F>, F>, AssignedCell<Assigned<F>, F>), CircuitsError> {
+        let offset = 0;
+        let flag = saturated.map(|s| Assigned::from(F::from(s as u64)));
+        let out = a.zip(b).zip(saturated).map(|((a, b), s)| {
+            if s {
+                Assigned::from(F::from(MAX as u64))
+            } else {
+                a + b
+            }
+        });
+        layouter.assign_region(
+            || "assign sat add",
+            |mut region| {
+                self.q_enable.enable(&mut region, offset)?;
+                region.assign_advice(|| "a", self.a, offset, || a)?;
+                region.assign_advice(|| "b", self.b, offset, || b)?;
+                let out = region.assign_advice(|| "out", self.out, offset, || out)?;
+                let flag = region.assign_advice(|| "saturated", self.saturated, offset, || flag)?;
+                Ok((out, flag))
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::value_u64;
+
+    #[derive(Default)]
+    struct SatAddCircuit<F: FieldExt, const MAX: usize> {
+        a: Value<Assigned<F>>,
+        b: Value<Assigned<F>>,
+        //when set, witnesses this flag instead of the honest one
+        flag: Option<bool>,
+        expected: (u64, bool),
+    }
+
+    impl<F: FieldExt, const MAX: usize> Circuit<F> for SatAddCircuit<F, MAX> {
+        type Config = SatAddConfig<F, MAX>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = cs.advice_column();
+            let b = cs.advice_column();
+            let out = cs.advice_column();
+            SatAddConfig::configure(cs, a, b, out)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            config.load(&mut layouter)?;
+            let (out, flag) = match self.flag {
+                Some(flag) => config.assign_with_flag(layouter.namespace(|| "sat add"), self.a, self.b, Value::known(flag))?,
+                None => config.assign(layouter.namespace(|| "sat add"), self.a, self.b)?,
+            };
+            if self.flag.is_none() {
+                out.value().assert_if_known(|v| v.evaluate() == F::from(self.expected.0));
+                flag.value().assert_if_known(|v| v.evaluate() == F::from(self.expected.1 as u64));
+            }
+            Ok(())
+        }
+    }
+
+    fn verify(a: u64, b: u64, flag: Option<bool>, expected: (u64, bool)) -> bool {
+        let k = 5;
+        let circuit = SatAddCircuit::<Fp, 10> {
+            a: value_u64(a),
+            b: value_u64(b),
+            flag,
+            expected,
+        };
+        MockProver::run(k, &circuit, vec![]).unwrap().verify().is_ok()
+    }
+
+    #[test]
+    fn test_sat_add() {
+        assert!(verify(3, 4, None, (7, false)));
+        assert!(verify(8, 5, None, (10, true)));
+        //exactly MAX doesn't saturate
+        assert!(verify(4, 6, None, (10, false)));
+    }
+
+    #[test]
+    fn test_sat_add_sound() {
+        //claiming saturation when a + b <= MAX
+        assert!(!verify(3, 4, Some(true), (0, false)));
+        //claiming no saturation when a + b > MAX, out would be 13
+        assert!(!verify(8, 5, Some(false), (0, false)));
+        //inputs above MAX
+        assert!(!verify(11, 0, None, (10, true)));
+    }
+}
@@ -0,0 +1,306 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, TableColumn, Selector},
+    poly::Rotation,
+};
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, field::lower_128, report, rows::{self, RowBudget, TablePadding}};
+
+//the AES S-box, the multiplicative inverse in GF(2^8) followed by the affine map. p walks the
+//field by multiplying by 3 while q walks it by dividing by 3, so q is always p's inverse
+pub fn aes_sbox() -> [u8; 256] {
+    let mut sbox = [0u8; 256];
+    let (mut p, mut q) = (1u8, 1u8);
+    loop {
+        p = p ^ (p << 1) ^ if p & 0x80 != 0 { 0x1b } else { 0 };
+        q ^= q << 1;
+        q ^= q << 2;
+        q ^= q << 4;
+        if q & 0x80 != 0 {
+            q ^= 0x09;
+        }
+        let affine = q ^ q.rotate_left(1) ^ q.rotate_left(2) ^ q.rotate_left(3) ^ q.rotate_left(4);
+        sbox[p as usize] = affine ^ 0x63;
+        if p == 1 {
+            break;
+        }
+    }
+    //0 has no inverse and maps to the affine constant
+    sbox[0] = 0x63;
+    sbox
+}
+
+//the substitution a config is loaded with, always over a byte input
+#[derive(Clone, Debug)]
+pub enum SBox {
+    //x^5 in the field, the poseidon s-box on the byte domain
+    Pow5,
+    Aes,
+    Table([u8; 256]),
+}
+
+impl SBox {
+    pub fn output<F: FieldExt>(&self, input: u8) -> F {
+        match self {
+            SBox::Pow5 => {
+                let x = F::from(input as u64);
+                x * x * x * x * x
+            }
+            SBox::Aes => F::from(aes_sbox()[input as usize] as u64),
+            SBox::Table(table) => F::from(table[input as usize] as u64),
+        }
+    }
+
+    fn outputs<F: FieldExt>(&self) -> Vec<F> {
+        //computed once rather than per byte
+        let aes = aes_sbox();
+        (0..=255u8).map(|input| match self {
+            SBox::Aes => F::from(aes[input as usize] as u64),
+            _ => self.output(input),
+        }).collect()
+    }
+}
+
+//looks (input, output) pairs up in a table of the s-box over every byte, which also checks
+//the input is a byte.
+//
+//rows off the selector look up all zeros, and (0, 0) isn't a pair of every s-box, the AES one
+//sends 0 to 0x63. the table rows carry a tag of 1 and a single all zero row has tag 0, so
+//the padding can't stand in for a real input of 0
+#[derive(Clone, Debug)]
+pub struct SBoxConfig<F: FieldExt> {
+    pub input: Column<Advice>,
+    pub output: Column<Advice>,
+    pub q_enable: Selector,
+    pub table_tag: TableColumn,
+    pub table_input: TableColumn,
+    pub table_output: TableColumn,
+    pub sbox: SBox,
+    //what load_padded puts in the rows past the table, a filler being a row of it with the
+    //all zero row first
+    pub padding: TablePadding,
+    _marker: std::marker::PhantomData<F>,
+}
+
+impl<F: FieldExt> SBoxConfig<F> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, input: Column<Advice>, output: Column<Advice>, sbox: SBox) -> Self {
+        Self::configure_padded(cs, input, output, sbox, TablePadding::Unpadded)
+    }
+
+    pub fn configure_padded(cs: &mut ConstraintSystem<F>, input: Column<Advice>, output: Column<Advice>, sbox: SBox, padding: TablePadding) -> Self {
+        let q_enable = cs.complex_selector();
+        let table_tag = cs.lookup_table_column();
+        let table_input = cs.lookup_table_column();
+        let table_output = cs.lookup_table_column();
+
+        cs.enable_equality(input);
+        cs.enable_equality(output);
+
+        let lookup_index = cs.lookup(|cs| {
+            let q = cs.query_selector(q_enable);
+            let input = cs.query_advice(input, Rotation::cur());
+            let output = cs.query_advice(output, Rotation::cur());
+            vec![(q.clone(), table_tag), (q.clone() * input, table_input), (q * output, table_output)]
+        });
+        report::annotate_lookup("sbox", lookup_index);
+
+        Self {
+            input,
+            output,
+            q_enable,
+            table_tag,
+            table_input,
+            table_output,
+            sbox,
+            padding: padding.for_rows(257),
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>, sbox: SBox) -> Self {
+        let columns = pool.advice_many(cs, 2);
+        Self::configure(cs, columns[0], columns[1], sbox)
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), CircuitsError> {
+        self.load_rows(layouter, None)
+    }
+
+    //load that first checks the table fits in the budget, then fills the rest of its usable
+    //rows as the padding says
+    pub fn load_padded(&self, layouter: &mut impl Layouter<F>, budget: RowBudget) -> Result<(), CircuitsError> {
+        self.load_rows(layouter, Some(budget))
+    }
+
+    fn load_rows(&self, layouter: &mut impl Layouter<F>, budget: Option<RowBudget>) -> Result<(), CircuitsError> {
+        let outputs = self.sbox.outputs::<F>();
+        let rows: Vec<_> = std::iter::once(vec![F::zero(); 3])
+            .chain(outputs.iter().enumerate().map(|(input, output)| vec![F::one(), F::from(input as u64), *output]))
+            .collect();
+        let columns = [("tag", self.table_tag), ("input", self.table_input), ("output", self.table_output)];
+        rows::load_table(layouter, "sbox table", &columns, &rows, self.padding, budget)
+    }
+
+    //the output cell for a byte cell assigned elsewhere
+    pub fn apply(&self, layouter: impl Layouter<F>, byte: &AssignedCell<F, F>) -> Result<AssignedCell<F, F>, CircuitsError> {
+        let output = byte.value().map(|byte| self.sbox.output(lower_128(byte) as u8));
+        self.apply_with_outputs(layouter, &[byte.clone()], &[output]).map(|mut outputs| outputs.remove(0))
+    }
+
+    //the s-box over every byte of a word, e.g. the byte cells out of LeBytesConfig, one row
+    //per byte
+    pub fn apply_word(&self, layouter: impl Layouter<F>, bytes: &[AssignedCell<F, F>]) -> Result<Vec<AssignedCell<F, F>>, CircuitsError> {
+        let outputs: Vec<_> = bytes.iter().map(|byte| byte.value().map(|byte| self.sbox.output(lower_128(byte) as u8))).collect();
+        self.apply_with_outputs(layouter, bytes, &outputs)
+    }
+
+    fn apply_with_outputs(&self, mut layouter: impl Layouter<F>, bytes: &[AssignedCell<F, F>], outputs: &[Value<F>]) -> Result<Vec<AssignedCell<F, F>>, CircuitsError> {
+        layouter.assign_region(
+            || "apply sbox",
+            |mut region| {
+                bytes.iter().zip(outputs).enumerate().map(|(offset, (byte, output))| {
+                    self.q_enable.enable(&mut region, offset)?;
+                    byte.copy_advice(|| "input", &mut region, self.input, offset)?;
+                    region.assign_advice(|| "output", self.output, offset, || *output)
+                }).collect::<Result<Vec<_>, Error>>()
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::{le_bytes::LeBytesConfig, test_utils::{expect_failures, ExpectedFailure}};
+
+    #[test]
+    fn test_aes_sbox() {
+        let sbox = aes_sbox();
+        //from FIPS-197 figure 7
+        for (input, output) in [(0x00, 0x63), (0x01, 0x7c), (0x10, 0xca), (0x53, 0xed), (0x9a, 0xb8), (0xff, 0x16)] {
+            assert_eq!(sbox[input], output);
+        }
+        //a permutation of the bytes
+        let mut sorted = sbox;
+        sorted.sort();
+        assert!(sorted.iter().enumerate().all(|(i, &v)| i == v as usize));
+    }
+
+    //the s-box is picked at configure time, which only has the type to go on
+    const POW5: u8 = 0;
+    const AES: u8 = 1;
+    //the bytes reversed, 255 - x
+    const REVERSE: u8 = 2;
+
+    struct SBoxCircuit<const KIND: u8> {
+        byte: Value<Fp>,
+        //when set, witnessed instead of the real output
+        output: Option<Value<Fp>>,
+    }
+
+    impl<const KIND: u8> Circuit<Fp> for SBoxCircuit<KIND> {
+        type Config = (SBoxConfig<Fp>, Column<Advice>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { byte: Value::unknown(), output: self.output.map(|_| Value::unknown()) }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let sbox = match KIND {
+                POW5 => SBox::Pow5,
+                AES => SBox::Aes,
+                _ => SBox::Table(std::array::from_fn(|i| 255 - i as u8)),
+            };
+            let mut pool = ColumnPool::new(3);
+            let byte = pool.advice(cs);
+            cs.enable_equality(byte);
+            (SBoxConfig::configure_with_pool(cs, &mut pool, sbox), byte)
+        }
+
+        fn synthesize(&self, (config, byte): Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.load(&mut layouter)?;
+            let byte = layouter.assign_region(|| "assign byte", |mut region| region.assign_advice(|| "byte", byte, 0, || self.byte))?;
+            match self.output {
+                Some(output) => config.apply_with_outputs(layouter.namespace(|| "sbox"), &[byte], &[output])?,
+                None => vec![config.apply(layouter.namespace(|| "sbox"), &byte)?],
+            };
+            Ok(())
+        }
+    }
+
+    fn prover<const KIND: u8>(byte: u64, output: Option<Fp>) -> MockProver<Fp> {
+        let k = 9;
+        let circuit = SBoxCircuit::<KIND> { byte: Value::known(Fp::from(byte)), output: output.map(Value::known) };
+        MockProver::run(k, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_sboxes() {
+        for byte in [0, 1, 0x53, 0xff] {
+            prover::<AES>(byte, None).assert_satisfied();
+            prover::<AES>(byte, Some(Fp::from(aes_sbox()[byte as usize] as u64))).assert_satisfied();
+            prover::<POW5>(byte, Some(Fp::from(byte * byte * byte * byte * byte))).assert_satisfied();
+            prover::<REVERSE>(byte, Some(Fp::from(255 - byte))).assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_forged_output() {
+        fn forged<const KIND: u8>(byte: u64, output: u64) {
+            expect_failures(&prover::<KIND>(byte, Some(Fp::from(output))), &[ExpectedFailure::lookup(0, "apply sbox", 0)]);
+        }
+        //0x53 goes to 0xed
+        forged::<AES>(0x53, 0xee);
+        //0 to 0x63, not the all zero padding row
+        forged::<AES>(0, 0);
+        forged::<POW5>(3, 242);
+        forged::<REVERSE>(0, 0);
+        //256 isn't a byte, whatever output is claimed for it
+        forged::<POW5>(256, 0);
+    }
+
+    //a u32 through the AES s-box a byte at a time, like AES SubWord
+    #[derive(Default)]
+    struct SubWordCircuit {
+        word: Value<Fp>,
+        expected: u32,
+    }
+
+    impl Circuit<Fp> for SubWordCircuit {
+        type Config = (SBoxConfig<Fp>, LeBytesConfig<Fp, 4>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { expected: self.expected, ..Self::default() }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(2);
+            (SBoxConfig::configure_with_pool(cs, &mut pool, SBox::Aes), LeBytesConfig::configure_with_pool(cs, &mut pool))
+        }
+
+        fn synthesize(&self, (sbox, bytes): Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            sbox.load(&mut layouter)?;
+            bytes.table.load(&mut layouter)?;
+            let word = layouter.assign_region(|| "assign word", |mut region| region.assign_advice(|| "word", bytes.sum.acc, 0, || self.word))?;
+            let bytes = bytes.to_le_bytes(layouter.namespace(|| "bytes"), &word)?;
+            let outputs = sbox.apply_word(layouter.namespace(|| "sub word"), &bytes)?;
+            for (output, expected) in outputs.iter().zip(self.expected.to_le_bytes()) {
+                output.value().assert_if_known(|v| **v == Fp::from(expected as u64));
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_apply_word() {
+        //SubWord(0x2a6c7605) from the FIPS-197 key expansion example
+        let circuit = SubWordCircuit { word: Value::known(Fp::from(0x2a6c7605)), expected: 0xe550386b };
+        MockProver::run(10, &circuit, vec![]).unwrap().assert_satisfied();
+    }
+}
@@ -0,0 +1,229 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Fixed, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use std::marker::PhantomData;
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, field::lower_128, report, rows::{self, RowBudget, TablePadding}};
+
+//the rows (bits, v) for every v < 2^bits and bits in 0..=NUM_BITS, 2^(NUM_BITS + 1) - 1 rows.
+//bits = 0 only has v = 0, so rows off the selector looking up (0, 0) hit a real row
+#[derive(Clone, Debug)]
+pub struct BitsRangeTableConfig<F: FieldExt, const NUM_BITS: usize> {
+    pub bits: TableColumn,
+    pub value: TableColumn,
+    //what load_padded puts in the rows past the (bits, value) pairs
+    pub padding: TablePadding,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const NUM_BITS: usize> BitsRangeTableConfig<F, NUM_BITS> {
+    pub fn configure(cs: &mut ConstraintSystem<F>) -> Self {
+        Self::configure_padded(cs, TablePadding::Unpadded)
+    }
+
+    pub fn configure_padded(cs: &mut ConstraintSystem<F>, padding: TablePadding) -> Self {
+        //already 2^17 rows past 16 bits
+        assert!(NUM_BITS > 0 && NUM_BITS <= 16);
+        Self {
+            bits: cs.lookup_table_column(),
+            value: cs.lookup_table_column(),
+            padding: padding.for_rows((1 << (NUM_BITS + 1)) - 1),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), CircuitsError> {
+        self.load_rows(layouter, None)
+    }
+
+    //load that first checks the table fits in the budget, then fills the rest of its usable
+    //rows as the padding says
+    pub fn load_padded(&self, layouter: &mut impl Layouter<F>, budget: RowBudget) -> Result<(), CircuitsError> {
+        self.load_rows(layouter, Some(budget))
+    }
+
+    fn load_rows(&self, layouter: &mut impl Layouter<F>, budget: Option<RowBudget>) -> Result<(), CircuitsError> {
+        let rows: Vec<_> = (0..=NUM_BITS as u64)
+            .flat_map(|bits| (0..1u64 << bits).map(move |v| vec![F::from(bits), F::from(v)]))
+            .collect();
+        rows::load_table(layouter, &format!("bits range table<{}>", NUM_BITS), &[("bits", self.bits), ("value", self.value)], &rows, self.padding, budget)
+    }
+}
+
+//out = x >> s for a NUM_BITS wide x and a shift s fixed at keygen, without decomposing x.
+//
+//the row witnesses out and the shifted out low bits with x = out * 2^s + low, and looks up
+//(s, low) and (NUM_BITS - s, out) in the bits range table. with low < 2^s the split is
+//unique, and out < 2^(NUM_BITS - s) keeps x itself within NUM_BITS. s and 2^s sit in fixed
+//columns so each row can shift by its own amount
+#[derive(Clone, Debug)]
+pub struct ShrConstConfig<F: FieldExt, const NUM_BITS: usize> {
+    pub x: Column<Advice>,
+    pub out: Column<Advice>,
+    pub low: Column<Advice>,
+    pub shift: Column<Fixed>,
+    pub pow: Column<Fixed>,
+    pub q_enable: Selector,
+    pub table: BitsRangeTableConfig<F, NUM_BITS>,
+}
+
+impl<F: FieldExt, const NUM_BITS: usize> ShrConstConfig<F, NUM_BITS> {
+    pub fn configure(
+        cs: &mut ConstraintSystem<F>,
+        x: Column<Advice>,
+        out: Column<Advice>,
+        low: Column<Advice>,
+        table: BitsRangeTableConfig<F, NUM_BITS>,
+    ) -> Self {
+        let q_enable = cs.complex_selector();
+        let shift = cs.fixed_column();
+        let pow = cs.fixed_column();
+
+        cs.enable_equality(x);
+        cs.enable_equality(out);
+
+        cs.create_gate("shr const", |cs| {
+            let q = cs.query_selector(q_enable);
+            let x = cs.query_advice(x, Rotation::cur());
+            let out = cs.query_advice(out, Rotation::cur());
+            let low = cs.query_advice(low, Rotation::cur());
+            let pow = cs.query_fixed(pow, Rotation::cur());
+            Constraints::with_selector(q, [("x = out * 2^s + low", x - (out * pow + low))])
+        });
+        report::annotate_gate("shr_const", "shr const");
+
+        let low_lookup = cs.lookup(|cs| {
+            let q = cs.query_selector(q_enable);
+            let shift = cs.query_fixed(shift, Rotation::cur());
+            let low = cs.query_advice(low, Rotation::cur());
+            vec![(q.clone() * shift, table.bits), (q * low, table.value)]
+        });
+        report::annotate_lookup("shr_const", low_lookup);
+
+        let out_lookup = cs.lookup(|cs| {
+            let q = cs.query_selector(q_enable);
+            let shift = cs.query_fixed(shift, Rotation::cur());
+            let out = cs.query_advice(out, Rotation::cur());
+            let high_bits = Expression::Constant(F::from(NUM_BITS as u64)) - shift;
+            vec![(q.clone() * high_bits, table.bits), (q * out, table.value)]
+        });
+        report::annotate_lookup("shr_const", out_lookup);
+
+        Self { x, out, low, shift, pow, q_enable, table }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 3);
+        let table = BitsRangeTableConfig::configure(cs);
+        Self::configure(cs, columns[0], columns[1], columns[2], table)
+    }
+
+    //returns the cell of x >> s for a cell x assigned elsewhere, s in 0..=NUM_BITS
+    pub fn assign(&self, layouter: impl Layouter<F>, x: &AssignedCell<F, F>, s: usize) -> Result<AssignedCell<F, F>, CircuitsError> {
+        let x_value = x.value().map(|x| lower_128(x));
+        let out = x_value.map(|x| F::from_u128(x >> s));
+        let low = x_value.map(|x| F::from_u128(x & ((1 << s) - 1)));
+        self.assign_with_split(layouter, x, s, out, low)
+    }
+
+    fn assign_with_split(&self, mut layouter: impl Layouter<F>, x: &AssignedCell<F, F>, s: usize, out: Value<F>, low: Value<F>) -> Result<AssignedCell<F, F>, CircuitsError> {
+        if s > NUM_BITS {
+            return Err(CircuitsError::RangeTooLarge { gadget: "shr_const", range: s as u64, max: NUM_BITS as u64 });
+        }
+        let offset = 0;
+        layouter.assign_region(
+            || "assign shr const",
+            |mut region| {
+                self.q_enable.enable(&mut region, offset)?;
+                region.assign_fixed(|| "s", self.shift, offset, || Value::known(F::from(s as u64)))?;
+                region.assign_fixed(|| "2^s", self.pow, offset, || Value::known(F::from_u128(1 << s)))?;
+                x.copy_advice(|| "x", &mut region, self.x, offset)?;
+                region.assign_advice(|| "low", self.low, offset, || low)?;
+                region.assign_advice(|| "out", self.out, offset, || out)
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, known, ExpectedFailure, Forged};
+
+    #[derive(Default)]
+    struct ShrCircuit {
+        x: Value<Fp>,
+        //the constant shift, not a witness
+        s: usize,
+        //witnessed as (out, low) instead of the real split when forged
+        split: Forged<Fp>,
+        expected: Option<u64>,
+    }
+
+    impl Circuit<Fp> for ShrCircuit {
+        type Config = ShrConstConfig<Fp, 8>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { x: Value::unknown(), s: self.s, split: self.split.without_witnesses(), expected: None }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(3);
+            ShrConstConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            let x = layouter.assign_region(|| "assign x", |mut region| region.assign_advice(|| "x", config.x, 0, || self.x))?;
+            let out = match self.split.pair() {
+                Some((out, low)) => config.assign_with_split(layouter.namespace(|| "shr"), &x, self.s, out, low)?,
+                None => config.assign(layouter.namespace(|| "shr"), &x, self.s)?,
+            };
+            if let Some(expected) = self.expected {
+                out.value().assert_if_known(|out| **out == Fp::from(expected));
+            }
+            Ok(())
+        }
+    }
+
+    fn prover(x: u64, s: usize, split: Option<(u64, u64)>) -> MockProver<Fp> {
+        //the table takes 511 rows
+        let k = 10;
+        let circuit = ShrCircuit {
+            x: known(x),
+            s,
+            split: split.map_or_else(Forged::honest, |(out, low)| Forged::from_u64s(&[out, low])),
+            expected: split.is_none().then(|| x >> s),
+        };
+        MockProver::run(k, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_shr() {
+        for s in [0, 1, 3, 4, 7] {
+            for x in [0, 1, 0b1011_0110, 0x80, 0xFF] {
+                prover(x, s, None).assert_satisfied();
+            }
+        }
+    }
+
+    #[test]
+    fn test_forged_split() {
+        let at = |lookup| ExpectedFailure::lookup(lookup, "assign shr const", 0);
+        //0b1011_0110 >> 4 is 0b1011, shifting in a low bit adds up but low is past 2^4
+        expect_failures(&prover(0b1011_0110, 4, Some((0b1010, 0b10110))), &[at(0)]);
+        //an out that doesn't add back up to x
+        expect_failures(&prover(0b1011_0110, 4, Some((0b1010, 0b0110))), &[ExpectedFailure::constraint("shr const", "x = out * 2^s + low", "assign shr const", 0)]);
+        //x wider than 8 bits leaves out past 2^(8 - s)
+        expect_failures(&prover(0x1FF, 7, None), &[at(1)]);
+        assert!(matches!(MockProver::run(10, &ShrCircuit { x: known(0), s: 9, ..ShrCircuit::default() }, vec![]), Err(Error::Synthesis)));
+    }
+}
@@ -0,0 +1,190 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Selector},
+    poly::Rotation,
+};
+
+use crate::{
+    bit_decompose::BitDecomposeConfig, column_pool::ColumnPool, error::CircuitsError, range_lookup::RangeTableConfig, report,
+    shr_const::ShrConstConfig,
+};
+
+//out = x >> s for a NUM_BITS wide x and a witnessed shift s in 0..NUM_BITS.
+//
+//s is looked up in 0..NUM_BITS and decomposed into its bits b_j, and stage j of the mux tree
+//shifts by the constant 2^j with ShrConstConfig and keeps that or the unshifted value,
+//next = cur + b_j * (shifted - cur). log2(NUM_BITS) stages shift by every s
+#[derive(Clone, Debug)]
+pub struct ShrVarConfig<F: FieldExt, const NUM_BITS: usize> {
+    pub cur: Column<Advice>,
+    pub shifted: Column<Advice>,
+    pub bit: Column<Advice>,
+    pub next: Column<Advice>,
+    pub shift: Column<Advice>,
+    pub q_mux: Selector,
+    pub q_shift: Selector,
+    pub range: RangeTableConfig<F, NUM_BITS>,
+    pub decompose: BitDecomposeConfig<F>,
+    pub shr: ShrConstConfig<F, NUM_BITS>,
+}
+
+impl<F: FieldExt, const NUM_BITS: usize> ShrVarConfig<F, NUM_BITS> {
+    pub fn configure(
+        cs: &mut ConstraintSystem<F>,
+        [cur, shifted, bit, next]: [Column<Advice>; 4],
+        shift: Column<Advice>,
+        decompose: BitDecomposeConfig<F>,
+        shr: ShrConstConfig<F, NUM_BITS>,
+    ) -> Self {
+        assert!(NUM_BITS > 1);
+        let q_mux = cs.selector();
+        let q_shift = cs.complex_selector();
+        let range = RangeTableConfig::configure(cs);
+
+        for column in [cur, shifted, bit, next, shift] {
+            cs.enable_equality(column);
+        }
+
+        let lookup_index = cs.lookup(|cs| {
+            let q = cs.query_selector(q_shift);
+            let shift = cs.query_advice(shift, Rotation::cur());
+            vec![(q * shift, range.value)]
+        });
+        report::annotate_lookup("shr_var", lookup_index);
+
+        //the bit is copied from the decomposition of s, which has already made it boolean
+        cs.create_gate("shr var mux", |cs| {
+            let q = cs.query_selector(q_mux);
+            let cur = cs.query_advice(cur, Rotation::cur());
+            let shifted = cs.query_advice(shifted, Rotation::cur());
+            let bit = cs.query_advice(bit, Rotation::cur());
+            let next = cs.query_advice(next, Rotation::cur());
+            Constraints::with_selector(q, [("next = cur + bit * (shifted - cur)", next - (cur.clone() + bit * (shifted - cur)))])
+        });
+        report::annotate_gate("shr_var", "shr var mux");
+
+        Self { cur, shifted, bit, next, shift, q_mux, q_shift, range, decompose, shr }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 4).try_into().unwrap();
+        let shift = pool.advice(cs);
+        let decompose = BitDecomposeConfig::configure_with_pool(cs, pool);
+        let shr = ShrConstConfig::configure_with_pool(cs, pool);
+        Self::configure(cs, columns, shift, decompose, shr)
+    }
+
+    //bits of s, enough for NUM_BITS - 1
+    pub fn num_stages() -> usize {
+        (usize::BITS - (NUM_BITS - 1).leading_zeros()) as usize
+    }
+
+    //returns the cell of x >> s for cells x and s assigned elsewhere
+    pub fn assign(&self, mut layouter: impl Layouter<F>, x: &AssignedCell<F, F>, s: &AssignedCell<F, F>) -> Result<AssignedCell<F, F>, CircuitsError> {
+        let offset = 0;
+        layouter.assign_region(
+            || "assign shift amount",
+            |mut region| {
+                self.q_shift.enable(&mut region, offset)?;
+                s.copy_advice(|| "s", &mut region, self.shift, offset)
+            },
+        )?;
+        let bits = self.decompose.decompose_cell(layouter.namespace(|| "shift bits"), s, Self::num_stages())?;
+
+        let mut cur = x.clone();
+        for (j, bit) in bits.iter().enumerate() {
+            let shifted = self.shr.assign(layouter.namespace(|| format!("shift by {}", 1 << j)), &cur, 1 << j)?;
+            cur = layouter.assign_region(
+                || "assign shr var stage",
+                |mut region| {
+                    self.q_mux.enable(&mut region, offset)?;
+                    let cur = cur.copy_advice(|| "cur", &mut region, self.cur, offset)?;
+                    let shifted = shifted.copy_advice(|| "shifted", &mut region, self.shifted, offset)?;
+                    let bit = bit.copy_advice(|| "bit", &mut region, self.bit, offset)?;
+                    let next = cur.value().zip(shifted.value()).zip(bit.value()).map(|((cur, shifted), bit)| *cur + *bit * (*shifted - cur));
+                    region.assign_advice(|| "next", self.next, offset, || next)
+                },
+            )?;
+        }
+        Ok(cur)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct ShrVarCircuit {
+        x: u64,
+        s: u64,
+        expected: Option<u64>,
+    }
+
+    impl Circuit<Fp> for ShrVarCircuit {
+        type Config = ShrVarConfig<Fp, 8>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { x: self.x, s: self.s, expected: self.expected }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(5);
+            ShrVarConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.range.load(&mut layouter)?;
+            config.shr.table.load(&mut layouter)?;
+            let (x, s) = layouter.assign_region(
+                || "assign x and s",
+                |mut region| {
+                    let x = region.assign_advice(|| "x", config.cur, 0, || Value::known(Fp::from(self.x)))?;
+                    let s = region.assign_advice(|| "s", config.shift, 0, || Value::known(Fp::from(self.s)))?;
+                    Ok((x, s))
+                },
+            )?;
+            let out = config.assign(layouter.namespace(|| "shr"), &x, &s)?;
+            if let Some(expected) = self.expected {
+                out.value().assert_if_known(|out| **out == Fp::from(expected));
+            }
+            Ok(())
+        }
+    }
+
+    fn prover(x: u64, s: u64, expected: Option<u64>) -> MockProver<Fp> {
+        //the bits range table of ShrConstConfig takes 511 rows
+        let k = 10;
+        MockProver::run(k, &ShrVarCircuit { x, s, expected }, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_stages() {
+        assert_eq!(ShrVarConfig::<Fp, 8>::num_stages(), 3);
+        assert_eq!(ShrVarConfig::<Fp, 9>::num_stages(), 4);
+        assert_eq!(ShrVarConfig::<Fp, 2>::num_stages(), 1);
+    }
+
+    #[test]
+    fn test_shr_var() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for x in [0xFF, 0x80].into_iter().chain((0..3).map(|_| rng.gen::<u8>() as u64)) {
+            for s in 0..8 {
+                prover(x, s, Some(x >> s)).assert_satisfied();
+            }
+        }
+    }
+
+    #[test]
+    fn test_shift_out_of_range() {
+        //8 isn't below NUM_BITS, and doesn't fit in the three bits of s either
+        assert!(prover(0xFF, 8, None).verify().is_err());
+        assert!(prover(0xFF, 13, None).verify().is_err());
+    }
+}
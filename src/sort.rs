@@ -0,0 +1,306 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Assigned, Column, ConstraintSystem, Constraints, Error, Fixed, Selector},
+    poly::Rotation,
+};
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, field::lower_128, range_lookup::RangeTableConfig};
+
+//proves the output column is the input column sorted ascending, with all values below RANGE.
+//
+//sortedness is a lookup of every adjacent difference into 0..RANGE. to tie outputs to inputs
+//we check prod(x - input_i) == prod(x - output_i) at n fixed points x = 0..n. both sides are
+//monic of degree n so agreeing on n points means they're the same polynomial, i.e. the two
+//columns hold the same multiset. each point gets its own block of n rows with the values
+//copied in from the first block, so the layout only depends on n.
+#[derive(Clone, Debug)]
+pub struct SortConfig<F: FieldExt, const RANGE: usize> {
+    pub input: Column<Advice>,
+    pub output: Column<Advice>,
+    pub input_product: Column<Advice>,
+    pub output_product: Column<Advice>,
+    pub point: Column<Fixed>,
+    pub q_first: Selector,
+    pub q_step: Selector,
+    pub q_last: Selector,
+    //range check on the outputs
+    pub q_range: Selector,
+    //adjacent difference check on the outputs
+    pub q_sorted: Selector,
+    pub table: RangeTableConfig<F, RANGE>,
+}
+
+impl<F: FieldExt, const RANGE: usize> SortConfig<F, RANGE> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, input: Column<Advice>, output: Column<Advice>) -> Self {
+        let input_product = cs.advice_column();
+        let output_product = cs.advice_column();
+        Self::configure_columns(cs, input, output, input_product, output_product)
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 4);
+        Self::configure_columns(cs, columns[0], columns[1], columns[2], columns[3])
+    }
+
+    fn configure_columns(
+        cs: &mut ConstraintSystem<F>,
+        input: Column<Advice>,
+        output: Column<Advice>,
+        input_product: Column<Advice>,
+        output_product: Column<Advice>,
+    ) -> Self {
+        let point = cs.fixed_column();
+        let q_first = cs.selector();
+        let q_step = cs.selector();
+        let q_last = cs.selector();
+        let q_range = cs.complex_selector();
+        let q_sorted = cs.complex_selector();
+        let table = RangeTableConfig::configure(cs);
+
+        cs.enable_equality(input);
+        cs.enable_equality(output);
+
+        cs.create_gate("sort product first", |cs| {
+            let q = cs.query_selector(q_first);
+            let x = cs.query_fixed(point, Rotation::cur());
+            let a = cs.query_advice(input, Rotation::cur());
+            let b = cs.query_advice(output, Rotation::cur());
+            let pa = cs.query_advice(input_product, Rotation::cur());
+            let pb = cs.query_advice(output_product, Rotation::cur());
+            Constraints::with_selector(
+                q,
+                [("input product", pa - (x.clone() - a)), ("output product", pb - (x - b))],
+            )
+        });
+
+        cs.create_gate("sort product step", |cs| {
+            let q = cs.query_selector(q_step);
+            let x = cs.query_fixed(point, Rotation::cur());
+            let a = cs.query_advice(input, Rotation::cur());
+            let b = cs.query_advice(output, Rotation::cur());
+            let pa = cs.query_advice(input_product, Rotation::cur());
+            let pb = cs.query_advice(output_product, Rotation::cur());
+            let pa_prev = cs.query_advice(input_product, Rotation::prev());
+            let pb_prev = cs.query_advice(output_product, Rotation::prev());
+            Constraints::with_selector(
+                q,
+                [
+                    ("input product", pa - pa_prev * (x.clone() - a)),
+                    ("output product", pb - pb_prev * (x - b)),
+                ],
+            )
+        });
+
+        cs.create_gate("sort product end", |cs| {
+            let q = cs.query_selector(q_last);
+            let pa = cs.query_advice(input_product, Rotation::cur());
+            let pb = cs.query_advice(output_product, Rotation::cur());
+            Constraints::with_selector(q, [("same multiset", pa - pb)])
+        });
+
+        cs.lookup(|cs| {
+            let q = cs.query_selector(q_range);
+            let b = cs.query_advice(output, Rotation::cur());
+            vec![(q * b, table.value)]
+        });
+
+        cs.lookup(|cs| {
+            let q = cs.query_selector(q_sorted);
+            let b = cs.query_advice(output, Rotation::cur());
+            let b_next = cs.query_advice(output, Rotation::next());
+            vec![(q * (b_next - b), table.value)]
+        });
+
+        Self {
+            input,
+            output,
+            input_product,
+            output_product,
+            point,
+            q_first,
+            q_step,
+            q_last,
+            q_range,
+            q_sorted,
+            table,
+        }
+    }
+
+    //sorts the inputs natively and proves the result
+    pub fn assign(
+        &self,
+        layouter: impl Layouter<F>,
+        inputs: &[Value<Assigned<F>>],
+    ) -> Result<Vec<AssignedCell<Assigned<F>, F>>, CircuitsError> {
+        let sorted: Value<Vec<F>> = inputs.iter().map(|v| v.evaluate()).collect();
+        let sorted = sorted.map(|mut values| {
+            values.sort_by_key(lower_128);
+            values
+        });
+        let outputs: Vec<_> = (0..inputs.len())
+            .map(|i| sorted.as_ref().map(|values| Assigned::from(values[i])))
+            .collect();
+        self.assign_with_outputs(layouter, inputs, &outputs)
+    }
+
+    //proves a caller supplied ordering, only accepted if it really is the sorted inputs
+    pub fn assign_with_outputs(
+        &self,
+        mut layouter: impl Layouter<F>,
+        inputs: &[Value<Assigned<F>>],
+        outputs: &[Value<Assigned<F>>],
+    ) -> Result<Vec<AssignedCell<Assigned<F>, F>>, CircuitsError> {
+        if inputs.len() != outputs.len() {
+            return Err(CircuitsError::LengthMismatch { expected: inputs.len(), got: outputs.len() });
+        }
+        let n = inputs.len();
+        layouter.assign_region(
+            || "assign sort",
+            |mut region| {
+                let mut input_cells = vec![];
+                let mut output_cells = vec![];
+
+                for block in 0..n {
+                    let x = F::from(block as u64);
+                    let mut pa = Value::known(F::one());
+                    let mut pb = Value::known(F::one());
+
+                    for i in 0..n {
+                        let offset = block * n + i;
+                        region.assign_fixed(|| "point", self.point, offset, || Value::known(x))?;
+
+                        //the first block holds the values, the others copy them
+                        let (a, b) = if block == 0 {
+                            let a = region.assign_advice(|| "input", self.input, offset, || inputs[i])?;
+                            let b = region.assign_advice(|| "output", self.output, offset, || outputs[i])?;
+                            input_cells.push(a.clone());
+                            output_cells.push(b.clone());
+                            (a, b)
+                        } else {
+                            (
+                                input_cells[i].copy_advice(|| "input", &mut region, self.input, offset)?,
+                                output_cells[i].copy_advice(|| "output", &mut region, self.output, offset)?,
+                            )
+                        };
+
+                        pa = pa.zip(a.value().copied().evaluate()).map(|(p, a)| p * (x - a));
+                        pb = pb.zip(b.value().copied().evaluate()).map(|(p, b)| p * (x - b));
+                        region.assign_advice(|| "input product", self.input_product, offset, || pa)?;
+                        region.assign_advice(|| "output product", self.output_product, offset, || pb)?;
+
+                        if i == 0 {
+                            self.q_first.enable(&mut region, offset)?;
+                        } else {
+                            self.q_step.enable(&mut region, offset)?;
+                        }
+                        if i == n - 1 {
+                            self.q_last.enable(&mut region, offset)?;
+                        }
+                        if block == 0 {
+                            self.q_range.enable(&mut region, offset)?;
+                            if i < n - 1 {
+                                self.q_sorted.enable(&mut region, offset)?;
+                            }
+                        }
+                    }
+                }
+                Ok(output_cells)
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::values_u64;
+
+    #[derive(Default)]
+    struct SortCircuit<F: FieldExt, const RANGE: usize> {
+        inputs: Vec<Value<Assigned<F>>>,
+        //when set, proves this ordering instead of sorting natively
+        outputs: Option<Vec<Value<Assigned<F>>>>,
+        expected: Vec<u64>,
+    }
+
+    impl<F: FieldExt, const RANGE: usize> Circuit<F> for SortCircuit<F, RANGE> {
+        type Config = SortConfig<F, RANGE>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                inputs: vec![Value::unknown(); self.inputs.len()],
+                outputs: None,
+                expected: vec![],
+            }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let input = cs.advice_column();
+            let output = cs.advice_column();
+            SortConfig::configure(cs, input, output)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            let sorted = match &self.outputs {
+                Some(outputs) => config.assign_with_outputs(layouter.namespace(|| "sort"), &self.inputs, outputs)?,
+                None => config.assign(layouter.namespace(|| "sort"), &self.inputs)?,
+            };
+            for (cell, expected) in sorted.iter().zip(self.expected.iter()) {
+                cell.value().assert_if_known(|v| v.evaluate() == F::from(*expected));
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_complete() {
+        let k = 5;
+        let circuit = SortCircuit::<Fp, 8> {
+            inputs: values_u64(&[3, 1, 2]),
+            outputs: None,
+            expected: vec![1, 2, 3],
+        };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let k = 5;
+        let circuit = SortCircuit::<Fp, 8> {
+            inputs: values_u64(&[5, 0, 5, 7]),
+            outputs: None,
+            expected: vec![0, 5, 5, 7],
+        };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_sound() {
+        let k = 5;
+        //sorted but not a permutation of the inputs
+        let circuit = SortCircuit::<Fp, 8> {
+            inputs: values_u64(&[3, 1, 2]),
+            outputs: Some(values_u64(&[1, 2, 2])),
+            expected: vec![],
+        };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+
+        //a permutation but not sorted
+        let circuit = SortCircuit::<Fp, 8> {
+            inputs: values_u64(&[3, 1, 2]),
+            outputs: Some(values_u64(&[2, 1, 3])),
+            expected: vec![],
+        };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,260 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use std::marker::PhantomData;
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, field::lower_128, report, rows::{self, RowBudget, TablePadding}};
+
+//interleaves the bits of x with zeros, bit i of x going to bit 2i
+pub fn spread(x: u64) -> u64 {
+    (0..32).fold(0, |acc, i| acc | ((x >> i) & 1) << (2 * i))
+}
+
+//the pairs (dense, spread(dense)) for dense in 0..2^NUM_BITS. spread(0) = 0, so rows off
+//the selector looking up (0, 0) hit a real row
+#[derive(Clone, Debug)]
+pub struct SpreadTableConfig<F: FieldExt, const NUM_BITS: usize> {
+    pub dense: TableColumn,
+    pub spread: TableColumn,
+    //what load_padded puts in the rows past the 2^NUM_BITS pairs
+    pub padding: TablePadding,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const NUM_BITS: usize> SpreadTableConfig<F, NUM_BITS> {
+    pub fn configure(cs: &mut ConstraintSystem<F>) -> Self {
+        Self::configure_padded(cs, TablePadding::Unpadded)
+    }
+
+    pub fn configure_padded(cs: &mut ConstraintSystem<F>, padding: TablePadding) -> Self {
+        //spread takes up to 32 bits
+        assert!(NUM_BITS > 0 && NUM_BITS <= 32);
+        Self {
+            dense: cs.lookup_table_column(),
+            spread: cs.lookup_table_column(),
+            padding: padding.for_rows(1 << NUM_BITS),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), CircuitsError> {
+        self.load_rows(layouter, None)
+    }
+
+    //load that first checks the table fits in the budget, then fills the rest of its usable
+    //rows as the padding says
+    pub fn load_padded(&self, layouter: &mut impl Layouter<F>, budget: RowBudget) -> Result<(), CircuitsError> {
+        self.load_rows(layouter, Some(budget))
+    }
+
+    fn load_rows(&self, layouter: &mut impl Layouter<F>, budget: Option<RowBudget>) -> Result<(), CircuitsError> {
+        let rows: Vec<_> = (0..1u64 << NUM_BITS).map(|dense| vec![F::from(dense), F::from(spread(dense))]).collect();
+        rows::load_table(layouter, &format!("spread_table<{}>", NUM_BITS), &[("dense", self.dense), ("spread", self.spread)], &rows, self.padding, budget)
+    }
+}
+
+//xor of two NUM_BITS chunks through their spread forms, as in the sha256 gadget.
+//
+//adding two spread values never carries into the next pair of bits, so each pair holds
+//a_i + b_i, whose low bit is a_i ^ b_i and high bit a_i & b_i. the sum is split as
+//even + 2 * odd with both halves looked up as spread values, which makes the split unique,
+//and the dense side of even is the xor. one region of four rows, every one of them a
+//(dense, spread) pair in the table:
+//
+//  a    spread(a)
+//  b    spread(b)
+//  xor  even
+//  and  odd
+#[derive(Clone, Debug)]
+pub struct SpreadConfig<F: FieldExt, const NUM_BITS: usize> {
+    pub dense: Column<Advice>,
+    pub spread: Column<Advice>,
+    pub q_lookup: Selector,
+    pub q_xor: Selector,
+    pub table: SpreadTableConfig<F, NUM_BITS>,
+}
+
+impl<F: FieldExt, const NUM_BITS: usize> SpreadConfig<F, NUM_BITS> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, dense: Column<Advice>, spread: Column<Advice>, table: SpreadTableConfig<F, NUM_BITS>) -> Self {
+        let q_lookup = cs.complex_selector();
+        let q_xor = cs.selector();
+
+        cs.enable_equality(dense);
+        cs.enable_equality(spread);
+
+        let lookup_index = cs.lookup(|cs| {
+            let q = cs.query_selector(q_lookup);
+            let dense = cs.query_advice(dense, Rotation::cur());
+            let spread = cs.query_advice(spread, Rotation::cur());
+            vec![(q.clone() * dense, table.dense), (q * spread, table.spread)]
+        });
+        report::annotate_lookup("spread", lookup_index);
+
+        cs.create_gate("spread xor", |cs| {
+            let q = cs.query_selector(q_xor);
+            let a = cs.query_advice(spread, Rotation::cur());
+            let b = cs.query_advice(spread, Rotation::next());
+            let even = cs.query_advice(spread, Rotation(2));
+            let odd = cs.query_advice(spread, Rotation(3));
+            Constraints::with_selector(q, [("a + b = even + 2 * odd", a + b - (even + odd * F::from(2)))])
+        });
+        report::annotate_gate("spread", "spread xor");
+
+        Self { dense, spread, q_lookup, q_xor, table }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 2);
+        let table = SpreadTableConfig::configure(cs);
+        Self::configure(cs, columns[0], columns[1], table)
+    }
+
+    //returns the dense cell of a ^ b, for dense cells a and b assigned elsewhere
+    pub fn spread_xor(&self, layouter: impl Layouter<F>, a: &AssignedCell<F, F>, b: &AssignedCell<F, F>) -> Result<AssignedCell<F, F>, CircuitsError> {
+        let a_value = a.value().map(|a| lower_128(a) as u64);
+        let b_value = b.value().map(|b| lower_128(b) as u64);
+        let split = a_value.zip(b_value).map(|(a, b)| {
+            let (xor, and) = (a ^ b, a & b);
+            [(F::from(xor), F::from(spread(xor))), (F::from(and), F::from(spread(and)))]
+        });
+        self.assign_with_split(layouter, a, b, split)
+    }
+
+    //split holds the (dense, spread) pairs of the xor and the and rows
+    fn assign_with_split(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        split: Value<[(F, F); 2]>,
+    ) -> Result<AssignedCell<F, F>, CircuitsError> {
+        layouter.assign_region(
+            || "assign spread xor",
+            |mut region| {
+                self.q_xor.enable(&mut region, 0)?;
+                for offset in 0..4 {
+                    self.q_lookup.enable(&mut region, offset)?;
+                }
+                for (offset, input) in [a, b].into_iter().enumerate() {
+                    let dense = input.copy_advice(|| "input", &mut region, self.dense, offset)?;
+                    let spread = dense.value().map(|dense| F::from(spread(lower_128(dense) as u64)));
+                    region.assign_advice(|| "input spread", self.spread, offset, || spread)?;
+                }
+                let xor = region.assign_advice(|| "xor", self.dense, 2, || split.map(|split| split[0].0))?;
+                region.assign_advice(|| "even", self.spread, 2, || split.map(|split| split[0].1))?;
+                region.assign_advice(|| "and", self.dense, 3, || split.map(|split| split[1].0))?;
+                region.assign_advice(|| "odd", self.spread, 3, || split.map(|split| split[1].1))?;
+                Ok(xor)
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, known, ExpectedFailure, Forged};
+
+    #[derive(Default)]
+    struct SpreadXorCircuit<const NUM_BITS: usize> {
+        pairs: Vec<(Value<Fp>, Value<Fp>)>,
+        //witnessed as the one pair's (xor, and) rows instead of the real ones when forged,
+        //dense then spread for each
+        split: Forged<Fp>,
+        //checked against each pair's xor cell
+        expected: Option<Vec<u64>>,
+    }
+
+    impl<const NUM_BITS: usize> Circuit<Fp> for SpreadXorCircuit<NUM_BITS> {
+        type Config = SpreadConfig<Fp, NUM_BITS>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { pairs: vec![(Value::unknown(), Value::unknown()); self.pairs.len()], split: self.split.without_witnesses(), expected: None }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(2);
+            SpreadConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            for (i, &(a, b)) in self.pairs.iter().enumerate() {
+                let (a_cell, b_cell) = layouter.assign_region(
+                    || "assign inputs",
+                    |mut region| {
+                        let a = region.assign_advice(|| "a", config.dense, 0, || a)?;
+                        let b = region.assign_advice(|| "b", config.dense, 1, || b)?;
+                        Ok((a, b))
+                    },
+                )?;
+                let xor = match self.split.values() {
+                    Some(&[xor, xor_spread, and, and_spread]) => {
+                        let split = xor.zip(xor_spread).zip(and.zip(and_spread)).map(|(xor, and)| [xor, and]);
+                        config.assign_with_split(layouter.namespace(|| "xor"), &a_cell, &b_cell, split)?
+                    }
+                    Some(_) => panic!("forged two rows of (dense, spread)"),
+                    None => config.spread_xor(layouter.namespace(|| "xor"), &a_cell, &b_cell)?,
+                };
+                if let Some(expected) = &self.expected {
+                    xor.value().assert_if_known(|xor| **xor == Fp::from(expected[i]));
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn prover<const NUM_BITS: usize>(pairs: &[(u64, u64)], split: Option<[(u64, u64); 2]>) -> MockProver<Fp> {
+        let k = NUM_BITS as u32 + 1;
+        let circuit = SpreadXorCircuit::<NUM_BITS> {
+            pairs: pairs.iter().map(|&(a, b)| (known(a), known(b))).collect(),
+            split: split.map_or_else(Forged::honest, |[(xor, xor_spread), (and, and_spread)]| Forged::from_u64s(&[xor, xor_spread, and, and_spread])),
+            expected: split.is_none().then(|| pairs.iter().map(|(a, b)| a ^ b).collect()),
+        };
+        MockProver::run(k, &circuit, vec![]).unwrap()
+    }
+
+    fn random_pairs(bits: usize) -> Vec<(u64, u64)> {
+        let mut rng = StdRng::seed_from_u64(0);
+        let max = (1 << bits) - 1;
+        (0..8).map(|_| (rng.gen_range(0..=max), rng.gen_range(0..=max))).chain([(0, 0), (max, max), (max, 0)]).collect()
+    }
+
+    #[test]
+    fn test_spread() {
+        assert_eq!(spread(0), 0);
+        assert_eq!(spread(0b1011), 0b1000101);
+        assert_eq!(spread(0xff), 0x5555);
+        assert_eq!(spread(0x7ff), 0x155555);
+    }
+
+    #[test]
+    fn test_spread_xor() {
+        prover::<8>(&random_pairs(8), None).assert_satisfied();
+        prover::<11>(&random_pairs(11), None).assert_satisfied();
+    }
+
+    #[test]
+    fn test_forged_split() {
+        let (a, b) = (0b1100_1010, 0b1010_0110);
+        //a | b in place of the xor, both rows real table pairs
+        let or = a | b;
+        let split = [(or, spread(or)), (0, 0)];
+        expect_failures(&prover::<8>(&[(a, b)], Some(split)), &[ExpectedFailure::constraint("spread xor", "a + b = even + 2 * odd", "assign spread xor", 0)]);
+        //the whole sum on the even row, which isn't a spread value
+        let sum = spread(a) + spread(b);
+        let split = [(a ^ b, sum), (0, 0)];
+        expect_failures(&prover::<8>(&[(a, b)], Some(split)), &[ExpectedFailure::lookup(0, "assign spread xor", 2)]);
+        //past the table
+        expect_failures(&prover::<8>(&[(0x100, 0)], None), &[ExpectedFailure::lookup(0, "assign spread xor", 0), ExpectedFailure::lookup(0, "assign spread xor", 2)]);
+    }
+}
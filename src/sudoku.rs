@@ -0,0 +1,215 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    pasta::Fp,
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Constraints, Error, Expression, Instance, Selector},
+    poly::Rotation,
+};
+
+use crate::{all_distinct::AllDistinctConfig, column_pool::ColumnPool, proving::Instances, range_lookup::RangeTableConfig, report};
+
+//27 groups of 36 pairs for the distinctness checks, the 81 digits fit next to them
+pub const SUDOKU_K: u32 = 10;
+
+pub type Grid = [[u64; 9]; 9];
+
+//a grid from 81 digits in row order, '.' or '0' for a blank. whitespace is skipped so the
+//rows can be written on lines of their own
+pub fn parse_grid(s: &str) -> Option<Grid> {
+    let digits: Vec<u64> = s
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| if c == '.' { Some(0) } else { c.to_digit(10).map(u64::from) })
+        .collect::<Option<_>>()?;
+    if digits.len() != 81 {
+        return None;
+    }
+    let mut grid = [[0; 9]; 9];
+    for (i, digit) in digits.into_iter().enumerate() {
+        grid[i / 9][i % 9] = digit;
+    }
+    Some(grid)
+}
+
+//the cells of row, column and box i, as (row, column)
+pub fn groups() -> Vec<(String, Vec<(usize, usize)>)> {
+    let rows = (0..9).map(|i| (format!("row {}", i), (0..9).map(|j| (i, j)).collect()));
+    let columns = (0..9).map(|j| (format!("column {}", j), (0..9).map(|i| (i, j)).collect()));
+    let boxes = (0..9).map(|b| (format!("box {}", b), (0..9).map(|k| (b / 3 * 3 + k / 3, b % 3 * 3 + k % 3)).collect()));
+    rows.chain(columns).chain(boxes).collect()
+}
+
+#[derive(Clone, Debug)]
+pub struct SudokuConfig {
+    pub digit: Column<Advice>,
+    //the puzzle copied from the instance, 0 for a blank
+    pub clue: Column<Advice>,
+    pub instance: Column<Instance>,
+    //digit - 1 in 0..9 and the clue gate, on every cell of the grid
+    pub q_cell: Selector,
+    pub table: RangeTableConfig<Fp, 9>,
+    pub distinct: AllDistinctConfig<Fp>,
+}
+
+//proves a private grid solves the public puzzle: every digit in 1..=9, every row, column
+//and box all distinct, and every clue matched. the instance column holds the puzzle as 81
+//values in row order with 0 for the blanks, so one verifying key serves every puzzle
+#[derive(Clone, Debug, Default)]
+pub struct SudokuCircuit {
+    pub grid: [[Value<Fp>; 9]; 9],
+}
+
+impl SudokuCircuit {
+    pub fn from_solution(solution: &Grid) -> Self {
+        Self { grid: solution.map(|row| row.map(|digit| Value::known(Fp::from(digit)))) }
+    }
+
+    pub fn public_inputs(puzzle: &Grid) -> Instances {
+        vec![puzzle.iter().flatten().map(|&clue| Fp::from(clue)).collect()]
+    }
+}
+
+impl Circuit<Fp> for SudokuCircuit {
+    type Config = SudokuConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let mut pool = ColumnPool::new(5);
+        let [digit, clue]: [Column<Advice>; 2] = pool.advice_many(cs, 2).try_into().unwrap();
+        let distinct = AllDistinctConfig::configure_with_pool(cs, &mut pool);
+        let instance = cs.instance_column();
+        let q_cell = cs.complex_selector();
+        let table = RangeTableConfig::configure(cs);
+
+        cs.enable_equality(instance);
+        cs.enable_equality(digit);
+        cs.enable_equality(clue);
+
+        //shifted down by one so the table is 0..9 and an unselected row's 0 is still in it
+        let lookup_index = cs.lookup(|cs| {
+            let q = cs.query_selector(q_cell);
+            let digit = cs.query_advice(digit, Rotation::cur());
+            vec![(q * (digit - Expression::Constant(Fp::one())), table.value)]
+        });
+        report::annotate_lookup("sudoku", lookup_index);
+
+        cs.create_gate("sudoku clue", |cs| {
+            let q = cs.query_selector(q_cell);
+            let digit = cs.query_advice(digit, Rotation::cur());
+            let clue = cs.query_advice(clue, Rotation::cur());
+            Constraints::with_selector(q, [("blank or digit = clue", clue.clone() * (digit - clue))])
+        });
+        report::annotate_gate("sudoku", "sudoku clue");
+
+        SudokuConfig { digit, clue, instance, q_cell, table, distinct }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        config.table.load(&mut layouter)?;
+
+        let cells = layouter.assign_region(
+            || "assign grid",
+            |mut region| {
+                let mut cells = vec![];
+                for (offset, digit) in self.grid.iter().flatten().enumerate() {
+                    config.q_cell.enable(&mut region, offset)?;
+                    region.assign_advice_from_instance(|| "clue", config.instance, offset, config.clue, offset)?;
+                    cells.push(region.assign_advice(|| format!("digit {}", offset), config.digit, offset, || *digit)?);
+                }
+                Ok(cells)
+            },
+        )?;
+
+        for (name, group) in groups() {
+            let group: Vec<AssignedCell<Fp, Fp>> = group.iter().map(|&(i, j)| cells[i * 9 + j].clone()).collect();
+            config.distinct.assign_annotated(layouter.namespace(|| name.as_str()), name.as_str(), &group)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::dev::MockProver;
+
+    use super::*;
+    use crate::{
+        all_distinct::pair_offset,
+        test_utils::{expect_failures, ExpectedFailure},
+    };
+
+    const PUZZLE: &str = "
+        53..7....
+        6..195...
+        .98....6.
+        8...6...3
+        4..8.3..1
+        7...2...6
+        .6....28.
+        ...419..5
+        ....8..79";
+
+    const SOLUTION: &str = "
+        534678912
+        672195348
+        198342567
+        859761423
+        426853791
+        713924856
+        961537284
+        287419635
+        345286179";
+
+    fn prover(solution: &Grid, puzzle: &Grid) -> MockProver<Fp> {
+        let circuit = SudokuCircuit::from_solution(solution);
+        MockProver::run(SUDOKU_K, &circuit, SudokuCircuit::public_inputs(puzzle)).unwrap()
+    }
+
+    #[test]
+    fn test_parse_grid() {
+        let puzzle = parse_grid(PUZZLE).unwrap();
+        assert_eq!(puzzle[0], [5, 3, 0, 0, 7, 0, 0, 0, 0]);
+        assert_eq!(parse_grid("123"), None);
+        assert_eq!(parse_grid(&PUZZLE.replace('5', "x")), None);
+        assert_eq!(groups()[18].1[..4], [(0, 0), (0, 1), (0, 2), (1, 0)]);
+    }
+
+    #[test]
+    fn test_valid_solution() {
+        let puzzle = parse_grid(PUZZLE).unwrap();
+        let solution = parse_grid(SOLUTION).unwrap();
+        prover(&solution, &puzzle).assert_satisfied();
+        //a solved grid is its own puzzle
+        prover(&solution, &solution).assert_satisfied();
+    }
+
+    #[test]
+    fn test_wrong_solution() {
+        let puzzle = parse_grid(PUZZLE).unwrap();
+        let solution = parse_grid(SOLUTION).unwrap();
+
+        //swapping the blanks at (1, 1) and (1, 2) keeps row 1 and box 0 valid, but column 1
+        //now has a second 2 and column 2 a second 7, in rows 4 and 7
+        let mut swapped = solution;
+        swapped[1].swap(1, 2);
+        let distinct = |group: &str, i, j| ExpectedFailure::constraint("all distinct", "(a - b) * inv = 1", group, pair_offset(9, i, j));
+        expect_failures(&prover(&swapped, &puzzle), &[distinct("column 1", 1, 4), distinct("column 2", 1, 7)]);
+
+        //a 0 keeps every group distinct, only the digit lookup sees it
+        let mut zero = solution;
+        zero[1][1] = 0;
+        expect_failures(&prover(&zero, &puzzle), &[ExpectedFailure::lookup(0, "assign grid", 10)]);
+
+        //the right solution to a different puzzle
+        let mut other = puzzle;
+        other[0][0] = 3;
+        expect_failures(
+            &prover(&solution, &other),
+            &[ExpectedFailure::constraint("sudoku clue", "blank or digit = clue", "assign grid", 0)],
+        );
+    }
+}
@@ -0,0 +1,240 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use std::marker::PhantomData;
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, report, rows::{self, RowBudget, TablePadding}};
+
+//packs small tables into one (tag, value) table so they share its rows instead of each
+//taking a table column of its own.
+//
+//tables are registered at configure time and get the tags 1, 2, .. in registration order.
+//tag 0 is kept for the single row (0, 0) that rows off the selector look up, the same as
+//ScheduledRangeLookupConfig
+#[derive(Clone, Debug)]
+pub struct TableRegistry<F: FieldExt> {
+    pub tag: TableColumn,
+    pub value: TableColumn,
+    //(name, contents), the tag of each being its index + 1
+    tables: Vec<(String, Vec<u64>)>,
+    //what load_padded puts in the rows past the packed tables, a filler being a row of them
+    //with the (0, 0) row first
+    pub padding: TablePadding,
+    _marker: PhantomData<F>,
+}
+
+//what a lookup config needs of one registered table
+#[derive(Clone, Debug)]
+pub struct TaggedTableHandle {
+    pub name: String,
+    pub tag: u64,
+    pub table_tag: TableColumn,
+    pub table_value: TableColumn,
+}
+
+impl TaggedTableHandle {
+    //the lookup of value into this table, on rows where q is 1
+    pub fn lookup<F: FieldExt>(&self, q: Expression<F>, value: Expression<F>) -> Vec<(Expression<F>, TableColumn)> {
+        let tag = Expression::Constant(F::from(self.tag));
+        vec![(q.clone() * tag, self.table_tag), (q * value, self.table_value)]
+    }
+}
+
+impl<F: FieldExt> TableRegistry<F> {
+    pub fn configure(cs: &mut ConstraintSystem<F>) -> Self {
+        Self::configure_padded(cs, TablePadding::Unpadded)
+    }
+
+    //the registry grows after configure, so a filler row is only checked once it loads
+    pub fn configure_padded(cs: &mut ConstraintSystem<F>, padding: TablePadding) -> Self {
+        Self {
+            tag: cs.lookup_table_column(),
+            value: cs.lookup_table_column(),
+            tables: vec![],
+            padding,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn register(&mut self, name: &str, contents: &[u64]) -> Result<TaggedTableHandle, CircuitsError> {
+        if self.tables.iter().any(|(registered, _)| registered == name) {
+            return Err(CircuitsError::DuplicateTable { name: name.to_string() });
+        }
+        self.tables.push((name.to_string(), contents.to_vec()));
+        Ok(TaggedTableHandle {
+            name: name.to_string(),
+            tag: self.tables.len() as u64,
+            table_tag: self.tag,
+            table_value: self.value,
+        })
+    }
+
+    //rows the packed table takes, the (0, 0) row included
+    pub fn rows(&self) -> usize {
+        1 + self.tables.iter().map(|(_, contents)| contents.len()).sum::<usize>()
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), CircuitsError> {
+        self.load_rows(layouter, None)
+    }
+
+    //load that first checks the table fits in the budget, then fills the rest of its usable
+    //rows as the padding says
+    pub fn load_padded(&self, layouter: &mut impl Layouter<F>, budget: RowBudget) -> Result<(), CircuitsError> {
+        self.load_rows(layouter, Some(budget))
+    }
+
+    fn load_rows(&self, layouter: &mut impl Layouter<F>, budget: Option<RowBudget>) -> Result<(), CircuitsError> {
+        let rows: Vec<_> = std::iter::once((0, 0))
+            .chain(self.tables.iter().enumerate().flat_map(|(i, (_, contents))| contents.iter().map(move |v| (i as u64 + 1, *v))))
+            .map(|(tag, value)| vec![F::from(tag), F::from(value)])
+            .collect();
+        let padding = self.padding.for_rows(rows.len());
+        rows::load_table(layouter, "table registry", &[("tag", self.tag), ("value", self.value)], &rows, padding, budget)
+    }
+}
+
+//checks values are in one registered table, one row per value
+#[derive(Clone, Debug)]
+pub struct TaggedLookupConfig<F: FieldExt> {
+    pub value: Column<Advice>,
+    pub q_enable: Selector,
+    pub handle: TaggedTableHandle,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> TaggedLookupConfig<F> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, value: Column<Advice>, handle: TaggedTableHandle) -> Self {
+        let q_enable = cs.complex_selector();
+
+        cs.enable_equality(value);
+
+        let lookup_index = cs.lookup(|cs| {
+            let q = cs.query_selector(q_enable);
+            let value = cs.query_advice(value, Rotation::cur());
+            handle.lookup(q, value)
+        });
+        report::annotate_lookup("table_registry", lookup_index);
+
+        Self { value, q_enable, handle, _marker: PhantomData }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>, handle: TaggedTableHandle) -> Self {
+        let value = pool.advice(cs);
+        Self::configure(cs, value, handle)
+    }
+
+    pub fn assign(&self, mut layouter: impl Layouter<F>, values: &[Value<F>]) -> Result<Vec<AssignedCell<F, F>>, CircuitsError> {
+        //no region for no values, MockProver can't locate a failure once there's an empty one
+        if values.is_empty() {
+            return Ok(vec![]);
+        }
+        layouter.assign_region(
+            || "assign tagged lookup",
+            |mut region| {
+                values.iter().enumerate().map(|(offset, value)| {
+                    self.q_enable.enable(&mut region, offset)?;
+                    region.assign_advice(|| "value", self.value, offset, || *value)
+                }).collect::<Result<Vec<_>, Error>>()
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, ExpectedFailure};
+
+    //the nibbles with an odd number of set bits
+    fn odd_parity() -> Vec<u64> {
+        (0..16).filter(|v: &u64| v.count_ones() % 2 == 1).collect()
+    }
+
+    #[derive(Clone, Debug)]
+    struct RegistryConfig {
+        registry: TableRegistry<Fp>,
+        //bit, nibble and parity, in that order
+        lookups: [TaggedLookupConfig<Fp>; 3],
+    }
+
+    #[derive(Default)]
+    struct RegistryCircuit {
+        //values for the bit, nibble and parity lookups
+        values: [Vec<u64>; 3],
+    }
+
+    impl Circuit<Fp> for RegistryCircuit {
+        type Config = RegistryConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { values: self.values.clone() }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut registry = TableRegistry::configure(cs);
+            let bit = registry.register("bit", &[0, 1]).unwrap();
+            let nibble = registry.register("nibble", &(0..16).collect::<Vec<_>>()).unwrap();
+            let parity = registry.register("parity", &odd_parity()).unwrap();
+            //the three lookups share the one advice column too
+            let mut pool = ColumnPool::new(1);
+            let lookups = [bit, nibble, parity].map(|handle| TaggedLookupConfig::configure_with_pool(cs, &mut pool, handle));
+            RegistryConfig { registry, lookups }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.registry.load(&mut layouter)?;
+            for (lookup, values) in config.lookups.iter().zip(&self.values) {
+                let values: Vec<_> = values.iter().map(|v| Value::known(Fp::from(*v))).collect();
+                lookup.assign(layouter.namespace(|| lookup.handle.name.clone()), &values)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn prover(bits: &[u64], nibbles: &[u64], parities: &[u64]) -> MockProver<Fp> {
+        let k = 6;
+        let circuit = RegistryCircuit { values: [bits.to_vec(), nibbles.to_vec(), parities.to_vec()] };
+        MockProver::run(k, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_registry() {
+        prover(&[0, 1], &[0, 7, 15], &[1, 7, 14]).assert_satisfied();
+        prover(&[], &[], &[]).assert_satisfied();
+    }
+
+    #[test]
+    fn test_per_tag_semantics() {
+        let at = |lookup, row| [ExpectedFailure::lookup(lookup, "assign tagged lookup", row)];
+        //in the nibble table, not the bit one
+        expect_failures(&prover(&[0, 2], &[], &[]), &at(0, 1));
+        expect_failures(&prover(&[], &[16], &[]), &at(1, 0));
+        //a nibble, but with even parity
+        expect_failures(&prover(&[], &[], &[1, 3]), &at(2, 1));
+        //0 is on the padding row, under tag 0, and not an odd parity nibble
+        expect_failures(&prover(&[], &[], &[0]), &at(2, 0));
+    }
+
+    #[test]
+    fn test_tags() {
+        let mut cs = ConstraintSystem::<Fp>::default();
+        let mut registry = TableRegistry::configure(&mut cs);
+        let tags: Vec<u64> = ["bit", "nibble", "parity"].iter().map(|name| registry.register(name, &[0]).unwrap().tag).collect();
+        assert_eq!(tags, [1, 2, 3]);
+        assert_eq!(registry.rows(), 4);
+        let err = registry.register("nibble", &[0, 1]).unwrap_err();
+        assert!(matches!(err, CircuitsError::DuplicateTable { ref name } if name == "nibble"), "{:?}", err);
+        //the failed registration takes neither a tag nor rows
+        assert_eq!(registry.register("byte", &[0]).unwrap().tag, 4);
+        assert_eq!(registry.rows(), 5);
+    }
+}
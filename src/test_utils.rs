@@ -0,0 +1,290 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::Value,
+    dev::{FailureLocation, MockProver, VerifyFailure},
+    plonk::Assigned,
+};
+use rand_core::RngCore;
+
+use std::fmt;
+
+use crate::report::quoted_names;
+
+pub fn value_u64<F: FieldExt>(v: u64) -> Value<Assigned<F>> {
+    Value::known(F::from(v).into())
+}
+
+//negative values map to p - |v|, the same wraparound a signed gadget sees
+pub fn value_i64<F: FieldExt>(v: i64) -> Value<Assigned<F>> {
+    let magnitude = F::from(v.unsigned_abs());
+    Value::known(if v < 0 { -magnitude } else { magnitude }.into())
+}
+
+pub fn values_u64<F: FieldExt>(values: &[u64]) -> Vec<Value<Assigned<F>>> {
+    values.iter().map(|v| value_u64(*v)).collect()
+}
+
+pub fn values_i64<F: FieldExt>(values: &[i64]) -> Vec<Value<Assigned<F>>> {
+    values.iter().map(|v| value_i64(*v)).collect()
+}
+
+//n values uniformly drawn from 0..range
+pub fn in_range_values<F: FieldExt>(range: u64, n: usize, rng: &mut impl RngCore) -> Vec<Value<Assigned<F>>> {
+    assert!(range > 0);
+    (0..n).map(|_| value_u64(rng.next_u64() % range)).collect()
+}
+
+//the smallest and largest values in 0..range
+pub fn boundary_values<F: FieldExt>(range: u64) -> Vec<Value<Assigned<F>>> {
+    assert!(range > 0);
+    if range == 1 {
+        values_u64(&[0])
+    } else {
+        values_u64(&[0, range - 1])
+    }
+}
+
+//n values outside 0..range. the first two are always range itself and -1, i.e. just past
+//either end once the field wraps around, the rest are drawn from range..=u64::MAX
+pub fn out_of_range_values<F: FieldExt>(range: u64, n: usize, rng: &mut impl RngCore) -> Vec<Value<Assigned<F>>> {
+    let edges = [value_u64(range), value_i64(-1)];
+    let random = std::iter::repeat_with(|| value_u64(range + rng.next_u64().checked_rem(u64::MAX - range).unwrap_or(0)));
+    edges.into_iter().chain(random).take(n).collect()
+}
+
+//known and Forged are for this crate's own soundness tests, the test-utils feature doesn't
+//export them
+#[cfg(test)]
+pub fn known<F: FieldExt>(v: u64) -> Value<F> {
+    Value::known(F::from(v))
+}
+
+//witnesses a soundness test puts in place of the ones a gadget would compute, e.g. a
+//quotient and remainder that add up but aren't the real split. None takes the honest path.
+//without_witnesses keeps how many there are, so keygen goes down the same path
+#[cfg(test)]
+#[derive(Clone, Debug)]
+pub struct Forged<F: FieldExt>(Option<Vec<Value<F>>>);
+
+#[cfg(test)]
+impl<F: FieldExt> Default for Forged<F> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+#[cfg(test)]
+impl<F: FieldExt> Forged<F> {
+    pub fn honest() -> Self {
+        Self(None)
+    }
+
+    pub fn new(values: &[F]) -> Self {
+        Self(Some(values.iter().map(|v| Value::known(*v)).collect()))
+    }
+
+    pub fn from_u64s(values: &[u64]) -> Self {
+        Self(Some(values.iter().map(|v| known(*v)).collect()))
+    }
+
+    //the same number of values, all unknown
+    pub fn without_witnesses(&self) -> Self {
+        Self(self.0.as_ref().map(|values| vec![Value::unknown(); values.len()]))
+    }
+
+    pub fn values(&self) -> Option<&[Value<F>]> {
+        self.0.as_deref()
+    }
+
+    //for the gadgets that take their forged witnesses in pairs
+    pub fn pair(&self) -> Option<(Value<F>, Value<F>)> {
+        self.values().map(|values| {
+            assert_eq!(values.len(), 2, "forged a pair");
+            (values[0], values[1])
+        })
+    }
+}
+
+//for the property tests, mostly values right around the bound plus some anywhere in
+//[0, 2*range]. proptest is only a dev dependency
+#[cfg(test)]
+pub fn value_near(range: usize) -> impl proptest::strategy::Strategy<Value = u64> {
+    use proptest::prelude::*;
+    let range = range as u64;
+    prop_oneof![Just(0), Just(range - 1), Just(range), Just(range + 1), 0..=2 * range]
+}
+
+//a failure the mock prover is expected to report, matched on names rather than indices so
+//tests don't break when unrelated gates or regions get added
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ExpectedFailure {
+    //"gate: constraint" for constraints, "lookup N" for lookups
+    pub name: String,
+    //region name, or "outside any region"
+    pub region: String,
+    //offset within the region, or the absolute row outside regions
+    pub row: usize,
+}
+
+impl ExpectedFailure {
+    pub fn constraint(gate: &str, constraint: &str, region: &str, row: usize) -> Self {
+        Self {
+            name: format!("{}: {}", gate, constraint),
+            region: region.to_string(),
+            row,
+        }
+    }
+
+    pub fn lookup(lookup_index: usize, region: &str, row: usize) -> Self {
+        Self {
+            name: format!("lookup {}", lookup_index),
+            region: region.to_string(),
+            row,
+        }
+    }
+}
+
+impl fmt::Display for ExpectedFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} in '{}' at row {}", self.name, self.region, self.row)
+    }
+}
+
+fn location(location: &FailureLocation) -> (String, usize) {
+    match location {
+        FailureLocation::InRegion { region, offset } => {
+            let region = region.to_string();
+            (quoted_names(&region).first().unwrap_or(&"").to_string(), *offset)
+        }
+        FailureLocation::OutsideRegion { row } => ("outside any region".to_string(), *row),
+    }
+}
+
+fn actual(failure: &VerifyFailure) -> ExpectedFailure {
+    match failure {
+        VerifyFailure::ConstraintNotSatisfied { constraint, location: loc, .. } => {
+            let constraint = constraint.to_string();
+            let names = quoted_names(&constraint);
+            let (region, row) = location(loc);
+            ExpectedFailure::constraint(names.get(1).unwrap_or(&""), names.first().unwrap_or(&""), &region, row)
+        }
+        VerifyFailure::Lookup { lookup_index, location: loc } => {
+            let (region, row) = location(loc);
+            ExpectedFailure::lookup(*lookup_index, &region, row)
+        }
+        VerifyFailure::CellNotAssigned { gate, region, offset, .. } => {
+            let gate = gate.to_string();
+            let region = region.to_string();
+            ExpectedFailure {
+                name: format!("{}: cell not assigned", quoted_names(&gate).first().unwrap_or(&"")),
+                region: quoted_names(&region).first().unwrap_or(&"").to_string(),
+                row: (*offset).max(0) as usize,
+            }
+        }
+        VerifyFailure::Permutation { location: loc, .. } => {
+            let (region, row) = location(loc);
+            ExpectedFailure {
+                name: "permutation".to_string(),
+                region,
+                row,
+            }
+        }
+        other => ExpectedFailure {
+            name: other.to_string(),
+            region: String::new(),
+            row: 0,
+        },
+    }
+}
+
+//asserts the prover reports exactly the expected failures, in any order. on a mismatch the
+//panic message lists every failure marked - (expected, missing), + (unexpected) or unmarked
+pub fn expect_failures<F: FieldExt>(prover: &MockProver<F>, expected: &[ExpectedFailure]) {
+    let mut actual: Vec<ExpectedFailure> = prover.verify().err().unwrap_or_default().iter().map(actual).collect();
+    let mut expected = expected.to_vec();
+    actual.sort();
+    expected.sort();
+    if actual == expected {
+        return;
+    }
+
+    let mut diff = vec![];
+    let (mut a, mut e) = (actual.iter().peekable(), expected.iter().peekable());
+    loop {
+        match (a.peek(), e.peek()) {
+            (Some(x), Some(y)) if x == y => {
+                diff.push(format!("  {}", x));
+                a.next();
+                e.next();
+            }
+            (Some(x), Some(y)) if x < y => diff.push(format!("+ {}", a.next().unwrap())),
+            (Some(_), Some(_)) | (None, Some(_)) => diff.push(format!("- {}", e.next().unwrap())),
+            (Some(_), None) => diff.push(format!("+ {}", a.next().unwrap())),
+            (None, None) => break,
+        }
+    }
+    panic!("unexpected mock prover failures (- expected, + actual):\n{}", diff.join("\n"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{field::lower_128, range_check::RangeCheckCircuit};
+    use halo2_proofs::pasta::Fp;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_quoted_names() {
+        assert_eq!(quoted_names("Constraint 0 ('range check') in gate 0 ('range')"), vec!["range check", "range"]);
+        assert_eq!(quoted_names("Region 1 ('assign range val')"), vec!["assign range val"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "+ range: range check in 'range_check<8> assign range val' at row 0")]
+    fn test_expect_failures_diff() {
+        let circuit = RangeCheckCircuit::<Fp, 8>::from_values(&[8]);
+        let prover = MockProver::run(4, &circuit, vec![]).unwrap();
+        expect_failures(&prover, &[]);
+    }
+
+    #[test]
+    fn test_forged() {
+        let forged = Forged::<Fp>::from_u64s(&[3, 17]);
+        let (q, r) = forged.pair().unwrap();
+        q.assert_if_known(|q| *q == Fp::from(3));
+        r.assert_if_known(|r| *r == Fp::from(17));
+
+        //still forged, with nothing known
+        let unknown = forged.without_witnesses();
+        assert_eq!(unknown.values().unwrap().len(), 2);
+        let mut seen = false;
+        unknown.values().unwrap()[0].map(|_| seen = true);
+        assert!(!seen);
+        assert!(Forged::<Fp>::honest().without_witnesses().values().is_none());
+    }
+
+    #[test]
+    fn test_generators() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let lower = |v: &Value<Assigned<Fp>>| {
+            let mut out = None;
+            v.map(|v| out = Some(v.evaluate()));
+            out.unwrap()
+        };
+
+        for v in in_range_values::<Fp>(10, 32, &mut rng) {
+            assert!(lower_128(&lower(&v)) < 10);
+        }
+        let boundary: Vec<Fp> = boundary_values::<Fp>(10).iter().map(lower).collect();
+        assert_eq!(boundary, vec![Fp::from(0), Fp::from(9)]);
+
+        let out = out_of_range_values::<Fp>(10, 8, &mut rng);
+        assert_eq!(out.len(), 8);
+        assert_eq!(lower(&out[0]), Fp::from(10));
+        assert_eq!(lower(&out[1]), -Fp::one());
+        for v in &out[2..] {
+            assert!(lower_128(&lower(v)) >= 10);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    pasta::Fp,
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+};
+
+use crate::{column_pool::ColumnPool, monotonic::MonotonicConfig, proving::Instances};
+
+//timestamps are u64s
+pub const TIMESTAMP_BITS: usize = 64;
+//k the circuit fits in, five 64 bit decompositions
+pub const TIMESTAMP_WINDOW_K: u32 = 9;
+
+//instance rows
+const START_ROW: usize = 0;
+const END_ROW: usize = 1;
+
+#[derive(Clone, Debug)]
+pub struct TimestampWindowConfig {
+    //the timestamp, and start and end copied from the instance
+    pub private: Column<Advice>,
+    pub instance: Column<Instance>,
+    //start <= ts <= end as a non-decreasing column of the three, each step has to fit in
+    //TIMESTAMP_BITS
+    pub window: MonotonicConfig<Fp, TIMESTAMP_BITS>,
+}
+
+//proves a private timestamp lies in a public window [start, end], both ends inclusive. the
+//instance column holds [start, end]
+#[derive(Clone, Debug, Default)]
+pub struct TimestampWindowCircuit {
+    pub ts: Value<Fp>,
+}
+
+impl TimestampWindowCircuit {
+    pub fn new(ts: u64) -> Self {
+        Self { ts: Value::known(Fp::from(ts)) }
+    }
+
+    //[start, end], None for an empty window. the circuit can't be satisfied for one either,
+    //this just says so before proving
+    pub fn public_inputs(start: u64, end: u64) -> Option<Instances> {
+        (start <= end).then(|| vec![vec![Fp::from(start), Fp::from(end)]])
+    }
+}
+
+impl Circuit<Fp> for TimestampWindowCircuit {
+    type Config = TimestampWindowConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let mut pool = ColumnPool::new(5);
+        let private = pool.advice(cs);
+        let instance = cs.instance_column();
+        cs.enable_equality(private);
+        cs.enable_equality(instance);
+        let window = MonotonicConfig::configure_with_pool(cs, &mut pool, false);
+
+        TimestampWindowConfig { private, instance, window }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        let (start, ts, end) = layouter.assign_region(
+            || "load window",
+            |mut region| {
+                let start = region.assign_advice_from_instance(|| "start", config.instance, START_ROW, config.private, 0)?;
+                let ts = region.assign_advice(|| "ts", config.private, 1, || self.ts)?;
+                let end = region.assign_advice_from_instance(|| "end", config.instance, END_ROW, config.private, 2)?;
+                Ok((start, ts, end))
+            },
+        )?;
+
+        //the steps are decomposed, which orders the three. the values are decomposed too,
+        //or a start near the modulus could wrap around to a small step up to ts
+        for (name, cell) in [("start bits", &start), ("ts bits", &ts), ("end bits", &end)] {
+            config.window.bits.decompose_cell(layouter.namespace(|| name), cell, TIMESTAMP_BITS)?;
+        }
+        config.window.assign_cells(layouter.namespace(|| "start <= ts <= end"), &[start, ts, end])?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::dev::MockProver;
+
+    use super::*;
+
+    const START: u64 = 1_700_000_000;
+    const END: u64 = 1_700_086_400;
+
+    fn verify(ts: u64, instances: Instances) -> bool {
+        let prover = MockProver::run(TIMESTAMP_WINDOW_K, &TimestampWindowCircuit::new(ts), instances).unwrap();
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn test_boundaries() {
+        let window = TimestampWindowCircuit::public_inputs(START, END).unwrap();
+        assert!(verify(START, window.clone()));
+        assert!(verify(END, window.clone()));
+        assert!(verify(START + 3600, window.clone()));
+        //one second out either side
+        assert!(!verify(START - 1, window.clone()));
+        assert!(!verify(END + 1, window));
+        //the whole u64 range is a valid window
+        assert!(verify(u64::MAX, TimestampWindowCircuit::public_inputs(0, u64::MAX).unwrap()));
+    }
+
+    #[test]
+    fn test_empty_window() {
+        assert_eq!(TimestampWindowCircuit::public_inputs(END, START), None);
+        //handed to the prover anyway, no timestamp satisfies it
+        let reversed = vec![vec![Fp::from(END), Fp::from(START)]];
+        for ts in [START, START + 3600, END] {
+            assert!(!verify(ts, reversed.clone()));
+        }
+    }
+
+    #[test]
+    fn test_start_near_modulus() {
+        //start = -5 is ts - 8 in the field, a small step that only the start bits catch
+        let instances = vec![vec![-Fp::from(5), Fp::from(END)]];
+        assert!(!verify(3, instances));
+    }
+}
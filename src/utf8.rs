@@ -0,0 +1,137 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Assigned, Column, ConstraintSystem, Error, Fixed},
+};
+
+use crate::{bounds::BoundsConfig, column_pool::ColumnPool, error::CircuitsError};
+
+//continuation bytes are 0b10xxxxxx
+pub const CONTINUATION_MIN: u64 = 0x80;
+pub const CONTINUATION_MAX: u64 = 0xBF;
+
+//checks a byte is a utf-8 continuation byte, in [0x80, 0xC0).
+//
+//an interval check with the bounds pinned to constants, the bounds config alone would let
+//the prover pick them
+#[derive(Clone, Debug)]
+pub struct ContinuationByteConfig<F: FieldExt> {
+    pub bounds: BoundsConfig<F, 64>,
+}
+
+impl<F: FieldExt> ContinuationByteConfig<F> {
+    pub fn configure(
+        cs: &mut ConstraintSystem<F>,
+        byte: Column<Advice>,
+        min: Column<Advice>,
+        max: Column<Advice>,
+        constants: Column<Fixed>,
+    ) -> Self {
+        cs.enable_equality(min);
+        cs.enable_equality(max);
+        cs.enable_constant(constants);
+        Self {
+            bounds: BoundsConfig::configure(cs, byte, min, max),
+        }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>, constants: Column<Fixed>) -> Self {
+        let columns = pool.advice_many(cs, 3);
+        Self::configure(cs, columns[0], columns[1], columns[2], constants)
+    }
+
+    //returns the byte cell
+    pub fn assign(&self, layouter: impl Layouter<F>, byte: Value<Assigned<F>>) -> Result<AssignedCell<Assigned<F>, F>, CircuitsError> {
+        let min = Value::known(F::from(CONTINUATION_MIN).into());
+        let max = Value::known(F::from(CONTINUATION_MAX).into());
+        self.assign_with_bounds(layouter, byte, min, max)
+    }
+
+    fn assign_with_bounds(
+        &self,
+        mut layouter: impl Layouter<F>,
+        byte: Value<Assigned<F>>,
+        min: Value<Assigned<F>>,
+        max: Value<Assigned<F>>,
+    ) -> Result<AssignedCell<Assigned<F>, F>, CircuitsError> {
+        let (byte, min, max) = self.bounds.assign_with_bounds(layouter.namespace(|| "continuation byte"), byte, min, max)?;
+        layouter.assign_region(
+            || "pin continuation bounds",
+            |mut region| {
+                let fixed_min = region.assign_advice_from_constant(|| "min", self.bounds.min, 0, Assigned::from(F::from(CONTINUATION_MIN)))?;
+                let fixed_max = region.assign_advice_from_constant(|| "max", self.bounds.max, 0, Assigned::from(F::from(CONTINUATION_MAX)))?;
+                region.constrain_equal(fixed_min.cell(), min.cell())?;
+                region.constrain_equal(fixed_max.cell(), max.cell())
+            },
+        )?;
+        Ok(byte)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, value_u64, ExpectedFailure};
+
+    #[derive(Default)]
+    struct ContinuationCircuit<F: FieldExt> {
+        byte: Value<Assigned<F>>,
+        //when set, witnesses these bounds instead of the constants
+        bounds: Option<(Value<Assigned<F>>, Value<Assigned<F>>)>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for ContinuationCircuit<F> {
+        type Config = ContinuationByteConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let constants = cs.fixed_column();
+            let mut pool = ColumnPool::new(3);
+            ContinuationByteConfig::configure_with_pool(cs, &mut pool, constants)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            config.bounds.table.load(&mut layouter)?;
+            match self.bounds {
+                Some((min, max)) => config.assign_with_bounds(layouter.namespace(|| "byte"), self.byte, min, max)?,
+                None => config.assign(layouter.namespace(|| "byte"), self.byte)?,
+            };
+            Ok(())
+        }
+    }
+
+    fn prover(byte: u64) -> MockProver<Fp> {
+        let k = 7;
+        MockProver::run(k, &ContinuationCircuit { byte: value_u64(byte), bounds: None }, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_continuation_bytes() {
+        prover(0x80).assert_satisfied();
+        prover(0xBF).assert_satisfied();
+        //the interval is exactly as wide as the table, so a byte just outside it is 64 away
+        //from the far bound and fails both lookups
+        let both = [ExpectedFailure::lookup(0, "assign bounds", 0), ExpectedFailure::lookup(1, "assign bounds", 0)];
+        //ascii
+        expect_failures(&prover(0x7F), &both);
+        //a two byte lead
+        expect_failures(&prover(0xC0), &both);
+    }
+
+    #[test]
+    fn test_bounds_pinned() {
+        //an ascii byte passes the lookups once the bounds are moved, but not the constants
+        let circuit = ContinuationCircuit::<Fp> {
+            byte: value_u64(0x41),
+            bounds: Some((value_u64(0x40), value_u64(0x7F))),
+        };
+        assert!(MockProver::run(7, &circuit, vec![]).unwrap().verify().is_err());
+    }
+}
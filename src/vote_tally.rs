@@ -0,0 +1,193 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::{Field, FieldExt},
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    pasta::Fp,
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Constraints, Error, Expression, Instance, Selector},
+    poly::Rotation,
+};
+
+use crate::{column_pool::ColumnPool, proving::Instances, range_check::RangeCheckCircuitConfig, report, running_sum::RunningSumConfig};
+
+pub const CANDIDATES: usize = 3;
+
+//[ballot == c] for each candidate c, as the lagrange basis on {0, 1, 2}. it is only an
+//indicator for ballots in range, the range check is what rules the others out
+pub fn indicators<F: FieldExt>(ballot: F) -> [F; CANDIDATES] {
+    let [one, two] = [F::one(), F::from(2)];
+    let half = two.invert().unwrap();
+    [(one - ballot) * (two - ballot) * half, ballot * (two - ballot), ballot * (ballot - one) * half]
+}
+
+fn indicator_expressions<F: FieldExt>(ballot: Expression<F>) -> [Expression<F>; CANDIDATES] {
+    let [one, two] = [Expression::Constant(F::one()), Expression::Constant(F::from(2))];
+    let half = Expression::Constant(F::from(2).invert().unwrap());
+    [
+        (one.clone() - ballot.clone()) * (two.clone() - ballot.clone()) * half.clone(),
+        ballot.clone() * (two - ballot.clone()),
+        ballot.clone() * (ballot - one) * half,
+    ]
+}
+
+//the votes for each candidate, None if a ballot isn't one of them
+pub fn tally(ballots: &[u64]) -> Option<[u64; CANDIDATES]> {
+    ballots.iter().try_fold([0; CANDIDATES], |mut tallies, &ballot| {
+        *tallies.get_mut(ballot as usize)? += 1;
+        Some(tallies)
+    })
+}
+
+#[derive(Clone, Debug)]
+pub struct VoteTallyConfig {
+    pub ballot: Column<Advice>,
+    //one running sum per candidate over its indicators of the ballots
+    pub sums: [RunningSumConfig<Fp>; CANDIDATES],
+    pub instance: Column<Instance>,
+    pub q_vote: Selector,
+    //ballot in {0, 1, 2}
+    pub range: RangeCheckCircuitConfig<Fp, CANDIDATES>,
+}
+
+//proves N private ballots, each a vote for candidate 0, 1 or 2, add up to the public
+//tallies. the instance column holds the CANDIDATES tallies in candidate order
+#[derive(Clone, Debug)]
+pub struct VoteTallyCircuit<const N: usize> {
+    pub ballots: [Value<Fp>; N],
+}
+
+impl<const N: usize> Default for VoteTallyCircuit<N> {
+    fn default() -> Self {
+        Self { ballots: [Value::unknown(); N] }
+    }
+}
+
+impl<const N: usize> VoteTallyCircuit<N> {
+    pub fn from_ballots(ballots: [u64; N]) -> Self {
+        Self { ballots: ballots.map(|ballot| Value::known(Fp::from(ballot))) }
+    }
+
+    pub fn public_inputs(tallies: [u64; CANDIDATES]) -> Instances {
+        vec![tallies.iter().map(|&tally| Fp::from(tally)).collect()]
+    }
+}
+
+impl<const N: usize> Circuit<Fp> for VoteTallyCircuit<N> {
+    type Config = VoteTallyConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+        assert!(N > 0);
+        let mut pool = ColumnPool::new(7);
+        let range = RangeCheckCircuitConfig::configure_with_pool(cs, &mut pool);
+        let columns = pool.advice_many(cs, 7);
+        let ballot = columns[0];
+        let sums = [0, 1, 2].map(|c| RunningSumConfig::configure(cs, columns[1 + 2 * c], columns[2 + 2 * c], 1));
+        let instance = cs.instance_column();
+        let q_vote = cs.selector();
+
+        cs.enable_equality(instance);
+        cs.enable_equality(ballot);
+
+        cs.create_gate("tally vote", |cs| {
+            let q = cs.query_selector(q_vote);
+            let ballot = cs.query_advice(ballot, Rotation::cur());
+            let votes = sums.clone().map(|sum| cs.query_advice(sum.term, Rotation::cur()));
+            Constraints::with_selector(q, votes.into_iter().zip(indicator_expressions(ballot)).map(|(vote, indicator)| ("vote", vote - indicator)))
+        });
+        report::annotate_gate("vote_tally", "tally vote");
+
+        VoteTallyConfig { ballot, sums, instance, q_vote, range }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        let checked = self
+            .ballots
+            .iter()
+            .map(|ballot| config.range.assign_simple(layouter.namespace(|| "ballot"), *ballot))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let totals = layouter.assign_region(
+            || "assign tally",
+            |mut region| {
+                let mut votes = vec![vec![]; CANDIDATES];
+                for (offset, ballot) in checked.iter().enumerate() {
+                    config.q_vote.enable(&mut region, offset)?;
+                    let ballot = ballot.copy_advice_to(|| "ballot", &mut region, config.ballot, offset)?.evaluate();
+                    let ballot_votes = ballot.value().map(|ballot| indicators(*ballot));
+                    for (c, votes) in votes.iter_mut().enumerate() {
+                        votes.push(ballot_votes.map(|ballot_votes| ballot_votes[c]));
+                    }
+                }
+                config.sums.iter().zip(&votes).map(|(sum, votes)| {
+                    let rows = sum.assign_rows(&mut region, 0, votes, None)?;
+                    Ok(rows[N - 1].1.clone())
+                }).collect::<Result<Vec<_>, Error>>()
+            },
+        )?;
+
+        for (row, total) in totals.iter().enumerate() {
+            layouter.constrain_instance(total.cell(), config.instance, row)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::dev::MockProver;
+
+    use super::*;
+    use crate::test_utils::{expect_failures, ExpectedFailure};
+
+    const BALLOTS: [u64; 8] = [0, 2, 1, 2, 2, 0, 1, 2];
+
+    fn prover(ballots: [u64; 8], instances: Instances) -> MockProver<Fp> {
+        let k = 5;
+        MockProver::run(k, &VoteTallyCircuit::from_ballots(ballots), instances).unwrap()
+    }
+
+    #[test]
+    fn test_tally() {
+        assert_eq!(tally(&BALLOTS), Some([2, 2, 4]));
+        assert_eq!(tally(&[0, 3]), None);
+        for ballot in 0..3 {
+            let mut expected = [Fp::zero(); CANDIDATES];
+            expected[ballot] = Fp::one();
+            assert_eq!(indicators(Fp::from(ballot as u64)), expected);
+        }
+    }
+
+    #[test]
+    fn test_valid_election() {
+        prover(BALLOTS, VoteTallyCircuit::<8>::public_inputs([2, 2, 4])).assert_satisfied();
+        prover([1; 8], VoteTallyCircuit::<8>::public_inputs([0, 8, 0])).assert_satisfied();
+    }
+
+    #[test]
+    fn test_out_of_range_ballot() {
+        //the published tallies are whatever the sums come to, so only the range check can
+        //catch the ballot
+        let mut ballots = BALLOTS;
+        ballots[3] = 3;
+        let sums = ballots.iter().map(|&ballot| indicators(Fp::from(ballot))).fold([Fp::zero(); CANDIDATES], |mut sums, votes| {
+            sums.iter_mut().zip(votes).for_each(|(sum, vote)| *sum += vote);
+            sums
+        });
+        expect_failures(
+            &prover(ballots, vec![sums.to_vec()]),
+            &[ExpectedFailure::constraint("range", "range check", "range_check<3> assign range val", 0)],
+        );
+    }
+
+    #[test]
+    fn test_wrong_tallies() {
+        //a vote moved from candidate 2 to 0, and one vote too many
+        for tallies in [[3, 2, 3], [2, 2, 5]] {
+            assert!(prover(BALLOTS, VoteTallyCircuit::<8>::public_inputs(tallies)).verify().is_err());
+        }
+    }
+}
@@ -0,0 +1,189 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Assigned, Column, ConstraintSystem, Error, Expression, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use std::marker::PhantomData;
+
+use crate::{column_pool::ColumnPool, error::CircuitsError, rows::{self, RowBudget, TablePadding}};
+
+//checks now - window <= ts <= now for a window of at most MAX_WINDOW
+#[derive(Clone, Debug)]
+pub struct TimeWindowConfig<F: FieldExt, const MAX_WINDOW: usize> {
+    pub ts: Column<Advice>,
+    pub now: Column<Advice>,
+    pub window: Column<Advice>,
+    pub q_enable: Selector,
+    //holds 0..=MAX_WINDOW
+    pub table: TableColumn,
+    //what load_padded puts in the rows past 0..=MAX_WINDOW
+    pub padding: TablePadding,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const MAX_WINDOW: usize> TimeWindowConfig<F, MAX_WINDOW> {
+    pub fn configure(
+        cs: &mut ConstraintSystem<F>,
+        ts: Column<Advice>,
+        now: Column<Advice>,
+        window: Column<Advice>,
+    ) -> Self {
+        Self::configure_padded(cs, ts, now, window, TablePadding::Unpadded)
+    }
+
+    pub fn configure_padded(
+        cs: &mut ConstraintSystem<F>,
+        ts: Column<Advice>,
+        now: Column<Advice>,
+        window: Column<Advice>,
+        padding: TablePadding,
+    ) -> Self {
+        let q_enable = cs.complex_selector();
+        let table = cs.lookup_table_column();
+
+        //ts is not after now: now - ts in [0, MAX_WINDOW]
+        cs.lookup(|cs| {
+            let q = cs.query_selector(q_enable);
+            let ts = cs.query_advice(ts, Rotation::cur());
+            let now = cs.query_advice(now, Rotation::cur());
+            vec![(q * (now - ts), table)]
+        });
+
+        //ts is not before the window start: ts - (now - window) in [0, MAX_WINDOW]
+        cs.lookup(|cs| {
+            let q = cs.query_selector(q_enable);
+            let ts = cs.query_advice(ts, Rotation::cur());
+            let now = cs.query_advice(now, Rotation::cur());
+            let window = cs.query_advice(window, Rotation::cur());
+            vec![(q * (ts - (now - window)), table)]
+        });
+
+        //the window itself has to fit, otherwise the two checks above don't bound anything
+        cs.lookup(|cs| {
+            let q = cs.query_selector(q_enable);
+            let window = cs.query_advice(window, Rotation::cur());
+            vec![(q * window, table)]
+        });
+
+        Self {
+            ts,
+            now,
+            window,
+            q_enable,
+            table,
+            padding: padding.for_rows(MAX_WINDOW + 1),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let columns = pool.advice_many(cs, 3);
+        Self::configure(cs, columns[0], columns[1], columns[2])
+    }
+
+    fn rows() -> Vec<Vec<F>> {
+        (0..=MAX_WINDOW as u64).map(|i| vec![F::from(i)]).collect()
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), CircuitsError> {
+        rows::load_table(layouter, "window table", &[("assign window table value", self.table)], &Self::rows(), self.padding, None)
+    }
+
+    //load that first checks the table fits in the budget, then fills the rest of its usable
+    //rows as the padding says
+    pub fn load_padded(&self, layouter: &mut impl Layouter<F>, budget: RowBudget) -> Result<(), CircuitsError> {
+        rows::load_table(layouter, "window table", &[("assign window table value", self.table)], &Self::rows(), self.padding, Some(budget))
+    }
+
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        ts: Value<Assigned<F>>,
+        now: Value<Assigned<F>>,
+        window: Value<Assigned<F>>,
+    ) -> Result<AssignedCell<Assigned<F>, F>, CircuitsError> {
+        let offset = 0;
+        layouter.assign_region(
+            || "assign time window",
+            |mut region| {
+                self.q_enable.enable(&mut region, offset)?;
+                region.assign_advice(|| "now", self.now, offset, || now)?;
+                region.assign_advice(|| "window", self.window, offset, || window)?;
+                region.assign_advice(|| "ts", self.ts, offset, || ts)
+            },
+        ).map_err(CircuitsError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::Circuit,
+    };
+
+    use super::*;
+    use crate::test_utils::value_u64;
+
+    #[derive(Default)]
+    struct TimeWindowCircuit<F: FieldExt, const MAX_WINDOW: usize> {
+        ts: Value<Assigned<F>>,
+        now: Value<Assigned<F>>,
+        window: Value<Assigned<F>>,
+    }
+
+    impl<F: FieldExt, const MAX_WINDOW: usize> Circuit<F> for TimeWindowCircuit<F, MAX_WINDOW> {
+        type Config = TimeWindowConfig<F, MAX_WINDOW>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let ts = cs.advice_column();
+            let now = cs.advice_column();
+            let window = cs.advice_column();
+            TimeWindowConfig::configure(cs, ts, now, window)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            config.load(&mut layouter)?;
+            config.assign(layouter.namespace(|| "time window"), self.ts, self.now, self.window)?;
+            Ok(())
+        }
+    }
+
+    fn circuit(ts: u64, now: u64, window: u64) -> TimeWindowCircuit<Fp, 8> {
+        TimeWindowCircuit {
+            ts: value_u64(ts),
+            now: value_u64(now),
+            window: value_u64(window),
+        }
+    }
+
+    #[test]
+    fn test_in_window() {
+        let k = 4;
+        //both ends of the window are inclusive
+        for ts in 15..=20 {
+            let prover = MockProver::run(k, &circuit(ts, 20, 5), vec![]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_out_of_window() {
+        let k = 4;
+        //one before the start of the window and one after now
+        for ts in [14, 21] {
+            let prover = MockProver::run(k, &circuit(ts, 20, 5), vec![]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+}
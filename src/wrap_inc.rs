@@ -0,0 +1,165 @@
+#![allow(warnings, unused)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Assigned, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{column_pool::ColumnPool, comparator::ComparatorConfig, error::CircuitsError, range_lookup::RangeTableConfig, report};
+
+//checks next = (cur + 1) mod M for cur in 0..M.
+//
+//the wrap row has cur, M - 1 pinned by the gate here, lt and next, and the select
+//next = lt * (cur + 1) picks cur + 1 or the wrapped 0. cur, M - 1 and lt are then copied into
+//a comparison, which binds lt = [cur < M - 1] and also keeps cur in 0..M, so it can't be
+//pushed past M - 1 to dodge the wrap
+#[derive(Clone, Debug)]
+pub struct WrapIncConfig<F: FieldExt, const M: usize> {
+    pub comparator: ComparatorConfig<F, M>,
+    pub next: Column<Advice>,
+    pub q_wrap: Selector,
+}
+
+impl<F: FieldExt, const M: usize> WrapIncConfig<F, M> {
+    //cur, M - 1 and lt go in the comparator's a, b and lt columns on the wrap row
+    pub fn configure(cs: &mut ConstraintSystem<F>, comparator: ComparatorConfig<F, M>, next: Column<Advice>) -> Self {
+        assert!(M > 0);
+        let q_wrap = cs.selector();
+
+        cs.enable_equality(next);
+
+        cs.create_gate("wrap inc", |cs| {
+            let q = cs.query_selector(q_wrap);
+            let cur = cs.query_advice(comparator.a, Rotation::cur());
+            let b = cs.query_advice(comparator.b, Rotation::cur());
+            let lt = cs.query_advice(comparator.lt, Rotation::cur());
+            let next = cs.query_advice(next, Rotation::cur());
+            let one = Expression::Constant(F::one());
+            Constraints::with_selector(
+                q,
+                [
+                    ("b = M - 1", b - Expression::Constant(F::from(M as u64 - 1))),
+                    ("next = lt * (cur + 1)", next - lt * (cur + one)),
+                ],
+            )
+        });
+        report::annotate_gate("wrap_inc", "wrap inc");
+
+        Self { comparator, next, q_wrap }
+    }
+
+    pub fn configure_with_pool(cs: &mut ConstraintSystem<F>, pool: &mut ColumnPool<F>) -> Self {
+        let comparator = ComparatorConfig::configure_with_pool(cs, pool);
+        let next = pool.advice(cs);
+        Self::configure(cs, comparator, next)
+    }
+
+    pub fn table(&self) -> &RangeTableConfig<F, M> {
+        &self.comparator.table
+    }
+
+    //returns the next cell for a cell cur assigned elsewhere
+    pub fn assign(&self, layouter: impl Layouter<F>, cur: &AssignedCell<Assigned<F>, F>) -> Result<AssignedCell<Assigned<F>, F>, CircuitsError> {
+        let last = Value::known(Assigned::from(F::from(M as u64 - 1)));
+        let lt = ComparatorConfig::<F, M>::lt_value(cur.value().cloned(), last);
+        let next = cur.value().cloned().zip(lt).map(|(cur, lt)| lt * (cur + F::one()));
+        self.assign_with_next(layouter, cur, lt, next)
+    }
+
+    fn assign_with_next(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cur: &AssignedCell<Assigned<F>, F>,
+        lt: Value<Assigned<F>>,
+        next: Value<Assigned<F>>,
+    ) -> Result<AssignedCell<Assigned<F>, F>, CircuitsError> {
+        let offset = 0;
+        let (cur, last, lt, next) = layouter.assign_region(
+            || "assign wrap inc",
+            |mut region| {
+                self.q_wrap.enable(&mut region, offset)?;
+                let cur = cur.copy_advice(|| "cur", &mut region, self.comparator.a, offset)?;
+                let last = region.assign_advice(|| "last", self.comparator.b, offset, || Value::known(Assigned::from(F::from(M as u64 - 1))))?;
+                let lt = region.assign_advice(|| "lt", self.comparator.lt, offset, || lt)?;
+                let next = region.assign_advice(|| "next", self.next, offset, || next)?;
+                Ok((cur, last, lt, next))
+            },
+        )?;
+        self.comparator.assign_with_lt(layouter.namespace(|| "cur < M - 1"), &cur, &last, &lt)?;
+        Ok(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+    use crate::test_utils::{expect_failures, value_u64, ExpectedFailure, Forged};
+
+    #[derive(Default)]
+    struct WrapIncCircuit {
+        cur: Value<Assigned<Fp>>,
+        //witnessed as (lt, next) instead of the real ones when forged
+        forged: Forged<Fp>,
+        expected: Option<u64>,
+    }
+
+    impl Circuit<Fp> for WrapIncCircuit {
+        type Config = WrapIncConfig<Fp, 8>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { forged: self.forged.without_witnesses(), ..Self::default() }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut pool = ColumnPool::new(4);
+            WrapIncConfig::configure_with_pool(cs, &mut pool)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table().load(&mut layouter)?;
+            let cur = layouter.assign_region(|| "assign cur", |mut region| region.assign_advice(|| "cur", config.comparator.a, 0, || self.cur))?;
+            let next = match self.forged.pair() {
+                Some((lt, next)) => config.assign_with_next(layouter.namespace(|| "inc"), &cur, lt.map(Assigned::from), next.map(Assigned::from))?,
+                None => config.assign(layouter.namespace(|| "inc"), &cur)?,
+            };
+            if let Some(expected) = self.expected {
+                next.value().assert_if_known(|next| next.evaluate() == Fp::from(expected));
+            }
+            Ok(())
+        }
+    }
+
+    fn prover(cur: u64, forged: Option<(u64, u64)>, expected: Option<u64>) -> MockProver<Fp> {
+        let k = 5;
+        let forged = forged.map_or_else(Forged::honest, |(lt, next)| Forged::from_u64s(&[lt, next]));
+        MockProver::run(k, &WrapIncCircuit { cur: value_u64(cur), forged, expected }, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_wrap_inc() {
+        prover(3, None, Some(4)).assert_satisfied();
+        prover(0, None, Some(1)).assert_satisfied();
+        prover(6, None, Some(7)).assert_satisfied();
+        prover(7, None, Some(0)).assert_satisfied();
+    }
+
+    #[test]
+    fn test_no_wrap_fails() {
+        //7 -> 8 with the honest lt = 0 misses the select
+        let select = ExpectedFailure::constraint("wrap inc", "next = lt * (cur + 1)", "assign wrap inc", 0);
+        expect_failures(&prover(7, Some((0, 8)), None), &[select]);
+        //claiming 7 < 7 gets past the select, but b - a - 1 = -1 misses the table
+        expect_failures(&prover(7, Some((1, 8)), None), &[ExpectedFailure::lookup(2, "assign comparison", 0)]);
+    }
+
+    #[test]
+    fn test_cur_out_of_range() {
+        //8 -> 9 would be a plain increment, but cur has to be below M
+        let failures = [ExpectedFailure::lookup(0, "assign comparison", 0), ExpectedFailure::lookup(2, "assign comparison", 0)];
+        expect_failures(&prover(8, Some((1, 9)), None), &failures);
+    }
+}
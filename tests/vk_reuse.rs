@@ -0,0 +1,58 @@
+//one verifying key, many proofs. keygen only sees without_witnesses, so this also checks
+//that the circuit shape doesn't depend on the witness
+use halo2_circuits::{
+    params_cache::ParamsCache,
+    proving::{create_proof, keygen, verify_proof},
+    range_check::RangeCheckCircuit,
+    range_lookup::RangeCheckLookupCircuit,
+};
+use halo2_proofs::{
+    pasta::Fp,
+    plonk::{keygen_vk, Circuit},
+};
+
+const K: u32 = 5;
+const RANGE: usize = 8;
+
+//every circuit here checks three values, the batch size is part of the shape
+fn gate_circuit(values: &[u64]) -> RangeCheckCircuit<Fp, RANGE> {
+    RangeCheckCircuit::from_values(values)
+}
+
+fn lookup_circuit(values: &[u64]) -> RangeCheckLookupCircuit<Fp, RANGE> {
+    RangeCheckLookupCircuit::from_values(values)
+}
+
+#[test]
+fn test_gate_vk_reuse() {
+    let cache = ParamsCache::new();
+    let (params, pk) = keygen(&cache, K, &gate_circuit(&[0, 0, 0]).without_witnesses()).unwrap();
+
+    //keying a witnessed circuit gives the same vk
+    let witnessed = keygen_vk(&params, &gate_circuit(&[1, 2, 3])).unwrap();
+    assert_eq!(format!("{:?}", witnessed.pinned()), format!("{:?}", pk.get_vk().pinned()));
+
+    for values in [[0, 1, 2], [7, 7, 7], [3, 0, 5], [6, 4, 1]] {
+        let proof = create_proof(&params, &pk, gate_circuit(&values), &vec![]).unwrap();
+        verify_proof(&params, pk.get_vk(), &proof, &vec![]).unwrap();
+    }
+
+    //the prover doesn't check gates, the proof is made and then rejected
+    let proof = create_proof(&params, &pk, gate_circuit(&[1, 8, 2]), &vec![]).unwrap();
+    assert!(verify_proof(&params, pk.get_vk(), &proof, &vec![]).is_err());
+}
+
+#[test]
+fn test_lookup_vk_reuse() {
+    let cache = ParamsCache::new();
+    let (params, pk) = keygen(&cache, K, &lookup_circuit(&[0, 0, 0]).without_witnesses()).unwrap();
+
+    for values in [[0, 1, 2], [7, 7, 7], [5, 3, 0]] {
+        let proof = create_proof(&params, &pk, lookup_circuit(&values), &vec![]).unwrap();
+        verify_proof(&params, pk.get_vk(), &proof, &vec![]).unwrap();
+    }
+
+    //a lookup input missing from the table can't be permuted, so proving fails outright
+    let proof = create_proof(&params, &pk, lookup_circuit(&[1, 9, 2]), &vec![]);
+    assert!(proof.map_or(true, |proof| verify_proof(&params, pk.get_vk(), &proof, &vec![]).is_err()));
+}